@@ -0,0 +1,129 @@
+//! Run a list of commands on a session and report each outcome
+//!
+//! A single `DeviceResult` for a whole batch can't say which commands went
+//! through before one failed. `dispatch_batch` keeps every command's reply or
+//! error, and `BatchMode` decides whether a failure ends the batch.
+
+use serde_json::Value;
+use crate::device::{DeviceError, DeviceResult, DeviceSession};
+use crate::device::session::SessionCommand;
+
+/// What a batch does after a command fails
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BatchMode {
+    /// Skip the remaining commands
+    #[default]
+    StopOnError,
+    /// Run every command regardless of earlier failures
+    Continue,
+}
+
+/// Outcome of each command in a batch
+#[derive(Debug, Default)]
+pub struct BatchResult {
+    /// Commands that succeeded, with their replies, in the order run
+    pub succeeded: Vec<(SessionCommand, Value)>,
+    /// Commands that failed, with their errors, in the order run
+    pub failed: Vec<(SessionCommand, DeviceError)>,
+    /// Commands not run because `StopOnError` ended the batch
+    pub skipped: Vec<SessionCommand>,
+}
+
+impl BatchResult {
+    /// Whether every command ran and succeeded
+    pub fn is_success(&self) -> bool {
+        self.failed.is_empty() && self.skipped.is_empty()
+    }
+    
+    /// Collapse to all-or-nothing: the first failure, if any
+    pub fn into_result(self) -> DeviceResult<Vec<(SessionCommand, Value)>> {
+        match self.failed.into_iter().next() {
+            Some((_, error)) => Err(error),
+            None => Ok(self.succeeded),
+        }
+    }
+}
+
+/// Invoke `commands` on `session` in order
+pub async fn dispatch_batch(session: &mut dyn DeviceSession, commands: &[SessionCommand], mode: BatchMode) -> BatchResult {
+    let mut result = BatchResult::default();
+    
+    for (i, command) in commands.iter().enumerate() {
+        match session.invoke_async(&command.endpoint, command.args.clone()).await {
+            Ok(reply) => result.succeeded.push((command.clone(), reply)),
+            Err(e) => {
+                result.failed.push((command.clone(), e));
+                if mode == BatchMode::StopOnError {
+                    result.skipped = commands[i + 1..].to_vec();
+                    break;
+                }
+            }
+        }
+    }
+    
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use crate::device::mock::MockSession;
+    
+    /// Session that echoes the endpoint back and rejects `failing_endpoint`
+    fn scripted_session(failing_endpoint: &'static str) -> MockSession {
+        MockSession::new(move |endpoint, _| {
+            if endpoint == failing_endpoint {
+                return Err(DeviceError::DeviceRejection(format!("{} refused", endpoint)));
+            }
+            Ok(json!({ "endpoint": endpoint }))
+        })
+    }
+    
+    fn batch() -> Vec<SessionCommand> {
+        vec![
+            SessionCommand::new("pinMode", vec![json!(3), json!("OUTPUT")]),
+            SessionCommand::new("setServo", vec![json!(0), json!(90)]),
+            SessionCommand::new("digitalWrite", vec![json!(3), json!(true)]),
+        ]
+    }
+    
+    fn endpoints<T>(entries: &[(SessionCommand, T)]) -> Vec<&str> {
+        entries.iter().map(|(command, _)| command.endpoint.as_str()).collect()
+    }
+    
+    #[tokio::test]
+    async fn test_continue_mode_reports_both_lists() {
+        let mut session = scripted_session("setServo");
+        let result = dispatch_batch(&mut session, &batch(), BatchMode::Continue).await;
+        
+        assert_eq!(endpoints(&result.succeeded), vec!["pinMode", "digitalWrite"]);
+        assert_eq!(result.succeeded[1].1, json!({ "endpoint": "digitalWrite" }));
+        assert_eq!(endpoints(&result.failed), vec!["setServo"]);
+        assert!(matches!(result.failed[0].1, DeviceError::DeviceRejection(_)));
+        assert!(result.skipped.is_empty());
+        assert!(!result.is_success());
+        assert_eq!(session.endpoints().len(), 3);
+    }
+    
+    #[tokio::test]
+    async fn test_stop_on_error_skips_remaining() {
+        let mut session = scripted_session("setServo");
+        let result = dispatch_batch(&mut session, &batch(), BatchMode::StopOnError).await;
+        
+        assert_eq!(endpoints(&result.succeeded), vec!["pinMode"]);
+        assert_eq!(endpoints(&result.failed), vec!["setServo"]);
+        assert_eq!(result.skipped, vec![batch()[2].clone()]);
+        assert_eq!(session.endpoints(), vec!["pinMode", "setServo"]);
+        assert!(result.into_result().is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_all_succeeding_batch() {
+        let mut session = scripted_session("none");
+        let result = dispatch_batch(&mut session, &batch(), BatchMode::StopOnError).await;
+        
+        assert!(result.is_success());
+        assert_eq!(result.into_result().unwrap().len(), 3);
+    }
+}
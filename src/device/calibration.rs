@@ -0,0 +1,374 @@
+//! Linear calibration of analog inputs
+//!
+//! A first-run calibration walks the user through a few known reference points
+//! (e.g. "apply 0 kg", "apply 5 kg"). `CalibrationSession` reads the input at
+//! each point, averages a handful of samples and fits `value = raw * scale +
+//! offset` by least squares. The result is stored per input in the device's
+//! profile and `CalibratedSession` applies it to every read of that input.
+
+use async_trait::async_trait;
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+use thiserror::Error;
+use tokio::sync::mpsc;
+use crate::device::{DeviceResult, DeviceError, DeviceSession};
+use crate::device::clock_sync::ClockOffset;
+use crate::protocols::handshake::NegotiatedVersion;
+use crate::device::response_parser::ResponseParser;
+use crate::device::session::{StreamData, SubscriptionHandle, SessionStatistics, InputPinSet, EndpointMetrics};
+
+/// Samples averaged per reference point unless configured otherwise
+pub const DEFAULT_SAMPLES_PER_POINT: usize = 8;
+
+/// Calibration errors
+#[derive(Debug, Error)]
+pub enum CalibrationError {
+    #[error("At least 2 reference points are needed, got {0}")]
+    NotEnoughPoints(usize),
+    
+    #[error("All reference points read the same raw value {0}")]
+    DegenerateReadings(f64),
+    
+    #[error("Non-numeric reading: {0}")]
+    NonNumericReading(Value),
+    
+    #[error("Device error: {0}")]
+    Device(#[from] DeviceError),
+}
+
+/// `value = raw * scale + offset`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LinearCalibration {
+    pub scale: f64,
+    pub offset: f64,
+}
+
+impl LinearCalibration {
+    /// Calibration that leaves readings unchanged
+    pub const IDENTITY: Self = Self { scale: 1.0, offset: 0.0 };
+    
+    pub fn new(scale: f64, offset: f64) -> Self {
+        Self { scale, offset }
+    }
+    
+    /// Least-squares fit through `(raw, reference)` points
+    pub fn fit(points: &[CalibrationPoint]) -> Result<Self, CalibrationError> {
+        if points.len() < 2 {
+            return Err(CalibrationError::NotEnoughPoints(points.len()));
+        }
+        
+        let n = points.len() as f64;
+        let mean_raw = points.iter().map(|p| p.raw).sum::<f64>() / n;
+        let mean_reference = points.iter().map(|p| p.reference).sum::<f64>() / n;
+        
+        let mut covariance = 0.0;
+        let mut variance = 0.0;
+        for point in points {
+            let d_raw = point.raw - mean_raw;
+            covariance += d_raw * (point.reference - mean_reference);
+            variance += d_raw * d_raw;
+        }
+        if variance <= f64::EPSILON {
+            return Err(CalibrationError::DegenerateReadings(mean_raw));
+        }
+        
+        let scale = covariance / variance;
+        Ok(Self {
+            scale,
+            offset: mean_reference - scale * mean_raw,
+        })
+    }
+    
+    pub fn apply(&self, raw: f64) -> f64 {
+        raw * self.scale + self.offset
+    }
+    
+    /// Calibrate a read reply: a number, or an object with a numeric "value"
+    /// Anything else is returned unchanged
+    pub fn apply_to_reply(&self, reply: Value) -> Value {
+        match reply {
+            Value::Number(n) => n.as_f64()
+                .map(|raw| Value::from(self.apply(raw)))
+                .unwrap_or(Value::Number(n)),
+            Value::Object(mut fields) => {
+                if let Some(value) = fields.remove("value") {
+                    fields.insert("value".to_string(), self.apply_to_reply(value));
+                }
+                Value::Object(fields)
+            }
+            other => other,
+        }
+    }
+}
+
+impl Default for LinearCalibration {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+/// Key a calibration is stored under for reads of `endpoint` with `args`,
+/// e.g. `analogRead:0`
+pub fn calibration_key(endpoint: &str, args: &[Value]) -> String {
+    let args: Vec<String> = args.iter().map(|arg| match arg {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }).collect();
+    format!("{}:{}", endpoint, args.join(","))
+}
+
+/// Averaged raw reading taken at a known reference value
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CalibrationPoint {
+    pub reference: f64,
+    pub raw: f64,
+}
+
+/// Guided calibration of one input
+pub struct CalibrationSession {
+    endpoint: String,
+    args: Vec<Value>,
+    samples_per_point: usize,
+    points: Vec<CalibrationPoint>,
+}
+
+impl CalibrationSession {
+    /// Calibrate reads of `endpoint` with `args`
+    pub fn new(endpoint: &str, args: Vec<Value>) -> Self {
+        Self {
+            endpoint: endpoint.to_string(),
+            args,
+            samples_per_point: DEFAULT_SAMPLES_PER_POINT,
+            points: Vec::new(),
+        }
+    }
+    
+    /// Readings averaged at each reference point
+    pub fn with_samples_per_point(mut self, samples: usize) -> Self {
+        self.samples_per_point = samples.max(1);
+        self
+    }
+    
+    /// Storage key for the resulting calibration
+    pub fn key(&self) -> String {
+        calibration_key(&self.endpoint, &self.args)
+    }
+    
+    /// Points captured so far
+    pub fn points(&self) -> &[CalibrationPoint] {
+        &self.points
+    }
+    
+    /// Read the input while it is held at `reference` and record the averaged reading
+    pub async fn capture_point(
+        &mut self,
+        session: &mut dyn DeviceSession,
+        reference: f64,
+    ) -> Result<CalibrationPoint, CalibrationError> {
+        let mut total = 0.0;
+        for _ in 0..self.samples_per_point {
+            let reply = session.invoke_async(&self.endpoint, self.args.clone()).await?;
+            total += reading(&reply).ok_or(CalibrationError::NonNumericReading(reply))?;
+        }
+        
+        let point = CalibrationPoint {
+            reference,
+            raw: total / self.samples_per_point as f64,
+        };
+        tracing::debug!("Calibration point for {}: raw {:.3} -> {}", self.key(), point.raw, reference);
+        self.points.push(point);
+        Ok(point)
+    }
+    
+    /// Record a point read elsewhere
+    pub fn add_point(&mut self, reference: f64, raw: f64) {
+        self.points.push(CalibrationPoint { reference, raw });
+    }
+    
+    /// Drop the captured points to start over
+    pub fn clear(&mut self) {
+        self.points.clear();
+    }
+    
+    /// Fit the calibration from the captured points
+    pub fn compute(&self) -> Result<LinearCalibration, CalibrationError> {
+        LinearCalibration::fit(&self.points)
+    }
+}
+
+/// Numeric reading in a reply: a number or an object with a numeric "value"
+fn reading(reply: &Value) -> Option<f64> {
+    match reply {
+        Value::Number(n) => n.as_f64(),
+        Value::Object(fields) => fields.get("value").and_then(reading),
+        _ => None,
+    }
+}
+
+/// Session wrapper that applies stored calibrations to matching reads
+pub struct CalibratedSession {
+    inner: Box<dyn DeviceSession>,
+    calibrations: HashMap<String, LinearCalibration>,
+}
+
+impl CalibratedSession {
+    /// Wrap `inner` with calibrations keyed by `calibration_key`
+    pub fn new(inner: Box<dyn DeviceSession>, calibrations: HashMap<String, LinearCalibration>) -> Self {
+        Self { inner, calibrations }
+    }
+    
+    /// Add or replace a calibration
+    pub fn set_calibration(&mut self, key: &str, calibration: LinearCalibration) {
+        self.calibrations.insert(key.to_string(), calibration);
+    }
+    
+    pub fn calibrations(&self) -> &HashMap<String, LinearCalibration> {
+        &self.calibrations
+    }
+    
+    /// Wrapped session
+    pub fn inner(&self) -> &dyn DeviceSession {
+        self.inner.as_ref()
+    }
+}
+
+#[async_trait]
+impl DeviceSession for CalibratedSession {
+    fn session_id(&self) -> &str {
+        self.inner.session_id()
+    }
+    
+    fn device_name(&self) -> &str {
+        self.inner.device_name()
+    }
+    
+    async fn invoke_async(&mut self, endpoint: &str, args: Vec<Value>) -> DeviceResult<Value> {
+        let calibration = self.calibrations.get(&calibration_key(endpoint, &args)).copied();
+        let reply = self.inner.invoke_async(endpoint, args).await?;
+        Ok(match calibration {
+            Some(calibration) => calibration.apply_to_reply(reply),
+            None => reply,
+        })
+    }
+    
+    async fn subscribe_async(
+        &mut self,
+        stream: &str,
+        handler: mpsc::UnboundedSender<StreamData>,
+    ) -> DeviceResult<SubscriptionHandle> {
+        self.inner.subscribe_async(stream, handler).await
+    }
+    
+    async fn close_async(&mut self) -> DeviceResult<()> {
+        self.inner.close_async().await
+    }
+    
+    fn is_active(&self) -> bool {
+        self.inner.is_active()
+    }
+    
+    fn statistics(&self) -> SessionStatistics {
+        self.inner.statistics()
+    }
+    
+    fn command_metrics(&self) -> HashMap<String, EndpointMetrics> {
+        self.inner.command_metrics()
+    }
+    
+    async fn send_raw(&mut self, data: &[u8]) -> DeviceResult<Vec<u8>> {
+        self.inner.send_raw(data).await
+    }
+    
+    async fn sync_clock(&mut self) -> DeviceResult<ClockOffset> {
+        self.inner.sync_clock().await
+    }
+    
+    fn clock_offset(&self) -> Option<ClockOffset> {
+        self.inner.clock_offset()
+    }
+    
+    fn clock_resync_due(&self) -> bool {
+        self.inner.clock_resync_due()
+    }
+    
+    fn negotiated_version(&self) -> Option<NegotiatedVersion> {
+        self.inner.negotiated_version()
+    }
+    
+    fn set_response_parser(&mut self, parser: Arc<dyn ResponseParser>) -> DeviceResult<()> {
+        self.inner.set_response_parser(parser)
+    }
+    
+    async fn query_capabilities(&mut self) -> DeviceResult<BTreeSet<String>> {
+        self.inner.query_capabilities().await
+    }
+    
+    // `read_all_inputs` keeps the default so its per-pin reads are calibrated
+    async fn readable_inputs(&self) -> InputPinSet {
+        self.inner.readable_inputs().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    
+    use std::sync::Mutex;
+    use crate::device::mock::MockSession;
+    
+    /// Session whose analog reads return the raw value set for each pin
+    fn fixed_read_session(raw: Arc<Mutex<HashMap<u64, u64>>>) -> MockSession {
+        MockSession::new(move |endpoint, args| {
+            let pin = args.first().and_then(Value::as_u64).unwrap_or(0);
+            match endpoint {
+                "analogRead" => Ok(json!({ "pin": pin, "value": raw.lock().unwrap()[&pin] })),
+                _ => Ok(json!("OK")),
+            }
+        })
+    }
+    
+    #[test]
+    fn test_fit_computes_scale_and_offset() {
+        let points = [
+            CalibrationPoint { reference: 0.0, raw: 100.0 },
+            CalibrationPoint { reference: 5.0, raw: 600.0 },
+            CalibrationPoint { reference: 10.0, raw: 1100.0 },
+        ];
+        let calibration = LinearCalibration::fit(&points).unwrap();
+        assert!((calibration.scale - 0.01).abs() < 1e-9);
+        assert!((calibration.offset + 1.0).abs() < 1e-9);
+        assert!((calibration.apply(350.0) - 2.5).abs() < 1e-9);
+        
+        assert!(matches!(LinearCalibration::fit(&points[..1]), Err(CalibrationError::NotEnoughPoints(1))));
+        let flat = [points[0], CalibrationPoint { reference: 5.0, raw: 100.0 }];
+        assert!(matches!(LinearCalibration::fit(&flat), Err(CalibrationError::DegenerateReadings(_))));
+    }
+    
+    #[tokio::test]
+    async fn test_calibration_applies_to_reads() {
+        let raw = Arc::new(Mutex::new(HashMap::from([(0, 200), (1, 300)])));
+        let mut device = fixed_read_session(raw.clone());
+        
+        let mut calibration = CalibrationSession::new("analogRead", vec![json!(0)]).with_samples_per_point(3);
+        calibration.capture_point(&mut device, 0.0).await.unwrap();
+        raw.lock().unwrap().insert(0, 700);
+        calibration.capture_point(&mut device, 100.0).await.unwrap();
+        let linear = calibration.compute().unwrap();
+        assert!((linear.scale - 0.2).abs() < 1e-9);
+        assert!((linear.offset + 40.0).abs() < 1e-9);
+        assert_eq!(calibration.key(), "analogRead:0");
+        
+        raw.lock().unwrap().insert(0, 450);
+        let mut session = CalibratedSession::new(Box::new(device), HashMap::from([(calibration.key(), linear)]));
+        let reply = session.invoke_async("analogRead", vec![json!(0)]).await.unwrap();
+        assert!((reply["value"].as_f64().unwrap() - 50.0).abs() < 1e-9);
+        assert_eq!(reply["pin"], 0);
+        
+        // Uncalibrated inputs pass through untouched
+        let reply = session.invoke_async("analogRead", vec![json!(1)]).await.unwrap();
+        assert_eq!(reply["value"], 300);
+    }
+}
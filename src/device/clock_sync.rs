@@ -0,0 +1,170 @@
+//! Device-to-host clock offset estimation
+//!
+//! Device timestamps (usually milliseconds since boot) and host wall-clock
+//! time drift apart. A sync round-trip records the host time before and after
+//! asking the device for its clock; assuming a symmetric link, the device
+//! reading corresponds to the midpoint, which gives the offset to add to
+//! device timestamps. The error is bounded by half the round-trip time.
+
+use serde::{Serialize, Deserialize};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use crate::telemetry::TelemetrySample;
+
+/// Default interval between clock re-syncs to correct drift
+pub const DEFAULT_RESYNC_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Estimated offset between a device clock and host time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClockOffset {
+    /// Milliseconds to add to a device timestamp to get host time
+    pub offset_ms: i64,
+    
+    /// Round-trip time of the sync exchange
+    pub rtt_ms: u64,
+    
+    /// Host time (unix ms) when the estimate was taken
+    pub measured_at_ms: u64,
+}
+
+impl ClockOffset {
+    /// Estimate the offset from one exchange
+    /// `host_send_ms`/`host_recv_ms` bracket the request; `device_ms` is the device's reply
+    pub fn estimate(host_send_ms: u64, device_ms: u64, host_recv_ms: u64) -> Self {
+        let host_recv_ms = host_recv_ms.max(host_send_ms);
+        let rtt_ms = host_recv_ms - host_send_ms;
+        let host_mid_ms = host_send_ms + rtt_ms / 2;
+        
+        Self {
+            offset_ms: host_mid_ms as i64 - device_ms as i64,
+            rtt_ms,
+            measured_at_ms: host_recv_ms,
+        }
+    }
+    
+    /// Worst-case error of the estimate (half the round trip)
+    pub fn uncertainty_ms(&self) -> u64 {
+        self.rtt_ms.div_ceil(2)
+    }
+    
+    /// Convert a device timestamp to host time
+    pub fn to_host_time(&self, device_ms: u64) -> u64 {
+        (device_ms as i64 + self.offset_ms).max(0) as u64
+    }
+    
+    /// Rebase a sample stamped with device time onto host time
+    pub fn rebase_sample(&self, sample: &mut TelemetrySample) {
+        sample.timestamp_ms = self.to_host_time(sample.timestamp_ms);
+    }
+}
+
+/// Latest clock offset for a session, with re-sync scheduling
+#[derive(Debug, Clone)]
+pub struct ClockSync {
+    offset: Option<ClockOffset>,
+    resync_interval: Duration,
+}
+
+impl ClockSync {
+    /// Track offsets, re-syncing every `resync_interval`
+    pub fn new(resync_interval: Duration) -> Self {
+        Self {
+            offset: None,
+            resync_interval,
+        }
+    }
+    
+    /// Store a new estimate, replacing the previous one
+    pub fn record(&mut self, offset: ClockOffset) {
+        self.offset = Some(offset);
+    }
+    
+    /// Latest estimate, if the clock has been synced
+    pub fn offset(&self) -> Option<ClockOffset> {
+        self.offset
+    }
+    
+    /// Whether the clock was never synced or the last sync is older than the interval
+    pub fn needs_resync(&self) -> bool {
+        match self.offset {
+            Some(offset) => now_ms().saturating_sub(offset.measured_at_ms) >= self.resync_interval.as_millis() as u64,
+            None => true,
+        }
+    }
+    
+    /// Convert a device timestamp to host time (unchanged until synced)
+    pub fn to_host_time(&self, device_ms: u64) -> u64 {
+        self.offset.map_or(device_ms, |offset| offset.to_host_time(device_ms))
+    }
+}
+
+impl Default for ClockSync {
+    fn default() -> Self {
+        Self::new(DEFAULT_RESYNC_INTERVAL)
+    }
+}
+
+/// Current host time in unix milliseconds
+pub(crate) fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::telemetry::SampleValue;
+    
+    #[test]
+    fn test_offset_within_rtt_bounds() {
+        // Device booted 5s ago: its clock reads 5_000 when the host reads 1_700_000_005_000
+        let true_offset: i64 = 1_700_000_000_000;
+        
+        for (send_delay, return_delay) in [(10u64, 10u64), (2, 18), (18, 2), (0, 40)] {
+            let host_send = 1_700_000_005_000u64;
+            let device_time = (host_send + send_delay) as i64 - true_offset;
+            let host_recv = host_send + send_delay + return_delay;
+            
+            let estimate = ClockOffset::estimate(host_send, device_time as u64, host_recv);
+            
+            assert_eq!(estimate.rtt_ms, send_delay + return_delay);
+            let error = (estimate.offset_ms - true_offset).unsigned_abs();
+            assert!(error <= estimate.uncertainty_ms(), "error {}ms exceeds bound {}ms", error, estimate.uncertainty_ms());
+        }
+        
+        // Symmetric link: exact
+        let estimate = ClockOffset::estimate(1_000, 400, 1_020);
+        assert_eq!(estimate.offset_ms, 610);
+    }
+    
+    #[test]
+    fn test_rebase_sample() {
+        let offset = ClockOffset::estimate(1_700_000_010_000, 12_345, 1_700_000_010_020);
+        assert_eq!(offset.offset_ms, 1_700_000_010_010 - 12_345);
+        
+        let mut sample = TelemetrySample::with_timestamp(SampleValue::Float32(1.5), 13_345);
+        offset.rebase_sample(&mut sample);
+        assert_eq!(sample.timestamp_ms, 1_700_000_011_010);
+        
+        // Negative offsets (device ahead of host) work too
+        let ahead = ClockOffset { offset_ms: -500, rtt_ms: 0, measured_at_ms: 0 };
+        assert_eq!(ahead.to_host_time(2_000), 1_500);
+    }
+    
+    #[test]
+    fn test_resync_schedule() {
+        let mut sync = ClockSync::new(Duration::from_secs(60));
+        assert!(sync.needs_resync());
+        assert_eq!(sync.to_host_time(100), 100);
+        
+        let now = now_ms();
+        sync.record(ClockOffset { offset_ms: 1_000, rtt_ms: 4, measured_at_ms: now });
+        assert!(!sync.needs_resync());
+        assert_eq!(sync.to_host_time(100), 1_100);
+        
+        // An estimate older than the interval is due for a re-sync
+        sync.record(ClockOffset { offset_ms: 1_000, rtt_ms: 4, measured_at_ms: now - 61_000 });
+        assert!(sync.needs_resync());
+    }
+}
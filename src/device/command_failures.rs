@@ -0,0 +1,143 @@
+//! Alerting on repeated command failures
+//!
+//! One failed command is usually noise (a dropped byte, a busy device), but a
+//! run of them means something is really wrong. `CommandFailureTracker`
+//! counts consecutive failures per device and raises a single alert once the
+//! count reaches the threshold; the next success clears it.
+
+use std::collections::HashMap;
+
+/// Consecutive failures before an alert when none is configured
+pub const DEFAULT_FAILURE_ALERT_THRESHOLD: u32 = 3;
+
+/// Raised when a device's consecutive failures reach the threshold
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FailureAlert {
+    pub device_id: String,
+    pub consecutive_failures: u32,
+    pub last_error: String,
+}
+
+impl FailureAlert {
+    /// Text shown to the user
+    pub fn message(&self) -> String {
+        format!(
+            "{}: {} commands failed in a row (last: {})",
+            self.device_id, self.consecutive_failures, self.last_error
+        )
+    }
+}
+
+/// Consecutive failure counts per device
+#[derive(Debug, Clone)]
+pub struct CommandFailureTracker {
+    /// Failures in a row that raise an alert (0 = never alert)
+    threshold: u32,
+    failures: HashMap<String, u32>,
+}
+
+impl CommandFailureTracker {
+    pub fn new(threshold: u32) -> Self {
+        Self {
+            threshold,
+            failures: HashMap::new(),
+        }
+    }
+    
+    pub fn threshold(&self) -> u32 {
+        self.threshold
+    }
+    
+    /// Change the threshold; counts so far are kept
+    pub fn set_threshold(&mut self, threshold: u32) {
+        self.threshold = threshold;
+    }
+    
+    /// Count a failure, returning the alert when this one reaches the threshold
+    /// Further failures while alerting don't raise it again
+    pub fn record_failure(&mut self, device_id: &str, error: &str) -> Option<FailureAlert> {
+        let count = self.failures.entry(device_id.to_string()).or_insert(0);
+        *count += 1;
+        
+        (self.threshold > 0 && *count == self.threshold).then(|| FailureAlert {
+            device_id: device_id.to_string(),
+            consecutive_failures: *count,
+            last_error: error.to_string(),
+        })
+    }
+    
+    /// Reset the device's count; true if this cleared an active alert
+    pub fn record_success(&mut self, device_id: &str) -> bool {
+        let cleared = self.is_alerting(device_id);
+        self.failures.remove(device_id);
+        cleared
+    }
+    
+    pub fn consecutive_failures(&self, device_id: &str) -> u32 {
+        self.failures.get(device_id).copied().unwrap_or(0)
+    }
+    
+    /// Whether the device has reached the threshold and not succeeded since
+    pub fn is_alerting(&self, device_id: &str) -> bool {
+        self.threshold > 0 && self.consecutive_failures(device_id) >= self.threshold
+    }
+    
+    /// Forget a device (e.g. when it disconnects)
+    pub fn forget(&mut self, device_id: &str) {
+        self.failures.remove(device_id);
+    }
+}
+
+impl Default for CommandFailureTracker {
+    fn default() -> Self {
+        Self::new(DEFAULT_FAILURE_ALERT_THRESHOLD)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_alert_only_at_threshold() {
+        let mut tracker = CommandFailureTracker::new(3);
+        
+        assert_eq!(tracker.record_failure("uno", "timeout"), None);
+        assert_eq!(tracker.record_failure("uno", "timeout"), None);
+        assert!(!tracker.is_alerting("uno"));
+        
+        let alert = tracker.record_failure("uno", "no reply").unwrap();
+        assert_eq!(alert.consecutive_failures, 3);
+        assert_eq!(alert.last_error, "no reply");
+        assert!(alert.message().contains("3 commands failed"));
+        assert!(tracker.is_alerting("uno"));
+        
+        // Raised once, not on every further failure
+        assert_eq!(tracker.record_failure("uno", "timeout"), None);
+        // Counts are per device
+        assert_eq!(tracker.consecutive_failures("mega"), 0);
+    }
+    
+    #[test]
+    fn test_success_clears_counter() {
+        let mut tracker = CommandFailureTracker::new(2);
+        tracker.record_failure("uno", "timeout");
+        assert!(!tracker.record_success("uno"));
+        assert_eq!(tracker.consecutive_failures("uno"), 0);
+        
+        // The count restarts, so one more failure doesn't alert
+        assert_eq!(tracker.record_failure("uno", "timeout"), None);
+        assert!(tracker.record_failure("uno", "timeout").is_some());
+        assert!(tracker.record_success("uno"));
+        assert!(!tracker.is_alerting("uno"));
+    }
+    
+    #[test]
+    fn test_zero_threshold_never_alerts() {
+        let mut tracker = CommandFailureTracker::new(0);
+        for _ in 0..10 {
+            assert_eq!(tracker.record_failure("uno", "timeout"), None);
+        }
+        assert!(!tracker.is_alerting("uno"));
+    }
+}
@@ -0,0 +1,208 @@
+//! Recorded device commands, exportable as a script
+//!
+//! `CommandHistory` keeps the commands sent to a device with the time each
+//! was sent. `to_script` turns them into a Rhai script for the Scripts tab
+//! written against the scripting API: each command becomes a `device_write`
+//! call on the device, the gaps between them become `sleep(ms)` calls, and
+//! arguments the script language can't express are kept as a comment so the
+//! script stays editable and re-runnable.
+
+use std::collections::VecDeque;
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde_json::Value;
+use crate::device::SessionCommand;
+
+/// Commands kept when no capacity is given
+pub const DEFAULT_HISTORY_CAPACITY: usize = 1000;
+
+/// Script variable holding the device handle
+const DEVICE_VAR: &str = "device";
+
+/// One recorded command
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryEntry {
+    /// When the command was sent (Unix ms)
+    pub timestamp_ms: u64,
+    pub command: SessionCommand,
+}
+
+/// Most recent commands sent to a device, oldest first
+#[derive(Debug, Clone)]
+pub struct CommandHistory {
+    entries: VecDeque<HistoryEntry>,
+    capacity: usize,
+}
+
+impl CommandHistory {
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_HISTORY_CAPACITY)
+    }
+    
+    /// History keeping at most `capacity` commands, dropping the oldest
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: VecDeque::new(),
+            capacity: capacity.max(1),
+        }
+    }
+    
+    /// Record `command` as sent now
+    pub fn record(&mut self, command: SessionCommand) {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64;
+        self.record_at(command, now);
+    }
+    
+    /// Record `command` as sent at `timestamp_ms`
+    pub fn record_at(&mut self, command: SessionCommand, timestamp_ms: u64) {
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(HistoryEntry { timestamp_ms, command });
+    }
+    
+    pub fn entries(&self) -> impl Iterator<Item = &HistoryEntry> {
+        self.entries.iter()
+    }
+    
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+    
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+    
+    /// Script replaying the history on `device_id` in order, with the recorded spacing
+    pub fn to_script(&self, device_id: &str) -> String {
+        let mut script = format!("// Recorded command history ({} commands)\n", self.entries.len());
+        script.push_str(&format!("let {} = get_device({});\n", DEVICE_VAR, Value::from(device_id)));
+        let mut previous: Option<u64> = None;
+        
+        for entry in &self.entries {
+            let gap = previous.map_or(0, |at| entry.timestamp_ms.saturating_sub(at));
+            if gap > 0 {
+                script.push_str(&format!("sleep({});\n", gap));
+            }
+            previous = Some(entry.timestamp_ms);
+            
+            script.push_str(&script_line(&entry.command));
+            script.push('\n');
+        }
+        script
+    }
+}
+
+impl Default for CommandHistory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// `device_write` call for `command`, or a comment when its arguments have no script literal
+fn script_line(command: &SessionCommand) -> String {
+    match script_literal(&Value::Array(command.args.clone())) {
+        Some(args) => format!("device_write({}, {}, {});", DEVICE_VAR, Value::from(command.endpoint.as_str()), args),
+        None => format!("// not scriptable: {}({})", command.endpoint, Value::Array(command.args.clone())),
+    }
+}
+
+/// Bool, number, string or array argument as a script literal
+/// Rhai string and array literals read the same as JSON ones
+fn script_literal(value: &Value) -> Option<String> {
+    match value {
+        Value::Bool(_) | Value::Number(_) | Value::String(_) => Some(value.to_string()),
+        Value::Array(items) => {
+            let items: Option<Vec<String>> = items.iter().map(script_literal).collect();
+            items.map(|items| format!("[{}]", items.join(", ")))
+        }
+        Value::Null | Value::Object(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    
+    fn recorded() -> CommandHistory {
+        let mut history = CommandHistory::new();
+        history.record_at(SessionCommand::new("digitalWrite", vec![json!(13), json!(true)]), 1_000);
+        history.record_at(SessionCommand::new("analogWrite", vec![json!(9), json!(128)]), 1_250);
+        history.record_at(SessionCommand::new("setServo", vec![json!(0), json!(90)]), 1_250);
+        history.record_at(SessionCommand::new("i2cWrite", vec![json!(0x40), json!("ff")]), 2_000);
+        history.record_at(SessionCommand::new("configure", vec![json!({"mode": "fast"})]), 2_000);
+        history.record_at(SessionCommand::new("digitalWrite", vec![json!(13), json!(false)]), 3_000);
+        history
+    }
+    
+    #[test]
+    fn test_script_reproduces_commands_in_order() {
+        let script = recorded().to_script("uno");
+        let lines: Vec<&str> = script.lines().skip(1).collect();
+        
+        assert_eq!(lines, vec![
+            "let device = get_device(\"uno\");",
+            "device_write(device, \"digitalWrite\", [13, true]);",
+            "sleep(250);",
+            "device_write(device, \"analogWrite\", [9, 128]);",
+            "device_write(device, \"setServo\", [0, 90]);",
+            "sleep(750);",
+            "device_write(device, \"i2cWrite\", [64, \"ff\"]);",
+            "// not scriptable: configure([{\"mode\":\"fast\"}])",
+            "sleep(1000);",
+            "device_write(device, \"digitalWrite\", [13, false]);",
+        ]);
+    }
+    
+    #[tokio::test]
+    async fn test_script_replays_commands_on_device_session() {
+        use std::sync::Arc;
+        use std::time::Duration;
+        use crate::device::DeviceManager;
+        use crate::device::mock::MockSession;
+        use crate::scripting::{DeviceApi, ScriptDeviceHandle};
+        
+        let mut history = CommandHistory::new();
+        history.record_at(SessionCommand::new("digitalWrite", vec![json!(13), json!(true)]), 1_000);
+        history.record_at(SessionCommand::new("analogWrite", vec![json!(9), json!(128)]), 1_040);
+        history.record_at(SessionCommand::new("digitalWrite", vec![json!(13), json!(false)]), 1_100);
+        
+        let session = MockSession::inert();
+        let calls = session.calls();
+        let api = Arc::new(DeviceApi::new(Arc::new(DeviceManager::new("./drivers"))));
+        api.register_device(ScriptDeviceHandle::new("uno".to_string(), Box::new(session), vec!["write".to_string()])).await;
+        let mut engine = rhai::Engine::new();
+        DeviceApi::register_api(&mut engine, api);
+        
+        // Device calls block the script, so it runs off the runtime's threads
+        let script = history.to_script("uno");
+        tokio::task::spawn_blocking(move || engine.run(&script)).await.unwrap()
+            .expect("script should run on the scripting API");
+        
+        let calls = calls.lock().unwrap();
+        let replayed: Vec<(&str, &[Value])> = calls.iter().map(|call| (call.endpoint.as_str(), call.args.as_slice())).collect();
+        assert_eq!(replayed, vec![
+            ("digitalWrite", &[json!(13), json!(true)][..]),
+            ("analogWrite", &[json!(9), json!(128)][..]),
+            ("digitalWrite", &[json!(13), json!(false)][..]),
+        ]);
+        // The recorded gaps are waited out between the calls
+        assert!(calls[1].at - calls[0].at >= Duration::from_millis(40));
+        assert!(calls[2].at - calls[1].at >= Duration::from_millis(60));
+    }
+    
+    #[test]
+    fn test_capacity_drops_oldest() {
+        let mut history = CommandHistory::with_capacity(2);
+        for pin in 0..3 {
+            history.record_at(SessionCommand::new("digitalWrite", vec![json!(pin), json!(true)]), pin);
+        }
+        
+        let pins: Vec<Value> = history.entries().map(|e| e.command.args[0].clone()).collect();
+        assert_eq!(pins, vec![json!(1), json!(2)]);
+    }
+}
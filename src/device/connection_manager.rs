@@ -1,5 +1,5 @@
 use std::sync::Arc;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use tokio::sync::{RwLock, mpsc};
 use uuid::Uuid;
 use tracing::{info, warn, error, debug};
@@ -51,6 +51,13 @@ pub enum ConnectionEvent {
         attempts: u32,
     },
     
+    /// Capabilities reported after a reconnect differ from those cached at connect
+    CapabilitiesChanged {
+        device_id: String,
+        added: Vec<String>,
+        removed: Vec<String>,
+    },
+    
     /// Device removed (unplugged)
     DeviceRemoved {
         device_id: String,
@@ -76,9 +83,36 @@ pub struct ConnectionState {
     pub reconnect_attempts: u32,
     pub last_error: Option<String>,
     pub metadata: HashMap<String, String>,
+    /// Capabilities reported when the session was opened (None if the device can't report them)
+    pub capabilities: Option<BTreeSet<String>>,
+    /// Capabilities a reconnect must still report for the session to resume
+    pub required_capabilities: BTreeSet<String>,
+}
+
+/// Difference between two capability sets
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CapabilityDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+impl CapabilityDiff {
+    pub fn between(before: &BTreeSet<String>, after: &BTreeSet<String>) -> Self {
+        CapabilityDiff {
+            added: after.difference(before).cloned().collect(),
+            removed: before.difference(after).cloned().collect(),
+        }
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
 }
 
 /// Connection manager for device lifecycle
+///
+/// Clones share the same connections, sessions and event channel.
+#[derive(Clone)]
 pub struct ConnectionManager {
     /// Active connections
     connections: Arc<RwLock<HashMap<String, ConnectionState>>>,
@@ -86,6 +120,9 @@ pub struct ConnectionManager {
     /// Active sessions
     sessions: Arc<RwLock<HashMap<String, Box<dyn DeviceSession>>>>,
     
+    /// Transport and driver each device was opened with, reused to reconnect it
+    links: Arc<RwLock<HashMap<String, (Arc<dyn Transport>, Arc<dyn DeviceDriver>)>>>,
+    
     /// Event channel
     event_tx: mpsc::UnboundedSender<ConnectionEvent>,
     event_rx: Arc<RwLock<mpsc::UnboundedReceiver<ConnectionEvent>>>,
@@ -103,6 +140,7 @@ impl ConnectionManager {
         ConnectionManager {
             connections: Arc::new(RwLock::new(HashMap::new())),
             sessions: Arc::new(RwLock::new(HashMap::new())),
+            links: Arc::new(RwLock::new(HashMap::new())),
             event_tx,
             event_rx: Arc::new(RwLock::new(event_rx)),
             max_reconnect_attempts: 5,
@@ -147,6 +185,8 @@ impl ConnectionManager {
             reconnect_attempts: 0,
             last_error: None,
             metadata: metadata.clone(),
+            capabilities: None,
+            required_capabilities: BTreeSet::new(),
         };
         
         let mut connections = self.connections.write().await;
@@ -181,8 +221,10 @@ impl ConnectionManager {
         });
         
         // Open device session
-        match driver.open_async(transport).await {
-            Ok(session) => {
+        match driver.open_async(transport.clone()).await {
+            Ok(mut session) => {
+                let capabilities = query_capabilities(session.as_mut()).await;
+                
                 // Update connection state
                 let mut connections = self.connections.write().await;
                 if let Some(state) = connections.get_mut(device_id) {
@@ -191,11 +233,13 @@ impl ConnectionManager {
                     state.connected = true;
                     state.reconnect_attempts = 0;
                     state.last_error = None;
+                    state.capabilities = capabilities;
                 }
                 
                 // Store session
                 let mut sessions = self.sessions.write().await;
                 sessions.insert(session_id.clone(), session);
+                self.links.write().await.insert(device_id.to_string(), (transport, driver.clone()));
                 
                 // Send connection established event
                 let _ = self.event_tx.send(ConnectionEvent::ConnectionEstablished {
@@ -232,6 +276,99 @@ impl ConnectionManager {
         }
     }
     
+    /// Refuse to resume the device after a reconnect unless it still reports `capabilities`
+    pub async fn set_required_capabilities(&self, device_id: &str, capabilities: BTreeSet<String>) {
+        let mut connections = self.connections.write().await;
+        if let Some(state) = connections.get_mut(device_id) {
+            state.required_capabilities = capabilities;
+        }
+    }
+    
+    /// Reopen a device whose connection was lost, re-checking its capabilities
+    ///
+    /// The firmware may have changed while the device was away, so capabilities
+    /// are queried again and compared with the cached set. Any difference emits
+    /// `CapabilitiesChanged`; if a required capability disappeared the new
+    /// session is closed and the resume fails.
+    pub async fn reconnect_device(
+        &self,
+        device_id: &str,
+        transport: Arc<dyn Transport>,
+        driver: Arc<dyn DeviceDriver>,
+    ) -> DeviceResult<String> {
+        let (cached, required, attempts) = {
+            let connections = self.connections.read().await;
+            let state = connections.get(device_id)
+                .ok_or_else(|| DeviceError::DeviceNotFound(device_id.to_string()))?;
+            (state.capabilities.clone(), state.required_capabilities.clone(), state.reconnect_attempts)
+        };
+        
+        let mut session = driver.open_async(transport.clone()).await?;
+        let capabilities = query_capabilities(session.as_mut()).await;
+        
+        if let (Some(before), Some(after)) = (&cached, &capabilities) {
+            let diff = CapabilityDiff::between(before, after);
+            if !diff.is_empty() {
+                warn!("Capabilities of {} changed on reconnect: +{:?} -{:?}", device_id, diff.added, diff.removed);
+                let _ = self.event_tx.send(ConnectionEvent::CapabilitiesChanged {
+                    device_id: device_id.to_string(),
+                    added: diff.added.clone(),
+                    removed: diff.removed.clone(),
+                });
+            }
+        }
+        
+        // A device that can no longer report capabilities can't prove it still has the required ones
+        let missing: Vec<String> = match (&cached, &capabilities) {
+            (_, Some(after)) => required.difference(after).cloned().collect(),
+            (Some(_), None) => required.iter().cloned().collect(),
+            (None, None) => Vec::new(),
+        };
+        if !missing.is_empty() {
+            let _ = session.close_async().await;
+            let e = DeviceError::UnsupportedDevice(format!(
+                "required capabilities missing after reconnect: {}", missing.join(", ")
+            ));
+            
+            let mut connections = self.connections.write().await;
+            if let Some(state) = connections.get_mut(device_id) {
+                state.last_error = Some(e.to_string());
+            }
+            let _ = self.event_tx.send(ConnectionEvent::ConnectionError {
+                device_id: device_id.to_string(),
+                error: e.to_string(),
+                recoverable: false,
+            });
+            
+            error!("Not resuming {}: {}", device_id, e);
+            return Err(e);
+        }
+        
+        let session_id = Uuid::new_v4().to_string();
+        {
+            let mut connections = self.connections.write().await;
+            if let Some(state) = connections.get_mut(device_id) {
+                state.session_id = Some(session_id.clone());
+                state.driver_name = Some(driver.name().to_string());
+                state.connected = true;
+                state.reconnect_attempts = 0;
+                state.last_error = None;
+                state.capabilities = capabilities;
+            }
+        }
+        self.sessions.write().await.insert(session_id.clone(), session);
+        self.links.write().await.insert(device_id.to_string(), (transport, driver));
+        
+        let _ = self.event_tx.send(ConnectionEvent::ReconnectionSuccessful {
+            device_id: device_id.to_string(),
+            session_id: session_id.clone(),
+            attempts,
+        });
+        
+        info!("Reconnected device {} with session {}", device_id, session_id);
+        Ok(session_id)
+    }
+    
     /// Disconnect a device
     pub async fn disconnect_device(&self, device_id: &str) -> DeviceResult<()> {
         let mut connections = self.connections.write().await;
@@ -267,6 +404,8 @@ impl ConnectionManager {
         let mut connections = self.connections.write().await;
         
         if let Some(state) = connections.remove(device_id) {
+            self.links.write().await.remove(device_id);
+            
             // Close session if active
             if let Some(session_id) = &state.session_id {
                 let mut sessions = self.sessions.write().await;
@@ -321,14 +460,17 @@ impl ConnectionManager {
     }
     
     /// Trigger automatic reconnection
+    ///
+    /// Reopens the device through `reconnect_device` with the transport and
+    /// driver it was connected with, so the capability check runs on every
+    /// resume. Stops on success, on an unrecoverable error, or once the
+    /// attempts run out.
     async fn trigger_reconnection(&self, device_id: &str) {
-        let connections = self.connections.clone();
-        let event_tx = self.event_tx.clone();
-        let max_attempts = self.max_reconnect_attempts;
-        let delay_ms = self.reconnect_delay_ms;
+        let manager = self.clone();
         let device_id = device_id.to_string();
         
         tokio::spawn(async move {
+            let max_attempts = manager.max_reconnect_attempts;
             let mut attempt = 0;
             
             loop {
@@ -336,7 +478,7 @@ impl ConnectionManager {
                 
                 // Check if we should continue
                 let should_continue = {
-                    let conns = connections.read().await;
+                    let conns = manager.connections.read().await;
                     if let Some(state) = conns.get(&device_id) {
                         !state.connected && attempt <= max_attempts
                     } else {
@@ -348,24 +490,40 @@ impl ConnectionManager {
                     break;
                 }
                 
+                let Some((transport, driver)) = manager.links.read().await.get(&device_id).cloned() else {
+                    warn!("No transport recorded for {}, not reconnecting", device_id);
+                    break;
+                };
+                
                 // Send reconnection attempt event
-                let _ = event_tx.send(ConnectionEvent::ReconnectionAttempt {
+                let _ = manager.event_tx.send(ConnectionEvent::ReconnectionAttempt {
                     device_id: device_id.clone(),
                     attempt,
                     max_attempts,
                 });
                 
                 // Wait with exponential backoff
-                let delay = delay_ms * 2u64.pow(attempt - 1);
+                let delay = manager.reconnect_delay_ms * 2u64.pow(attempt - 1);
                 tokio::time::sleep(tokio::time::Duration::from_millis(delay)).await;
                 
-                // TODO: Actual reconnection logic would go here
-                // This would involve recreating the transport and calling connect_device
+                {
+                    let mut conns = manager.connections.write().await;
+                    if let Some(state) = conns.get_mut(&device_id) {
+                        state.reconnect_attempts = attempt;
+                    }
+                }
+                
+                if !transport.is_connected() {
+                    if let Err(e) = transport.connect().await {
+                        debug!("Reconnect attempt {} for {} failed: {}", attempt, device_id, e);
+                        continue;
+                    }
+                }
                 
-                // For now, we just update the attempt counter
-                let mut conns = connections.write().await;
-                if let Some(state) = conns.get_mut(&device_id) {
-                    state.reconnect_attempts = attempt;
+                match manager.reconnect_device(&device_id, transport, driver).await {
+                    Ok(_) => break,
+                    Err(DeviceError::UnsupportedDevice(_)) | Err(DeviceError::PermissionDenied(_)) => break,
+                    Err(e) => debug!("Reconnect attempt {} for {} failed: {}", attempt, device_id, e),
                 }
             }
         });
@@ -394,6 +552,17 @@ impl ConnectionManager {
     }
 }
 
+/// Capabilities reported by `session`, or None if it can't report them
+async fn query_capabilities(session: &mut dyn DeviceSession) -> Option<BTreeSet<String>> {
+    match session.query_capabilities().await {
+        Ok(capabilities) => Some(capabilities),
+        Err(e) => {
+            debug!("Capabilities unavailable for {}: {}", session.device_name(), e);
+            None
+        }
+    }
+}
+
 impl Default for ConnectionManager {
     fn default() -> Self {
         Self::new()
@@ -422,4 +591,132 @@ mod tests {
         assert_eq!(states.len(), 1);
         assert_eq!(states[0].device_id, device_id);
     }
-}
\ No newline at end of file
+    
+    use async_trait::async_trait;
+    use std::sync::Mutex;
+    use crate::device::DriverCapabilities;
+    use crate::device::mock::MockSession;
+    use crate::transport::mock::{MockTransport, MockConfig};
+    
+    /// Driver whose sessions report whatever capabilities the current firmware has
+    struct FirmwareDriver {
+        capabilities: Arc<Mutex<Vec<&'static str>>>,
+    }
+    
+    #[async_trait]
+    impl DeviceDriver for FirmwareDriver {
+        fn name(&self) -> &str {
+            "Firmware"
+        }
+        
+        fn version(&self) -> &str {
+            "1.0.0"
+        }
+        
+        fn supported_transports(&self) -> Vec<TransportType> {
+            vec![TransportType::Serial]
+        }
+        
+        async fn probe_async(&self, _transport: Arc<dyn Transport>) -> DeviceResult<bool> {
+            Ok(true)
+        }
+        
+        async fn open_async(&self, _transport: Arc<dyn Transport>) -> DeviceResult<Box<dyn DeviceSession>> {
+            let capabilities = self.capabilities.lock().unwrap().clone();
+            Ok(Box::new(MockSession::inert().with_name("firmware", "Firmware").with_capabilities(capabilities)))
+        }
+        
+        fn capabilities(&self) -> DriverCapabilities {
+            DriverCapabilities::default()
+        }
+    }
+    
+    fn mock_transport() -> Arc<dyn Transport> {
+        Arc::new(MockTransport::new("mock".into(), TransportConfig::default(), MockConfig::default()))
+    }
+    
+    /// Connect with `pwm` + `gpio`, then "update the firmware" so `pwm` disappears
+    async fn connect_then_drop_pwm(manager: &ConnectionManager) -> (String, Arc<FirmwareDriver>) {
+        let driver = Arc::new(FirmwareDriver {
+            capabilities: Arc::new(Mutex::new(vec!["gpio", "pwm"])),
+        });
+        let device_id = manager.register_device(TransportType::Serial, "COM4".to_string(), HashMap::new()).await;
+        manager.connect_device(&device_id, mock_transport(), driver.clone()).await.unwrap();
+        manager.handle_connection_lost(&device_id, "firmware update".into(), false).await;
+        
+        *driver.capabilities.lock().unwrap() = vec!["gpio", "i2c"];
+        (device_id, driver)
+    }
+    
+    async fn drain_events(manager: &ConnectionManager) -> Vec<ConnectionEvent> {
+        let mut rx = manager.event_rx.write().await;
+        std::iter::from_fn(|| rx.try_recv().ok()).collect()
+    }
+    
+    #[tokio::test]
+    async fn test_reconnect_reports_capability_changes() {
+        let manager = ConnectionManager::new();
+        let (device_id, driver) = connect_then_drop_pwm(&manager).await;
+        drain_events(&manager).await;
+        
+        manager.reconnect_device(&device_id, mock_transport(), driver).await.unwrap();
+        
+        let events = drain_events(&manager).await;
+        assert!(events.iter().any(|e| matches!(e,
+            ConnectionEvent::CapabilitiesChanged { added, removed, .. }
+                if added == &vec!["i2c".to_string()] && removed == &vec!["pwm".to_string()]
+        )), "{:?}", events);
+        assert!(events.iter().any(|e| matches!(e, ConnectionEvent::ReconnectionSuccessful { .. })));
+        
+        assert!(manager.is_connected(&device_id).await);
+        let state = manager.get_connection_states().await.remove(0);
+        assert_eq!(state.capabilities.unwrap(), BTreeSet::from(["gpio".to_string(), "i2c".to_string()]));
+    }
+    
+    #[tokio::test]
+    async fn test_reconnect_refuses_when_required_capability_removed() {
+        let manager = ConnectionManager::new();
+        let (device_id, driver) = connect_then_drop_pwm(&manager).await;
+        manager.set_required_capabilities(&device_id, BTreeSet::from(["pwm".to_string()])).await;
+        drain_events(&manager).await;
+        
+        let result = manager.reconnect_device(&device_id, mock_transport(), driver).await;
+        assert!(matches!(result, Err(DeviceError::UnsupportedDevice(ref msg)) if msg.contains("pwm")));
+        
+        let events = drain_events(&manager).await;
+        assert!(events.iter().any(|e| matches!(e, ConnectionEvent::CapabilitiesChanged { .. })));
+        assert!(events.iter().any(|e| matches!(e, ConnectionEvent::ConnectionError { recoverable: false, .. })));
+        assert!(!manager.is_connected(&device_id).await);
+        assert!(manager.sessions.read().await.is_empty());
+    }
+    
+    #[tokio::test]
+    async fn test_auto_reconnect_runs_capability_check() {
+        let mut manager = ConnectionManager::new();
+        manager.reconnect_delay_ms = 1;
+        let driver = Arc::new(FirmwareDriver {
+            capabilities: Arc::new(Mutex::new(vec!["gpio", "pwm"])),
+        });
+        let device_id = manager.register_device(TransportType::Serial, "COM5".to_string(), HashMap::new()).await;
+        manager.connect_device(&device_id, mock_transport(), driver.clone()).await.unwrap();
+        drain_events(&manager).await;
+        
+        *driver.capabilities.lock().unwrap() = vec!["gpio"];
+        manager.handle_connection_lost(&device_id, "cable pulled".into(), true).await;
+        
+        let mut events = Vec::new();
+        for _ in 0..100 {
+            events.extend(drain_events(&manager).await);
+            if events.iter().any(|e| matches!(e, ConnectionEvent::ReconnectionSuccessful { .. })) {
+                break;
+            }
+            tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
+        }
+        
+        assert!(events.iter().any(|e| matches!(e,
+            ConnectionEvent::CapabilitiesChanged { removed, .. } if removed == &vec!["pwm".to_string()]
+        )), "{:?}", events);
+        assert!(events.iter().any(|e| matches!(e, ConnectionEvent::ReconnectionSuccessful { attempts: 1, .. })));
+        assert!(manager.is_connected(&device_id).await);
+    }
+}
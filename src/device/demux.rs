@@ -0,0 +1,245 @@
+//! Demultiplexing of channel-tagged stream lines
+//!
+//! Some firmwares interleave several sensor streams over one connection,
+//! tagging each line with its channel (e.g. `CH:temp 25`). `StreamDemux`
+//! routes each tagged value to the handler registered for that channel so a
+//! single reader task can feed many telemetry channels.
+
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use crate::device::session::{StreamData, SubscriptionHandle};
+use crate::transport::{CommandCodec, TransportError};
+
+/// Default tag that starts a channel-tagged line
+pub const DEFAULT_CHANNEL_PREFIX: &str = "CH:";
+
+/// Routes `<prefix><channel> <value>` lines to per-channel handlers
+pub struct StreamDemux {
+    prefix: String,
+    handlers: HashMap<String, mpsc::UnboundedSender<StreamData>>,
+    /// Receives untagged lines and channels without a handler
+    fallback: Option<mpsc::UnboundedSender<StreamData>>,
+    sequences: HashMap<String, u64>,
+}
+
+impl StreamDemux {
+    /// Create a demultiplexer for `CH:` tagged lines
+    pub fn new() -> Self {
+        Self {
+            prefix: DEFAULT_CHANNEL_PREFIX.to_string(),
+            handlers: HashMap::new(),
+            fallback: None,
+            sequences: HashMap::new(),
+        }
+    }
+    
+    /// Use a different channel tag
+    pub fn with_prefix(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.to_string();
+        self
+    }
+    
+    /// Send values tagged with `channel` to `handler`
+    pub fn with_channel(mut self, channel: &str, handler: mpsc::UnboundedSender<StreamData>) -> Self {
+        self.route(channel, handler);
+        self
+    }
+    
+    /// Send lines no channel handler claims to `handler`
+    pub fn with_fallback(mut self, handler: mpsc::UnboundedSender<StreamData>) -> Self {
+        self.fallback = Some(handler);
+        self
+    }
+    
+    /// Register (or replace) the handler for a channel
+    pub fn route(&mut self, channel: &str, handler: mpsc::UnboundedSender<StreamData>) {
+        self.handlers.insert(channel.to_string(), handler);
+    }
+    
+    /// Channels with a registered handler
+    pub fn channels(&self) -> Vec<String> {
+        let mut channels: Vec<_> = self.handlers.keys().cloned().collect();
+        channels.sort();
+        channels
+    }
+    
+    /// Split a tagged line into its channel and value
+    pub fn parse_line<'a>(&self, line: &'a str) -> Option<(&'a str, Value)> {
+        let rest = line.trim().strip_prefix(self.prefix.as_str())?;
+        let (channel, value) = match rest.split_once(char::is_whitespace) {
+            Some((channel, value)) => (channel, value.trim()),
+            None => (rest, ""),
+        };
+        if channel.is_empty() {
+            return None;
+        }
+        Some((channel, parse_value(value)))
+    }
+    
+    /// Route one line; returns false if nothing accepted it
+    pub fn dispatch(&mut self, line: &str) -> bool {
+        let (stream, data) = match self.parse_line(line) {
+            Some((channel, value)) => (channel.to_string(), value),
+            None => (String::new(), Value::String(line.trim().to_string())),
+        };
+        
+        let handler = match self.handlers.get(&stream).or(self.fallback.as_ref()) {
+            Some(handler) => handler,
+            None => return false,
+        };
+        
+        let sequence = self.sequences.entry(stream.clone()).or_insert(0);
+        *sequence += 1;
+        
+        handler.send(StreamData {
+            stream,
+            timestamp: now_ms(),
+            data,
+            sequence: *sequence,
+        }).is_ok()
+    }
+    
+    /// Read lines from `codec` on a background task and dispatch them
+    /// The task stops when the returned handle is dropped or the transport fails
+    pub fn spawn(mut self, codec: Arc<CommandCodec>) -> SubscriptionHandle {
+        let (stop_tx, mut stop_rx) = mpsc::channel::<String>(1);
+        
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    _ = stop_rx.recv() => break,
+                    line = codec.read_line() => match line {
+                        Ok(line) if !line.is_empty() => {
+                            if !self.dispatch(&line) {
+                                tracing::trace!("Unrouted stream line: {}", line);
+                            }
+                        }
+                        Ok(_) | Err(TransportError::Timeout(_)) => continue,
+                        Err(e) => {
+                            tracing::warn!("Demultiplexed stream reader stopped: {}", e);
+                            break;
+                        }
+                    },
+                }
+            }
+        });
+        
+        SubscriptionHandle::new(format!("demux_{}", uuid::Uuid::new_v4()), stop_tx)
+    }
+}
+
+impl Default for StreamDemux {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Interpret a value as a number or bool where possible, otherwise text
+pub(crate) fn parse_value(text: &str) -> Value {
+    if let Ok(i) = text.parse::<i64>() {
+        Value::from(i)
+    } else if let Ok(f) = text.parse::<f64>() {
+        Value::from(f)
+    } else if let Ok(b) = text.parse::<bool>() {
+        Value::Bool(b)
+    } else {
+        Value::String(text.to_string())
+    }
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::time::Duration;
+    use crate::transport::mock::MockTransport;
+    
+    fn drain(rx: &mut mpsc::UnboundedReceiver<StreamData>) -> Vec<StreamData> {
+        let mut items = Vec::new();
+        while let Ok(item) = rx.try_recv() {
+            items.push(item);
+        }
+        items
+    }
+    
+    #[test]
+    fn test_parse_line() {
+        let demux = StreamDemux::new();
+        assert_eq!(demux.parse_line("CH:temp 25"), Some(("temp", json!(25))));
+        assert_eq!(demux.parse_line("  CH:volts 3.3\r"), Some(("volts", json!(3.3))));
+        assert_eq!(demux.parse_line("CH:door true"), Some(("door", json!(true))));
+        assert_eq!(demux.parse_line("CH:mode idle"), Some(("mode", json!("idle"))));
+        assert_eq!(demux.parse_line("OK"), None);
+        assert_eq!(demux.parse_line("CH: 5"), None);
+        
+        let demux = StreamDemux::new().with_prefix("#");
+        assert_eq!(demux.parse_line("#rpm 1200"), Some(("rpm", json!(1200))));
+    }
+    
+    #[tokio::test]
+    async fn test_interleaved_channels_routed() {
+        let (temp_tx, mut temp_rx) = mpsc::unbounded_channel();
+        let (rpm_tx, mut rpm_rx) = mpsc::unbounded_channel();
+        let (other_tx, mut other_rx) = mpsc::unbounded_channel();
+        
+        let transport = Arc::new(MockTransport::scripted(|_| Vec::new()));
+        for chunk in [
+            "CH:temp 25\r\nCH:rpm 1200\r\n",
+            "CH:temp 2",
+            "6\r\nCH:rpm 1250\r\nREADY\r\n",
+            "CH:rpm 1300\r\n",
+        ] {
+            transport.inject_receive_data(chunk.as_bytes().to_vec()).await.unwrap();
+        }
+        let codec = Arc::new(CommandCodec::new(transport).with_timeout(Duration::from_millis(50)));
+        
+        let handle = StreamDemux::new()
+            .with_channel("temp", temp_tx)
+            .with_channel("rpm", rpm_tx)
+            .with_fallback(other_tx)
+            .spawn(codec);
+        
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        
+        let temps = drain(&mut temp_rx);
+        assert_eq!(temps.iter().map(|d| d.data.clone()).collect::<Vec<_>>(), vec![json!(25), json!(26)]);
+        assert!(temps.iter().all(|d| d.stream == "temp"));
+        assert_eq!(temps.iter().map(|d| d.sequence).collect::<Vec<_>>(), vec![1, 2]);
+        
+        let rpms = drain(&mut rpm_rx);
+        assert_eq!(
+            rpms.iter().map(|d| d.data.clone()).collect::<Vec<_>>(),
+            vec![json!(1200), json!(1250), json!(1300)]
+        );
+        
+        let others = drain(&mut other_rx);
+        assert_eq!(others.len(), 1);
+        assert_eq!(others[0].data, json!("READY"));
+        
+        drop(handle);
+    }
+    
+    #[test]
+    fn test_unrouted_lines_dropped_without_fallback() {
+        let (temp_tx, mut temp_rx) = mpsc::unbounded_channel();
+        let mut demux = StreamDemux::new().with_channel("temp", temp_tx);
+        
+        assert!(!demux.dispatch("CH:humidity 40"));
+        assert!(!demux.dispatch("hello"));
+        assert!(demux.dispatch("CH:temp 21.5"));
+        
+        let temps = drain(&mut temp_rx);
+        assert_eq!(temps.len(), 1);
+        assert_eq!(temps[0].data, json!(21.5));
+        assert_eq!(demux.channels(), vec!["temp".to_string()]);
+    }
+}
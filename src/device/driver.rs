@@ -2,6 +2,7 @@ use async_trait::async_trait;
 use serde::{Serialize, Deserialize};
 use std::sync::Arc;
 use crate::device::{DeviceResult, DeviceError, Transport, TransportType, DeviceSession};
+use crate::protocols::handshake::{IdentifyResponse, Version, check_minimum_firmware};
 
 /// Device driver interface (equivalent to IDeviceDriver)
 /// All device plugins must implement this trait
@@ -23,6 +24,32 @@ pub trait DeviceDriver: Send + Sync {
     /// Open a device session for communication
     async fn open_async(&self, transport: Arc<dyn Transport>) -> DeviceResult<Box<dyn DeviceSession>>;
     
+    /// Query the device identity via the handshake protocol
+    /// Drivers that don't speak the handshake protocol return None
+    async fn identify_async(&self, _transport: Arc<dyn Transport>) -> DeviceResult<Option<IdentifyResponse>> {
+        Ok(None)
+    }
+    
+    /// Open a device session, refusing firmware older than `min_firmware`
+    /// The firmware check runs before the session is established
+    async fn open_with_min_firmware_async(
+        &self,
+        transport: Arc<dyn Transport>,
+        min_firmware: Option<Version>,
+    ) -> DeviceResult<Box<dyn DeviceSession>> {
+        if let Some(minimum) = min_firmware {
+            let identity = self.identify_async(transport.clone()).await?.ok_or_else(|| {
+                DeviceError::UnsupportedDevice(format!(
+                    "{} cannot report firmware version (minimum {} required)",
+                    self.name(), minimum
+                ))
+            })?;
+            check_minimum_firmware(&identity, &minimum)?;
+        }
+        
+        self.open_async(transport).await
+    }
+    
     /// Get driver capabilities
     fn capabilities(&self) -> DriverCapabilities;
     
@@ -69,6 +96,23 @@ pub struct DriverCapabilities {
     
     /// Minimum latency requirement (ms)
     pub min_latency_ms: Option<u32>,
+    
+    /// ADC resolution in bits (None = 10-bit default)
+    #[serde(default)]
+    pub adc_resolution_bits: Option<u8>,
+}
+
+impl DriverCapabilities {
+    /// Default ADC resolution when a driver doesn't report one
+    pub const DEFAULT_ADC_RESOLUTION_BITS: u8 = 10;
+    
+    /// Largest value an analog read can legitimately return
+    pub fn max_analog_value(&self) -> u16 {
+        let bits = self.adc_resolution_bits
+            .unwrap_or(Self::DEFAULT_ADC_RESOLUTION_BITS)
+            .clamp(1, 16);
+        ((1u32 << bits) - 1) as u16
+    }
 }
 
 impl Default for DriverCapabilities {
@@ -84,6 +128,7 @@ impl Default for DriverCapabilities {
             requires_auth: false,
             max_data_rate: None,
             min_latency_ms: Some(50), // Default 50ms latency requirement
+            adc_resolution_bits: None,
         }
     }
 }
@@ -131,4 +176,80 @@ impl DriverInfo {
         self.priority = priority;
         self
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocols::handshake::{HandshakeError, MessageExamples};
+    use crate::transport::TransportConfig;
+    use crate::transport::mock::{MockTransport, MockConfig};
+    
+    /// Driver stub reporting a fixed firmware version through the handshake
+    struct FirmwareStubDriver {
+        firmware_version: String,
+    }
+    
+    #[async_trait]
+    impl DeviceDriver for FirmwareStubDriver {
+        fn name(&self) -> &str {
+            "Firmware Stub"
+        }
+        
+        fn version(&self) -> &str {
+            "1.0.0"
+        }
+        
+        fn supported_transports(&self) -> Vec<TransportType> {
+            vec![TransportType::Serial]
+        }
+        
+        async fn probe_async(&self, _transport: Arc<dyn Transport>) -> DeviceResult<bool> {
+            Ok(true)
+        }
+        
+        async fn identify_async(&self, _transport: Arc<dyn Transport>) -> DeviceResult<Option<IdentifyResponse>> {
+            let mut response = MessageExamples::identify_response_success();
+            response.firmware_version = self.firmware_version.clone();
+            Ok(Some(response))
+        }
+        
+        async fn open_async(&self, _transport: Arc<dyn Transport>) -> DeviceResult<Box<dyn DeviceSession>> {
+            Err(DeviceError::Unknown("session opened".into()))
+        }
+        
+        fn capabilities(&self) -> DriverCapabilities {
+            DriverCapabilities::default()
+        }
+    }
+    
+    fn mock_transport() -> Arc<dyn Transport> {
+        Arc::new(MockTransport::new("mock".into(), TransportConfig::default(), MockConfig::default()))
+    }
+    
+    #[tokio::test]
+    async fn test_open_rejects_old_firmware() {
+        let driver = FirmwareStubDriver { firmware_version: "1.4.2".into() };
+        
+        let result = driver.open_with_min_firmware_async(mock_transport(), Some(Version::new(2, 0, 0))).await;
+        match result {
+            Err(DeviceError::Handshake(HandshakeError::IncompatibleFirmware { device_version, minimum_required, .. })) => {
+                assert_eq!(device_version, "1.4.2");
+                assert_eq!(minimum_required, "2.0.0");
+            }
+            Err(e) => panic!("Expected IncompatibleFirmware, got {}", e),
+            Ok(_) => panic!("Expected IncompatibleFirmware, session was opened"),
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_open_accepts_equal_or_newer_firmware() {
+        for firmware in ["2.0.0", "2.3.1"] {
+            let driver = FirmwareStubDriver { firmware_version: firmware.into() };
+            
+            // The stub's open_async reports reaching session creation via this error
+            let result = driver.open_with_min_firmware_async(mock_transport(), Some(Version::new(2, 0, 0))).await;
+            assert!(matches!(result, Err(DeviceError::Unknown(ref msg)) if msg == "session opened"));
+        }
+    }
+}
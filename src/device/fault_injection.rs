@@ -0,0 +1,237 @@
+//! Deterministic fault injection for driver tests
+//!
+//! `FaultInjectingSession` wraps any session (typically a driver session over a
+//! scripted or simulated transport) and fails the invocations matched by its
+//! rules with a configured error, passing everything else through. Rules match
+//! on endpoint, exact arguments and/or the 1-based index of the command, so a
+//! scenario such as "protocol error on the 3rd command" or "timeout on reads of
+//! pin 5" plays out identically every run; `reset` replays it from the start.
+
+use async_trait::async_trait;
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use crate::device::{DeviceResult, DeviceError, DeviceSession};
+use crate::device::clock_sync::ClockOffset;
+use crate::protocols::handshake::NegotiatedVersion;
+use crate::device::response_parser::ResponseParser;
+use crate::device::session::{StreamData, SubscriptionHandle, SessionStatistics, InputPinSet, EndpointMetrics};
+
+/// Error returned by a matching rule
+#[derive(Debug, Clone, PartialEq)]
+pub enum InjectedFault {
+    Protocol(String),
+    Timeout(u64),
+    NotConnected,
+    DeviceRejection(String),
+}
+
+impl InjectedFault {
+    fn to_error(&self) -> DeviceError {
+        match self {
+            InjectedFault::Protocol(msg) => DeviceError::Protocol(msg.clone()),
+            InjectedFault::Timeout(ms) => DeviceError::Timeout(*ms),
+            InjectedFault::NotConnected => DeviceError::NotConnected,
+            InjectedFault::DeviceRejection(msg) => DeviceError::DeviceRejection(msg.clone()),
+        }
+    }
+}
+
+/// Which invocations to fail, and how
+/// Unset criteria match anything, so a bare rule fails every command
+#[derive(Debug, Clone)]
+pub struct FaultRule {
+    endpoint: Option<String>,
+    args: Option<Vec<Value>>,
+    call: Option<u64>,
+    times: Option<u32>,
+    fault: InjectedFault,
+}
+
+impl FaultRule {
+    pub fn new(fault: InjectedFault) -> Self {
+        Self {
+            endpoint: None,
+            args: None,
+            call: None,
+            times: None,
+            fault,
+        }
+    }
+    
+    /// Only match this endpoint
+    pub fn with_endpoint(mut self, endpoint: &str) -> Self {
+        self.endpoint = Some(endpoint.to_string());
+        self
+    }
+    
+    /// Only match these exact arguments
+    pub fn with_args(mut self, args: Vec<Value>) -> Self {
+        self.args = Some(args);
+        self
+    }
+    
+    /// Only match the `n`th command sent through the session (1-based)
+    pub fn on_call(mut self, n: u64) -> Self {
+        self.call = Some(n);
+        self
+    }
+    
+    /// Stop firing after `n` injections
+    pub fn times(mut self, n: u32) -> Self {
+        self.times = Some(n);
+        self
+    }
+    
+    fn matches(&self, call: u64, endpoint: &str, args: &[Value]) -> bool {
+        self.endpoint.as_deref().is_none_or(|e| e == endpoint)
+            && self.args.as_deref().is_none_or(|a| a == args)
+            && self.call.is_none_or(|n| n == call)
+    }
+}
+
+/// One injected failure, for asserting on what a test scenario exercised
+#[derive(Debug, Clone, PartialEq)]
+pub struct InjectedCall {
+    pub call: u64,
+    pub endpoint: String,
+    pub fault: InjectedFault,
+}
+
+/// Session wrapper that fails invocations matching its rules
+/// The first matching rule wins
+pub struct FaultInjectingSession {
+    inner: Box<dyn DeviceSession>,
+    rules: Vec<FaultRule>,
+    fired: Vec<u32>,
+    calls: u64,
+    injected: Vec<InjectedCall>,
+}
+
+impl FaultInjectingSession {
+    pub fn new(inner: Box<dyn DeviceSession>) -> Self {
+        Self {
+            inner,
+            rules: Vec::new(),
+            fired: Vec::new(),
+            calls: 0,
+            injected: Vec::new(),
+        }
+    }
+    
+    /// Add a rule, checked after any added earlier
+    pub fn with_rule(mut self, rule: FaultRule) -> Self {
+        self.rules.push(rule);
+        self.fired.push(0);
+        self
+    }
+    
+    /// Failures injected so far, in order
+    pub fn injected(&self) -> &[InjectedCall] {
+        &self.injected
+    }
+    
+    /// Restart the command count and rule budgets so the scenario replays identically
+    pub fn reset(&mut self) {
+        self.calls = 0;
+        self.fired.iter_mut().for_each(|f| *f = 0);
+        self.injected.clear();
+    }
+    
+    /// Wrapped session
+    pub fn inner(&self) -> &dyn DeviceSession {
+        self.inner.as_ref()
+    }
+}
+
+#[async_trait]
+impl DeviceSession for FaultInjectingSession {
+    fn session_id(&self) -> &str {
+        self.inner.session_id()
+    }
+    
+    fn device_name(&self) -> &str {
+        self.inner.device_name()
+    }
+    
+    async fn invoke_async(&mut self, endpoint: &str, args: Vec<Value>) -> DeviceResult<Value> {
+        self.calls += 1;
+        let call = self.calls;
+        
+        let hit = self.rules.iter().zip(&self.fired).position(|(rule, &fired)| {
+            rule.times.is_none_or(|limit| fired < limit) && rule.matches(call, endpoint, &args)
+        });
+        
+        if let Some(index) = hit {
+            self.fired[index] += 1;
+            let fault = self.rules[index].fault.clone();
+            tracing::debug!("Injecting {:?} into call {} ({})", fault, call, endpoint);
+            self.injected.push(InjectedCall {
+                call,
+                endpoint: endpoint.to_string(),
+                fault: fault.clone(),
+            });
+            return Err(fault.to_error());
+        }
+        
+        self.inner.invoke_async(endpoint, args).await
+    }
+    
+    async fn subscribe_async(
+        &mut self,
+        stream: &str,
+        handler: mpsc::UnboundedSender<StreamData>,
+    ) -> DeviceResult<SubscriptionHandle> {
+        self.inner.subscribe_async(stream, handler).await
+    }
+    
+    async fn close_async(&mut self) -> DeviceResult<()> {
+        self.inner.close_async().await
+    }
+    
+    fn is_active(&self) -> bool {
+        self.inner.is_active()
+    }
+    
+    fn statistics(&self) -> SessionStatistics {
+        self.inner.statistics()
+    }
+    
+    fn command_metrics(&self) -> HashMap<String, EndpointMetrics> {
+        self.inner.command_metrics()
+    }
+    
+    async fn send_raw(&mut self, data: &[u8]) -> DeviceResult<Vec<u8>> {
+        self.inner.send_raw(data).await
+    }
+    
+    async fn sync_clock(&mut self) -> DeviceResult<ClockOffset> {
+        self.inner.sync_clock().await
+    }
+    
+    fn clock_offset(&self) -> Option<ClockOffset> {
+        self.inner.clock_offset()
+    }
+    
+    fn clock_resync_due(&self) -> bool {
+        self.inner.clock_resync_due()
+    }
+    
+    fn negotiated_version(&self) -> Option<NegotiatedVersion> {
+        self.inner.negotiated_version()
+    }
+    
+    fn set_response_parser(&mut self, parser: Arc<dyn ResponseParser>) -> DeviceResult<()> {
+        self.inner.set_response_parser(parser)
+    }
+    
+    async fn query_capabilities(&mut self) -> DeviceResult<BTreeSet<String>> {
+        self.inner.query_capabilities().await
+    }
+    
+    // `read_all_inputs` keeps the default so its per-pin reads go through the rules
+    async fn readable_inputs(&self) -> InputPinSet {
+        self.inner.readable_inputs().await
+    }
+}
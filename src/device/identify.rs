@@ -0,0 +1,222 @@
+//! Blink a board's LED so it can be found on the bench
+//!
+//! With several identical boards connected there is no telling which session
+//! drives which board. `identify_physical` toggles an output (the on-board LED
+//! on pin 13 unless the board's profile says otherwise) in a short-short-long
+//! pattern that is easy to spot among blinking status LEDs.
+
+use serde::{Serialize, Deserialize};
+use serde_json::json;
+use std::time::Duration;
+use crate::device::{DeviceResult, DeviceSession, SharedSession};
+
+/// On-board LED pin on Uno/Mega class boards
+pub const DEFAULT_IDENTIFY_PIN: u8 = 13;
+
+/// Pin and timing of an identify blink
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IdentifyPattern {
+    pub pin: u8,
+    /// How long the pin stays high for each flash, in order
+    pub flashes_ms: Vec<u64>,
+    /// Low time between flashes
+    pub gap_ms: u64,
+    /// Times the whole pattern is played
+    pub repeats: u32,
+}
+
+impl Default for IdentifyPattern {
+    fn default() -> Self {
+        Self {
+            pin: DEFAULT_IDENTIFY_PIN,
+            flashes_ms: vec![150, 150, 600],
+            gap_ms: 150,
+            repeats: 2,
+        }
+    }
+}
+
+impl IdentifyPattern {
+    /// Default pattern on `pin`
+    pub fn new(pin: u8) -> Self {
+        Self {
+            pin,
+            ..Default::default()
+        }
+    }
+    
+    pub fn with_flashes(mut self, flashes_ms: Vec<u64>) -> Self {
+        self.flashes_ms = flashes_ms;
+        self
+    }
+    
+    pub fn with_gap(mut self, gap_ms: u64) -> Self {
+        self.gap_ms = gap_ms;
+        self
+    }
+    
+    pub fn with_repeats(mut self, repeats: u32) -> Self {
+        self.repeats = repeats;
+        self
+    }
+    
+    /// Pin levels to write in order, each with the time to hold it
+    /// (the final low write is not held)
+    pub fn steps(&self) -> Vec<(bool, Duration)> {
+        let mut steps = Vec::new();
+        for _ in 0..self.repeats {
+            for &on_ms in &self.flashes_ms {
+                steps.push((true, Duration::from_millis(on_ms)));
+                steps.push((false, Duration::from_millis(self.gap_ms)));
+            }
+        }
+        if let Some(last) = steps.last_mut() {
+            last.1 = Duration::ZERO;
+        }
+        steps
+    }
+    
+    /// Time the whole pattern takes to play
+    pub fn duration(&self) -> Duration {
+        self.steps().iter().map(|(_, hold)| *hold).sum()
+    }
+}
+
+/// Blink `pattern` on the session's board with `pinMode` and `digitalWrite` calls
+///
+/// If a write fails partway through, the pin is driven low (best effort)
+/// so the LED isn't left on.
+pub async fn identify_physical(session: &mut dyn DeviceSession, pattern: &IdentifyPattern) -> DeviceResult<()> {
+    tracing::info!("Identifying {} on pin {}", session.device_name(), pattern.pin);
+    session.invoke_async("pinMode", vec![json!(pattern.pin), json!("OUTPUT")]).await?;
+    
+    for (level, hold) in pattern.steps() {
+        if let Err(e) = session.invoke_async("digitalWrite", vec![json!(pattern.pin), json!(level)]).await {
+            let _ = session.invoke_async("digitalWrite", vec![json!(pattern.pin), json!(false)]).await;
+            return Err(e);
+        }
+        if !hold.is_zero() {
+            tokio::time::sleep(hold).await;
+        }
+    }
+    
+    Ok(())
+}
+
+/// `identify_physical` on a shared session, locking it only for each write
+/// so commands from other users still get through while the pattern plays
+pub async fn identify_shared(session: &SharedSession, pattern: &IdentifyPattern) -> DeviceResult<()> {
+    let write = |level: bool| async move {
+        session.lock().await.invoke_async("digitalWrite", vec![json!(pattern.pin), json!(level)]).await
+    };
+    
+    {
+        let mut session = session.lock().await;
+        tracing::info!("Identifying {} on pin {}", session.device_name(), pattern.pin);
+        session.invoke_async("pinMode", vec![json!(pattern.pin), json!("OUTPUT")]).await?;
+    }
+    
+    for (level, hold) in pattern.steps() {
+        if let Err(e) = write(level).await {
+            let _ = write(false).await;
+            return Err(e);
+        }
+        if !hold.is_zero() {
+            tokio::time::sleep(hold).await;
+        }
+    }
+    
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use crate::device::DeviceError;
+    use crate::device::mock::{MockCall, MockSession};
+    
+    /// Session that records every call with the time it arrived, failing call number `fail_after`
+    fn recording_session(fail_after: Option<usize>) -> MockSession {
+        let count = AtomicUsize::new(0);
+        MockSession::new(move |_, _| {
+            let count = count.fetch_add(1, Ordering::Relaxed) + 1;
+            match fail_after {
+                Some(n) if count == n => Err(DeviceError::Timeout(100)),
+                _ => Ok(json!({ "success": true })),
+            }
+        })
+    }
+    
+    fn calls(session: &MockSession) -> Vec<MockCall> {
+        session.calls().lock().unwrap().clone()
+    }
+    
+    #[test]
+    fn test_pattern_steps() {
+        let pattern = IdentifyPattern::new(7).with_flashes(vec![10, 30]).with_gap(20).with_repeats(2);
+        let ms = |ms| Duration::from_millis(ms);
+        assert_eq!(pattern.steps(), vec![
+            (true, ms(10)), (false, ms(20)), (true, ms(30)), (false, ms(20)),
+            (true, ms(10)), (false, ms(20)), (true, ms(30)), (false, ms(0)),
+        ]);
+        assert_eq!(pattern.duration(), ms(140));
+        assert!(IdentifyPattern::default().with_repeats(0).steps().is_empty());
+    }
+    
+    #[tokio::test]
+    async fn test_identify_writes_pattern_with_timing() {
+        let pattern = IdentifyPattern::new(4).with_flashes(vec![20, 60]).with_gap(30).with_repeats(1);
+        let mut session = recording_session(None);
+        identify_physical(&mut session, &pattern).await.unwrap();
+        let calls = calls(&session);
+        
+        assert_eq!(calls[0].endpoint, "pinMode");
+        assert_eq!(calls[0].args, vec![json!(4), json!("OUTPUT")]);
+        
+        let writes = &calls[1..];
+        let levels: Vec<Value> = writes.iter().map(|call| call.args[1].clone()).collect();
+        assert!(writes.iter().all(|call| call.endpoint == "digitalWrite" && call.args[0] == json!(4)));
+        assert_eq!(levels, vec![json!(true), json!(false), json!(true), json!(false)]);
+        
+        // Each write is held at least as long as the pattern says
+        for (window, (_, hold)) in writes.windows(2).zip(pattern.steps()) {
+            let held = window[1].at - window[0].at;
+            assert!(held >= hold, "held {:?}, expected {:?}", held, hold);
+            assert!(held < hold + Duration::from_millis(200), "held {:?}, expected {:?}", held, hold);
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_failed_write_turns_pin_off() {
+        let pattern = IdentifyPattern::default().with_flashes(vec![1]).with_gap(1);
+        let mut session = recording_session(Some(3));
+        
+        assert!(identify_physical(&mut session, &pattern).await.is_err());
+        let calls = calls(&session);
+        let last = calls.last().unwrap();
+        assert_eq!(last.endpoint, "digitalWrite");
+        assert_eq!(last.args, vec![json!(DEFAULT_IDENTIFY_PIN), json!(false)]);
+        assert_eq!(calls.len(), 4);
+    }
+    
+    #[tokio::test]
+    async fn test_shared_identify_releases_session_between_steps() {
+        let pattern = IdentifyPattern::default().with_flashes(vec![300]).with_repeats(1);
+        let session: SharedSession = Arc::new(tokio::sync::Mutex::new(Box::new(recording_session(None))));
+        
+        let blinking = session.clone();
+        let identify = tokio::spawn(async move { identify_shared(&blinking, &pattern).await });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        
+        // Another caller gets the session while the LED is held on
+        let mut other = tokio::time::timeout(Duration::from_millis(100), session.lock()).await
+            .expect("session locked for the whole pattern");
+        other.invoke_async("analogRead", vec![json!(0)]).await.unwrap();
+        drop(other);
+        
+        identify.await.unwrap().unwrap();
+    }
+}
@@ -0,0 +1,101 @@
+//! Host-side keep-alive for firmware with its own watchdog
+//!
+//! Some firmwares reset themselves when no command arrives within a timeout,
+//! which drops an idle connection. `KeepAlive` runs a background task that
+//! sends a harmless command whenever the session has been idle for the
+//! configured interval; every real command pushes the next ping back.
+
+use parking_lot::Mutex;
+use serde::{Serialize, Deserialize};
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tokio::time::Instant;
+use tokio::task::JoinHandle;
+use crate::device::DeviceResult;
+
+/// Command and idle interval of a keep-alive
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeepAliveSettings {
+    /// No-op command the firmware accepts without side effects
+    pub command: String,
+    /// Idle time before a ping is sent (0 = disabled)
+    pub interval_ms: u64,
+}
+
+impl KeepAliveSettings {
+    pub fn new(command: &str, interval: Duration) -> Self {
+        Self {
+            command: command.to_string(),
+            interval_ms: interval.as_millis() as u64,
+        }
+    }
+    
+    pub fn interval(&self) -> Duration {
+        Duration::from_millis(self.interval_ms)
+    }
+    
+    pub fn is_enabled(&self) -> bool {
+        self.interval_ms > 0 && !self.command.is_empty()
+    }
+}
+
+/// Running keep-alive task; stopped when dropped
+pub struct KeepAlive {
+    last_activity: Arc<Mutex<Instant>>,
+    pings_sent: Arc<AtomicU64>,
+    task: JoinHandle<()>,
+}
+
+impl KeepAlive {
+    /// Call `ping` each time nothing has happened for `interval`
+    ///
+    /// A failed ping is logged and the next one is attempted an interval later.
+    pub fn spawn<F, Fut>(interval: Duration, ping: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = DeviceResult<()>> + Send,
+    {
+        let last_activity = Arc::new(Mutex::new(Instant::now()));
+        let pings_sent = Arc::new(AtomicU64::new(0));
+        
+        let task = {
+            let last_activity = last_activity.clone();
+            let pings_sent = pings_sent.clone();
+            tokio::spawn(async move {
+                loop {
+                    let idle = last_activity.lock().elapsed();
+                    if idle < interval {
+                        tokio::time::sleep(interval - idle).await;
+                        continue;
+                    }
+                    
+                    if let Err(e) = ping().await {
+                        tracing::warn!("Keep-alive ping failed: {}", e);
+                    }
+                    pings_sent.fetch_add(1, Ordering::Relaxed);
+                    *last_activity.lock() = Instant::now();
+                }
+            })
+        };
+        
+        Self { last_activity, pings_sent, task }
+    }
+    
+    /// Record real traffic, restarting the idle interval
+    pub fn touch(&self) {
+        *self.last_activity.lock() = Instant::now();
+    }
+    
+    /// Pings sent since the keep-alive started
+    pub fn pings_sent(&self) -> u64 {
+        self.pings_sent.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for KeepAlive {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
@@ -1,14 +1,53 @@
 use std::sync::Arc;
-use std::collections::HashMap;
-use tokio::sync::RwLock;
+use std::collections::{HashMap, HashSet};
+use tokio::sync::{Mutex, RwLock};
 use tokio::sync::mpsc;
+use tokio::task::JoinSet;
 use notify::{Watcher, RecursiveMode, Event};
 use crate::device::{
     DeviceResult, DeviceError, DeviceDriver, DeviceSession, 
     Transport, PluginLoader, SafetyController, EmergencyStop
 };
 use crate::device::driver::DriverInfo;
+use crate::transport::{ConnectionBudget, ConnectionUsage};
+use crate::device::raw_session::RawSession;
+use crate::device::calibration::{CalibratedSession, LinearCalibration};
+use crate::device::clock_sync::ClockOffset;
+use crate::device::shutdown::{ShutdownStage, ShutdownHook, ShutdownReport, ShutdownStageResult, DEFAULT_SHUTDOWN_STAGE_TIMEOUT};
+use crate::device::safety::StopReason;
 use crate::device::safety::{HotPlugMonitor, HotPlugEvent};
+use crate::device::self_test::{SelfTestReport, SelfTestStep, SelfTestPlan, StepStatus};
+use crate::device::session::{SessionCommand, SessionSelector, SharedSession, apply_session_config, run_on_connect_commands};
+use crate::protocols::handshake::Version;
+use std::future::Future;
+use std::time::{Duration, Instant};
+use serde::{Serialize, Deserialize};
+
+/// How much identification `open_device_with_mode` does before opening a session
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum HandshakeMode {
+    /// Probe every driver and open the one that identifies the device
+    #[default]
+    Full,
+    /// Check a driver's probe recognizes the device, then open a raw session
+    /// instead of the driver's
+    ProbeOnly,
+    /// Open a raw session without touching the device
+    Skip,
+}
+
+/// Default number of ports probed at once by `probe_ports`
+pub const DEFAULT_PROBE_PARALLELISM: usize = 4;
+
+/// Owner recorded in the port claims while a port is being probed
+const PROBE_CLAIM: &str = "<probe>";
+
+/// A port and the driver that recognized the device on it
+#[derive(Clone)]
+pub struct ProbeMatch {
+    pub transport: Arc<dyn Transport>,
+    pub driver: Arc<dyn DeviceDriver>,
+}
 
 /// Central device manager
 /// Coordinates plugin loading, device detection, and session management
@@ -20,7 +59,22 @@ pub struct DeviceManager {
     drivers: Arc<RwLock<Vec<DriverInfo>>>,
     
     /// Active sessions
-    sessions: Arc<RwLock<HashMap<String, Box<dyn DeviceSession>>>>,
+    sessions: Arc<RwLock<HashMap<String, SharedSession>>>,
+    
+    /// Ports with an in-progress or open session (address -> session ID)
+    port_claims: Arc<RwLock<HashMap<String, String>>>,
+    
+    /// Commands run on every new session for a port (address -> commands)
+    on_connect_commands: Arc<RwLock<HashMap<String, Vec<SessionCommand>>>>,
+    
+    /// Input calibrations applied to every new session for a port (address -> calibrations)
+    calibrations: Arc<RwLock<HashMap<String, HashMap<String, LinearCalibration>>>>,
+    
+    /// Oldest firmware accepted on a port (address -> version)
+    min_firmware: Arc<RwLock<HashMap<String, Version>>>,
+    
+    /// Extra work run during each shutdown stage, in registration order
+    shutdown_hooks: Arc<RwLock<Vec<(ShutdownStage, String, ShutdownHook)>>>,
     
     /// Safety controller
     safety: Arc<SafetyController>,
@@ -34,6 +88,9 @@ pub struct DeviceManager {
     
     /// File system watcher for plugin changes
     watcher: Arc<RwLock<Option<notify::RecommendedWatcher>>>,
+    
+    /// Maximum concurrent probes in `probe_ports`
+    probe_parallelism: usize,
 }
 
 impl DeviceManager {
@@ -47,14 +104,26 @@ impl DeviceManager {
             plugin_loader: Arc::new(RwLock::new(PluginLoader::new(plugin_dir))),
             drivers: Arc::new(RwLock::new(Vec::new())),
             sessions: Arc::new(RwLock::new(HashMap::new())),
+            port_claims: Arc::new(RwLock::new(HashMap::new())),
+            on_connect_commands: Arc::new(RwLock::new(HashMap::new())),
+            calibrations: Arc::new(RwLock::new(HashMap::new())),
+            min_firmware: Arc::new(RwLock::new(HashMap::new())),
+            shutdown_hooks: Arc::new(RwLock::new(Vec::new())),
             safety,
             emergency_stop,
             hotplug,
             hotplug_rx: Arc::new(RwLock::new(hotplug_rx)),
             watcher: Arc::new(RwLock::new(None)),
+            probe_parallelism: DEFAULT_PROBE_PARALLELISM,
         }
     }
     
+    /// Probe at most `parallelism` ports at once during discovery
+    pub fn with_probe_parallelism(mut self, parallelism: usize) -> Self {
+        self.probe_parallelism = parallelism.max(1);
+        self
+    }
+    
     /// Initialize the device manager
     pub async fn initialize(&self) -> DeviceResult<()> {
         // Load all plugins
@@ -82,39 +151,116 @@ impl DeviceManager {
         Ok(loaded)
     }
     
+    /// Register a driver directly (e.g. built-in drivers not loaded as plugins)
+    pub async fn register_driver(&self, driver: DriverInfo) {
+        self.drivers.write().await.push(driver);
+    }
+    
     /// Probe for a device on a transport
     pub async fn probe_device(&self, transport: Arc<dyn Transport>) -> DeviceResult<Arc<dyn DeviceDriver>> {
         // Check emergency stop
         self.emergency_stop.guard().ensure_running()?;
         
-        let drivers = self.drivers.read().await;
+        let drivers = self.drivers_by_priority().await;
+        detect_driver(&drivers, transport).await
+            .ok_or_else(|| DeviceError::DeviceNotFound("No driver recognized the device".into()))
+    }
+    
+    /// Probe candidate ports concurrently and return the first one a driver recognizes
+    ///
+    /// At most `probe_parallelism` probes run at once and the rest are cancelled
+    /// once a match is found. Each port is claimed while it is probed, so ports
+    /// with an open session or another probe in progress are skipped, as are
+    /// duplicate addresses.
+    pub async fn probe_ports(&self, transports: Vec<Arc<dyn Transport>>) -> DeviceResult<ProbeMatch> {
+        self.emergency_stop.guard().ensure_running()?;
         
-        // Sort by priority and probe
-        let mut sorted_drivers = drivers.clone();
-        sorted_drivers.sort_by_key(|d| std::cmp::Reverse(d.priority));
+        let drivers = Arc::new(self.drivers_by_priority().await);
+        let mut pending = transports.into_iter();
+        let mut seen = HashSet::new();
+        let mut in_flight = HashSet::new();
+        let mut probes = JoinSet::new();
+        let mut found = None;
         
-        for driver_info in sorted_drivers {
-            match driver_info.driver.probe_async(transport.clone()).await {
-                Ok(true) => {
-                    tracing::info!("Device detected by driver: {}", driver_info.name);
-                    return Ok(driver_info.driver.clone());
+        loop {
+            while probes.len() < self.probe_parallelism {
+                let Some(transport) = pending.next() else {
+                    break;
+                };
+                let address = transport.config().address.clone();
+                if !seen.insert(address.clone()) {
+                    continue;
                 }
-                Ok(false) => continue,
-                Err(e) => {
-                    tracing::warn!("Probe failed for {}: {}", driver_info.name, e);
+                if let Err(e) = self.claim_port(&address, PROBE_CLAIM).await {
+                    tracing::debug!("Skipping probe: {}", e);
                     continue;
                 }
+                
+                in_flight.insert(address.clone());
+                let drivers = drivers.clone();
+                probes.spawn(async move {
+                    let driver = detect_driver(&drivers, transport.clone()).await;
+                    (address, driver.map(|driver| ProbeMatch { transport, driver }))
+                });
+            }
+            
+            let Some(joined) = probes.join_next().await else {
+                break;
+            };
+            match joined {
+                Ok((address, result)) => {
+                    in_flight.remove(&address);
+                    self.port_claims.write().await.remove(&address);
+                    if result.is_some() {
+                        found = result;
+                        break;
+                    }
+                }
+                Err(e) => tracing::warn!("Probe task failed: {}", e),
             }
         }
         
-        Err(DeviceError::DeviceNotFound("No driver recognized the device".into()))
+        // Cancel the remaining probes and release their ports
+        probes.shutdown().await;
+        let mut claims = self.port_claims.write().await;
+        for address in in_flight {
+            claims.remove(&address);
+        }
+        drop(claims);
+        
+        found.ok_or_else(|| DeviceError::DeviceNotFound("No driver recognized a device on any port".into()))
+    }
+    
+    /// Probe candidate ports and open a session on the first recognized device
+    pub async fn auto_connect(&self, transports: Vec<Arc<dyn Transport>>) -> DeviceResult<String> {
+        let found = self.probe_ports(transports).await?;
+        tracing::info!("Auto-connecting to {} on {}", found.driver.name(), found.transport.config().address);
+        self.open_device(found.transport, None).await
+    }
+    
+    /// Registered drivers, highest priority first
+    async fn drivers_by_priority(&self) -> Vec<DriverInfo> {
+        let mut sorted_drivers = self.drivers.read().await.clone();
+        sorted_drivers.sort_by_key(|d| std::cmp::Reverse(d.priority));
+        sorted_drivers
     }
     
-    /// Open a device session
+    /// Open a device session, identifying it with a full driver probe
     pub async fn open_device(
         &self, 
         transport: Arc<dyn Transport>,
         session_id: Option<String>
+    ) -> DeviceResult<String> {
+        self.open_device_with_mode(transport, session_id, HandshakeMode::Full).await
+    }
+    
+    /// Open a device session with the given amount of identification
+    /// `ProbeOnly` and `Skip` open a `RawSession` for devices without a handshake protocol
+    pub async fn open_device_with_mode(
+        &self,
+        transport: Arc<dyn Transport>,
+        session_id: Option<String>,
+        mode: HandshakeMode,
     ) -> DeviceResult<String> {
         // Check emergency stop
         self.emergency_stop.guard().ensure_running()?;
@@ -122,31 +268,234 @@ impl DeviceManager {
         // Rate limit device opening
         self.safety.check_rate_limit("open_device").await?;
         
-        // Probe for appropriate driver
-        let driver = self.probe_device(transport.clone()).await?;
-        
-        // Open session
-        let session = driver.open_async(transport).await?;
-        
         // Generate session ID
         let id = session_id.unwrap_or_else(|| {
             format!("session_{}", uuid::Uuid::new_v4())
         });
         
+        // Claim the port before touching the OS so concurrent opens fail clearly
+        let address = transport.config().address.clone();
+        self.claim_port(&address, &id).await?;
+        
+        let mut session = match self.open_session(transport, mode).await {
+            Ok(session) => session,
+            Err(e) => {
+                self.port_claims.write().await.remove(&address);
+                return Err(e);
+            }
+        };
+        
+        let calibrations = self.calibrations.read().await.get(&address).cloned();
+        if let Some(calibrations) = calibrations {
+            session = Box::new(CalibratedSession::new(session, calibrations));
+        }
+        
+        // Put outputs in a known safe state before anyone else can use the session
+        let commands = self.on_connect_commands.read().await.get(&address).cloned();
+        if let Some(commands) = commands {
+            let succeeded = run_on_connect_commands(session.as_mut(), &commands).await;
+            if succeeded < commands.len() {
+                tracing::warn!("{} of {} on-connect commands failed for {}", commands.len() - succeeded, commands.len(), address);
+            }
+        }
+        
         // Store session
         let mut sessions = self.sessions.write().await;
-        sessions.insert(id.clone(), session);
+        sessions.insert(id.clone(), Arc::new(Mutex::new(session)));
         
-        tracing::info!("Opened device session: {}", id);
+        tracing::info!("Opened device session: {} on {}", id, address);
         Ok(id)
     }
     
+    /// Commands to run, in order, on every session opened on `address`
+    /// An empty list removes them
+    pub async fn set_on_connect_commands(&self, address: &str, commands: Vec<SessionCommand>) {
+        let mut all = self.on_connect_commands.write().await;
+        if commands.is_empty() {
+            all.remove(address);
+        } else {
+            all.insert(address.to_string(), commands);
+        }
+    }
+    
+    /// Calibrations applied to reads on every session opened on `address`
+    /// An empty map removes them
+    pub async fn set_calibrations(&self, address: &str, calibrations: HashMap<String, LinearCalibration>) {
+        let mut all = self.calibrations.write().await;
+        if calibrations.is_empty() {
+            all.remove(address);
+        } else {
+            all.insert(address.to_string(), calibrations);
+        }
+    }
+    
+    /// Refuse sessions on `address` whose firmware is older than `minimum`
+    /// `None` accepts any firmware
+    pub async fn set_min_firmware(&self, address: &str, minimum: Option<Version>) {
+        let mut all = self.min_firmware.write().await;
+        match minimum {
+            Some(minimum) => {
+                all.insert(address.to_string(), minimum);
+            }
+            None => {
+                all.remove(address);
+            }
+        }
+    }
+    
+    /// Open a session on `transport` according to `mode`
+    async fn open_session(&self, transport: Arc<dyn Transport>, mode: HandshakeMode) -> DeviceResult<Box<dyn DeviceSession>> {
+        match mode {
+            HandshakeMode::Full => self.probe_and_open(transport).await,
+            HandshakeMode::ProbeOnly => {
+                if !transport.is_connected() {
+                    transport.connect().await
+                        .map_err(|e| DeviceError::ConnectionFailed(e.to_string()))?;
+                }
+                self.probe_device(transport.clone()).await?;
+                Ok(Box::new(RawSession::new(transport)))
+            }
+            HandshakeMode::Skip => Ok(Box::new(RawSession::new(transport))),
+        }
+    }
+    
+    /// Probe for a driver and open a session with it
+    async fn probe_and_open(&self, transport: Arc<dyn Transport>) -> DeviceResult<Box<dyn DeviceSession>> {
+        let driver = self.probe_device(transport.clone()).await?;
+        let minimum = self.min_firmware.read().await.get(&transport.config().address).copied();
+        driver.open_with_min_firmware_async(transport, minimum).await
+    }
+    
+    /// Reserve a port for a session, rejecting ports already in use
+    async fn claim_port(&self, address: &str, session_id: &str) -> DeviceResult<()> {
+        let mut claims = self.port_claims.write().await;
+        if let Some(owner) = claims.get(address) {
+            return Err(DeviceError::ConnectionFailed(format!(
+                "port {} already in use by session {}", address, owner
+            )));
+        }
+        claims.insert(address.to_string(), session_id.to_string());
+        Ok(())
+    }
+    
+    /// Run connect → handshake → read → write → disconnect against a device
+    /// The device is always disconnected, even when an earlier step fails
+    pub async fn self_test(&self, transport: Arc<dyn Transport>) -> SelfTestReport {
+        self.self_test_with_plan(transport, SelfTestPlan::default()).await
+    }
+    
+    /// Run the self-test with a custom write step
+    pub async fn self_test_with_plan(&self, transport: Arc<dyn Transport>, plan: SelfTestPlan) -> SelfTestReport {
+        let mut report = SelfTestReport::default();
+        let address = transport.config().address.clone();
+        let test_id = format!("self_test_{}", uuid::Uuid::new_v4());
+        
+        // Connect (refusing ports another session is using)
+        let started = Instant::now();
+        let mut claimed = false;
+        let connected = match self.claim_port(&address, &test_id).await {
+            Ok(()) => {
+                claimed = true;
+                transport.connect().await.map_err(|e| DeviceError::ConnectionFailed(e.to_string()))
+            }
+            Err(e) => Err(e),
+        };
+        match connected {
+            Ok(()) => report.record(SelfTestStep::Connect, StepStatus::Passed, started.elapsed(), Some(address.clone())),
+            Err(e) => report.record(SelfTestStep::Connect, StepStatus::Failed(e.to_string()), started.elapsed(), None),
+        }
+        
+        // Handshake: find a driver and open a session
+        let mut session = None;
+        if report.first_failure().is_none() {
+            let started = Instant::now();
+            match self.probe_and_open(transport.clone()).await {
+                Ok(opened) => {
+                    let detail = format!("{} ({})", opened.device_name(), opened.session_id());
+                    report.record(SelfTestStep::Handshake, StepStatus::Passed, started.elapsed(), Some(detail));
+                    session = Some(opened);
+                }
+                Err(e) => report.record(SelfTestStep::Handshake, StepStatus::Failed(e.to_string()), started.elapsed(), None),
+            }
+        } else {
+            report.skip(SelfTestStep::Handshake);
+        }
+        
+        // Read every input the device exposes
+        match session.as_mut() {
+            Some(session) if report.first_failure().is_none() => {
+                let started = Instant::now();
+                let status = match session.read_all_inputs().await {
+                    Ok(values) => {
+                        let failed: Vec<_> = values.iter()
+                            .filter(|(_, v)| v.get("error").is_some())
+                            .map(|(k, _)| k.as_str())
+                            .collect();
+                        if failed.is_empty() {
+                            Ok(format!("{} inputs read", values.len()))
+                        } else {
+                            Err(format!("failed to read {}", failed.join(", ")))
+                        }
+                    }
+                    Err(e) => Err(e.to_string()),
+                };
+                match status {
+                    Ok(detail) => report.record(SelfTestStep::Read, StepStatus::Passed, started.elapsed(), Some(detail)),
+                    Err(msg) => report.record(SelfTestStep::Read, StepStatus::Failed(msg), started.elapsed(), None),
+                }
+            }
+            _ => report.skip(SelfTestStep::Read),
+        }
+        
+        // Exercise a write
+        match session.as_mut() {
+            Some(session) if report.first_failure().is_none() => {
+                let started = Instant::now();
+                match session.invoke_async(&plan.write_endpoint, plan.write_args.clone()).await {
+                    Ok(_) => report.record(SelfTestStep::Write, StepStatus::Passed, started.elapsed(), Some(plan.write_endpoint.clone())),
+                    Err(e) => report.record(SelfTestStep::Write, StepStatus::Failed(e.to_string()), started.elapsed(), None),
+                }
+            }
+            _ => report.skip(SelfTestStep::Write),
+        }
+        
+        // Disconnect always runs so a failed test never leaves the port open
+        let started = Instant::now();
+        let mut errors = Vec::new();
+        if let Some(mut session) = session {
+            if let Err(e) = session.close_async().await {
+                errors.push(format!("close session: {}", e));
+            }
+        }
+        if claimed {
+            if let Err(e) = transport.disconnect().await {
+                errors.push(format!("disconnect: {}", e));
+            }
+            self.port_claims.write().await.remove(&address);
+        }
+        if errors.is_empty() {
+            report.record(SelfTestStep::Disconnect, StepStatus::Passed, started.elapsed(), None);
+        } else {
+            report.record(SelfTestStep::Disconnect, StepStatus::Failed(errors.join("; ")), started.elapsed(), None);
+        }
+        
+        report
+    }
+    
+    /// Session currently holding a port, if any
+    pub async fn port_owner(&self, address: &str) -> Option<String> {
+        self.port_claims.read().await.get(address).cloned()
+    }
+    
     /// Close a device session
     pub async fn close_device(&self, session_id: &str) -> DeviceResult<()> {
-        let mut sessions = self.sessions.write().await;
+        let session = self.sessions.write().await.remove(session_id);
         
-        if let Some(mut session) = sessions.remove(session_id) {
-            session.close_async().await?;
+        if let Some(session) = session {
+            // The port stays claimed until the device is closed, even if closing fails
+            let closed = session.lock().await.close_async().await;
+            self.port_claims.write().await.retain(|_, owner| owner != session_id);
+            closed?;
             tracing::info!("Closed device session: {}", session_id);
             Ok(())
         } else {
@@ -154,14 +503,9 @@ impl DeviceManager {
         }
     }
     
-    /// Get an active session
-    pub async fn get_session(&self, session_id: &str) -> Option<Box<dyn DeviceSession>> {
-        let sessions = self.sessions.read().await;
-        sessions.get(session_id).map(|s| {
-            // This would need proper cloning/boxing in real implementation
-            // For now, returning None as sessions can't be easily cloned
-            None
-        }).flatten()
+    /// Handle to an active session
+    pub async fn get_session(&self, session_id: &str) -> Option<SharedSession> {
+        self.sessions.read().await.get(session_id).cloned()
     }
     
     /// List active sessions
@@ -170,6 +514,104 @@ impl DeviceManager {
         sessions.keys().cloned().collect()
     }
     
+    /// Re-sync the clock of every session whose offset is missing or stale
+    /// Call periodically to correct drift; returns the synced sessions with their new offsets
+    pub async fn resync_clocks(&self) -> Vec<(String, ClockOffset)> {
+        // Sync exchanges talk to the devices, so don't hold the session map meanwhile
+        let sessions: Vec<(String, SharedSession)> = self.sessions.read().await
+            .iter()
+            .map(|(id, session)| (id.clone(), session.clone()))
+            .collect();
+        let mut synced = Vec::new();
+        
+        for (id, session) in sessions {
+            let mut session = session.lock().await;
+            if !session.clock_resync_due() {
+                continue;
+            }
+            match session.sync_clock().await {
+                Ok(offset) => {
+                    tracing::debug!("Clock offset for {}: {}ms (rtt {}ms)", id, offset.offset_ms, offset.rtt_ms);
+                    synced.push((id, offset));
+                }
+                Err(e) => tracing::warn!("Clock sync failed for {}: {}", id, e),
+            }
+        }
+        
+        synced
+    }
+    
+    /// Apply `config` to every session matching `selector`, all sessions concurrently
+    /// Each session runs the commands in order and stops at its own first failure;
+    /// returns every matched session's outcome keyed by session ID
+    pub async fn apply_config_to_matching(
+        &self,
+        config: &[SessionCommand],
+        selector: &SessionSelector,
+    ) -> DeviceResult<HashMap<String, DeviceResult<()>>> {
+        self.emergency_stop.guard().ensure_running()?;
+        
+        let config: Arc<[SessionCommand]> = config.into();
+        Ok(self.fan_out(selector, move |session| {
+            let config = config.clone();
+            async move {
+                let mut session = session.lock().await;
+                apply_session_config(session.as_mut(), &config).await
+            }
+        }).await)
+    }
+    
+    /// Run `op` on every session matching `selector`, all sessions concurrently
+    async fn fan_out<T, F, Fut>(&self, selector: &SessionSelector, op: F) -> HashMap<String, DeviceResult<T>>
+    where
+        T: Send + 'static,
+        F: Fn(SharedSession) -> Fut,
+        Fut: Future<Output = DeviceResult<T>> + Send + 'static,
+    {
+        // Snapshot the handles so the map isn't held while sessions are locked or busy
+        let candidates: Vec<(String, SharedSession)> = self.sessions.read().await
+            .iter()
+            .map(|(id, session)| (id.clone(), session.clone()))
+            .collect();
+        
+        // Each task checks the selector itself, so a busy session only delays its own
+        // task; `op`'s future is lazy and only runs for sessions that match
+        let mut tasks = Vec::new();
+        for (id, session) in candidates {
+            let work = op(session.clone());
+            let selector = selector.clone();
+            let (task_id, target) = (id.clone(), session.clone());
+            tasks.push((id, session, tokio::spawn(async move {
+                if !selector.matches(&task_id, target.lock().await.as_ref()) {
+                    return None;
+                }
+                Some(work.await)
+            })));
+        }
+        
+        let mut results = HashMap::new();
+        for (id, session, task) in tasks {
+            match task.await {
+                Ok(Some(result)) => {
+                    results.insert(id, result);
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    // The session panicked and can't be trusted; drop it (unless it was
+                    // already replaced) and free its port
+                    let mut sessions = self.sessions.write().await;
+                    if sessions.get(&id).is_some_and(|current| Arc::ptr_eq(current, &session)) {
+                        sessions.remove(&id);
+                        self.port_claims.write().await.retain(|_, owner| *owner != id);
+                    }
+                    results.insert(id.clone(), Err(DeviceError::Session(format!("session {} lost: {}", id, e))));
+                }
+            }
+        }
+        
+        results
+    }
+    
     /// Trigger emergency stop
     pub async fn emergency_stop(&self, reason: String) {
         self.emergency_stop.trigger(crate::device::safety::StopReason::UserRequested).await;
@@ -181,6 +623,80 @@ impl DeviceManager {
         }
     }
     
+    /// Register `hook` to run during `stage` of `shutdown`, after the stage's built-in work
+    /// e.g. the UI dropping subscription handles or flushing telemetry
+    pub async fn add_shutdown_hook(&self, stage: ShutdownStage, name: &str, hook: ShutdownHook) {
+        self.shutdown_hooks.write().await.push((stage, name.to_string(), hook));
+    }
+    
+    /// Shut down in a defined order with the default per-stage timeout
+    pub async fn shutdown(&self) -> ShutdownReport {
+        self.shutdown_with_timeout(DEFAULT_SHUTDOWN_STAGE_TIMEOUT).await
+    }
+    
+    /// Run every `ShutdownStage` in order, abandoning any stage that exceeds `stage_timeout`
+    pub async fn shutdown_with_timeout(&self, stage_timeout: Duration) -> ShutdownReport {
+        let mut report = ShutdownReport::default();
+        
+        for stage in ShutdownStage::ALL {
+            let started = Instant::now();
+            let completed = tokio::time::timeout(stage_timeout, self.run_shutdown_stage(stage)).await.is_ok();
+            let elapsed = started.elapsed();
+            
+            if completed {
+                tracing::info!("Shutdown stage {:?} finished in {:?}", stage, elapsed);
+            } else {
+                tracing::warn!("Shutdown stage {:?} timed out after {:?}, continuing", stage, stage_timeout);
+            }
+            report.stages.push(ShutdownStageResult { stage, completed, elapsed });
+        }
+        
+        report
+    }
+    
+    /// Built-in work for `stage`, then its registered hooks
+    async fn run_shutdown_stage(&self, stage: ShutdownStage) {
+        match stage {
+            ShutdownStage::ArmSafeState => {
+                // Block new sessions first, so nothing opens a port while its safe state goes out
+                self.emergency_stop.trigger(StopReason::Shutdown).await;
+                
+                // Reuse each port's on-connect commands as its safe state while transports still exist
+                let claims = self.port_claims.read().await.clone();
+                let mut safe_states = self.on_connect_commands.read().await.clone();
+                // Snapshot the handles so the session map is free while devices answer
+                let targets: Vec<(SharedSession, Vec<SessionCommand>)> = {
+                    let sessions = self.sessions.read().await;
+                    claims.into_iter()
+                        .filter_map(|(address, session_id)| Some((sessions.get(&session_id)?.clone(), safe_states.remove(&address)?)))
+                        .collect()
+                };
+                for (session, commands) in targets {
+                    run_on_connect_commands(session.lock().await.as_mut(), &commands).await;
+                }
+            }
+            ShutdownStage::CloseSessions => {
+                for (id, session) in self.sessions.write().await.iter() {
+                    if let Err(e) = session.lock().await.close_async().await {
+                        tracing::warn!("Failed to close session {} during shutdown: {}", id, e);
+                    }
+                }
+            }
+            ShutdownStage::DropTransports => {
+                // Sessions own their transports, so dropping them releases the ports
+                self.sessions.write().await.clear();
+                self.port_claims.write().await.clear();
+            }
+            ShutdownStage::StopSubscriptions | ShutdownStage::FlushTelemetry => {}
+        }
+        
+        let hooks = self.shutdown_hooks.read().await;
+        for (_, name, hook) in hooks.iter().filter(|(s, _, _)| *s == stage) {
+            tracing::debug!("Running shutdown hook '{}' ({:?})", name, stage);
+            hook().await;
+        }
+    }
+    
     /// Reset emergency stop
     pub async fn reset_emergency_stop(&self) {
         self.emergency_stop.reset().await;
@@ -264,7 +780,669 @@ impl DeviceManager {
     pub fn emergency_stop_handle(&self) -> Arc<EmergencyStop> {
         self.emergency_stop.clone()
     }
+    
+    /// Open transports counted against the global connection budget
+    pub fn connection_usage(&self) -> ConnectionUsage {
+        ConnectionBudget::global().usage()
+    }
 }
 
 // Add uuid for session IDs
-use uuid;
\ No newline at end of file
+/// First driver in `drivers` whose probe recognizes the device on `transport`
+async fn detect_driver(drivers: &[DriverInfo], transport: Arc<dyn Transport>) -> Option<Arc<dyn DeviceDriver>> {
+    for driver_info in drivers {
+        match driver_info.driver.probe_async(transport.clone()).await {
+            Ok(true) => {
+                tracing::info!("Device detected by driver: {}", driver_info.name);
+                return Some(driver_info.driver.clone());
+            }
+            Ok(false) => continue,
+            Err(e) => {
+                tracing::warn!("Probe failed for {}: {}", driver_info.name, e);
+                continue;
+            }
+        }
+    }
+    None
+}
+
+use uuid;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use serde_json::Value;
+    use crate::device::{DriverCapabilities, TransportType};
+    use crate::device::mock::MockSession;
+    use crate::transport::TransportConfig;
+    use crate::transport::mock::{MockTransport, MockConfig};
+    
+    /// Driver that accepts any device and opens an inert session
+    struct AcceptAllDriver;
+    
+    fn inert_session() -> Box<dyn DeviceSession> {
+        Box::new(MockSession::inert().with_name("inert", "Inert"))
+    }
+    
+    #[async_trait]
+    impl DeviceDriver for AcceptAllDriver {
+        fn name(&self) -> &str {
+            "Accept All"
+        }
+        
+        fn version(&self) -> &str {
+            "1.0.0"
+        }
+        
+        fn supported_transports(&self) -> Vec<TransportType> {
+            vec![TransportType::Serial]
+        }
+        
+        async fn probe_async(&self, _transport: Arc<dyn Transport>) -> DeviceResult<bool> {
+            Ok(true)
+        }
+        
+        async fn open_async(&self, _transport: Arc<dyn Transport>) -> DeviceResult<Box<dyn DeviceSession>> {
+            Ok(inert_session())
+        }
+        
+        fn capabilities(&self) -> DriverCapabilities {
+            DriverCapabilities::default()
+        }
+    }
+    
+    fn transport_on(address: &str) -> Arc<dyn Transport> {
+        let config = TransportConfig {
+            address: address.to_string(),
+            ..Default::default()
+        };
+        Arc::new(MockTransport::new("mock".into(), config, MockConfig::default()))
+    }
+    
+    async fn manager_with_driver() -> DeviceManager {
+        let manager = DeviceManager::new("./drivers");
+        manager.register_driver(DriverInfo::new(Arc::new(AcceptAllDriver))).await;
+        manager
+    }
+    
+    #[tokio::test]
+    async fn test_duplicate_port_open_rejected() {
+        let manager = manager_with_driver().await;
+        
+        let first = manager.open_device(transport_on("/dev/ttyACM0"), Some("ui".into())).await.unwrap();
+        assert_eq!(first, "ui");
+        
+        match manager.open_device(transport_on("/dev/ttyACM0"), Some("script".into())).await {
+            Err(DeviceError::ConnectionFailed(msg)) => {
+                assert_eq!(msg, "port /dev/ttyACM0 already in use by session ui");
+            }
+            other => panic!("Expected ConnectionFailed, got {:?}", other.map(|_| ())),
+        }
+        
+        // Other ports are unaffected
+        assert!(manager.open_device(transport_on("/dev/ttyACM1"), None).await.is_ok());
+        assert_eq!(manager.list_sessions().await.len(), 2);
+    }
+    
+    #[tokio::test]
+    async fn test_port_released_on_close() {
+        let manager = manager_with_driver().await;
+        
+        manager.open_device(transport_on("COM3"), Some("first".into())).await.unwrap();
+        assert_eq!(manager.port_owner("COM3").await.as_deref(), Some("first"));
+        
+        manager.close_device("first").await.unwrap();
+        assert!(manager.port_owner("COM3").await.is_none());
+        
+        assert!(manager.open_device(transport_on("COM3"), Some("second".into())).await.is_ok());
+    }
+    
+    #[tokio::test]
+    async fn test_failed_open_releases_port() {
+        // No drivers registered, so probing fails
+        let manager = DeviceManager::new("./drivers");
+        
+        assert!(manager.open_device(transport_on("COM4"), None).await.is_err());
+        assert!(manager.port_owner("COM4").await.is_none());
+    }
+    
+    #[tokio::test]
+    async fn test_min_firmware_applies_per_port() {
+        let manager = manager_with_driver().await;
+        manager.set_min_firmware("COM5", Some(Version::new(2, 0, 0))).await;
+        
+        // The driver can't report its firmware, so the minimum can't be met
+        match manager.open_device(transport_on("COM5"), None).await {
+            Err(DeviceError::UnsupportedDevice(msg)) => assert!(msg.contains("2.0.0"), "{}", msg),
+            other => panic!("Expected UnsupportedDevice, got {:?}", other.map(|_| ())),
+        }
+        assert!(manager.port_owner("COM5").await.is_none());
+        
+        // Other ports, and the same port once the minimum is lifted, open normally
+        assert!(manager.open_device(transport_on("COM6"), None).await.is_ok());
+        manager.set_min_firmware("COM5", None).await;
+        assert!(manager.open_device(transport_on("COM5"), None).await.is_ok());
+    }
+    
+    #[tokio::test]
+    async fn test_self_test_all_steps_pass() {
+        let manager = manager_with_driver().await;
+        let transport = transport_on("COM5");
+        
+        let report = manager.self_test(transport.clone()).await;
+        assert!(report.passed(), "unexpected failure: {:?}", report.first_failure());
+        
+        let steps: Vec<_> = report.steps.iter().map(|s| s.step).collect();
+        assert_eq!(steps, SelfTestStep::ALL.to_vec());
+        assert_eq!(report.step(SelfTestStep::Handshake).unwrap().detail.as_deref(), Some("Inert (inert)"));
+        
+        // Cleaned up afterwards
+        assert!(!transport.is_connected());
+        assert!(manager.port_owner("COM5").await.is_none());
+    }
+    
+    #[tokio::test]
+    async fn test_self_test_failed_handshake_skips_later_steps() {
+        // No drivers registered, so the handshake cannot succeed
+        let manager = DeviceManager::new("./drivers");
+        let transport = transport_on("COM6");
+        
+        let report = manager.self_test(transport.clone()).await;
+        assert!(!report.passed());
+        
+        assert_eq!(report.step(SelfTestStep::Connect).unwrap().status, StepStatus::Passed);
+        assert!(matches!(report.step(SelfTestStep::Handshake).unwrap().status, StepStatus::Failed(_)));
+        assert_eq!(report.step(SelfTestStep::Read).unwrap().status, StepStatus::Skipped);
+        assert_eq!(report.step(SelfTestStep::Write).unwrap().status, StepStatus::Skipped);
+        assert_eq!(report.first_failure().unwrap().step, SelfTestStep::Handshake);
+        
+        // Disconnect still runs
+        assert_eq!(report.step(SelfTestStep::Disconnect).unwrap().status, StepStatus::Passed);
+        assert!(!transport.is_connected());
+        assert!(manager.port_owner("COM6").await.is_none());
+    }
+    
+    /// Driver whose sessions record every endpoint they are asked to invoke
+    struct RecordingDriver {
+        calls: Arc<std::sync::Mutex<Vec<String>>>,
+    }
+    
+    #[async_trait]
+    impl DeviceDriver for RecordingDriver {
+        fn name(&self) -> &str {
+            "Recording"
+        }
+        
+        fn version(&self) -> &str {
+            "1.0.0"
+        }
+        
+        fn supported_transports(&self) -> Vec<TransportType> {
+            vec![TransportType::Serial]
+        }
+        
+        async fn probe_async(&self, _transport: Arc<dyn Transport>) -> DeviceResult<bool> {
+            Ok(true)
+        }
+        
+        async fn open_async(&self, _transport: Arc<dyn Transport>) -> DeviceResult<Box<dyn DeviceSession>> {
+            self.calls.lock().unwrap().push("open".to_string());
+            let calls = self.calls.clone();
+            let session = MockSession::new(move |endpoint, args| {
+                calls.lock().unwrap().push(format!("{} {:?}", endpoint, args));
+                match endpoint {
+                    "setServo" => Err(DeviceError::Unknown("no servo attached".into())),
+                    "analogRead" => Ok(serde_json::json!(100)),
+                    _ => Ok(Value::Null),
+                }
+            });
+            Ok(Box::new(session.with_name("recording", "Recording")))
+        }
+        
+        fn capabilities(&self) -> DriverCapabilities {
+            DriverCapabilities::default()
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_on_connect_commands_run_in_order_after_open() {
+        let calls = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let manager = DeviceManager::new("./drivers");
+        manager.register_driver(DriverInfo::new(Arc::new(RecordingDriver { calls: calls.clone() }))).await;
+        
+        manager.set_on_connect_commands("COM7", vec![
+            SessionCommand::new("pwmWrite", vec![serde_json::json!(9), serde_json::json!(0)]),
+            SessionCommand::new("setServo", vec![serde_json::json!(0), serde_json::json!(90)]),
+            SessionCommand::new("pwmWrite", vec![serde_json::json!(10), serde_json::json!(0)]),
+        ]).await;
+        
+        // The failing servo command is skipped without aborting the connection
+        let id = manager.open_device(transport_on("COM7"), None).await.unwrap();
+        assert_eq!(manager.list_sessions().await, vec![id]);
+        assert_eq!(*calls.lock().unwrap(), vec![
+            "open".to_string(),
+            "pwmWrite [Number(9), Number(0)]".to_string(),
+            "setServo [Number(0), Number(90)]".to_string(),
+            "pwmWrite [Number(10), Number(0)]".to_string(),
+        ]);
+        
+        // Other ports have no on-connect commands
+        calls.lock().unwrap().clear();
+        manager.open_device(transport_on("COM8"), None).await.unwrap();
+        assert_eq!(*calls.lock().unwrap(), vec!["open".to_string()]);
+    }
+    
+    /// Driver whose sessions record calls per port; "Pump" devices on ports starting
+    /// with PUMP, "Valve" devices elsewhere, and PUMP2 rejects sample rate changes
+    struct FleetDriver {
+        calls: Arc<std::sync::Mutex<HashMap<String, Vec<String>>>>,
+    }
+    
+    #[async_trait]
+    impl DeviceDriver for FleetDriver {
+        fn name(&self) -> &str {
+            "Fleet"
+        }
+        
+        fn version(&self) -> &str {
+            "1.0.0"
+        }
+        
+        fn supported_transports(&self) -> Vec<TransportType> {
+            vec![TransportType::Serial]
+        }
+        
+        async fn probe_async(&self, _transport: Arc<dyn Transport>) -> DeviceResult<bool> {
+            Ok(true)
+        }
+        
+        async fn open_async(&self, transport: Arc<dyn Transport>) -> DeviceResult<Box<dyn DeviceSession>> {
+            let address = transport.config().address.clone();
+            let name = if address.starts_with("PUMP") { "Pump" } else { "Valve" };
+            let calls = self.calls.clone();
+            let port = address.clone();
+            let session = MockSession::new(move |endpoint, args| {
+                calls.lock().unwrap().entry(port.clone()).or_default().push(format!("{} {:?}", endpoint, args));
+                if port == "PUMP2" && endpoint == "setSampleRate" {
+                    return Err(DeviceError::DeviceRejection("sample rate not supported".into()));
+                }
+                Ok(Value::Null)
+            });
+            Ok(Box::new(session.with_name(&address, name)))
+        }
+        
+        fn capabilities(&self) -> DriverCapabilities {
+            DriverCapabilities::default()
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_calibrations_apply_to_sessions_on_port() {
+        let calls = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let manager = DeviceManager::new("./drivers");
+        manager.register_driver(DriverInfo::new(Arc::new(RecordingDriver { calls: calls.clone() }))).await;
+        manager.set_calibrations("COM9", HashMap::from([
+            ("analogRead:0".to_string(), LinearCalibration::new(0.5, 2.0)),
+        ])).await;
+        
+        let id = manager.open_device(transport_on("COM9"), None).await.unwrap();
+        let session = manager.get_session(&id).await.unwrap();
+        let mut session = session.lock().await;
+        assert_eq!(session.invoke_async("analogRead", vec![serde_json::json!(0)]).await.unwrap(), serde_json::json!(52.0));
+        assert_eq!(session.invoke_async("analogRead", vec![serde_json::json!(1)]).await.unwrap(), serde_json::json!(100));
+        drop(session);
+        
+        // Other ports read raw values
+        let id = manager.open_device(transport_on("COM10"), None).await.unwrap();
+        let session = manager.get_session(&id).await.unwrap();
+        let reply = session.lock().await.invoke_async("analogRead", vec![serde_json::json!(0)]).await.unwrap();
+        assert_eq!(reply, serde_json::json!(100));
+    }
+    
+    #[tokio::test]
+    async fn test_resync_clocks_does_not_hold_session_map() {
+        let manager = Arc::new(DeviceManager::new("./drivers"));
+        // The device takes a while to answer the clock sync
+        let session: Box<dyn DeviceSession> = Box::new(
+            MockSession::inert()
+                .with_delay(Duration::from_millis(300))
+                .with_clock_offset(ClockOffset::estimate(1_000, 400, 1_010)),
+        );
+        manager.sessions.write().await.insert("slow".to_string(), Arc::new(Mutex::new(session)));
+        
+        let resync = tokio::spawn({
+            let manager = manager.clone();
+            async move { manager.resync_clocks().await }
+        });
+        tokio::task::yield_now().await;
+        
+        // The session map stays usable while the device answers
+        let sessions = tokio::time::timeout(Duration::from_millis(100), manager.list_sessions()).await
+            .expect("session map blocked by clock resync");
+        assert_eq!(sessions, vec!["slow".to_string()]);
+        assert!(manager.get_session("slow").await.is_some());
+        
+        assert_eq!(resync.await.unwrap(), vec![("slow".to_string(), ClockOffset::estimate(1_000, 400, 1_010))]);
+    }
+    
+    #[tokio::test]
+    async fn test_apply_config_does_not_hold_session_map() {
+        let manager = Arc::new(DeviceManager::new("./drivers"));
+        // The device takes a while to answer each command
+        let session: Box<dyn DeviceSession> = Box::new(MockSession::inert().with_delay(Duration::from_millis(300)));
+        manager.sessions.write().await.insert("slow".to_string(), Arc::new(Mutex::new(session)));
+        
+        let apply = tokio::spawn({
+            let manager = manager.clone();
+            async move {
+                let config = [SessionCommand::new("setSampleRate", vec![serde_json::json!(100)])];
+                manager.apply_config_to_matching(&config, &SessionSelector::All).await
+            }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        
+        // The session map stays usable while the device answers
+        let sessions = tokio::time::timeout(Duration::from_millis(100), manager.list_sessions()).await
+            .expect("session map blocked by apply_config_to_matching");
+        assert_eq!(sessions, vec!["slow".to_string()]);
+        
+        let results = apply.await.unwrap().unwrap();
+        assert!(results["slow"].is_ok());
+    }
+    
+    #[tokio::test]
+    async fn test_apply_config_not_delayed_by_busy_session() {
+        let manager = Arc::new(DeviceManager::new("./drivers"));
+        let idle = MockSession::inert();
+        let idle_calls = idle.calls();
+        let busy: SharedSession = Arc::new(Mutex::new(Box::new(MockSession::inert()) as Box<dyn DeviceSession>));
+        manager.sessions.write().await.insert("busy".to_string(), busy.clone());
+        manager.sessions.write().await.insert("idle".to_string(), Arc::new(Mutex::new(Box::new(idle) as Box<dyn DeviceSession>)));
+        
+        // Something else holds the busy session while the config goes out
+        let in_use = busy.lock().await;
+        let apply = tokio::spawn({
+            let manager = manager.clone();
+            async move {
+                let config = [SessionCommand::new("setSampleRate", vec![serde_json::json!(100)])];
+                manager.apply_config_to_matching(&config, &SessionSelector::All).await
+            }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(idle_calls.lock().unwrap().len(), 1, "idle session waited for the busy one");
+        
+        drop(in_use);
+        let results = apply.await.unwrap().unwrap();
+        assert!(results["busy"].is_ok());
+        assert!(results["idle"].is_ok());
+    }
+    
+    #[tokio::test]
+    async fn test_apply_config_to_matching_sessions() {
+        let calls = Arc::new(std::sync::Mutex::new(HashMap::new()));
+        let manager = DeviceManager::new("./drivers");
+        manager.register_driver(DriverInfo::new(Arc::new(FleetDriver { calls: calls.clone() }))).await;
+        
+        for port in ["PUMP1", "PUMP2", "PUMP3", "VALVE1"] {
+            manager.open_device(transport_on(port), Some(port.to_lowercase())).await.unwrap();
+        }
+        
+        let config = vec![
+            SessionCommand::new("pinMode", vec![serde_json::json!(3), serde_json::json!("OUTPUT")]),
+            SessionCommand::new("setSampleRate", vec![serde_json::json!(100)]),
+            SessionCommand::new("pinMode", vec![serde_json::json!(4), serde_json::json!("INPUT")]),
+        ];
+        let results = manager.apply_config_to_matching(&config, &SessionSelector::DeviceName("Pump".into())).await.unwrap();
+        
+        // Only the pumps were targeted, and PUMP2's failure doesn't affect the others
+        assert_eq!(results.len(), 3);
+        assert!(results["pump1"].is_ok());
+        assert!(results["pump3"].is_ok());
+        assert!(matches!(results["pump2"], Err(DeviceError::DeviceRejection(_))));
+        
+        let full = vec![
+            "pinMode [Number(3), String(\"OUTPUT\")]".to_string(),
+            "setSampleRate [Number(100)]".to_string(),
+            "pinMode [Number(4), String(\"INPUT\")]".to_string(),
+        ];
+        let calls = calls.lock().unwrap();
+        assert_eq!(calls["PUMP1"], full);
+        assert_eq!(calls["PUMP3"], full);
+        assert_eq!(calls["PUMP2"], full[..2].to_vec());
+        assert!(!calls.contains_key("VALVE1"));
+        drop(calls);
+        
+        // Every session is back in the manager afterwards
+        let mut sessions = manager.list_sessions().await;
+        sessions.sort();
+        assert_eq!(sessions, vec!["pump1", "pump2", "pump3", "valve1"]);
+    }
+    
+    /// Driver whose probe stands in for an identify exchange and is recorded
+    struct IdentifyingDriver {
+        calls: Arc<std::sync::Mutex<Vec<String>>>,
+    }
+    
+    #[async_trait]
+    impl DeviceDriver for IdentifyingDriver {
+        fn name(&self) -> &str {
+            "Identifying"
+        }
+        
+        fn version(&self) -> &str {
+            "1.0.0"
+        }
+        
+        fn supported_transports(&self) -> Vec<TransportType> {
+            vec![TransportType::Serial]
+        }
+        
+        async fn probe_async(&self, _transport: Arc<dyn Transport>) -> DeviceResult<bool> {
+            self.calls.lock().unwrap().push("identify".to_string());
+            Ok(true)
+        }
+        
+        async fn open_async(&self, _transport: Arc<dyn Transport>) -> DeviceResult<Box<dyn DeviceSession>> {
+            self.calls.lock().unwrap().push("open".to_string());
+            Ok(inert_session())
+        }
+        
+        fn capabilities(&self) -> DriverCapabilities {
+            DriverCapabilities::default()
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_handshake_modes() {
+        let calls = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let manager = DeviceManager::new("./drivers");
+        manager.register_driver(DriverInfo::new(Arc::new(IdentifyingDriver { calls: calls.clone() }))).await;
+        
+        // Skip opens a raw session without any identify exchange
+        let raw = manager.open_device_with_mode(transport_on("COM9"), Some("sink".into()), HandshakeMode::Skip).await.unwrap();
+        assert_eq!(raw, "sink");
+        assert!(calls.lock().unwrap().is_empty());
+        assert_eq!(manager.port_owner("COM9").await.as_deref(), Some("sink"));
+        
+        // Full (the default) identifies the device with its driver first
+        assert_eq!(HandshakeMode::default(), HandshakeMode::Full);
+        manager.open_device(transport_on("COM10"), Some("arduino".into())).await.unwrap();
+        assert_eq!(*calls.lock().unwrap(), vec!["identify".to_string(), "open".to_string()]);
+        
+        // ProbeOnly checks the probe reply but opens a raw session, not the driver's
+        calls.lock().unwrap().clear();
+        manager.open_device_with_mode(transport_on("COM11"), Some("probed".into()), HandshakeMode::ProbeOnly).await.unwrap();
+        assert_eq!(*calls.lock().unwrap(), vec!["identify".to_string()]);
+        
+        let mut sessions = manager.list_sessions().await;
+        sessions.sort();
+        assert_eq!(sessions, vec!["arduino", "probed", "sink"]);
+        
+        // A device no driver recognizes is refused and its port released
+        let unrecognized = DeviceManager::new("./drivers");
+        let result = unrecognized.open_device_with_mode(transport_on("COM12"), None, HandshakeMode::ProbeOnly).await;
+        assert!(matches!(result, Err(DeviceError::DeviceNotFound(_))));
+        assert!(unrecognized.port_owner("COM12").await.is_none());
+    }
+    
+    fn recording_hook(log: &Arc<std::sync::Mutex<Vec<String>>>, entry: &str) -> ShutdownHook {
+        let log = log.clone();
+        let entry = entry.to_string();
+        Box::new(move || {
+            let log = log.clone();
+            let entry = entry.clone();
+            Box::pin(async move { log.lock().unwrap().push(entry) })
+        })
+    }
+    
+    #[tokio::test]
+    async fn test_shutdown_stages_run_in_order() {
+        let calls = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let manager = DeviceManager::new("./drivers");
+        manager.register_driver(DriverInfo::new(Arc::new(RecordingDriver { calls: calls.clone() }))).await;
+        manager.set_on_connect_commands("COM11", vec![SessionCommand::new("pwmWrite", vec![serde_json::json!(9), serde_json::json!(0)])]).await;
+        manager.open_device(transport_on("COM11"), Some("motor".into())).await.unwrap();
+        calls.lock().unwrap().clear();
+        
+        // Register out of order; hooks still run in stage order
+        let stages = Arc::new(std::sync::Mutex::new(Vec::new()));
+        manager.add_shutdown_hook(ShutdownStage::DropTransports, "transports", recording_hook(&stages, "drop")).await;
+        manager.add_shutdown_hook(ShutdownStage::FlushTelemetry, "telemetry", recording_hook(&stages, "flush")).await;
+        manager.add_shutdown_hook(ShutdownStage::StopSubscriptions, "streams", recording_hook(&stages, "unsubscribe")).await;
+        manager.add_shutdown_hook(ShutdownStage::CloseSessions, "sessions", recording_hook(&stages, "close")).await;
+        manager.add_shutdown_hook(ShutdownStage::ArmSafeState, "safety", recording_hook(&stages, "safe")).await;
+        
+        let report = manager.shutdown().await;
+        assert!(report.clean());
+        assert_eq!(report.stages.iter().map(|s| s.stage).collect::<Vec<_>>(), ShutdownStage::ALL.to_vec());
+        assert_eq!(*stages.lock().unwrap(), vec!["safe", "unsubscribe", "flush", "close", "drop"]);
+        
+        // The safe state went out while the session was still open
+        assert_eq!(*calls.lock().unwrap(), vec!["pwmWrite [Number(9), Number(0)]".to_string()]);
+        assert!(manager.list_sessions().await.is_empty());
+        assert!(manager.port_owner("COM11").await.is_none());
+        assert!(manager.emergency_stop_handle().is_stopped());
+    }
+    
+    #[tokio::test]
+    async fn test_hung_shutdown_stage_times_out() {
+        let manager = manager_with_driver().await;
+        manager.open_device(transport_on("COM12"), Some("sensor".into())).await.unwrap();
+        
+        let stages = Arc::new(std::sync::Mutex::new(Vec::new()));
+        manager.add_shutdown_hook(ShutdownStage::FlushTelemetry, "stuck flush", Box::new(|| {
+            Box::pin(tokio::time::sleep(Duration::from_secs(60)))
+        })).await;
+        manager.add_shutdown_hook(ShutdownStage::CloseSessions, "sessions", recording_hook(&stages, "close")).await;
+        manager.add_shutdown_hook(ShutdownStage::DropTransports, "transports", recording_hook(&stages, "drop")).await;
+        
+        let started = Instant::now();
+        let report = manager.shutdown_with_timeout(Duration::from_millis(50)).await;
+        assert!(started.elapsed() < Duration::from_secs(2));
+        
+        assert!(!report.clean());
+        assert!(!report.stage(ShutdownStage::FlushTelemetry).unwrap().completed);
+        assert!(report.stage(ShutdownStage::CloseSessions).unwrap().completed);
+        
+        // Later stages still ran
+        assert_eq!(*stages.lock().unwrap(), vec!["close", "drop"]);
+        assert!(manager.list_sessions().await.is_empty());
+    }
+    
+    /// Driver that recognizes one address after a delay, tracking concurrent probes
+    struct SlowProbeDriver {
+        target: String,
+        active: Arc<std::sync::atomic::AtomicUsize>,
+        peak: Arc<std::sync::atomic::AtomicUsize>,
+        probed: Arc<std::sync::Mutex<Vec<String>>>,
+    }
+    
+    #[async_trait]
+    impl DeviceDriver for SlowProbeDriver {
+        fn name(&self) -> &str {
+            "Slow Probe"
+        }
+        
+        fn version(&self) -> &str {
+            "1.0.0"
+        }
+        
+        fn supported_transports(&self) -> Vec<TransportType> {
+            vec![TransportType::Serial]
+        }
+        
+        async fn probe_async(&self, transport: Arc<dyn Transport>) -> DeviceResult<bool> {
+            use std::sync::atomic::Ordering;
+            
+            let address = transport.config().address.clone();
+            self.probed.lock().unwrap().push(address.clone());
+            let now_active = self.active.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak.fetch_max(now_active, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            self.active.fetch_sub(1, Ordering::SeqCst);
+            Ok(address == self.target)
+        }
+        
+        async fn open_async(&self, _transport: Arc<dyn Transport>) -> DeviceResult<Box<dyn DeviceSession>> {
+            Ok(inert_session())
+        }
+        
+        fn capabilities(&self) -> DriverCapabilities {
+            DriverCapabilities::default()
+        }
+    }
+    
+    async fn slow_probe_manager(target: &str, parallelism: usize) -> (DeviceManager, Arc<SlowProbeDriver>) {
+        let driver = Arc::new(SlowProbeDriver {
+            target: target.to_string(),
+            active: Arc::new(std::sync::atomic::AtomicUsize::new(0)),
+            peak: Arc::new(std::sync::atomic::AtomicUsize::new(0)),
+            probed: Arc::new(std::sync::Mutex::new(Vec::new())),
+        });
+        let manager = DeviceManager::new("./drivers").with_probe_parallelism(parallelism);
+        manager.register_driver(DriverInfo::new(driver.clone())).await;
+        (manager, driver)
+    }
+    
+    fn ports(count: usize) -> Vec<Arc<dyn Transport>> {
+        (1..=count).map(|n| transport_on(&format!("COM{}", n))).collect()
+    }
+    
+    #[tokio::test]
+    async fn test_probe_ports_bounded_parallelism_returns_first_match() {
+        let (manager, driver) = slow_probe_manager("COM4", 2).await;
+        
+        let found = manager.probe_ports(ports(8)).await.unwrap();
+        assert_eq!(found.transport.config().address, "COM4");
+        assert_eq!(found.driver.name(), "Slow Probe");
+        
+        assert!(driver.peak.load(std::sync::atomic::Ordering::SeqCst) <= 2);
+        assert_eq!(driver.peak.load(std::sync::atomic::Ordering::SeqCst), 2);
+        // Probing stopped soon after the match instead of sweeping every port
+        assert!(driver.probed.lock().unwrap().len() < 8);
+        
+        // Every probe claim was released
+        for n in 1..=8 {
+            assert!(manager.port_owner(&format!("COM{}", n)).await.is_none());
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_probe_ports_skips_claimed_and_duplicate_ports() {
+        let (manager, driver) = slow_probe_manager("COM2", 4).await;
+        manager.open_device(transport_on("COM2"), Some("ui".into())).await.unwrap();
+        driver.probed.lock().unwrap().clear();
+        
+        let candidates = vec![transport_on("COM1"), transport_on("COM2"), transport_on("COM1")];
+        assert!(matches!(manager.probe_ports(candidates).await, Err(DeviceError::DeviceNotFound(_))));
+        
+        // The open port was never probed and each address was probed once
+        assert_eq!(*driver.probed.lock().unwrap(), vec!["COM1".to_string()]);
+        assert_eq!(manager.port_owner("COM2").await.as_deref(), Some("ui"));
+        
+        manager.close_device("ui").await.unwrap();
+        let session = manager.auto_connect(ports(3)).await.unwrap();
+        assert_eq!(manager.port_owner("COM2").await, Some(session));
+    }
+}
@@ -0,0 +1,188 @@
+//! Mock device session for testing
+//!
+//! `MockSession` answers every invocation through a responder closure and
+//! records each call with the time it arrived, so a test can stand in for a
+//! device without implementing `DeviceSession` itself. Slow or hung devices
+//! are simulated with a reply delay.
+
+use async_trait::async_trait;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use crate::device::{DeviceResult, DeviceError, DeviceSession};
+use crate::device::clock_sync::ClockOffset;
+use crate::device::session::{StreamData, SubscriptionHandle, SessionStatistics};
+
+/// Computes the reply to an invocation from its endpoint and arguments
+pub type MockResponder = Arc<dyn Fn(&str, &[Value]) -> DeviceResult<Value> + Send + Sync>;
+
+/// One invocation received by a mock session
+#[derive(Debug, Clone)]
+pub struct MockCall {
+    pub endpoint: String,
+    pub args: Vec<Value>,
+    pub at: Instant,
+}
+
+/// Mock session for testing
+pub struct MockSession {
+    session_id: String,
+    device_name: String,
+    respond: MockResponder,
+    calls: Arc<Mutex<Vec<MockCall>>>,
+    delay: Duration,
+    capabilities: Option<BTreeSet<String>>,
+    clock_offset: Option<ClockOffset>,
+    active: bool,
+}
+
+impl MockSession {
+    /// Session answering each invocation with `respond(endpoint, args)`
+    pub fn new(respond: impl Fn(&str, &[Value]) -> DeviceResult<Value> + Send + Sync + 'static) -> Self {
+        Self {
+            session_id: "mock".to_string(),
+            device_name: "Mock".to_string(),
+            respond: Arc::new(respond),
+            calls: Arc::new(Mutex::new(Vec::new())),
+            delay: Duration::ZERO,
+            capabilities: None,
+            clock_offset: None,
+            active: true,
+        }
+    }
+    
+    /// Session answering every invocation with `null`
+    pub fn inert() -> Self {
+        Self::new(|_, _| Ok(Value::Null))
+    }
+    
+    pub fn with_name(mut self, session_id: &str, device_name: &str) -> Self {
+        self.session_id = session_id.to_string();
+        self.device_name = device_name.to_string();
+        self
+    }
+    
+    /// Take `delay` to answer each invocation or clock sync, like a slow or hung device
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+    
+    /// Report `capabilities` from `query_capabilities`
+    pub fn with_capabilities<S: ToString>(mut self, capabilities: impl IntoIterator<Item = S>) -> Self {
+        self.capabilities = Some(capabilities.into_iter().map(|c| c.to_string()).collect());
+        self
+    }
+    
+    /// Answer `sync_clock` with `offset`, and always report a re-sync as due
+    pub fn with_clock_offset(mut self, offset: ClockOffset) -> Self {
+        self.clock_offset = Some(offset);
+        self
+    }
+    
+    /// Calls received so far; the handle stays valid once the session is boxed
+    pub fn calls(&self) -> Arc<Mutex<Vec<MockCall>>> {
+        self.calls.clone()
+    }
+    
+    /// Endpoints invoked so far, in order
+    pub fn endpoints(&self) -> Vec<String> {
+        self.calls.lock().unwrap().iter().map(|call| call.endpoint.clone()).collect()
+    }
+}
+
+#[async_trait]
+impl DeviceSession for MockSession {
+    fn session_id(&self) -> &str {
+        &self.session_id
+    }
+    
+    fn device_name(&self) -> &str {
+        &self.device_name
+    }
+    
+    async fn invoke_async(&mut self, endpoint: &str, args: Vec<Value>) -> DeviceResult<Value> {
+        self.calls.lock().unwrap().push(MockCall {
+            endpoint: endpoint.to_string(),
+            args: args.clone(),
+            at: Instant::now(),
+        });
+        
+        // Yield even without a delay so concurrently driven sessions interleave
+        if self.delay.is_zero() {
+            tokio::task::yield_now().await;
+        } else {
+            tokio::time::sleep(self.delay).await;
+        }
+        (self.respond)(endpoint, &args)
+    }
+    
+    async fn subscribe_async(
+        &mut self,
+        _stream: &str,
+        _handler: mpsc::UnboundedSender<StreamData>,
+    ) -> DeviceResult<SubscriptionHandle> {
+        Err(DeviceError::Unknown("not supported".into()))
+    }
+    
+    async fn close_async(&mut self) -> DeviceResult<()> {
+        self.active = false;
+        Ok(())
+    }
+    
+    fn is_active(&self) -> bool {
+        self.active
+    }
+    
+    fn statistics(&self) -> SessionStatistics {
+        SessionStatistics::default()
+    }
+    
+    async fn send_raw(&mut self, _data: &[u8]) -> DeviceResult<Vec<u8>> {
+        tokio::time::sleep(self.delay).await;
+        Ok(vec![])
+    }
+    
+    async fn sync_clock(&mut self) -> DeviceResult<ClockOffset> {
+        let offset = self.clock_offset.ok_or_else(|| {
+            DeviceError::UnsupportedDevice(format!("{} does not report its clock", self.device_name))
+        })?;
+        tokio::time::sleep(self.delay).await;
+        Ok(offset)
+    }
+    
+    fn clock_resync_due(&self) -> bool {
+        self.clock_offset.is_some()
+    }
+    
+    async fn query_capabilities(&mut self) -> DeviceResult<BTreeSet<String>> {
+        self.capabilities.clone().ok_or_else(|| {
+            DeviceError::UnsupportedDevice(format!("{} does not report its capabilities", self.device_name))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    
+    #[tokio::test]
+    async fn test_mock_session_records_calls() {
+        let mut session = MockSession::new(|endpoint, args| match endpoint {
+            "analogRead" => Ok(json!({ "pin": args[0], "value": 512 })),
+            _ => Err(DeviceError::DeviceRejection(format!("{} refused", endpoint))),
+        });
+        
+        assert_eq!(session.invoke_async("analogRead", vec![json!(2)]).await.unwrap(), json!({ "pin": 2, "value": 512 }));
+        assert!(session.invoke_async("setServo", vec![json!(0), json!(90)]).await.is_err());
+        assert_eq!(session.endpoints(), vec!["analogRead", "setServo"]);
+        assert_eq!(session.calls().lock().unwrap()[1].args, vec![json!(0), json!(90)]);
+        
+        assert!(session.query_capabilities().await.is_err());
+        session.close_async().await.unwrap();
+        assert!(!session.is_active());
+    }
+}
@@ -5,13 +5,44 @@ pub mod manager;
 pub mod plugin;
 pub mod safety;
 pub mod connection_manager;
+pub mod read_cache;
+pub mod self_test;
+pub mod demux;
+pub mod clock_sync;
+pub mod response_parser;
+pub mod raw_session;
+pub mod fault_injection;
+pub mod shutdown;
+pub mod registry;
+pub mod calibration;
+pub mod identify;
+pub mod keep_alive;
+pub mod batch;
+pub mod command_failures;
+pub mod command_history;
+pub mod mock;
 
 pub use driver::{DeviceDriver, DriverCapabilities, DriverInfo, DriverPriority};
-pub use session::{DeviceSession, DeviceEndpoint, StreamData};
-pub use manager::DeviceManager;
+pub use session::{DeviceSession, DeviceEndpoint, StreamData, InputPinSet, SessionCommand, SessionSelector, SharedSession};
+pub use manager::{DeviceManager, HandshakeMode, ProbeMatch, DEFAULT_PROBE_PARALLELISM};
 pub use plugin::{PluginLoader, PluginManifest};
 pub use safety::{SafetyController, EmergencyStop, HotPlugMonitor, HotPlugEvent};
 pub use connection_manager::{ConnectionManager, ConnectionEvent, ConnectionState};
+pub use read_cache::ReadCache;
+pub use self_test::{SelfTestReport, SelfTestStep, SelfTestPlan, StepResult, StepStatus};
+pub use demux::StreamDemux;
+pub use clock_sync::{ClockOffset, ClockSync};
+pub use response_parser::{ResponseParser, LineParser, JsonParser, KeyValueParser};
+pub use raw_session::RawSession;
+pub use shutdown::{ShutdownStage, ShutdownReport, ShutdownStageResult, ShutdownHook};
+pub use fault_injection::{FaultInjectingSession, FaultRule, InjectedFault, InjectedCall};
+pub use registry::{DeviceRegistry, KnownDevice, RegistryError};
+pub use calibration::{CalibrationSession, CalibratedSession, CalibrationPoint, CalibrationError, LinearCalibration};
+pub use identify::{identify_physical, identify_shared, IdentifyPattern, DEFAULT_IDENTIFY_PIN};
+pub use keep_alive::{KeepAlive, KeepAliveSettings};
+pub use batch::{dispatch_batch, BatchMode, BatchResult};
+pub use command_failures::{CommandFailureTracker, FailureAlert, DEFAULT_FAILURE_ALERT_THRESHOLD};
+pub use command_history::{CommandHistory, HistoryEntry};
 
 // Re-export transport types for convenience
 pub use crate::transport::{Transport, TransportType};
@@ -63,6 +94,19 @@ pub enum DeviceError {
     #[error("Unsupported device: {0}")]
     UnsupportedDevice(String),
     
+    #[error("Protocol error: {0}")]
+    Protocol(String),
+    
+    #[error("Session error: {0}")]
+    Session(String),
+    
+    /// The device answered with an explicit error/NAK (as opposed to not answering)
+    #[error("Device rejected command: {0}")]
+    DeviceRejection(String),
+    
+    #[error("Handshake failed: {0}")]
+    Handshake(#[from] crate::protocols::handshake::HandshakeError),
+    
     #[error("Unknown error: {0}")]
     Unknown(String),
 }
\ No newline at end of file
@@ -0,0 +1,110 @@
+//! Session for devices that speak no identification protocol
+//!
+//! Dumb serial sinks (displays, loggers, relay boards driven by plain bytes)
+//! never answer a probe or handshake. A raw session is opened without any
+//! exchange and simply passes bytes through to the transport.
+
+use async_trait::async_trait;
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use crate::device::{DeviceResult, DeviceError, DeviceSession};
+use crate::device::session::{StreamData, SubscriptionHandle, SessionStatistics, ensure_transport_connected};
+use crate::transport::{Transport, TransportError};
+
+/// How long `send_raw` waits for a reply before returning nothing
+const RAW_REPLY_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Byte pass-through session with no driver behind it
+pub struct RawSession {
+    session_id: String,
+    transport: Arc<dyn Transport>,
+    stats: SessionStatistics,
+    active: bool,
+}
+
+impl RawSession {
+    pub fn new(transport: Arc<dyn Transport>) -> Self {
+        Self {
+            session_id: format!("raw_{}", transport.name()),
+            transport,
+            stats: SessionStatistics::new(),
+            active: true,
+        }
+    }
+}
+
+#[async_trait]
+impl DeviceSession for RawSession {
+    fn session_id(&self) -> &str {
+        &self.session_id
+    }
+    
+    fn device_name(&self) -> &str {
+        "Raw device"
+    }
+    
+    /// Only `write` is available: sends its string argument verbatim
+    async fn invoke_async(&mut self, endpoint: &str, args: Vec<Value>) -> DeviceResult<Value> {
+        match (endpoint, args.first()) {
+            ("write", Some(Value::String(text))) => {
+                let data = text.clone().into_bytes();
+                self.send_raw(&data).await?;
+                Ok(Value::Null)
+            }
+            ("write", _) => Err(DeviceError::Protocol("write expects a string argument".into())),
+            _ => Err(DeviceError::UnsupportedDevice(format!("raw devices have no '{}' endpoint", endpoint))),
+        }
+    }
+    
+    async fn subscribe_async(
+        &mut self,
+        stream: &str,
+        _handler: mpsc::UnboundedSender<StreamData>,
+    ) -> DeviceResult<SubscriptionHandle> {
+        Err(DeviceError::UnsupportedDevice(format!("raw devices have no '{}' stream", stream)))
+    }
+    
+    async fn close_async(&mut self) -> DeviceResult<()> {
+        self.active = false;
+        Ok(())
+    }
+    
+    fn is_active(&self) -> bool {
+        self.active
+    }
+    
+    fn statistics(&self) -> SessionStatistics {
+        self.stats.clone()
+    }
+    
+    /// Send bytes and return whatever arrives shortly after (empty if nothing does)
+    async fn send_raw(&mut self, data: &[u8]) -> DeviceResult<Vec<u8>> {
+        ensure_transport_connected(self.transport.as_ref()).await?;
+        
+        let started = Instant::now();
+        if let Err(e) = self.transport.send(data).await {
+            self.stats.error_count += 1;
+            return Err(DeviceError::TransportError(e.to_string()));
+        }
+        self.stats.bytes_sent += data.len() as u64;
+        self.stats.commands_sent += 1;
+        
+        let reply = match self.transport.receive(RAW_REPLY_TIMEOUT).await {
+            Ok(reply) => reply,
+            Err(TransportError::Timeout(_)) => Vec::new(),
+            Err(e) => {
+                self.stats.error_count += 1;
+                return Err(DeviceError::TransportError(e.to_string()));
+            }
+        };
+        
+        if !reply.is_empty() {
+            self.stats.update_latency(started.elapsed().as_millis() as u64);
+            self.stats.bytes_received += reply.len() as u64;
+            self.stats.responses_received += 1;
+        }
+        Ok(reply)
+    }
+}
@@ -0,0 +1,154 @@
+//! Short-TTL cache for idempotent read endpoints
+//!
+//! Lets several UI widgets poll the same pin without each poll costing a
+//! device round-trip. Caching is opt-in per endpoint; writes to a pin
+//! invalidate any cached reads of that pin.
+
+use serde_json::Value;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Cache of recent read results keyed by endpoint and arguments
+#[derive(Debug, Clone, Default)]
+pub struct ReadCache {
+    /// TTL per cacheable endpoint; endpoints not listed are never cached
+    ttls: HashMap<String, Duration>,
+    /// Keyed by endpoint and serialized arguments
+    entries: HashMap<String, CacheEntry>,
+}
+
+#[derive(Debug, Clone)]
+struct CacheEntry {
+    stored_at: Instant,
+    ttl: Duration,
+    pin: Option<u64>,
+    value: Value,
+}
+
+impl ReadCache {
+    /// Create an empty cache with no cacheable endpoints
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Enable caching for an endpoint with the given TTL
+    pub fn with_ttl(mut self, endpoint: &str, ttl: Duration) -> Self {
+        self.set_ttl(endpoint, ttl);
+        self
+    }
+    
+    /// Enable caching for an endpoint, or disable it with a zero TTL
+    pub fn set_ttl(&mut self, endpoint: &str, ttl: Duration) {
+        if ttl.is_zero() {
+            self.ttls.remove(endpoint);
+            self.entries.retain(|key, _| !key.starts_with(&format!("{}:", endpoint)));
+        } else {
+            self.ttls.insert(endpoint.to_string(), ttl);
+        }
+    }
+    
+    /// Whether results from this endpoint are cached
+    pub fn is_cacheable(&self, endpoint: &str) -> bool {
+        self.ttls.contains_key(endpoint)
+    }
+    
+    /// Return a cached value if one is still fresh
+    pub fn get(&mut self, endpoint: &str, args: &[Value]) -> Option<Value> {
+        if !self.is_cacheable(endpoint) {
+            return None;
+        }
+        
+        let key = cache_key(endpoint, args);
+        match self.entries.get(&key) {
+            Some(entry) if entry.stored_at.elapsed() < entry.ttl => Some(entry.value.clone()),
+            Some(_) => {
+                self.entries.remove(&key);
+                None
+            }
+            None => None,
+        }
+    }
+    
+    /// Store a read result (ignored for endpoints without a TTL)
+    pub fn insert(&mut self, endpoint: &str, args: &[Value], value: Value) {
+        if let Some(&ttl) = self.ttls.get(endpoint) {
+            self.entries.insert(cache_key(endpoint, args), CacheEntry {
+                stored_at: Instant::now(),
+                ttl,
+                pin: args.first().and_then(|v| v.as_u64()),
+                value,
+            });
+        }
+    }
+    
+    /// Drop all cached reads of a pin (call after any write to it)
+    pub fn invalidate_pin(&mut self, pin: u64) {
+        self.entries.retain(|_, entry| entry.pin != Some(pin));
+    }
+    
+    /// Drop every cached value
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+    
+    /// Number of cached values (including expired ones not yet evicted)
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    
+    /// Whether the cache holds no values
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+fn cache_key(endpoint: &str, args: &[Value]) -> String {
+    format!("{}:{}", endpoint, Value::Array(args.to_vec()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    
+    #[test]
+    fn test_cache_hit_and_expiry() {
+        let mut cache = ReadCache::new().with_ttl("analogRead", Duration::from_millis(50));
+        
+        assert!(cache.get("analogRead", &[json!(0)]).is_none());
+        cache.insert("analogRead", &[json!(0)], json!({ "value": 512 }));
+        assert_eq!(cache.get("analogRead", &[json!(0)]), Some(json!({ "value": 512 })));
+        
+        // Different pin is a different entry
+        assert!(cache.get("analogRead", &[json!(1)]).is_none());
+        
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(cache.get("analogRead", &[json!(0)]).is_none());
+        assert!(cache.is_empty());
+    }
+    
+    #[test]
+    fn test_uncached_endpoints_are_ignored() {
+        let mut cache = ReadCache::new().with_ttl("analogRead", Duration::from_secs(1));
+        
+        cache.insert("digitalRead", &[json!(7)], json!({ "value": true }));
+        assert!(cache.get("digitalRead", &[json!(7)]).is_none());
+        assert!(cache.is_empty());
+    }
+    
+    #[test]
+    fn test_invalidate_pin() {
+        let mut cache = ReadCache::new()
+            .with_ttl("analogRead", Duration::from_secs(1))
+            .with_ttl("digitalRead", Duration::from_secs(1));
+        
+        cache.insert("analogRead", &[json!(3)], json!({ "value": 100 }));
+        cache.insert("digitalRead", &[json!(3)], json!({ "value": true }));
+        cache.insert("digitalRead", &[json!(4)], json!({ "value": false }));
+        
+        cache.invalidate_pin(3);
+        assert!(cache.get("analogRead", &[json!(3)]).is_none());
+        assert!(cache.get("digitalRead", &[json!(3)]).is_none());
+        assert!(cache.get("digitalRead", &[json!(4)]).is_some());
+    }
+}
@@ -0,0 +1,243 @@
+//! Persistent registry of known devices
+//!
+//! Discovery only reports what is plugged in right now. The registry remembers
+//! devices the user chose to keep (friendly name, transport config, metadata)
+//! in a JSON file so the fleet survives restarts, and tracks which of them the
+//! latest discovery pass actually found so offline devices can still be shown.
+
+use serde::{Serialize, Deserialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+use crate::transport::{TransportConfig, TransportType};
+
+/// Registry file name inside the app data directory
+pub const REGISTRY_FILE: &str = "devices.json";
+
+/// Registry persistence errors
+#[derive(Debug, Error)]
+pub enum RegistryError {
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+    
+    #[error("Registry file is malformed: {0}")]
+    Malformed(#[from] serde_json::Error),
+    
+    #[error("Unknown device: {0}")]
+    UnknownDevice(String),
+}
+
+/// A remembered device
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KnownDevice {
+    /// Stable registry key, derived from transport type and address
+    pub id: String,
+    
+    pub friendly_name: String,
+    
+    /// Config used to reconnect
+    pub transport: TransportConfig,
+    
+    /// Unix time (seconds) the device was last discovered
+    #[serde(default)]
+    pub last_seen: Option<u64>,
+    
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+    
+    /// Whether the latest discovery pass found the device (not persisted)
+    #[serde(skip)]
+    pub online: bool,
+}
+
+impl KnownDevice {
+    pub fn new(friendly_name: &str, transport: TransportConfig) -> Self {
+        Self {
+            id: device_key(transport.transport_type, &transport.address),
+            friendly_name: friendly_name.to_string(),
+            transport,
+            last_seen: None,
+            metadata: HashMap::new(),
+            online: false,
+        }
+    }
+    
+    /// Attach a metadata entry
+    pub fn with_metadata(mut self, key: &str, value: &str) -> Self {
+        self.metadata.insert(key.to_string(), value.to_string());
+        self
+    }
+}
+
+/// Registry key for a device reachable over `transport_type` at `address`
+pub fn device_key(transport_type: TransportType, address: &str) -> String {
+    format!("{:?}_{}", transport_type, address)
+}
+
+/// Default registry location in the app data directory
+pub fn default_registry_path() -> PathBuf {
+    match dirs::data_dir() {
+        Some(data_dir) => data_dir.join("multi-controller-app").join(REGISTRY_FILE),
+        None => PathBuf::from(".").join(REGISTRY_FILE),
+    }
+}
+
+/// Known devices, saved to disk on every change
+#[derive(Debug)]
+pub struct DeviceRegistry {
+    path: PathBuf,
+    devices: HashMap<String, KnownDevice>,
+}
+
+impl DeviceRegistry {
+    /// Empty registry that will be saved to `path`
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            devices: HashMap::new(),
+        }
+    }
+    
+    /// Load the registry at `path`; a missing file is an empty registry
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self, RegistryError> {
+        let path = path.into();
+        let devices = match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str::<Vec<KnownDevice>>(&content)?
+                .into_iter()
+                .map(|device| (device.id.clone(), device))
+                .collect(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::new(path)),
+            Err(e) => return Err(e.into()),
+        };
+        
+        Ok(Self { path, devices })
+    }
+    
+    /// File the registry is saved to
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+    
+    /// Add or replace a device and save
+    pub fn register(&mut self, device: KnownDevice) -> Result<(), RegistryError> {
+        tracing::info!("Registered device {} ({})", device.friendly_name, device.id);
+        self.devices.insert(device.id.clone(), device);
+        self.save()
+    }
+    
+    /// Forget a device and save
+    pub fn remove(&mut self, id: &str) -> Result<KnownDevice, RegistryError> {
+        let device = self.devices.remove(id)
+            .ok_or_else(|| RegistryError::UnknownDevice(id.to_string()))?;
+        self.save()?;
+        Ok(device)
+    }
+    
+    pub fn get(&self, id: &str) -> Option<&KnownDevice> {
+        self.devices.get(id)
+    }
+    
+    /// Known devices ordered by friendly name
+    pub fn devices(&self) -> Vec<KnownDevice> {
+        let mut devices: Vec<KnownDevice> = self.devices.values().cloned().collect();
+        devices.sort_by(|a, b| a.friendly_name.cmp(&b.friendly_name).then_with(|| a.id.cmp(&b.id)));
+        devices
+    }
+    
+    /// Merge a discovery pass: found devices go online and get a new
+    /// `last_seen`, every other known device goes offline
+    pub fn apply_discovery(&mut self, discovered: &[(TransportType, String)]) -> Result<(), RegistryError> {
+        let found: HashSet<String> = discovered.iter()
+            .map(|(transport_type, address)| device_key(*transport_type, address))
+            .collect();
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        
+        let mut seen_any = false;
+        for device in self.devices.values_mut() {
+            device.online = found.contains(&device.id);
+            if device.online {
+                device.last_seen = Some(now);
+                seen_any = true;
+            }
+        }
+        
+        if seen_any {
+            self.save()?;
+        }
+        Ok(())
+    }
+    
+    /// Write the registry to disk, creating its directory if needed
+    pub fn save(&self) -> Result<(), RegistryError> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let content = serde_json::to_string_pretty(&self.devices())?;
+        fs::write(&self.path, content)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+    
+    fn serial_config(address: &str) -> TransportConfig {
+        TransportConfig {
+            address: address.to_string(),
+            ..Default::default()
+        }
+    }
+    
+    #[test]
+    fn test_registered_device_persists_across_loads() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("nested").join(REGISTRY_FILE);
+        
+        let mut registry = DeviceRegistry::load(&path).unwrap();
+        assert!(registry.devices().is_empty());
+        registry.register(KnownDevice::new("Bench Uno", serial_config("/dev/ttyACM0")).with_metadata("location", "bench")).unwrap();
+        
+        let reloaded = DeviceRegistry::load(&path).unwrap();
+        let devices = reloaded.devices();
+        assert_eq!(devices.len(), 1);
+        assert_eq!(devices[0].friendly_name, "Bench Uno");
+        assert_eq!(devices[0].transport.address, "/dev/ttyACM0");
+        assert_eq!(devices[0].metadata["location"], "bench");
+    }
+    
+    #[test]
+    fn test_known_device_is_offline_until_discovered() {
+        let dir = TempDir::new().unwrap();
+        let mut registry = DeviceRegistry::load(dir.path().join(REGISTRY_FILE)).unwrap();
+        registry.register(KnownDevice::new("Bench Uno", serial_config("/dev/ttyACM0"))).unwrap();
+        registry.register(KnownDevice::new("Rover", serial_config("/dev/ttyUSB0"))).unwrap();
+        
+        let id = device_key(TransportType::Serial, "/dev/ttyACM0");
+        assert!(!registry.get(&id).unwrap().online);
+        assert!(registry.get(&id).unwrap().last_seen.is_none());
+        
+        registry.apply_discovery(&[(TransportType::Serial, "/dev/ttyACM0".to_string())]).unwrap();
+        assert!(registry.get(&id).unwrap().online);
+        assert!(!registry.get(&device_key(TransportType::Serial, "/dev/ttyUSB0")).unwrap().online);
+        
+        // Last-seen is persisted, online status is not
+        let reloaded = DeviceRegistry::load(registry.path()).unwrap();
+        assert!(reloaded.get(&id).unwrap().last_seen.is_some());
+        assert!(!reloaded.get(&id).unwrap().online);
+        
+        // A later pass that misses the device marks it offline again
+        registry.apply_discovery(&[]).unwrap();
+        assert!(!registry.get(&id).unwrap().online);
+    }
+    
+    #[test]
+    fn test_remove_unknown_device_fails() {
+        let dir = TempDir::new().unwrap();
+        let mut registry = DeviceRegistry::load(dir.path().join(REGISTRY_FILE)).unwrap();
+        assert!(matches!(registry.remove("Serial_COM9"), Err(RegistryError::UnknownDevice(_))));
+    }
+}
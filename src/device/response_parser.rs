@@ -0,0 +1,113 @@
+//! Pluggable parsing of device responses
+//!
+//! Firmwares format replies differently: plain status lines, JSON objects or
+//! `key=value` lists. A session holds one `ResponseParser` and runs every
+//! response through it before interpreting the reply, so supporting a new
+//! firmware format does not require a new driver.
+
+use serde_json::{Map, Value};
+use crate::device::{DeviceError, DeviceResult};
+use crate::device::demux::parse_value;
+
+/// Turns the raw bytes of one response into a JSON value
+pub trait ResponseParser: Send + Sync {
+    /// Parse a complete response (terminator already stripped or trailing)
+    fn parse(&self, bytes: &[u8]) -> DeviceResult<Value>;
+}
+
+/// Decode a response as trimmed UTF-8 text
+fn response_text(bytes: &[u8]) -> DeviceResult<&str> {
+    std::str::from_utf8(bytes)
+        .map(str::trim)
+        .map_err(|e| DeviceError::Protocol(format!("Response is not valid UTF-8: {}", e)))
+}
+
+/// A single text line, returned as a string (the original Arduino behavior)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LineParser;
+
+impl ResponseParser for LineParser {
+    fn parse(&self, bytes: &[u8]) -> DeviceResult<Value> {
+        let line = response_text(bytes)?;
+        if line.is_empty() {
+            return Err(DeviceError::Protocol("Empty response".into()));
+        }
+        if line.contains('\n') {
+            return Err(DeviceError::Protocol(format!("Expected a single line, got: {}", line)));
+        }
+        Ok(Value::String(line.to_string()))
+    }
+}
+
+/// A JSON document
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonParser;
+
+impl ResponseParser for JsonParser {
+    fn parse(&self, bytes: &[u8]) -> DeviceResult<Value> {
+        serde_json::from_str(response_text(bytes)?)
+            .map_err(|e| DeviceError::Protocol(format!("Invalid JSON response: {}", e)))
+    }
+}
+
+/// `key=value` pairs separated by commas, semicolons or whitespace
+/// Values become numbers or bools where they parse as one
+#[derive(Debug, Clone, Copy, Default)]
+pub struct KeyValueParser;
+
+impl ResponseParser for KeyValueParser {
+    fn parse(&self, bytes: &[u8]) -> DeviceResult<Value> {
+        let text = response_text(bytes)?;
+        let mut fields = Map::new();
+        
+        for pair in text.split(|c: char| c == ',' || c == ';' || c.is_whitespace()).filter(|p| !p.is_empty()) {
+            let (key, value) = pair.split_once('=')
+                .ok_or_else(|| DeviceError::Protocol(format!("Expected key=value, got: {}", pair)))?;
+            if key.is_empty() {
+                return Err(DeviceError::Protocol(format!("Missing key in: {}", pair)));
+            }
+            fields.insert(key.to_string(), parse_value(value));
+        }
+        
+        if fields.is_empty() {
+            return Err(DeviceError::Protocol("Empty response".into()));
+        }
+        Ok(Value::Object(fields))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    
+    #[test]
+    fn test_line_parser() {
+        assert_eq!(LineParser.parse(b"OK\r\n").unwrap(), json!("OK"));
+        assert_eq!(LineParser.parse(b"VALUE:512").unwrap(), json!("VALUE:512"));
+        
+        assert!(matches!(LineParser.parse(b"\r\n"), Err(DeviceError::Protocol(_))));
+        assert!(matches!(LineParser.parse(b"OK\nOK"), Err(DeviceError::Protocol(_))));
+        assert!(matches!(LineParser.parse(&[0x4F, 0xFF, 0x0A]), Err(DeviceError::Protocol(_))));
+    }
+    
+    #[test]
+    fn test_json_parser() {
+        let value = JsonParser.parse(b"{\"temp\": 21.5, \"pins\": [1, 0]}\r\n").unwrap();
+        assert_eq!(value, json!({ "temp": 21.5, "pins": [1, 0] }));
+        assert_eq!(JsonParser.parse(b"42").unwrap(), json!(42));
+        
+        assert!(matches!(JsonParser.parse(b"{\"temp\": "), Err(DeviceError::Protocol(_))));
+        assert!(matches!(JsonParser.parse(b"OK"), Err(DeviceError::Protocol(_))));
+    }
+    
+    #[test]
+    fn test_key_value_parser() {
+        let value = KeyValueParser.parse(b"temp=21.5,rpm=1200;door=true mode=idle\r\n").unwrap();
+        assert_eq!(value, json!({ "temp": 21.5, "rpm": 1200, "door": true, "mode": "idle" }));
+        
+        assert!(matches!(KeyValueParser.parse(b"temp=21.5,rpm"), Err(DeviceError::Protocol(_))));
+        assert!(matches!(KeyValueParser.parse(b"=5"), Err(DeviceError::Protocol(_))));
+        assert!(matches!(KeyValueParser.parse(b"  "), Err(DeviceError::Protocol(_))));
+    }
+}
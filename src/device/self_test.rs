@@ -0,0 +1,102 @@
+//! Device stack self-test report types
+//!
+//! `DeviceManager::self_test` walks connect → handshake → read → write →
+//! disconnect against a device and records each step here, so a new setup
+//! can be validated in one click.
+
+use serde::{Serialize, Deserialize};
+use serde_json::{Value, json};
+use std::time::Duration;
+
+/// Stages of the self-test, in execution order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SelfTestStep {
+    Connect,
+    Handshake,
+    Read,
+    Write,
+    Disconnect,
+}
+
+impl SelfTestStep {
+    /// Every step in execution order
+    pub const ALL: [SelfTestStep; 5] = [
+        SelfTestStep::Connect,
+        SelfTestStep::Handshake,
+        SelfTestStep::Read,
+        SelfTestStep::Write,
+        SelfTestStep::Disconnect,
+    ];
+}
+
+/// Outcome of a single step
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum StepStatus {
+    Passed,
+    Failed(String),
+    /// Not run because an earlier step failed
+    Skipped,
+}
+
+/// Result and timing of one self-test step
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepResult {
+    pub step: SelfTestStep,
+    pub status: StepStatus,
+    pub duration: Duration,
+    /// Extra information (e.g. detected driver, values read)
+    pub detail: Option<String>,
+}
+
+/// Write exercised by the self-test
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SelfTestPlan {
+    pub write_endpoint: String,
+    pub write_args: Vec<Value>,
+}
+
+impl Default for SelfTestPlan {
+    /// Configure the on-board LED pin as an output (harmless on most boards)
+    fn default() -> Self {
+        Self {
+            write_endpoint: "pinMode".to_string(),
+            write_args: vec![json!(13), json!("OUTPUT")],
+        }
+    }
+}
+
+/// Per-step results of a device self-test
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SelfTestReport {
+    pub steps: Vec<StepResult>,
+}
+
+impl SelfTestReport {
+    /// Whether every step passed
+    pub fn passed(&self) -> bool {
+        !self.steps.is_empty() && self.steps.iter().all(|s| s.status == StepStatus::Passed)
+    }
+    
+    /// Result of a particular step, if it was recorded
+    pub fn step(&self, step: SelfTestStep) -> Option<&StepResult> {
+        self.steps.iter().find(|s| s.step == step)
+    }
+    
+    /// First step that failed
+    pub fn first_failure(&self) -> Option<&StepResult> {
+        self.steps.iter().find(|s| matches!(s.status, StepStatus::Failed(_)))
+    }
+    
+    /// Total time spent across all steps
+    pub fn total_duration(&self) -> Duration {
+        self.steps.iter().map(|s| s.duration).sum()
+    }
+    
+    pub(crate) fn record(&mut self, step: SelfTestStep, status: StepStatus, duration: Duration, detail: Option<String>) {
+        self.steps.push(StepResult { step, status, duration, detail });
+    }
+    
+    pub(crate) fn skip(&mut self, step: SelfTestStep) {
+        self.record(step, StepStatus::Skipped, Duration::ZERO, None);
+    }
+}
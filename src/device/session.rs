@@ -1,9 +1,16 @@
 use async_trait::async_trait;
 use serde::{Serialize, Deserialize};
-use serde_json::Value;
+use serde_json::{Value, json};
+use std::collections::{BTreeSet, HashMap, VecDeque};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::mpsc;
 use crate::device::{DeviceResult, DeviceError};
+use crate::device::batch::{dispatch_batch, BatchMode};
+use crate::device::clock_sync::ClockOffset;
+use crate::device::response_parser::ResponseParser;
+use crate::protocols::handshake::NegotiatedVersion;
+use crate::transport::Transport;
 
 /// Device session interface (equivalent to IDeviceSession)
 /// Represents an active connection to a device
@@ -36,8 +43,177 @@ pub trait DeviceSession: Send + Sync {
     /// Get session statistics
     fn statistics(&self) -> SessionStatistics;
     
+    /// Per-endpoint invocation metrics accumulated over the session's lifetime
+    /// (empty for sessions that don't record them)
+    fn command_metrics(&self) -> HashMap<String, EndpointMetrics> {
+        HashMap::new()
+    }
+    
     /// Send raw command (for debugging/direct control)
     async fn send_raw(&mut self, data: &[u8]) -> DeviceResult<Vec<u8>>;
+    
+    /// Estimate the device-to-host clock offset and store it on the session
+    async fn sync_clock(&mut self) -> DeviceResult<ClockOffset> {
+        Err(DeviceError::UnsupportedDevice(format!("{} does not report its clock", self.device_name())))
+    }
+    
+    /// Latest clock offset from `sync_clock`, if any
+    fn clock_offset(&self) -> Option<ClockOffset> {
+        None
+    }
+    
+    /// Whether the stored clock offset is missing or due for a re-sync
+    fn clock_resync_due(&self) -> bool {
+        false
+    }
+    
+    /// Protocol version agreed with the device when the session was opened
+    /// (`None` if the driver didn't negotiate one)
+    fn negotiated_version(&self) -> Option<NegotiatedVersion> {
+        None
+    }
+    
+    /// Parse device responses with `parser` instead of the driver's default
+    fn set_response_parser(&mut self, _parser: Arc<dyn ResponseParser>) -> DeviceResult<()> {
+        Err(DeviceError::UnsupportedDevice(format!("{} does not support custom response parsers", self.device_name())))
+    }
+    
+    /// Ask the device which capabilities its firmware currently provides
+    async fn query_capabilities(&mut self) -> DeviceResult<BTreeSet<String>> {
+        Err(DeviceError::UnsupportedDevice(format!("{} does not report its capabilities", self.device_name())))
+    }
+    
+    /// Inputs the device currently exposes for reading (none by default)
+    async fn readable_inputs(&self) -> InputPinSet {
+        InputPinSet::default()
+    }
+    
+    /// Read every readable input in one call
+    /// Keys are "D<pin>" and "A<pin>"; a pin that fails maps to `{"error": ...}`
+    /// instead of aborting the whole read
+    async fn read_all_inputs(&mut self) -> DeviceResult<HashMap<String, Value>> {
+        if !self.is_active() {
+            return Err(DeviceError::NotConnected);
+        }
+        
+        let inputs = self.readable_inputs().await;
+        
+        if let Some(ref endpoint) = inputs.batch_endpoint {
+            match self.invoke_async(endpoint, vec![]).await {
+                Ok(Value::Object(values)) => return Ok(values.into_iter().collect()),
+                Ok(other) => tracing::warn!("Batched read '{}' returned non-object: {}", endpoint, other),
+                Err(e) => tracing::warn!("Batched read '{}' failed, reading pins individually: {}", endpoint, e),
+            }
+        }
+        
+        let reads = inputs.digital.iter().map(|&pin| (format!("D{}", pin), "digitalRead", pin))
+            .chain(inputs.analog.iter().map(|&pin| (format!("A{}", pin), "analogRead", pin)));
+        
+        let mut values = HashMap::new();
+        for (key, endpoint, pin) in reads {
+            let value = match self.invoke_async(endpoint, vec![json!(pin)]).await {
+                Ok(Value::Object(mut result)) if result.contains_key("value") => result.remove("value").unwrap(),
+                Ok(other) => other,
+                Err(e) => json!({ "error": e.to_string() }),
+            };
+            values.insert(key, value);
+        }
+        
+        Ok(values)
+    }
+}
+
+/// An open session shared between the device manager and its users (UI, scripts)
+/// Lock it per call rather than for a whole sequence so other users aren't starved
+pub type SharedSession = Arc<tokio::sync::Mutex<Box<dyn DeviceSession>>>;
+
+/// Fail fast with a session error if a session's transport has gone away
+/// If the transport is configured to auto-reconnect, one reconnect is attempted first
+pub async fn ensure_transport_connected(transport: &dyn Transport) -> DeviceResult<()> {
+    if transport.is_connected() {
+        return Ok(());
+    }
+    
+    if !transport.config().auto_reconnect {
+        return Err(DeviceError::Session("transport disconnected".into()));
+    }
+    
+    tracing::info!("Transport {} disconnected, attempting reconnect", transport.name());
+    match transport.connect().await {
+        Ok(()) if transport.is_connected() => Ok(()),
+        Ok(()) => Err(DeviceError::Session("transport disconnected".into())),
+        Err(e) => Err(DeviceError::Session(format!("transport disconnected (reconnect failed: {})", e))),
+    }
+}
+
+/// A single endpoint invocation, e.g. one of a device's safe-default commands
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionCommand {
+    pub endpoint: String,
+    #[serde(default)]
+    pub args: Vec<Value>,
+}
+
+impl SessionCommand {
+    pub fn new(endpoint: &str, args: Vec<Value>) -> Self {
+        Self {
+            endpoint: endpoint.to_string(),
+            args,
+        }
+    }
+}
+
+/// Run a freshly opened session's on-connect commands in order
+/// A failing command is logged and skipped rather than aborting the connection;
+/// returns the number of commands that succeeded
+pub async fn run_on_connect_commands(session: &mut dyn DeviceSession, commands: &[SessionCommand]) -> usize {
+    let result = dispatch_batch(session, commands, BatchMode::Continue).await;
+    for (command, e) in &result.failed {
+        tracing::warn!(
+            "On-connect command '{}' {:?} failed on {}: {}",
+            command.endpoint, command.args, session.device_name(), e
+        );
+    }
+    result.succeeded.len()
+}
+
+/// Apply a config's commands to a session in order, stopping at the first failure
+pub async fn apply_session_config(session: &mut dyn DeviceSession, commands: &[SessionCommand]) -> DeviceResult<()> {
+    dispatch_batch(session, commands, BatchMode::StopOnError).await.into_result().map(|_| ())
+}
+
+/// Which open sessions a fleet-wide operation targets
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionSelector {
+    /// Every open session
+    All,
+    /// Sessions whose device name matches exactly
+    DeviceName(String),
+    /// Specific session IDs
+    Ids(Vec<String>),
+}
+
+impl SessionSelector {
+    pub fn matches(&self, session_id: &str, session: &dyn DeviceSession) -> bool {
+        match self {
+            SessionSelector::All => true,
+            SessionSelector::DeviceName(name) => session.device_name() == name,
+            SessionSelector::Ids(ids) => ids.iter().any(|id| id == session_id),
+        }
+    }
+}
+
+/// Readable inputs reported by a session for `read_all_inputs`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InputPinSet {
+    /// Digital pins configured as inputs
+    pub digital: Vec<u8>,
+    
+    /// Analog input pins
+    pub analog: Vec<u8>,
+    
+    /// Endpoint returning every input in a single round-trip, if supported
+    pub batch_endpoint: Option<String>,
 }
 
 /// Device endpoint descriptor
@@ -145,7 +321,7 @@ impl SessionStatistics {
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap()
             .as_secs();
-            
+        
         SessionStatistics {
             start_time: now,
             last_activity: now,
@@ -170,4 +346,94 @@ impl SessionStatistics {
             latency_ms as f64
         };
     }
-}
\ No newline at end of file
+}
+
+/// Latencies kept per endpoint for the p95 estimate
+pub const METRICS_LATENCY_WINDOW: usize = 256;
+
+/// Invocation metrics for one endpoint
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EndpointMetrics {
+    /// Invocations, successful or not
+    pub count: u64,
+    
+    /// Invocations that returned an error
+    pub error_count: u64,
+    
+    /// Mean latency over all invocations (ms)
+    pub avg_latency_ms: f64,
+    
+    /// 95th percentile latency over the last `METRICS_LATENCY_WINDOW` invocations (ms)
+    pub p95_latency_ms: f64,
+}
+
+impl EndpointMetrics {
+    /// Fraction of invocations that failed (0.0 when never invoked)
+    pub fn error_rate(&self) -> f64 {
+        if self.count == 0 {
+            0.0
+        } else {
+            self.error_count as f64 / self.count as f64
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct EndpointRecord {
+    count: u64,
+    error_count: u64,
+    total_latency_ms: f64,
+    recent_ms: VecDeque<f64>,
+}
+
+/// Accumulates `EndpointMetrics` for a session; clones share the same records
+#[derive(Debug, Clone, Default)]
+pub struct CommandMetrics {
+    records: Arc<parking_lot::Mutex<HashMap<String, EndpointRecord>>>,
+}
+
+impl CommandMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Record one invocation of `endpoint`
+    pub fn record(&self, endpoint: &str, latency: Duration, succeeded: bool) {
+        let latency_ms = latency.as_secs_f64() * 1000.0;
+        let mut records = self.records.lock();
+        let record = records.entry(endpoint.to_string()).or_default();
+        record.count += 1;
+        if !succeeded {
+            record.error_count += 1;
+        }
+        record.total_latency_ms += latency_ms;
+        if record.recent_ms.len() >= METRICS_LATENCY_WINDOW {
+            record.recent_ms.pop_front();
+        }
+        record.recent_ms.push_back(latency_ms);
+    }
+    
+    /// Current metrics keyed by endpoint
+    pub fn snapshot(&self) -> HashMap<String, EndpointMetrics> {
+        self.records.lock()
+            .iter()
+            .map(|(endpoint, record)| {
+                let mut recent: Vec<f64> = record.recent_ms.iter().copied().collect();
+                recent.sort_by(|a, b| a.total_cmp(b));
+                // Nearest-rank percentile
+                let rank = ((recent.len() as f64 * 0.95).ceil() as usize).clamp(1, recent.len().max(1));
+                (endpoint.clone(), EndpointMetrics {
+                    count: record.count,
+                    error_count: record.error_count,
+                    avg_latency_ms: record.total_latency_ms / record.count.max(1) as f64,
+                    p95_latency_ms: recent.get(rank - 1).copied().unwrap_or(0.0),
+                })
+            })
+            .collect()
+    }
+    
+    /// Forget all recorded invocations
+    pub fn clear(&self) {
+        self.records.lock().clear();
+    }
+}
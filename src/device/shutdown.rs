@@ -0,0 +1,72 @@
+//! Ordered shutdown stages for `DeviceManager::shutdown`
+//!
+//! Stop commands are only useful while the transport they travel over still
+//! exists, so shutdown runs in a fixed order: put outputs in a safe state, stop
+//! streaming, flush telemetry and logs, close sessions, then drop transports.
+//! Each stage is bounded by a timeout so one hung step cannot stall the rest.
+
+use serde::{Serialize, Deserialize};
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+
+/// Default time allowed for each shutdown stage
+pub const DEFAULT_SHUTDOWN_STAGE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// One step of the shutdown sequence, in execution order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ShutdownStage {
+    /// Drive outputs to their safe defaults and latch the emergency stop
+    ArmSafeState,
+    /// Cancel stream subscriptions
+    StopSubscriptions,
+    /// Flush telemetry and log buffers
+    FlushTelemetry,
+    /// Close every device session
+    CloseSessions,
+    /// Release sessions and the transports they own
+    DropTransports,
+}
+
+impl ShutdownStage {
+    /// Every stage, in execution order
+    pub const ALL: [ShutdownStage; 5] = [
+        ShutdownStage::ArmSafeState,
+        ShutdownStage::StopSubscriptions,
+        ShutdownStage::FlushTelemetry,
+        ShutdownStage::CloseSessions,
+        ShutdownStage::DropTransports,
+    ];
+}
+
+/// Work registered to run during a shutdown stage
+pub type ShutdownHook = Box<dyn Fn() -> Pin<Box<dyn Future<Output = ()> + Send>> + Send + Sync>;
+
+/// How one stage went
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShutdownStageResult {
+    pub stage: ShutdownStage,
+    
+    /// False if the stage was cut off by its timeout
+    pub completed: bool,
+    
+    pub elapsed: Duration,
+}
+
+/// Outcome of every stage of a shutdown, in execution order
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShutdownReport {
+    pub stages: Vec<ShutdownStageResult>,
+}
+
+impl ShutdownReport {
+    /// Whether every stage finished within its timeout
+    pub fn clean(&self) -> bool {
+        self.stages.iter().all(|s| s.completed)
+    }
+    
+    /// Result for a specific stage
+    pub fn stage(&self, stage: ShutdownStage) -> Option<&ShutdownStageResult> {
+        self.stages.iter().find(|s| s.stage == stage)
+    }
+}
@@ -142,6 +142,7 @@ impl DeviceDriver for ArduinoMega2560Driver {
             requires_auth: false,
             max_data_rate: Some(115200 / 10),
             min_latency_ms: Some(50),
+            adc_resolution_bits: Some(10),
         }
     }
 }
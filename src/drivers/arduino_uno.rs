@@ -1,17 +1,21 @@
 use async_trait::async_trait;
 use std::sync::Arc;
-use std::collections::HashMap;
-use std::time::Duration;
+use std::collections::{BTreeSet, HashMap};
+use std::time::{Duration, Instant};
 use serde_json::{Value, json};
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, Semaphore};
 use serialport::{SerialPortType, SerialPortInfo};
 use tracing::{info, debug, warn};
 
 use crate::device::{
     DeviceDriver, DeviceSession, DeviceResult, DeviceError,
-    Transport, TransportType, DriverCapabilities
+    Transport, TransportType, DriverCapabilities, ReadCache, InputPinSet,
+    ClockOffset, ClockSync, ResponseParser, LineParser, KeepAlive, KeepAliveSettings,
 };
-use crate::transport::TransportError;
+use crate::device::clock_sync;
+use crate::device::session::{ensure_transport_connected, CommandMetrics, EndpointMetrics};
+use crate::protocols::handshake::{HandshakeRunner, IdentifyCommand, IdentifyResponse, NegotiatedVersion, Version, PROTOCOL_VERSION};
+use crate::transport::{TransportError, CommandCodec};
 
 // Arduino USB Vendor IDs
 const ARDUINO_VID: u16 = 0x2341;  // Official Arduino
@@ -33,16 +37,58 @@ const CMD_ANALOG_READ: &str = "ANALOG_READ";
 const CMD_PWM_WRITE: &str = "PWM_WRITE";
 const CMD_HALL_CONFIG: &str = "HALL_CONFIG";
 const CMD_HALL_READ: &str = "HALL_READ";
+const CMD_TIME: &str = "TIME";
+const CMD_CAPS: &str = "CAPS";
+
+// Analog input pins (A0-A5 on Uno)
+const ANALOG_PINS: std::ops::RangeInclusive<u8> = 0..=5;
 
 // Response codes
 const RESP_OK: &str = "OK";
 const RESP_ERROR: &str = "ERROR";
 const RESP_ARDUINO_UNO: &str = "ARDUINO_UNO_V1";
 
+/// Capabilities of firmware that predates the CAPS command
+const BASE_CAPABILITIES: &[&str] = &["gpio", "pwm", "analog", "servo", "scripting", "telemetry"];
+
+/// Commands a session sends before waiting for earlier replies (strict request/response)
+pub const DEFAULT_MAX_IN_FLIGHT: usize = 1;
+
+/// Timing of the PROBE exchange
+/// Most Arduinos reset when the port opens and ignore input for ~1.5s
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProbeSettings {
+    /// Wait this long before the first PROBE
+    pub settle_delay: Duration,
+    
+    /// Time allowed for each PROBE response
+    pub response_timeout: Duration,
+    
+    /// Extra PROBE attempts after one times out
+    pub retries: u32,
+}
+
+impl Default for ProbeSettings {
+    fn default() -> Self {
+        Self {
+            settle_delay: Duration::ZERO,
+            response_timeout: Duration::from_secs(2),
+            retries: 0,
+        }
+    }
+}
+
 /// Arduino Uno device driver
 pub struct ArduinoUnoDriver {
     name: String,
     version: String,
+    read_cache: ReadCache,  // Template for each session's read cache
+    probe: ProbeSettings,
+    max_in_flight: usize,
+    id_tagging: bool,
+    echo_suppression: bool,
+    keep_alive: Option<KeepAliveSettings>,
+    protocol_versions: Vec<Version>,  // Offered in a handshake on open; empty = no handshake
 }
 
 impl ArduinoUnoDriver {
@@ -50,9 +96,62 @@ impl ArduinoUnoDriver {
         ArduinoUnoDriver {
             name: "Arduino Uno".to_string(),
             version: "1.0.0".to_string(),
+            read_cache: ReadCache::new(),
+            probe: ProbeSettings::default(),
+            max_in_flight: DEFAULT_MAX_IN_FLIGHT,
+            id_tagging: false,
+            echo_suppression: false,
+            keep_alive: None,
+            protocol_versions: Vec::new(),
         }
     }
     
+    /// Probe with custom settle delay, response timeout and retries
+    pub fn with_probe_settings(mut self, probe: ProbeSettings) -> Self {
+        self.probe = probe;
+        self
+    }
+    
+    /// Let sessions pipeline up to `max_in_flight` commands (for firmware that queues them)
+    /// Pipelined replies are matched by id, so this needs `with_id_tagging(true)`;
+    /// without it sessions still send one command at a time
+    pub fn with_max_in_flight(mut self, max_in_flight: usize) -> Self {
+        self.max_in_flight = max_in_flight.max(1);
+        self
+    }
+    
+    /// Tag commands with `#<n>` ids so replies are matched even around unsolicited lines
+    pub fn with_id_tagging(mut self, enabled: bool) -> Self {
+        self.id_tagging = enabled;
+        self
+    }
+    
+    /// Skip the command echo some firmwares send before each reply
+    pub fn with_echo_suppression(mut self, enabled: bool) -> Self {
+        self.echo_suppression = enabled;
+        self
+    }
+    
+    /// Send `settings.command` whenever a session has been idle for `settings.interval_ms`
+    /// For firmware whose watchdog resets the board when no command arrives in time
+    pub fn with_keep_alive(mut self, settings: KeepAliveSettings) -> Self {
+        self.keep_alive = Some(settings);
+        self
+    }
+    
+    /// Handshake when opening a session and agree on one of `versions`
+    /// A device that only speaks an older minor of the same major is negotiated down
+    pub fn with_protocol_versions(mut self, versions: Vec<Version>) -> Self {
+        self.protocol_versions = versions;
+        self
+    }
+    
+    /// Cache results of an idempotent read endpoint (e.g. "analogRead") for `ttl`
+    pub fn with_read_cache_ttl(mut self, endpoint: &str, ttl: Duration) -> Self {
+        self.read_cache.set_ttl(endpoint, ttl);
+        self
+    }
+    
     /// Detect Arduino devices via USB VID/PID
     async fn detect_arduino_usb(&self) -> DeviceResult<bool> {
         match serialport::available_ports() {
@@ -93,6 +192,84 @@ impl ArduinoUnoDriver {
             }
         }
     }
+    
+    /// Send PROBE and check the reply looks like an Arduino, honoring the probe settings
+    async fn probe_responds(&self, transport: &dyn Transport) -> DeviceResult<bool> {
+        if !self.probe.settle_delay.is_zero() {
+            debug!("Waiting {:?} for the board to settle before probing", self.probe.settle_delay);
+            tokio::time::sleep(self.probe.settle_delay).await;
+        }
+        
+        let probe_command = format!("{}\n", CMD_PROBE);
+        let probe_timeout = self.probe.response_timeout;
+        let mut attempt = 0;
+        let response = loop {
+            attempt += 1;
+            debug!("Sending PROBE command to potential Arduino device (attempt {})", attempt);
+            transport.send(probe_command.as_bytes()).await.map_err(|e| {
+                warn!("Failed to send PROBE command: {}", e);
+                DeviceError::TransportError(format!("Probe send failed: {}", e))
+            })?;
+            
+            match transport.receive(probe_timeout).await {
+                Ok(response) => break response,
+                Err(TransportError::Timeout(_)) if attempt <= self.probe.retries => {
+                    debug!("PROBE attempt {} timed out, retrying", attempt);
+                }
+                Err(e) => {
+                    warn!("No response to PROBE command: {}", e);
+                    return Err(receive_error("Probe response failed", e, probe_timeout));
+                }
+            }
+        };
+        
+        let response_str = String::from_utf8_lossy(&response);
+        debug!("Arduino probe response: {}", response_str);
+        
+        // Check if response indicates Arduino presence
+        let is_arduino_response = response_str.contains(RESP_OK) || 
+                                 response_str.contains("ARDUINO") ||
+                                 response_str.to_uppercase().contains("UNO");
+        
+        if is_arduino_response {
+            info!("Arduino Uno detected and responsive via probe command");
+            return Ok(true);
+        }
+        
+        warn!("Arduino device detected via USB VID/PID but probe command failed");
+        Ok(false) // Device present but not responsive
+    }
+    
+    /// Run the IDENTIFY/VERSION handshake and return the protocol version agreed on
+    /// (`None` without a handshake, when no protocol versions are configured)
+    async fn negotiate_protocol(&self, transport: Arc<dyn Transport>) -> DeviceResult<Option<NegotiatedVersion>> {
+        let Some(preferred) = self.protocol_versions.iter().max() else {
+            return Ok(None);
+        };
+        
+        let outcome = HandshakeRunner::new(transport)
+            .with_protocol_versions(self.protocol_versions.clone())
+            .establish(&identify_command(&preferred.to_string()))
+            .await?;
+        if let Some(negotiated) = outcome.negotiated.filter(NegotiatedVersion::is_downgrade) {
+            info!("Arduino Uno speaks protocol {} (preferred {})", negotiated.version, negotiated.preferred);
+        }
+        Ok(outcome.negotiated)
+    }
+}
+
+/// IDENTIFY request offering `protocol_version` and the base capabilities
+fn identify_command(protocol_version: &str) -> IdentifyCommand {
+    IdentifyCommand {
+        command: "IDENTIFY".to_string(),
+        protocol_version: protocol_version.to_string(),
+        session_id: uuid::Uuid::new_v4(),
+        capabilities_requested: BASE_CAPABILITIES.iter().map(|c| c.to_string()).collect(),
+        timestamp: None,
+        client_info: None,
+        auth_token: None,
+        custom_params: HashMap::new(),
+    }
 }
 
 #[async_trait]
@@ -118,43 +295,32 @@ impl DeviceDriver for ArduinoUnoDriver {
             }
         }
         
-        // Now we can use the transport directly with the new &self methods!
-        debug!("Sending PROBE command to potential Arduino device");
-        
-        // Send probe command to verify Arduino is present and responsive
-        let probe_command = format!("{}\n", CMD_PROBE);
-        transport.send(probe_command.as_bytes()).await.map_err(|e| {
-            warn!("Failed to send PROBE command: {}", e);
-            DeviceError::CommunicationError(format!("Probe send failed: {}", e))
-        })?;
-        
-        // Wait for response with reasonable timeout
-        let response = transport.receive(Duration::from_secs(2)).await.map_err(|e| {
-            warn!("No response to PROBE command: {}", e);
-            DeviceError::CommunicationError(format!("Probe response failed: {}", e))
-        })?;
-        
-        let response_str = String::from_utf8_lossy(&response);
-        debug!("Arduino probe response: {}", response_str);
-        
-        // Check if response indicates Arduino presence
-        let is_arduino_response = response_str.contains(RESP_OK) || 
-                                 response_str.contains("ARDUINO") ||
-                                 response_str.to_uppercase().contains("UNO");
-                                 
-        if is_arduino_response {
-            info!("Arduino Uno detected and responsive via probe command");
-            return Ok(true);
-        }
-        
-        warn!("Arduino device detected via USB VID/PID but probe command failed");
-        Ok(false) // Device present but not responsive
+        self.probe_responds(transport.as_ref()).await
+    }
+    
+    async fn identify_async(&self, transport: Arc<dyn Transport>) -> DeviceResult<Option<IdentifyResponse>> {
+        // IDENTIFY only: the protocol version is negotiated when the session opens
+        let protocol_version = self.protocol_versions.iter().max()
+            .map_or_else(|| PROTOCOL_VERSION.to_string(), Version::to_string);
+        let response = HandshakeRunner::new(transport).run(&identify_command(&protocol_version)).await?;
+        Ok(Some(response))
     }
     
     async fn open_async(&self, transport: Arc<dyn Transport>) -> DeviceResult<Box<dyn DeviceSession>> {
+        let negotiated = self.negotiate_protocol(transport.clone()).await?;
+        
         // Create session with transport
         // Note: The session will face the same mutability constraints
-        let session = ArduinoSession::new(transport);
+        let mut session = ArduinoSession::new(transport)
+            .with_negotiated_version(negotiated)
+            .with_adc_max(self.capabilities().max_analog_value())
+            .with_read_cache(self.read_cache.clone())
+            .with_id_tagging(self.id_tagging)
+            .with_max_in_flight(self.max_in_flight)
+            .with_echo_suppression(self.echo_suppression);
+        if let Some(settings) = &self.keep_alive {
+            session.start_keep_alive(settings);
+        }
         info!("Opened Arduino Uno session: {}", session.session_id);
         Ok(Box::new(session))
     }
@@ -171,6 +337,7 @@ impl DeviceDriver for ArduinoUnoDriver {
             requires_auth: false,
             max_data_rate: Some(115200 / 10), // Roughly bytes per second at 115200 baud
             min_latency_ms: Some(50),
+            adc_resolution_bits: Some(10),
         }
     }
 }
@@ -180,11 +347,19 @@ impl DeviceDriver for ArduinoUnoDriver {
 /// Arduino Uno session implementation with full transport integration.
 /// Now works directly with Arc<dyn Transport> using interior mutability pattern.
 pub struct ArduinoSession {
-    transport: Arc<dyn Transport>,
+    codec: Arc<CommandCodec>,  // Newline-terminated command/response framing over the transport
     session_id: String,
     pin_modes: Arc<Mutex<HashMap<u8, PinMode>>>,
     active: Arc<Mutex<bool>>,
     command_counter: Arc<Mutex<u64>>,  // Track commands for debugging
+    adc_max: u16,  // Largest valid ANALOG_READ value
+    read_cache: Arc<Mutex<ReadCache>>,  // Short-TTL cache for idempotent reads
+    clock: ClockSync,  // Device millis() to host time offset
+    parser: Arc<dyn ResponseParser>,  // Format of every command response
+    in_flight: Arc<Semaphore>,  // Commands sent and not yet answered
+    metrics: CommandMetrics,  // Per-endpoint count, latency and errors
+    keep_alive: Option<KeepAlive>,  // Pings the firmware watchdog while idle
+    negotiated: Option<NegotiatedVersion>,  // Protocol version agreed in the open handshake
 }
 
 #[derive(Debug, Clone)]
@@ -208,18 +383,114 @@ impl ArduinoSession {
         let session_id = uuid::Uuid::new_v4().to_string();
         debug!("Creating Arduino session with ID: {}", session_id);
         ArduinoSession {
-            transport,
+            codec: Arc::new(CommandCodec::new(transport).with_terminator("\n")),
             session_id,
             pin_modes: Arc::new(Mutex::new(HashMap::new())),
             active: Arc::new(Mutex::new(true)),
             command_counter: Arc::new(Mutex::new(0)),
+            adc_max: DriverCapabilities::default().max_analog_value(),
+            read_cache: Arc::new(Mutex::new(ReadCache::new())),
+            clock: ClockSync::default(),
+            parser: Arc::new(LineParser),
+            in_flight: Arc::new(Semaphore::new(DEFAULT_MAX_IN_FLIGHT)),
+            metrics: CommandMetrics::new(),
+            keep_alive: None,
+            negotiated: None,
         }
     }
     
+    /// Record the protocol version agreed in the handshake
+    fn with_negotiated_version(mut self, negotiated: Option<NegotiatedVersion>) -> Self {
+        self.negotiated = negotiated;
+        self
+    }
+    
+    /// Use a read cache (with its per-endpoint TTLs) for this session
+    fn with_read_cache(mut self, cache: ReadCache) -> Self {
+        self.read_cache = Arc::new(Mutex::new(cache));
+        self
+    }
+    
+    /// Parse command responses with `parser`
+    fn with_parser(mut self, parser: Arc<dyn ResponseParser>) -> Self {
+        self.parser = parser;
+        self
+    }
+    
+    /// Allow up to `max_in_flight` commands to await replies at once
+    /// Untagged replies can't be told apart, so without id tagging the limit stays 1;
+    /// call after `with_id_tagging`
+    fn with_max_in_flight(mut self, max_in_flight: usize) -> Self {
+        let max_in_flight = if self.codec.id_tagging() {
+            max_in_flight.max(1)
+        } else {
+            if max_in_flight > 1 {
+                warn!("Arduino session {}: pipelining {} commands needs id tagging, sending one at a time", self.session_id, max_in_flight);
+            }
+            1
+        };
+        self.in_flight = Arc::new(Semaphore::new(max_in_flight));
+        self
+    }
+    
+    /// Prefix commands with `#<n>` and match replies by the echoed id
+    fn with_id_tagging(self, enabled: bool) -> Self {
+        self.map_codec(|codec| codec.with_id_tagging(enabled))
+    }
+    
+    /// Discard the device's echo of each command before reading its reply
+    fn with_echo_suppression(self, enabled: bool) -> Self {
+        self.map_codec(|codec| codec.with_echo_suppression(enabled))
+    }
+    
+    /// Reconfigure the codec; only valid while building, before a keep-alive shares it
+    fn map_codec(mut self, f: impl FnOnce(CommandCodec) -> CommandCodec) -> Self {
+        let codec = Arc::try_unwrap(self.codec)
+            .unwrap_or_else(|_| panic!("codec reconfigured after the keep-alive started"));
+        self.codec = Arc::new(f(codec));
+        self
+    }
+    
+    /// Ping with `settings.command` whenever no command has been sent for the interval
+    /// Call after the builder methods, since the ping shares the codec and in-flight limit
+    fn start_keep_alive(&mut self, settings: &KeepAliveSettings) {
+        if !settings.is_enabled() {
+            return;
+        }
+        
+        let codec = self.codec.clone();
+        let in_flight = self.in_flight.clone();
+        let command = settings.command.clone();
+        debug!("Arduino session {} keep-alive: {} every {}ms idle", self.session_id, command, settings.interval_ms);
+        self.keep_alive = Some(KeepAlive::spawn(settings.interval(), move || {
+            let codec = codec.clone();
+            let in_flight = in_flight.clone();
+            let command = command.clone();
+            async move {
+                let _permit = in_flight.acquire().await.map_err(|_| DeviceError::NotConnected)?;
+                let tag = if codec.id_tagging() {
+                    codec.send_tagged(&command).await.map(Some)
+                } else {
+                    codec.send_command(&command).await.map(|_| None)
+                }.map_err(|e| DeviceError::TransportError(format!("Send failed: {}", e)))?;
+                codec.read_response(&command, tag).await
+                    .map_err(|e| receive_error("Keep-alive receive failed", e, codec.timeout()))?;
+                Ok(())
+            }
+        }));
+    }
+    
+    /// Set the ADC range used to validate analog reads
+    fn with_adc_max(mut self, adc_max: u16) -> Self {
+        self.adc_max = adc_max;
+        self
+    }
+    
     /// Send a command and wait for response
     /// 
     /// Send command to Arduino and wait for response using the transport layer.
-    async fn send_command(&self, command: &str) -> DeviceResult<String> {
+    /// The reply is returned as read by the session's response parser.
+    async fn send_command(&self, command: &str) -> DeviceResult<Value> {
         // Increment command counter
         let mut counter = self.command_counter.lock().await;
         *counter += 1;
@@ -234,34 +505,60 @@ impl ArduinoSession {
             return Err(DeviceError::NotConnected);
         }
         drop(active);
+        self.touch_keep_alive();
+        
+        // Fail clearly if the transport vanished underneath the session
+        ensure_transport_connected(self.codec.transport().as_ref()).await?;
+        
+        // Held until the reply arrives so no more than `max_in_flight` commands are outstanding
+        let _permit = self.in_flight.acquire().await
+            .map_err(|_| DeviceError::NotConnected)?;
         
         // Send command through transport (now possible with interior mutability!)
-        let command_with_newline = format!("{}\n", command);
-        self.transport.send(command_with_newline.as_bytes()).await.map_err(|e| {
+        let sent = if self.codec.id_tagging() {
+            self.codec.send_tagged(command).await.map(Some)
+        } else {
+            self.codec.send_command(command).await.map(|_| None)
+        };
+        let tag = sent.map_err(|e| {
             warn!("Failed to send command '{}': {}", command, e);
-            DeviceError::CommunicationError(format!("Send failed: {}", e))
+            DeviceError::TransportError(format!("Send failed: {}", e))
         })?;
         
-        // Wait for response with timeout
-        let response_bytes = self.transport.receive(Duration::from_secs(2)).await.map_err(|e| {
+        // Wait for response line with timeout; silence is a timeout, not a protocol error
+        let response = self.codec.read_response(command, tag).await.map_err(|e| {
             warn!("No response to command '{}': {}", command, e);
-            DeviceError::CommunicationError(format!("Receive failed: {}", e))
+            receive_error("Receive failed", e, self.codec.timeout())
         })?;
         
-        let response = String::from_utf8_lossy(&response_bytes).trim().to_string();
-        
         debug!("Arduino response #{}: {}", cmd_num, response);
-        Ok(response)
+        self.touch_keep_alive();
+        let reply = self.parser.parse(response.as_bytes())?;
+        
+        // An explicit ERROR means the device heard and refused the command
+        if let Some(reason) = rejection_reason(&reply) {
+            return Err(DeviceError::DeviceRejection(format!("{} ({})", command, reason)));
+        }
+        
+        Ok(reply)
+    }
+    
+    /// Push the next keep-alive ping back; real commands keep the watchdog fed
+    fn touch_keep_alive(&self) {
+        if let Some(keep_alive) = &self.keep_alive {
+            keep_alive.touch();
+        }
     }
     
-    /// Parse response and check for OK
-    async fn expect_ok(&self, response: &str) -> DeviceResult<()> {
-        if response == RESP_OK {
+    /// Parse response and check for OK ("OK", or a structured reply with status OK)
+    async fn expect_ok(&self, response: &Value) -> DeviceResult<()> {
+        let status = response.get("status").unwrap_or(response);
+        if status == RESP_OK {
             Ok(())
-        } else if response.starts_with(RESP_ERROR) {
-            Err(DeviceError::Unknown(format!("Arduino error: {}", response)))
+        } else if let Some(reason) = rejection_reason(response) {
+            Err(DeviceError::DeviceRejection(reason))
         } else {
-            Err(DeviceError::Unknown(format!("Unexpected response: {}", response)))
+            Err(DeviceError::Protocol(format!("Unexpected response: {}", response)))
         }
     }
     
@@ -311,22 +608,12 @@ impl ArduinoSession {
         let cmd = format!("{} {}", CMD_DIGITAL_READ, pin);
         let response = self.send_command(&cmd).await?;
         
-        // Parse response: "VALUE:0" or "VALUE:1"
-        if response.starts_with("VALUE:") {
-            let value_str = &response[6..];
-            match value_str {
-                "0" => Ok(false),
-                "1" => Ok(true),
-                _ => Err(DeviceError::Unknown(format!("Invalid digital read response: {}", response)))
-            }
-        } else {
-            Err(DeviceError::Unknown(format!("Invalid response format: {}", response)))
-        }
+        parse_digital_value(&response)
     }
     
     async fn analog_read(&self, pin: u8) -> DeviceResult<u16> {
         // Check if it's an analog pin (A0-A5 on Uno)
-        if pin > 5 {
+        if !ANALOG_PINS.contains(&pin) {
             return Err(DeviceError::Unknown(format!("Invalid analog pin: {}", pin)));
         }
         
@@ -334,14 +621,7 @@ impl ArduinoSession {
         let cmd = format!("{} {}", CMD_ANALOG_READ, pin);
         let response = self.send_command(&cmd).await?;
         
-        // Parse response: "VALUE:1023" (0-1023 for 10-bit ADC)
-        if response.starts_with("VALUE:") {
-            let value_str = &response[6..];
-            value_str.parse::<u16>()
-                .map_err(|_| DeviceError::Unknown(format!("Invalid analog value: {}", value_str)))
-        } else {
-            Err(DeviceError::Unknown(format!("Invalid response format: {}", response)))
-        }
+        parse_analog_value(&response, self.adc_max)
     }
     
     async fn pwm_write(&self, pin: u8, value: u8) -> DeviceResult<()> {
@@ -382,13 +662,10 @@ impl ArduinoSession {
         let response = self.send_command(&cmd).await?;
         
         // Parse response: "RPM:1250.5"
-        if response.starts_with("RPM:") {
-            let rpm_str = &response[4..];
-            rpm_str.parse::<f32>()
-                .map_err(|_| DeviceError::Unknown(format!("Invalid RPM value: {}", rpm_str)))
-        } else {
-            Err(DeviceError::Unknown(format!("Invalid hall sensor response: {}", response)))
-        }
+        let rpm = reply_field(&response, "RPM")
+            .map_err(|_| DeviceError::Unknown(format!("Invalid hall sensor response: {}", response)))?;
+        field_number(&rpm)
+            .ok_or_else(|| DeviceError::Unknown(format!("Invalid RPM value: {}", rpm)))
     }
     
     async fn read_hall_counter(&self, pin: u8) -> DeviceResult<u32> {
@@ -397,13 +674,24 @@ impl ArduinoSession {
         let response = self.send_command(&cmd).await?;
         
         // Parse response: "COUNT:12345"
-        if response.starts_with("COUNT:") {
-            let count_str = &response[6..];
-            count_str.parse::<u32>()
-                .map_err(|_| DeviceError::Unknown(format!("Invalid counter value: {}", count_str)))
-        } else {
-            Err(DeviceError::Unknown(format!("Invalid counter response: {}", response)))
-        }
+        let count = reply_field(&response, "COUNT")
+            .map_err(|_| DeviceError::Unknown(format!("Invalid counter response: {}", response)))?;
+        field_number(&count)
+            .ok_or_else(|| DeviceError::Unknown(format!("Invalid counter value: {}", count)))
+    }
+    
+    /// Ask the firmware for its millis() clock, bracketed by host timestamps
+    async fn measure_clock_offset(&self) -> DeviceResult<ClockOffset> {
+        let host_send_ms = clock_sync::now_ms();
+        let response = self.send_command(CMD_TIME).await?;
+        let host_recv_ms = clock_sync::now_ms();
+        
+        // Parse response: "TIME:123456"
+        let device_ms = reply_field(&response, "TIME").ok()
+            .and_then(|time| field_number::<u64>(&time))
+            .ok_or_else(|| DeviceError::Protocol(format!("Invalid time response: {}", response)))?;
+        
+        Ok(ClockOffset::estimate(host_send_ms, device_ms, host_recv_ms))
     }
     
     async fn reset_hall_counter(&self, pin: u8) -> DeviceResult<()> {
@@ -414,17 +702,99 @@ impl ArduinoSession {
     }
 }
 
-#[async_trait]
-impl DeviceSession for ArduinoSession {
-    fn session_id(&self) -> &str {
-        &self.session_id
+/// Reason given in an "ERROR[:reason]" response (or a structured reply with an
+/// `error` field or status ERROR), if the response is one
+fn rejection_reason(response: &Value) -> Option<String> {
+    let reason = match response {
+        Value::String(line) => {
+            // "ERRORS=0" or "ERROR_COUNT:3" are readings, not refusals
+            let rest = line.strip_prefix(RESP_ERROR)?;
+            if !(rest.is_empty() || rest.starts_with(':') || rest.starts_with(char::is_whitespace)) {
+                return None;
+            }
+            rest.trim_start_matches(':').trim()
+        }
+        Value::Object(fields) => match (fields.get("error"), fields.get("status")) {
+            (Some(error), _) => error.as_str().unwrap_or(""),
+            (None, Some(status)) if status == RESP_ERROR => "",
+            _ => return None,
+        },
+        _ => return None,
+    };
+    Some(if reason.is_empty() { "no reason given" } else { reason }.to_string())
+}
+
+/// Map a failed receive: silence becomes `Timeout`, anything else a transport error
+fn receive_error(context: &str, error: TransportError, timeout: Duration) -> DeviceError {
+    match error {
+        TransportError::Timeout(_) => DeviceError::Timeout(timeout.as_millis() as u64),
+        other => DeviceError::TransportError(format!("{}: {}", context, other)),
+    }
+}
+
+/// Payload of the `key` field of a parsed response: the text after "KEY:" in a
+/// line reply, or the lowercase `key` member of a structured (JSON or key=value) one
+fn reply_field(response: &Value, key: &str) -> DeviceResult<Value> {
+    let field = match response {
+        Value::String(line) => line.strip_prefix(key)
+            .and_then(|rest| rest.strip_prefix(':'))
+            .map(|payload| Value::String(payload.trim().to_string())),
+        Value::Object(fields) => fields.get(&key.to_lowercase()).cloned(),
+        _ => None,
+    };
+    field.ok_or_else(|| DeviceError::Protocol(format!("Invalid response format: {}", response)))
+}
+
+/// A response field holding a number, either as a JSON number or as text
+fn field_number<T: std::str::FromStr>(field: &Value) -> Option<T> {
+    match field {
+        Value::String(text) => text.trim().parse().ok(),
+        Value::Number(number) => number.to_string().parse().ok(),
+        _ => None,
     }
+}
+
+/// Parse a "CAPS:<name>,<name>,..." response (or a structured list of names)
+fn parse_capabilities(response: &Value) -> DeviceResult<BTreeSet<String>> {
+    let names: Vec<String> = match reply_field(response, "CAPS")? {
+        Value::String(list) => list.split(',').map(|name| name.trim().to_string()).collect(),
+        Value::Array(items) => items.iter().filter_map(Value::as_str).map(str::to_string).collect(),
+        other => return Err(DeviceError::Protocol(format!("Invalid capability list: {}", other))),
+    };
+    Ok(names.into_iter().filter(|name| !name.is_empty()).collect())
+}
+
+/// Parse a DIGITAL_READ response; only 0 and 1 (or a bool) are valid
+fn parse_digital_value(response: &Value) -> DeviceResult<bool> {
+    match reply_field(response, "VALUE")? {
+        Value::Bool(level) => Ok(level),
+        field => match field_number::<u8>(&field) {
+            Some(0) => Ok(false),
+            Some(1) => Ok(true),
+            _ => Err(DeviceError::Protocol(format!("Invalid digital read value: {}", field))),
+        },
+    }
+}
+
+/// Parse an ANALOG_READ response, rejecting values outside the ADC range
+/// Out-of-range values mean the response was corrupted in transit
+fn parse_analog_value(response: &Value, adc_max: u16) -> DeviceResult<u16> {
+    let field = reply_field(response, "VALUE")?;
+    let value = field_number::<u32>(&field)
+        .ok_or_else(|| DeviceError::Protocol(format!("Invalid analog value: {}", field)))?;
     
-    fn device_name(&self) -> &str {
-        "Arduino Uno"
+    if value > adc_max as u32 {
+        return Err(DeviceError::Protocol(format!(
+            "Analog value {} out of range (0-{})", value, adc_max
+        )));
     }
     
-    async fn invoke_async(&mut self, endpoint: &str, args: Vec<Value>) -> DeviceResult<Value> {
+    Ok(value as u16)
+}
+
+impl ArduinoSession {
+    /// Execute an endpoint on the device, bypassing the read cache
+    async fn invoke_uncached(&mut self, endpoint: &str, args: Vec<Value>) -> DeviceResult<Value> {
         match endpoint {
             "pinMode" => {
                 let pin = args.get(0)
@@ -921,9 +1291,92 @@ impl DeviceSession for ArduinoSession {
                 }))
             }
             
+            "command" => {
+                // Passthrough for firmware-specific commands; returns the parsed reply as is
+                let command = args.get(0)
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| DeviceError::Unknown("Missing command argument".into()))?;
+                
+                self.send_command(command).await
+            }
+            
             _ => Err(DeviceError::Unknown(format!("Unknown endpoint: {}", endpoint))),
         }
     }
+}
+
+#[async_trait]
+impl DeviceSession for ArduinoSession {
+    fn session_id(&self) -> &str {
+        &self.session_id
+    }
+    
+    fn device_name(&self) -> &str {
+        "Arduino Uno"
+    }
+    
+    async fn sync_clock(&mut self) -> DeviceResult<ClockOffset> {
+        let offset = self.measure_clock_offset().await?;
+        self.clock.record(offset);
+        Ok(offset)
+    }
+    
+    fn clock_offset(&self) -> Option<ClockOffset> {
+        self.clock.offset()
+    }
+    
+    fn clock_resync_due(&self) -> bool {
+        self.clock.needs_resync()
+    }
+    
+    fn negotiated_version(&self) -> Option<NegotiatedVersion> {
+        self.negotiated
+    }
+    
+    fn set_response_parser(&mut self, parser: Arc<dyn ResponseParser>) -> DeviceResult<()> {
+        self.parser = parser;
+        Ok(())
+    }
+    
+    async fn query_capabilities(&mut self) -> DeviceResult<BTreeSet<String>> {
+        match self.send_command(CMD_CAPS).await {
+            Ok(response) => parse_capabilities(&response),
+            // Older firmware refuses CAPS but always has the base command set
+            Err(DeviceError::DeviceRejection(_)) => {
+                Ok(BASE_CAPABILITIES.iter().map(|name| name.to_string()).collect())
+            }
+            Err(e) => Err(e),
+        }
+    }
+    
+    async fn invoke_async(&mut self, endpoint: &str, args: Vec<Value>) -> DeviceResult<Value> {
+        let started = Instant::now();
+        if let Some(cached) = self.read_cache.lock().await.get(endpoint, &args) {
+            debug!("Arduino read cache hit: {} {:?}", endpoint, args);
+            self.metrics.record(endpoint, started.elapsed(), true);
+            return Ok(cached);
+        }
+        
+        let pin = args.get(0).and_then(|v| v.as_u64());
+        let result = self.invoke_uncached(endpoint, args.clone()).await;
+        self.metrics.record(endpoint, started.elapsed(), result.is_ok());
+        
+        let mut cache = self.read_cache.lock().await;
+        if cache.is_cacheable(endpoint) {
+            if let Ok(ref value) = result {
+                cache.insert(endpoint, &args, value.clone());
+            }
+        } else {
+            // Anything but a cached read may change device state: drop cached reads of
+            // the pin it addresses, or everything when it doesn't address one
+            match pin {
+                Some(pin) => cache.invalidate_pin(pin),
+                None => cache.clear(),
+            }
+        }
+        
+        result
+    }
     
     async fn subscribe_async(
         &mut self,
@@ -942,6 +1395,7 @@ impl DeviceSession for ArduinoSession {
     async fn close_async(&mut self) -> DeviceResult<()> {
         let mut active = self.active.lock().await;
         *active = false;
+        self.keep_alive = None;
         Ok(())
     }
     
@@ -954,7 +1408,13 @@ impl DeviceSession for ArduinoSession {
         crate::device::session::SessionStatistics::default()
     }
     
+    fn command_metrics(&self) -> HashMap<String, EndpointMetrics> {
+        self.metrics.snapshot()
+    }
+    
     async fn send_raw(&mut self, _data: &[u8]) -> DeviceResult<Vec<u8>> {
+        ensure_transport_connected(self.codec.transport().as_ref()).await?;
+        
         // Direct pass-through to transport
         // In real implementation:
         // self.transport.send(data).await?;
@@ -962,11 +1422,251 @@ impl DeviceSession for ArduinoSession {
         
         Ok(vec![])
     }
+    
+    async fn readable_inputs(&self) -> InputPinSet {
+        // digitalRead only accepts pins already configured as inputs
+        let mut digital: Vec<u8> = self.pin_modes.lock().await
+            .iter()
+            .filter(|(_, mode)| matches!(mode, PinMode::Input))
+            .map(|(&pin, _)| pin)
+            .collect();
+        digital.sort_unstable();
+        
+        InputPinSet {
+            digital,
+            analog: ANALOG_PINS.collect(),
+            batch_endpoint: None,  // Firmware has no batched read command
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::transport::mock::{MockConfig, MockTransport};
+    use crate::transport::{TransportConfig, TransportResult, TransportStats};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    
+    /// Line the scripted firmware sends back for `data`, or an error if it is the `failing` command
+    fn firmware_reply(data: &[u8], failing: Option<&str>) -> Vec<u8> {
+        let command = String::from_utf8_lossy(data).trim().to_string();
+        let response = if failing == Some(command.as_str()) {
+            "ERROR:pin busy"
+        } else if command.starts_with(CMD_ANALOG_READ) {
+            "VALUE:512"
+        } else if command.starts_with(CMD_DIGITAL_READ) {
+            "VALUE:1"
+        } else if command == CMD_TIME {
+            "TIME:5000"
+        } else if command == CMD_CAPS {
+            "CAPS:gpio, analog,telemetry"
+        } else if command == "STATUS" {
+            "{\"temp\":21.5,\"ready\":true}"
+        } else if command == "COUNTERS" {
+            "rx=120,tx=118"
+        } else {
+            RESP_OK
+        };
+        format!("{}\r\n", response).into_bytes()
+    }
+    
+    /// Connected mock Arduino answering firmware commands, rejecting the `failing` one
+    fn arduino(failing: Option<&'static str>) -> MockTransport {
+        MockTransport::scripted(move |data| vec![firmware_reply(data, failing)])
+    }
+    
+    /// Mock Arduino that starts disconnected, optionally letting `connect` restore the link
+    fn disconnected_arduino(auto_reconnect: bool) -> MockTransport {
+        let config = TransportConfig {
+            auto_reconnect,
+            ..Default::default()
+        };
+        let mock_config = MockConfig {
+            latency_ms: 0,
+            enforce_latency: false,
+            ..Default::default()
+        };
+        MockTransport::new("arduino".into(), config, mock_config).with_responder(|data| vec![firmware_reply(data, None)])
+    }
+    
+    /// Times this exact command was sent
+    async fn sent_count(transport: &MockTransport, command: &str) -> usize {
+        transport.get_sent_history().await.iter()
+            .filter(|sent| String::from_utf8_lossy(sent).trim() == command)
+            .count()
+    }
+    
+    fn cached_session(transport: Arc<MockTransport>) -> ArduinoSession {
+        ArduinoSession::new(transport).with_read_cache(
+            ReadCache::new()
+                .with_ttl("analogRead", Duration::from_secs(5))
+                .with_ttl("digitalRead", Duration::from_secs(5))
+        )
+    }
+    
+    #[tokio::test]
+    async fn test_cached_reads_share_round_trip() {
+        let transport = Arc::new(arduino(None));
+        let mut session = cached_session(transport.clone());
+        
+        let first = session.invoke_async("analogRead", vec![json!(2)]).await.unwrap();
+        let second = session.invoke_async("analogRead", vec![json!(2)]).await.unwrap();
+        
+        assert_eq!(first, json!({ "value": 512 }));
+        assert_eq!(first, second);
+        assert_eq!(transport.send_count(), 1);
+        
+        // A different pin is not served from the cache
+        session.invoke_async("analogRead", vec![json!(3)]).await.unwrap();
+        assert_eq!(transport.send_count(), 2);
+    }
+    
+    #[tokio::test]
+    async fn test_write_invalidates_cached_read() {
+        let transport = Arc::new(arduino(None));
+        let mut session = cached_session(transport.clone());
+        
+        session.invoke_async("pinMode", vec![json!(7), json!("INPUT")]).await.unwrap();
+        session.invoke_async("digitalRead", vec![json!(7)]).await.unwrap();
+        session.invoke_async("digitalRead", vec![json!(7)]).await.unwrap();
+        assert_eq!(transport.send_count(), 2);
+        
+        // Reconfiguring the pin is a write and drops its cached value
+        session.invoke_async("pinMode", vec![json!(7), json!("INPUT")]).await.unwrap();
+        session.invoke_async("digitalRead", vec![json!(7)]).await.unwrap();
+        assert_eq!(transport.send_count(), 4);
+        
+        // So is any other endpoint, even one without a pin argument
+        session.invoke_async("command", vec![json!("STATUS")]).await.unwrap();
+        session.invoke_async("digitalRead", vec![json!(7)]).await.unwrap();
+        assert_eq!(transport.send_count(), 6);
+    }
+    
+    #[tokio::test]
+    async fn test_reads_uncached_by_default() {
+        let transport = Arc::new(arduino(None));
+        let mut session = ArduinoSession::new(transport.clone());
+        
+        session.invoke_async("analogRead", vec![json!(0)]).await.unwrap();
+        session.invoke_async("analogRead", vec![json!(0)]).await.unwrap();
+        assert_eq!(transport.send_count(), 2);
+    }
+    
+    #[tokio::test]
+    async fn test_read_all_inputs() {
+        let transport = Arc::new(arduino(None));
+        let mut session = ArduinoSession::new(transport.clone());
+        session.invoke_async("pinMode", vec![json!(2), json!("INPUT")]).await.unwrap();
+        session.invoke_async("pinMode", vec![json!(7), json!("INPUT")]).await.unwrap();
+        session.invoke_async("pinMode", vec![json!(9), json!("OUTPUT")]).await.unwrap();
+        
+        let values = session.read_all_inputs().await.unwrap();
+        
+        let mut keys: Vec<_> = values.keys().cloned().collect();
+        keys.sort();
+        assert_eq!(keys, vec!["A0", "A1", "A2", "A3", "A4", "A5", "D2", "D7"]);
+        assert_eq!(values["D2"], json!(true));
+        assert_eq!(values["A5"], json!(512));
+    }
+    
+    #[tokio::test]
+    async fn test_command_metrics_per_endpoint() {
+        let transport = Arc::new(
+            arduino(Some("DIGITAL_WRITE 13 1")).with_reply_delay(|data| {
+                if String::from_utf8_lossy(data).trim() == "ANALOG_READ 0" {
+                    Duration::from_millis(40)
+                } else {
+                    Duration::ZERO
+                }
+            }),
+        );
+        let mut session = ArduinoSession::new(transport.clone());
+        
+        for _ in 0..3 {
+            session.invoke_async("analogRead", vec![json!(0)]).await.unwrap();
+        }
+        session.invoke_async("pinMode", vec![json!(13), json!("OUTPUT")]).await.unwrap();
+        session.invoke_async("digitalWrite", vec![json!(13), json!(false)]).await.unwrap();
+        assert!(session.invoke_async("digitalWrite", vec![json!(13), json!(true)]).await.is_err());
+        
+        let metrics = session.command_metrics();
+        let reads = &metrics["analogRead"];
+        assert_eq!(reads.count, 3);
+        assert_eq!(reads.error_count, 0);
+        assert!(reads.avg_latency_ms >= 40.0, "avg {}", reads.avg_latency_ms);
+        assert!(reads.p95_latency_ms >= 40.0, "p95 {}", reads.p95_latency_ms);
+        
+        let writes = &metrics["digitalWrite"];
+        assert_eq!(writes.count, 2);
+        assert_eq!(writes.error_count, 1);
+        assert_eq!(writes.error_rate(), 0.5);
+        assert!(writes.avg_latency_ms < reads.avg_latency_ms);
+        
+        assert_eq!(metrics["pinMode"].count, 1);
+        assert!(!metrics.contains_key("digitalRead"));
+    }
+    
+    #[tokio::test(start_paused = true)]
+    async fn test_keep_alive_pings_idle_session() {
+        let transport = Arc::new(arduino(None));
+        let mut session = ArduinoSession::new(transport.clone());
+        session.start_keep_alive(&KeepAliveSettings::new("NOP", Duration::from_millis(50)));
+        
+        // The clock is paused, so sleeping steps through the pings at 50, 100 and 150ms exactly
+        tokio::time::sleep(Duration::from_millis(180)).await;
+        assert_eq!(sent_count(&transport, "NOP").await, 3);
+        
+        session.close_async().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(120)).await;
+        assert_eq!(sent_count(&transport, "NOP").await, 3, "closed session kept pinging");
+    }
+    
+    #[tokio::test(start_paused = true)]
+    async fn test_keep_alive_reset_by_real_commands() {
+        let transport = Arc::new(arduino(None));
+        let mut session = ArduinoSession::new(transport.clone());
+        session.start_keep_alive(&KeepAliveSettings::new("NOP", Duration::from_millis(80)));
+        
+        // Busy for well over the interval: every command pushes the ping back
+        for _ in 0..8 {
+            session.invoke_async("analogRead", vec![json!(0)]).await.unwrap();
+            tokio::time::advance(Duration::from_millis(25)).await;
+        }
+        assert_eq!(sent_count(&transport, "NOP").await, 0);
+        
+        // Idle again: the ping is due one interval after the last command (175ms), at 255ms
+        tokio::time::advance(Duration::from_millis(50)).await;
+        assert_eq!(sent_count(&transport, "NOP").await, 0);
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert_eq!(sent_count(&transport, "NOP").await, 1);
+        
+        // Pings don't disturb the replies to real commands
+        let value = session.invoke_async("analogRead", vec![json!(0)]).await.unwrap();
+        assert_eq!(value, session.invoke_async("analogRead", vec![json!(0)]).await.unwrap());
+    }
+    
+    #[test]
+    fn test_keep_alive_disabled_settings() {
+        assert!(KeepAliveSettings::new("NOP", Duration::from_millis(100)).is_enabled());
+        assert!(!KeepAliveSettings::new("NOP", Duration::ZERO).is_enabled());
+        assert!(!KeepAliveSettings::new("", Duration::from_millis(100)).is_enabled());
+    }
+    
+    #[tokio::test]
+    async fn test_read_all_inputs_collects_pin_errors() {
+        let transport = Arc::new(arduino(Some("ANALOG_READ 3")));
+        let mut session = ArduinoSession::new(transport.clone());
+        session.invoke_async("pinMode", vec![json!(4), json!("INPUT")]).await.unwrap();
+        
+        let values = session.read_all_inputs().await.unwrap();
+        
+        assert_eq!(values.len(), 7);
+        assert!(values["A3"]["error"].as_str().unwrap().contains("pin busy"));
+        for key in ["A0", "A1", "A2", "A4", "A5"] {
+            assert_eq!(values[key], json!(512), "{} should still be read", key);
+        }
+        assert_eq!(values["D4"], json!(true));
+    }
     
     #[test]
     fn test_driver_creation() {
@@ -987,6 +1687,28 @@ mod tests {
         assert_eq!(caps.min_latency_ms, Some(50));
     }
     
+    #[test]
+    fn test_analog_read_range_validation() {
+        let adc_max = ArduinoUnoDriver::new().capabilities().max_analog_value();
+        assert_eq!(adc_max, 1023);
+        
+        assert_eq!(parse_analog_value(&json!("VALUE:512"), adc_max).unwrap(), 512);
+        assert_eq!(parse_analog_value(&json!("VALUE:1023"), adc_max).unwrap(), 1023);
+        assert!(matches!(parse_analog_value(&json!("VALUE:1500"), adc_max), Err(DeviceError::Protocol(_))));
+        assert!(matches!(parse_analog_value(&json!("VALUE:-1"), adc_max), Err(DeviceError::Protocol(_))));
+        assert!(matches!(parse_analog_value(&json!("OK"), adc_max), Err(DeviceError::Protocol(_))));
+        
+        // 12-bit ADC accepts values a 10-bit one would reject
+        assert_eq!(parse_analog_value(&json!("VALUE:1500"), 4095).unwrap(), 1500);
+    }
+    
+    #[test]
+    fn test_digital_read_accepts_only_binary() {
+        assert_eq!(parse_digital_value(&json!("VALUE:0")).unwrap(), false);
+        assert_eq!(parse_digital_value(&json!("VALUE:1")).unwrap(), true);
+        assert!(matches!(parse_digital_value(&json!("VALUE:2")), Err(DeviceError::Protocol(_))));
+    }
+    
     #[test]
     fn test_supported_transports() {
         let driver = ArduinoUnoDriver::new();
@@ -995,4 +1717,432 @@ mod tests {
         assert_eq!(transports.len(), 1);
         assert_eq!(transports[0], TransportType::Serial);
     }
-}
\ No newline at end of file
+    
+    #[tokio::test]
+    async fn test_sync_clock_stores_offset() {
+        let transport = Arc::new(arduino(None));
+        let mut session = ArduinoSession::new(transport.clone());
+        assert!(session.clock_offset().is_none());
+        assert!(session.clock_resync_due());
+        
+        let before = clock_sync::now_ms();
+        let offset = session.sync_clock().await.unwrap();
+        let after = clock_sync::now_ms();
+        
+        // Device reported 5000ms at some host time between before and after
+        assert!(offset.offset_ms >= before as i64 - 5000);
+        assert!(offset.offset_ms <= after as i64 - 5000);
+        assert_eq!(session.clock_offset(), Some(offset));
+        assert!(!session.clock_resync_due());
+        
+        let mut sample = crate::telemetry::TelemetrySample::new_f32(1.0);
+        sample.timestamp_ms = 6000;
+        offset.rebase_sample(&mut sample);
+        assert_eq!(sample.timestamp_ms as i64, 6000 + offset.offset_ms);
+    }
+    
+    #[tokio::test]
+    async fn test_invoke_on_disconnected_transport_is_session_error() {
+        let transport = Arc::new(disconnected_arduino(false));
+        let mut session = ArduinoSession::new(transport.clone());
+        
+        match session.invoke_async("pinMode", vec![json!(13), json!("OUTPUT")]).await {
+            Err(DeviceError::Session(msg)) => assert_eq!(msg, "transport disconnected"),
+            other => panic!("Expected session error, got {:?}", other),
+        }
+        assert!(matches!(session.send_raw(b"PING").await, Err(DeviceError::Session(_))));
+        
+        // Nothing reached the wire
+        assert_eq!(transport.send_count(), 0);
+    }
+    
+    #[tokio::test]
+    async fn test_invoke_reconnects_when_configured() {
+        let transport = Arc::new(disconnected_arduino(true));
+        let mut session = ArduinoSession::new(transport.clone());
+        
+        let value = session.invoke_async("analogRead", vec![json!(0)]).await.unwrap();
+        assert_eq!(value, json!({ "value": 512 }));
+        assert!(transport.is_connected());
+    }
+    
+    #[tokio::test]
+    async fn test_rejection_distinct_from_timeout() {
+        // Device answers with an explicit ERROR
+        let transport = Arc::new(arduino(Some("PIN_MODE 13 OUTPUT")));
+        let mut session = ArduinoSession::new(transport);
+        let rejected = session.invoke_async("pinMode", vec![json!(13), json!("OUTPUT")]).await.unwrap_err();
+        assert!(matches!(rejected, DeviceError::DeviceRejection(_)), "got {:?}", rejected);
+        assert_eq!(rejected.to_string(), "Device rejected command: PIN_MODE 13 OUTPUT (pin busy)");
+        
+        // Device never answers
+        let transport = Arc::new(MockTransport::scripted(|_| Vec::new()));
+        let mut session = ArduinoSession::new(transport);
+        let silent = session.invoke_async("pinMode", vec![json!(13), json!("OUTPUT")]).await.unwrap_err();
+        assert!(matches!(silent, DeviceError::Timeout(_)), "got {:?}", silent);
+        assert_ne!(silent.to_string(), rejected.to_string());
+    }
+    
+    #[test]
+    fn test_rejection_reason() {
+        assert_eq!(rejection_reason(&json!("ERROR:pin busy")), Some("pin busy".to_string()));
+        assert_eq!(rejection_reason(&json!("ERROR")), Some("no reason given".to_string()));
+        assert_eq!(rejection_reason(&json!("OK")), None);
+        assert_eq!(rejection_reason(&json!("VALUE:1")), None);
+        assert_eq!(rejection_reason(&json!("ERROR pin busy")), Some("pin busy".to_string()));
+        
+        // Lines that merely start with ERROR are not rejections
+        assert_eq!(rejection_reason(&json!("ERRORS=0")), None);
+        assert_eq!(rejection_reason(&json!("ERROR_COUNT:3")), None);
+        
+        // Structured replies refuse with an error field or an ERROR status
+        assert_eq!(rejection_reason(&json!({ "error": "pin busy" })), Some("pin busy".to_string()));
+        assert_eq!(rejection_reason(&json!({ "status": "ERROR" })), Some("no reason given".to_string()));
+        assert_eq!(rejection_reason(&json!({ "status": "OK" })), None);
+    }
+    
+    #[tokio::test]
+    async fn test_command_responses_use_configured_parser() {
+        let transport = Arc::new(arduino(None));
+        
+        // Default parser returns the raw line
+        let mut session = ArduinoSession::new(transport.clone());
+        let value = session.invoke_async("command", vec![json!("STATUS")]).await.unwrap();
+        assert_eq!(value, json!("{\"temp\":21.5,\"ready\":true}"));
+        
+        session.set_response_parser(Arc::new(crate::device::JsonParser)).unwrap();
+        let value = session.invoke_async("command", vec![json!("STATUS")]).await.unwrap();
+        assert_eq!(value, json!({ "temp": 21.5, "ready": true }));
+        
+        // A reply the parser can't read is a protocol error
+        let result = session.invoke_async("command", vec![json!("COUNTERS")]).await;
+        assert!(matches!(result, Err(DeviceError::Protocol(_))));
+        
+        let mut session = ArduinoSession::new(transport).with_parser(Arc::new(crate::device::KeyValueParser));
+        let value = session.invoke_async("command", vec![json!("COUNTERS")]).await.unwrap();
+        assert_eq!(value, json!({ "rx": 120, "tx": 118 }));
+    }
+    
+    #[tokio::test]
+    async fn test_endpoint_replies_use_configured_parser() {
+        // Firmware answering every command with a JSON object
+        let transport = Arc::new(MockTransport::scripted(|data| {
+            let command = String::from_utf8_lossy(data).trim().to_string();
+            let reply = if command.starts_with(CMD_ANALOG_READ) {
+                json!({ "value": 700 })
+            } else if command.starts_with(CMD_DIGITAL_WRITE) {
+                json!({ "error": "pin busy" })
+            } else if command == CMD_CAPS {
+                json!({ "caps": ["gpio", "analog"] })
+            } else {
+                json!({ "status": "OK" })
+            };
+            vec![format!("{}\n", reply).into_bytes()]
+        }));
+        let mut session = ArduinoSession::new(transport.clone()).with_parser(Arc::new(crate::device::JsonParser));
+        
+        assert_eq!(session.invoke_async("analogRead", vec![json!(0)]).await.unwrap(), json!({ "value": 700 }));
+        session.invoke_async("pinMode", vec![json!(13), json!("OUTPUT")]).await.unwrap();
+        let rejected = session.invoke_async("digitalWrite", vec![json!(13), json!(true)]).await.unwrap_err();
+        assert!(matches!(rejected, DeviceError::DeviceRejection(ref reason) if reason.contains("pin busy")), "got {:?}", rejected);
+        assert_eq!(session.query_capabilities().await.unwrap(), ["analog", "gpio"].iter().map(|s| s.to_string()).collect());
+        
+        // The default line parser can't read this firmware's replies
+        let mut session = ArduinoSession::new(transport);
+        assert!(matches!(session.invoke_async("analogRead", vec![json!(0)]).await, Err(DeviceError::Protocol(_))));
+    }
+    
+    #[tokio::test]
+    async fn test_injected_protocol_error_on_third_command() {
+        use crate::device::{FaultInjectingSession, FaultRule, InjectedFault};
+        
+        let transport = Arc::new(arduino(None));
+        let mut session = FaultInjectingSession::new(Box::new(ArduinoSession::new(transport.clone())))
+            .with_rule(FaultRule::new(InjectedFault::Protocol("garbled reply".into())).on_call(3))
+            .with_rule(FaultRule::new(InjectedFault::Timeout(1000))
+                .with_endpoint("digitalRead")
+                .with_args(vec![json!(5)]));
+        
+        session.invoke_async("pinMode", vec![json!(4), json!("INPUT")]).await.unwrap();
+        session.invoke_async("pinMode", vec![json!(5), json!("INPUT")]).await.unwrap();
+        match session.invoke_async("analogRead", vec![json!(0)]).await {
+            Err(DeviceError::Protocol(msg)) => assert_eq!(msg, "garbled reply"),
+            other => panic!("Expected injected protocol error, got {:?}", other),
+        }
+        
+        // Everything else reaches the device; reads of pin 5 always time out
+        assert_eq!(session.invoke_async("analogRead", vec![json!(0)]).await.unwrap(), json!({ "value": 512 }));
+        assert!(session.invoke_async("digitalRead", vec![json!(4)]).await.is_ok());
+        assert!(matches!(session.invoke_async("digitalRead", vec![json!(5)]).await, Err(DeviceError::Timeout(1000))));
+        assert_eq!(transport.send_count(), 4);
+        
+        let injected: Vec<_> = session.injected().iter().map(|i| (i.call, i.endpoint.as_str())).collect();
+        assert_eq!(injected, vec![(3, "analogRead"), (6, "digitalRead")]);
+        
+        // Replaying from the start fails the same call again
+        session.reset();
+        assert!(session.invoke_async("pinMode", vec![json!(4), json!("INPUT")]).await.is_ok());
+        assert!(session.invoke_async("pinMode", vec![json!(5), json!("INPUT")]).await.is_ok());
+        assert!(matches!(session.invoke_async("analogRead", vec![json!(0)]).await, Err(DeviceError::Protocol(_))));
+    }
+    
+    /// Board that resets when the port opens and ignores everything sent before it has booted
+    fn slow_boot(boot_time: Duration) -> MockTransport {
+        let opened = std::time::Instant::now();
+        MockTransport::scripted(move |_| {
+            if opened.elapsed() >= boot_time {
+                vec![format!("{}\r\n", RESP_ARDUINO_UNO).into_bytes()]
+            } else {
+                Vec::new()
+            }
+        })
+    }
+    
+    #[tokio::test]
+    async fn test_probe_waits_for_slow_boot() {
+        let short = ProbeSettings {
+            settle_delay: Duration::ZERO,
+            response_timeout: Duration::from_millis(40),
+            retries: 0,
+        };
+        
+        // A single short attempt lands while the board is still resetting
+        let transport = slow_boot(Duration::from_millis(150));
+        let driver = ArduinoUnoDriver::new().with_probe_settings(short);
+        assert!(matches!(driver.probe_responds(&transport).await, Err(DeviceError::Timeout(40))));
+        
+        // Settling first lets the first probe through
+        let transport = slow_boot(Duration::from_millis(150));
+        let driver = ArduinoUnoDriver::new().with_probe_settings(ProbeSettings {
+            settle_delay: Duration::from_millis(200),
+            ..short
+        });
+        assert!(driver.probe_responds(&transport).await.unwrap());
+        assert_eq!(transport.send_count(), 1);
+        
+        // Retrying until the board is up works without a settle delay
+        let transport = slow_boot(Duration::from_millis(150));
+        let driver = ArduinoUnoDriver::new().with_probe_settings(ProbeSettings {
+            retries: 10,
+            ..short
+        });
+        assert!(driver.probe_responds(&transport).await.unwrap());
+        assert!(transport.send_count() > 1);
+    }
+    
+    /// Device that answers every command OK after a delay and records how many were outstanding
+    struct PipelineTransport {
+        config: TransportConfig,
+        reply_delay: Duration,
+        /// Commands sent and not yet answered
+        queued: std::sync::Mutex<Vec<String>>,
+        max_outstanding: AtomicUsize,
+    }
+    
+    impl PipelineTransport {
+        fn new(reply_delay: Duration) -> Self {
+            Self {
+                config: TransportConfig::default(),
+                reply_delay,
+                queued: std::sync::Mutex::new(Vec::new()),
+                max_outstanding: AtomicUsize::new(0),
+            }
+        }
+    }
+    
+    #[async_trait]
+    impl Transport for PipelineTransport {
+        fn transport_type(&self) -> TransportType {
+            TransportType::Serial
+        }
+        
+        fn name(&self) -> &str {
+            "pipeline"
+        }
+        
+        fn is_connected(&self) -> bool {
+            true
+        }
+        
+        async fn connect(&self) -> TransportResult<()> {
+            Ok(())
+        }
+        
+        async fn disconnect(&self) -> TransportResult<()> {
+            Ok(())
+        }
+        
+        async fn send(&self, data: &[u8]) -> TransportResult<()> {
+            let mut queued = self.queued.lock().unwrap();
+            queued.push(String::from_utf8_lossy(data).trim().to_string());
+            self.max_outstanding.fetch_max(queued.len(), Ordering::SeqCst);
+            Ok(())
+        }
+        
+        /// Answers the newest command first; a tagged command's reply repeats it after the tag
+        async fn receive(&self, timeout: Duration) -> TransportResult<Vec<u8>> {
+            tokio::time::sleep(self.reply_delay.min(timeout)).await;
+            let Some(command) = self.queued.lock().unwrap().pop() else {
+                return Err(TransportError::Timeout(format!("{}ms", timeout.as_millis())));
+            };
+            let reply = match command.split_once(' ') {
+                Some((tag, rest)) if tag.starts_with('#') => format!("{} {}", tag, rest),
+                _ => RESP_OK.to_string(),
+            };
+            Ok(format!("{}\r\n", reply).into_bytes())
+        }
+        
+        fn stats(&self) -> TransportStats {
+            TransportStats::default()
+        }
+        
+        async fn reset(&self) -> TransportResult<()> {
+            Ok(())
+        }
+        
+        fn config(&self) -> &TransportConfig {
+            &self.config
+        }
+        
+        async fn cleanup_resources(&self) -> TransportResult<()> {
+            Ok(())
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_single_in_flight_serializes_commands() {
+        let transport = Arc::new(PipelineTransport::new(Duration::from_millis(40)));
+        let session = ArduinoSession::new(transport.clone());
+        
+        let (a, b) = tokio::join!(
+            session.send_command("DIGITAL_WRITE 2 1"),
+            session.send_command("DIGITAL_WRITE 3 1"),
+        );
+        assert_eq!(a.unwrap(), RESP_OK);
+        assert_eq!(b.unwrap(), RESP_OK);
+        assert_eq!(transport.max_outstanding.load(Ordering::SeqCst), 1);
+    }
+    
+    #[tokio::test]
+    async fn test_higher_in_flight_limit_pipelines_commands() {
+        let transport = Arc::new(PipelineTransport::new(Duration::from_millis(40)));
+        let session = ArduinoSession::new(transport.clone()).with_id_tagging(true).with_max_in_flight(2);
+        
+        // Replies arrive newest first; each caller still gets its own
+        let (a, b, c) = tokio::join!(
+            session.send_command("DIGITAL_WRITE 2 1"),
+            session.send_command("DIGITAL_WRITE 3 1"),
+            session.send_command("DIGITAL_WRITE 4 1"),
+        );
+        assert_eq!(a.unwrap(), "DIGITAL_WRITE 2 1");
+        assert_eq!(b.unwrap(), "DIGITAL_WRITE 3 1");
+        assert_eq!(c.unwrap(), "DIGITAL_WRITE 4 1");
+        assert_eq!(transport.max_outstanding.load(Ordering::SeqCst), 2);
+    }
+    
+    #[tokio::test]
+    async fn test_in_flight_limit_needs_id_tagging() {
+        let transport = Arc::new(PipelineTransport::new(Duration::from_millis(20)));
+        let session = ArduinoSession::new(transport.clone()).with_max_in_flight(3);
+        
+        let (a, b) = tokio::join!(
+            session.send_command("DIGITAL_WRITE 2 1"),
+            session.send_command("DIGITAL_WRITE 3 1"),
+        );
+        assert_eq!(a.unwrap(), RESP_OK);
+        assert_eq!(b.unwrap(), RESP_OK);
+        assert_eq!(transport.max_outstanding.load(Ordering::SeqCst), 1);
+    }
+    
+    #[tokio::test]
+    async fn test_query_capabilities() {
+        let mut session = ArduinoSession::new(Arc::new(arduino(None)));
+        let capabilities = session.query_capabilities().await.unwrap();
+        assert_eq!(capabilities, ["analog", "gpio", "telemetry"].iter().map(|s| s.to_string()).collect());
+        
+        // Firmware without CAPS reports the base command set
+        let mut session = ArduinoSession::new(Arc::new(arduino(Some(CMD_CAPS))));
+        let capabilities = session.query_capabilities().await.unwrap();
+        assert!(capabilities.contains("pwm") && capabilities.contains("scripting"));
+    }
+    
+    #[tokio::test]
+    async fn test_open_negotiates_down_to_older_protocol() {
+        use crate::protocols::handshake::MessageExamples;
+        
+        // Firmware that only speaks protocol 1.1.0 and refuses anything newer
+        let transport = Arc::new(MockTransport::scripted(|data| {
+            let message: Value = serde_json::from_slice(data).unwrap();
+            let reply = match message["command"].as_str() {
+                Some("IDENTIFY") => serde_json::to_value(MessageExamples::identify_response_success()).unwrap(),
+                Some("VERSION") if message["preferred_version"] == json!("1.1.0") => json!({
+                    "status": "OK",
+                    "session_id": message["session_id"],
+                    "negotiated_version": "1.1.0",
+                    "supported_versions": ["1.1.0"],
+                }),
+                _ => json!({
+                    "status": "ERROR",
+                    "session_id": message["session_id"],
+                    "negotiated_version": "",
+                    "supported_versions": ["1.1.0"],
+                    "error_message": "unsupported protocol version",
+                }),
+            };
+            vec![format!("{}\n", reply).into_bytes()]
+        }));
+        
+        let driver = ArduinoUnoDriver::new().with_protocol_versions(vec![Version::new(1, 2, 0), Version::new(1, 1, 0)]);
+        let session = driver.open_async(transport.clone()).await.unwrap();
+        let negotiated = session.negotiated_version().unwrap();
+        assert_eq!(negotiated.version, Version::new(1, 1, 0));
+        assert_eq!(negotiated.preferred, Version::new(1, 2, 0));
+        assert!(negotiated.is_downgrade());
+        
+        // Without protocol versions the session opens without a handshake
+        let session = ArduinoUnoDriver::new().open_async(Arc::new(arduino(None))).await.unwrap();
+        assert!(session.negotiated_version().is_none());
+    }
+    
+    /// Firmware answering IDENTIFY with `firmware_version`
+    fn identifying_arduino(firmware_version: &'static str) -> Arc<MockTransport> {
+        use crate::protocols::handshake::MessageExamples;
+        
+        Arc::new(MockTransport::scripted(move |_| {
+            let mut response = MessageExamples::identify_response_success();
+            response.firmware_version = firmware_version.to_string();
+            vec![format!("{}\n", serde_json::to_string(&response).unwrap()).into_bytes()]
+        }))
+    }
+    
+    #[tokio::test]
+    async fn test_open_rejects_firmware_below_minimum() {
+        use crate::protocols::handshake::HandshakeError;
+        
+        let result = ArduinoUnoDriver::new()
+            .open_with_min_firmware_async(identifying_arduino("1.9.9"), Some(Version::new(2, 0, 0)))
+            .await;
+        match result {
+            Err(DeviceError::Handshake(HandshakeError::IncompatibleFirmware { device_version, minimum_required, .. })) => {
+                assert_eq!(device_version, "1.9.9");
+                assert_eq!(minimum_required, "2.0.0");
+            }
+            Err(e) => panic!("Expected IncompatibleFirmware, got {}", e),
+            Ok(_) => panic!("Expected IncompatibleFirmware, session was opened"),
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_open_accepts_firmware_at_or_above_minimum() {
+        for firmware in ["2.0.0", "2.1.0"] {
+            let transport = identifying_arduino(firmware);
+            let identity = ArduinoUnoDriver::new().identify_async(transport.clone()).await.unwrap().unwrap();
+            assert_eq!(identity.firmware_version, firmware);
+            
+            let result = ArduinoUnoDriver::new()
+                .open_with_min_firmware_async(transport, Some(Version::new(2, 0, 0)))
+                .await;
+            assert!(result.is_ok(), "firmware {} should open", firmware);
+        }
+    }
+}
@@ -2,6 +2,6 @@ pub mod arduino_uno;
 pub mod arduino_mega;
 pub mod raspberry_pi;
 
-pub use arduino_uno::ArduinoUnoDriver;
+pub use arduino_uno::{ArduinoUnoDriver, ProbeSettings};
 pub use arduino_mega::ArduinoMega2560Driver;
 pub use raspberry_pi::RaspberryPi3BDriver;
\ No newline at end of file
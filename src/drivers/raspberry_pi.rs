@@ -120,6 +120,7 @@ impl DeviceDriver for RaspberryPi3BDriver {
             requires_auth: true,  // SSH requires authentication
             max_data_rate: Some(100_000_000 / 8),  // 100 Mbps Ethernet
             min_latency_ms: Some(10),  // Network latency
+            adc_resolution_bits: None,
         }
     }
 }
@@ -5,7 +5,7 @@ pub mod device;
 pub mod drivers;
 pub mod transport;
 pub mod protocols;
-// pub mod scripting; // Temporarily disabled due to lifetime issues
+pub mod scripting;
 pub mod telemetry;
 pub mod ui;
 pub mod profile;
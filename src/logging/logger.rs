@@ -1,6 +1,9 @@
 // Logger configuration and management
 
+use chrono::NaiveTime;
 use serde::{Serialize, Deserialize};
+use std::time::{Duration, Instant};
+use super::LogLevel;
 
 /// Logger configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,9 +20,13 @@ pub struct LoggerConfig {
     /// Maximum memory usage per buffer (bytes)
     pub max_buffer_memory: Option<usize>,
     
-    /// Minimum log level to capture
+    /// Minimum log level to capture outside any scheduled window or capture
     pub min_level: super::LogLevel,
     
+    /// Daily windows with their own minimum level (e.g. quiet hours)
+    #[serde(default)]
+    pub level_schedule: Vec<LevelWindow>,
+    
     /// Enable automatic export on buffer full
     pub auto_export_on_full: bool,
     
@@ -38,6 +45,7 @@ impl Default for LoggerConfig {
             system_buffer_size: 1000,      // 1k entries for system
             max_buffer_memory: Some(10 * 1024 * 1024), // 10MB per buffer
             min_level: super::LogLevel::Debug,
+            level_schedule: Vec::new(),
             auto_export_on_full: false,
             export_dir: std::path::PathBuf::from("logs"),
             rotation: RotationConfig::default(),
@@ -72,9 +80,98 @@ impl Default for RotationConfig {
     }
 }
 
+/// Minimum level applied during a daily time window (local time)
+/// A window whose end is before its start wraps past midnight
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LevelWindow {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+    pub level: LogLevel,
+}
+
+impl LevelWindow {
+    pub fn new(start: NaiveTime, end: NaiveTime, level: LogLevel) -> Self {
+        Self { start, end, level }
+    }
+    
+    /// Whether `time` falls in `[start, end)`
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            self.start <= time && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+}
+
+/// Decides the effective minimum log level over time
+///
+/// A triggered capture wins while it lasts, then the first scheduled window
+/// containing the current time, then the configured base level.
+#[derive(Debug, Clone)]
+pub struct LevelScheduler {
+    base: LogLevel,
+    windows: Vec<LevelWindow>,
+    capture: Option<(LogLevel, Instant)>,
+}
+
+impl LevelScheduler {
+    pub fn new(base: LogLevel, windows: Vec<LevelWindow>) -> Self {
+        Self {
+            base,
+            windows,
+            capture: None,
+        }
+    }
+    
+    pub fn from_config(config: &LoggerConfig) -> Self {
+        Self::new(config.min_level, config.level_schedule.clone())
+    }
+    
+    /// Use `level` for the next `duration`, then fall back to the schedule
+    pub fn begin_capture(&mut self, level: LogLevel, duration: Duration) {
+        self.capture = Some((level, Instant::now() + duration));
+    }
+    
+    /// Use `level` until `until`, then fall back to the schedule
+    pub fn begin_capture_until(&mut self, level: LogLevel, until: Instant) {
+        self.capture = Some((level, until));
+    }
+    
+    /// End any capture early
+    pub fn end_capture(&mut self) {
+        self.capture = None;
+    }
+    
+    /// Effective level at local time-of-day `time` and monotonic instant `now`
+    pub fn effective_level_at(&self, time: NaiveTime, now: Instant) -> LogLevel {
+        if let Some((level, until)) = self.capture {
+            if now < until {
+                return level;
+            }
+        }
+        
+        self.windows.iter()
+            .find(|window| window.contains(time))
+            .map(|window| window.level)
+            .unwrap_or(self.base)
+    }
+    
+    /// Effective level right now
+    pub fn effective_level(&self) -> LogLevel {
+        self.effective_level_at(chrono::Local::now().time(), Instant::now())
+    }
+    
+    /// Whether an entry at `level` should be kept right now
+    pub fn should_log(&self, level: LogLevel) -> bool {
+        level >= self.effective_level()
+    }
+}
+
 /// Simple logger interface
 pub struct Logger {
     config: LoggerConfig,
+    levels: LevelScheduler,
 }
 
 impl Logger {
@@ -85,7 +182,10 @@ impl Logger {
     
     /// Create a new logger with custom config
     pub fn with_config(config: LoggerConfig) -> Self {
-        Self { config }
+        Self {
+            levels: LevelScheduler::from_config(&config),
+            config,
+        }
     }
     
     /// Get the configuration
@@ -95,9 +195,20 @@ impl Logger {
     
     /// Update configuration
     pub fn set_config(&mut self, config: LoggerConfig) {
+        self.levels = LevelScheduler::from_config(&config);
         self.config = config;
     }
     
+    /// Schedule of minimum levels, for triggering captures
+    pub fn levels(&mut self) -> &mut LevelScheduler {
+        &mut self.levels
+    }
+    
+    /// Minimum level currently in effect
+    pub fn effective_level(&self) -> LogLevel {
+        self.levels.effective_level()
+    }
+    
     /// Create export directory if it doesn't exist
     pub fn ensure_export_dir(&self) -> std::io::Result<()> {
         std::fs::create_dir_all(&self.config.export_dir)
@@ -152,4 +263,46 @@ mod tests {
         assert!(filename_str.contains("test_"));
         assert!(filename_str.ends_with(".log"));
     }
-}
\ No newline at end of file
+    
+    fn at(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+    
+    #[test]
+    fn test_level_changes_at_window_boundaries() {
+        // Verbose debug window in the morning, quiet hours overnight
+        let scheduler = LevelScheduler::new(LogLevel::Info, vec![
+            LevelWindow::new(at(9, 0), at(10, 0), LogLevel::Trace),
+            LevelWindow::new(at(22, 0), at(6, 0), LogLevel::Error),
+        ]);
+        let now = Instant::now();
+        
+        assert_eq!(scheduler.effective_level_at(at(8, 59), now), LogLevel::Info);
+        assert_eq!(scheduler.effective_level_at(at(9, 0), now), LogLevel::Trace);
+        assert_eq!(scheduler.effective_level_at(at(9, 59), now), LogLevel::Trace);
+        assert_eq!(scheduler.effective_level_at(at(10, 0), now), LogLevel::Info);
+        
+        assert_eq!(scheduler.effective_level_at(at(21, 59), now), LogLevel::Info);
+        assert_eq!(scheduler.effective_level_at(at(23, 30), now), LogLevel::Error);
+        assert_eq!(scheduler.effective_level_at(at(5, 59), now), LogLevel::Error);
+        assert_eq!(scheduler.effective_level_at(at(6, 0), now), LogLevel::Info);
+    }
+    
+    #[test]
+    fn test_capture_overrides_schedule_until_it_expires() {
+        let mut scheduler = LevelScheduler::new(LogLevel::Warning, vec![
+            LevelWindow::new(at(0, 0), at(12, 0), LogLevel::Error),
+        ]);
+        let start = Instant::now();
+        scheduler.begin_capture_until(LogLevel::Debug, start + Duration::from_secs(60));
+        
+        assert_eq!(scheduler.effective_level_at(at(11, 0), start), LogLevel::Debug);
+        assert_eq!(scheduler.effective_level_at(at(11, 0), start + Duration::from_secs(59)), LogLevel::Debug);
+        assert_eq!(scheduler.effective_level_at(at(11, 0), start + Duration::from_secs(60)), LogLevel::Error);
+        assert_eq!(scheduler.effective_level_at(at(13, 0), start + Duration::from_secs(60)), LogLevel::Warning);
+        
+        scheduler.begin_capture(LogLevel::Trace, Duration::from_secs(60));
+        scheduler.end_capture();
+        assert_eq!(scheduler.effective_level_at(at(13, 0), Instant::now()), LogLevel::Warning);
+    }
+}
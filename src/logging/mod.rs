@@ -6,9 +6,10 @@ pub mod logger;
 
 pub use buffer::{LogBuffer, LogEntry, LogLevel};
 pub use exporter::{LogExporter, LogFormat};
-pub use logger::{Logger, LoggerConfig};
+pub use logger::{Logger, LoggerConfig, LevelScheduler, LevelWindow};
 
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::RwLock;
 
 /// Global logging system
@@ -24,6 +25,9 @@ pub struct LoggingSystem {
     
     /// Configuration
     config: LoggerConfig,
+    
+    /// Minimum level in effect; entries below it are dropped
+    levels: parking_lot::RwLock<LevelScheduler>,
 }
 
 impl LoggingSystem {
@@ -38,30 +42,59 @@ impl LoggingSystem {
             device_io: Arc::new(RwLock::new(LogBuffer::new(config.device_io_buffer_size))),
             events: Arc::new(RwLock::new(LogBuffer::new(config.event_buffer_size))),
             system: Arc::new(RwLock::new(LogBuffer::new(config.system_buffer_size))),
+            levels: parking_lot::RwLock::new(LevelScheduler::from_config(&config)),
             config,
         }
     }
     
+    /// Minimum level currently in effect
+    pub fn effective_level(&self) -> LogLevel {
+        self.levels.read().effective_level()
+    }
+    
+    /// Lower (or raise) the minimum level for `duration`, e.g. during a debug capture
+    pub fn begin_capture(&self, level: LogLevel, duration: Duration) {
+        tracing::info!("Log level {:?} for the next {:?}", level, duration);
+        self.levels.write().begin_capture(level, duration);
+    }
+    
+    /// End a capture early and return to the scheduled level
+    pub fn end_capture(&self) {
+        self.levels.write().end_capture();
+    }
+    
     /// Log device I/O
     pub async fn log_device_io(&self, level: LogLevel, message: String, data: Option<Vec<u8>>) {
+        if !self.levels.read().should_log(level) {
+            return;
+        }
         let mut buffer = self.device_io.write().await;
         buffer.log(level, "DeviceIO", message, data);
     }
     
     /// Log event
     pub async fn log_event(&self, level: LogLevel, source: &str, message: String) {
+        if !self.levels.read().should_log(level) {
+            return;
+        }
         let mut buffer = self.events.write().await;
         buffer.log(level, source, message, None);
     }
     
     /// Log system message
     pub async fn log_system(&self, level: LogLevel, message: String) {
+        if !self.levels.read().should_log(level) {
+            return;
+        }
         let mut buffer = self.system.write().await;
         buffer.log(level, "System", message, None);
     }
     
     /// Generic log method that routes to system buffer
     pub async fn log(&self, level: LogLevel, source: &str, message: String, data: Option<Vec<u8>>) {
+        if !self.levels.read().should_log(level) {
+            return;
+        }
         let mut buffer = self.system.write().await;
         buffer.log(level, source, message, data);
     }
@@ -128,4 +161,26 @@ mod tests {
         assert!(json_str.contains("Test event"));
         assert!(json_str.contains("Test system error"));
     }
-}
\ No newline at end of file
+    
+    #[tokio::test]
+    async fn test_logs_below_effective_level_are_dropped() {
+        let logging = LoggingSystem::with_config(LoggerConfig {
+            min_level: LogLevel::Warning,
+            ..Default::default()
+        });
+        
+        logging.log_system(LogLevel::Info, "routine detail".to_string()).await;
+        logging.log_system(LogLevel::Error, "motor fault".to_string()).await;
+        assert_eq!(logging.system.read().await.len(), 1);
+        
+        // A capture lowers the level until it is ended
+        logging.begin_capture(LogLevel::Debug, Duration::from_secs(60));
+        assert_eq!(logging.effective_level(), LogLevel::Debug);
+        logging.log_system(LogLevel::Debug, "captured detail".to_string()).await;
+        assert_eq!(logging.system.read().await.len(), 2);
+        
+        logging.end_capture();
+        logging.log_event(LogLevel::Info, "Test", "dropped again".to_string()).await;
+        assert!(logging.events.read().await.is_empty());
+    }
+}
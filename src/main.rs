@@ -1,6 +1,8 @@
 mod device;
 mod transport;
 mod drivers;
+mod protocols;
+mod scripting;
 mod telemetry;
 mod ui;
 mod performance;
@@ -11,6 +13,7 @@ use std::sync::Arc;
 use tracing_subscriber;
 use device::DeviceManager;
 use performance::{MonitorConfig, PerformanceMonitor};
+use profile::{Profile, ProfileConfig, ProfileManager};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
@@ -41,6 +44,14 @@ async fn main() -> anyhow::Result<()> {
     
     tracing::info!("Device manager initialized successfully");
     
+    // Settings from the default profile, or the built-in defaults without one
+    let startup_profile = ProfileManager::new(ProfileConfig::default())
+        .and_then(|profiles| profiles.load_profile(profile::DEFAULT_PROFILE))
+        .unwrap_or_else(|e| {
+            tracing::info!("Using default settings: {}", e);
+            Profile::default()
+        });
+    
     // Phase 3: UI Setup
     performance_monitor.end_startup_phase().await;
     performance_monitor.begin_startup_phase("ui_setup", "Configuring GUI application and viewport").await;
@@ -66,7 +77,9 @@ async fn main() -> anyhow::Result<()> {
         "Multi-Controller App",
         native_options,
         Box::new(move |_cc| {
-            Ok(Box::new(ui::MultiControllerApp::new(device_manager_clone)))
+            let mut app = ui::MultiControllerApp::new(device_manager_clone);
+            app.apply_device_settings(&startup_profile.device);
+            Ok(Box::new(app))
         }),
     ).map_err(|e| anyhow::anyhow!("Failed to launch GUI: {}", e))?;
     
@@ -91,8 +104,11 @@ async fn main() -> anyhow::Result<()> {
     
     tracing::info!("Shutting down...");
     
-    // Trigger emergency stop on shutdown
-    device_manager.emergency_stop("Application shutdown".to_string()).await;
+    // Safe state, streams, flush, sessions, transports - in that order
+    let report = device_manager.shutdown().await;
+    if !report.clean() {
+        tracing::warn!("Shutdown finished with timed-out stages: {:?}", report.stages);
+    }
     
     Ok(())
 }
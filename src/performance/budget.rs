@@ -23,6 +23,14 @@ pub struct ResourceBudget {
     
     /// Critical threshold as percentage of max (0-1)
     pub critical_threshold: f32,
+    
+    /// Maximum transports open at once across all types (0 = unlimited)
+    #[serde(default = "default_max_open_transports")]
+    pub max_open_transports: usize,
+}
+
+fn default_max_open_transports() -> usize {
+    32
 }
 
 impl Default for ResourceBudget {
@@ -34,6 +42,7 @@ impl Default for ResourceBudget {
             max_startup_ms: 2000,            // Task 17 requirement
             warning_threshold: 0.8,          // Warn at 80%
             critical_threshold: 0.95,        // Critical at 95%
+            max_open_transports: default_max_open_transports(),
         }
     }
 }
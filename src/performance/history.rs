@@ -0,0 +1,193 @@
+// Bounded history of performance samples and alerts for export
+
+use super::monitor::PerformanceAlert;
+
+use serde::{Serialize, Deserialize};
+use std::collections::VecDeque;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Alerts kept in history regardless of the sample capacity
+pub const MAX_ALERT_HISTORY: usize = 256;
+
+/// History export formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HistoryFormat {
+    /// JSON document with separate sample and alert lists
+    Json,
+    /// One CSV row per sample or alert, in time order
+    Csv,
+}
+
+/// One recorded resource reading
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PerformanceSample {
+    pub timestamp_ms: u64,
+    pub cpu_percent: f32,
+    pub memory_mb: f64,
+    
+    /// Most recent device round-trip latency, if any was reported
+    pub latency_ms: Option<f64>,
+}
+
+/// An alert with the time it was raised
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertRecord {
+    pub timestamp_ms: u64,
+    pub alert: PerformanceAlert,
+}
+
+/// Ring of recent samples and alerts; the oldest entries are dropped when full
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PerformanceHistory {
+    capacity: usize,
+    samples: VecDeque<PerformanceSample>,
+    alerts: VecDeque<AlertRecord>,
+    
+    /// Latency attached to the next samples
+    #[serde(skip)]
+    latest_latency_ms: Option<f64>,
+}
+
+impl PerformanceHistory {
+    /// History holding at most `capacity` samples
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            capacity,
+            samples: VecDeque::with_capacity(capacity.min(4096)),
+            alerts: VecDeque::new(),
+            latest_latency_ms: None,
+        }
+    }
+    
+    /// Change the sample capacity, dropping the oldest samples if needed
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity.max(1);
+        while self.samples.len() > self.capacity {
+            self.samples.pop_front();
+        }
+    }
+    
+    /// Report a latency measurement to attach to subsequent samples
+    pub fn set_latency(&mut self, latency_ms: f64) {
+        self.latest_latency_ms = Some(latency_ms);
+    }
+    
+    /// Record a CPU/memory reading stamped with the current time
+    pub fn record_sample(&mut self, memory_mb: f64, cpu_percent: f32) {
+        if self.samples.len() >= self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(PerformanceSample {
+            timestamp_ms: now_ms(),
+            cpu_percent,
+            memory_mb,
+            latency_ms: self.latest_latency_ms,
+        });
+    }
+    
+    /// Record an alert stamped with the current time
+    pub fn record_alert(&mut self, alert: PerformanceAlert) {
+        if self.alerts.len() >= MAX_ALERT_HISTORY {
+            self.alerts.pop_front();
+        }
+        self.alerts.push_back(AlertRecord {
+            timestamp_ms: now_ms(),
+            alert,
+        });
+    }
+    
+    pub fn samples(&self) -> &VecDeque<PerformanceSample> {
+        &self.samples
+    }
+    
+    pub fn alerts(&self) -> &VecDeque<AlertRecord> {
+        &self.alerts
+    }
+    
+    /// Serialize the history in `format`
+    pub fn export(&self, format: HistoryFormat) -> Vec<u8> {
+        match format {
+            HistoryFormat::Json => self.export_json(),
+            HistoryFormat::Csv => self.export_csv(),
+        }
+    }
+    
+    fn export_json(&self) -> Vec<u8> {
+        let export_data = serde_json::json!({
+            "exported_at_ms": now_ms(),
+            "samples": self.samples,
+            "alerts": self.alerts,
+        });
+        
+        serde_json::to_vec_pretty(&export_data).unwrap_or_default()
+    }
+    
+    fn export_csv(&self) -> Vec<u8> {
+        let mut rows: Vec<(u64, [String; 5])> = Vec::with_capacity(self.samples.len() + self.alerts.len());
+        for sample in &self.samples {
+            rows.push((sample.timestamp_ms, [
+                "sample".to_string(),
+                format!("{:.2}", sample.cpu_percent),
+                format!("{:.2}", sample.memory_mb),
+                sample.latency_ms.map(|l| format!("{:.2}", l)).unwrap_or_default(),
+                String::new(),
+            ]));
+        }
+        for record in &self.alerts {
+            rows.push((record.timestamp_ms, [
+                "alert".to_string(),
+                String::new(),
+                String::new(),
+                String::new(),
+                record.alert.to_log_message(),
+            ]));
+        }
+        // Stable sort keeps a sample ahead of an alert raised in the same millisecond
+        rows.sort_by_key(|(timestamp, _)| *timestamp);
+        
+        let mut wtr = csv::Writer::from_writer(Vec::new());
+        let _ = wtr.write_record(["timestamp_ms", "kind", "cpu_percent", "memory_mb", "latency_ms", "alert"]);
+        for (timestamp, fields) in &rows {
+            let timestamp = timestamp.to_string();
+            let _ = wtr.write_record(std::iter::once(&timestamp).chain(fields.iter()));
+        }
+        wtr.into_inner().unwrap_or_default()
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::performance::AlertSeverity;
+    
+    #[test]
+    fn test_history_is_bounded() {
+        let mut history = PerformanceHistory::new(3);
+        for i in 0..5 {
+            history.record_sample(100.0 + i as f64, 1.0);
+        }
+        
+        assert_eq!(history.samples().len(), 3);
+        assert_eq!(history.samples().front().unwrap().memory_mb, 102.0);
+        
+        history.set_capacity(1);
+        assert_eq!(history.samples().len(), 1);
+        assert_eq!(history.samples().front().unwrap().memory_mb, 104.0);
+        
+        for _ in 0..MAX_ALERT_HISTORY + 10 {
+            history.record_alert(PerformanceAlert::Generic {
+                message: "spike".to_string(),
+                severity: AlertSeverity::Info,
+            });
+        }
+        assert_eq!(history.alerts().len(), MAX_ALERT_HISTORY);
+    }
+}
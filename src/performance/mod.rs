@@ -5,9 +5,11 @@ pub mod metrics;
 pub mod budget;
 pub mod startup;
 pub mod profiler;
+pub mod history;
 
 pub use monitor::{PerformanceMonitor, MonitorConfig, PerformanceAlert, AlertSeverity};
 pub use metrics::{SystemMetrics, ProcessMetrics, ResourceUsage};
 pub use budget::{ResourceBudget, BudgetEnforcer, BudgetViolation};
 pub use startup::{StartupTracker, StartupPhase, StartupReport, PhaseTimings, tracker};
-pub use profiler::{Profiler, profiler, FlameGraph, FunctionStats};
\ No newline at end of file
+pub use profiler::{Profiler, profiler, FlameGraph, FunctionStats};
+pub use history::{PerformanceHistory, PerformanceSample, AlertRecord, HistoryFormat};
\ No newline at end of file
@@ -3,8 +3,10 @@
 use super::{
     metrics::{SystemMetrics, ProcessMetrics, ResourceUsage},
     budget::{ResourceBudget, BudgetEnforcer, BudgetViolation},
+    history::{PerformanceHistory, HistoryFormat},
 };
 use crate::logging::{LogLevel, LoggingSystem};
+use crate::transport::ConnectionBudget;
 
 use sysinfo::{System, Pid};
 
@@ -237,6 +239,7 @@ pub struct PerformanceMonitor {
     system_metrics: Arc<RwLock<SystemMetrics>>,
     process_metrics: Arc<RwLock<ProcessMetrics>>,
     resource_usage: Arc<RwLock<ResourceUsage>>,
+    history: Arc<RwLock<PerformanceHistory>>,
     budget_enforcer: Arc<RwLock<BudgetEnforcer>>,
     startup_validator: Arc<RwLock<StartupValidator>>,
     startup_time: Arc<RwLock<Option<Instant>>>,
@@ -250,9 +253,11 @@ impl PerformanceMonitor {
     /// Create a new performance monitor
     pub fn new(config: MonitorConfig) -> Self {
         let pid = std::process::id();
+        ConnectionBudget::global().apply(&config.budget);
         
         Self {
             budget_enforcer: Arc::new(RwLock::new(BudgetEnforcer::new(config.budget.clone()))),
+            history: Arc::new(RwLock::new(PerformanceHistory::new(config.max_samples))),
             config: Arc::new(RwLock::new(config)),
             system_metrics: Arc::new(RwLock::new(SystemMetrics::new())),
             process_metrics: Arc::new(RwLock::new(ProcessMetrics::new(pid))),
@@ -358,6 +363,7 @@ impl PerformanceMonitor {
         let system_metrics = self.system_metrics.clone();
         let process_metrics = self.process_metrics.clone();
         let resource_usage = self.resource_usage.clone();
+        let history = self.history.clone();
         let budget_enforcer = self.budget_enforcer.clone();
         let alert_callbacks = self.alert_callbacks.clone();
         let last_alert_time = self.last_alert_time.clone();
@@ -391,6 +397,13 @@ impl PerformanceMonitor {
                     let mut usage = resource_usage.write().await;
                     usage.add_sample(proc.memory_mb(), proc.cpu_percent);
                     usage.trim_to_size(config.max_samples);
+                    drop(usage);
+                    
+                    {
+                        let mut history = history.write().await;
+                        history.set_capacity(config.max_samples);
+                        history.record_sample(proc.memory_mb(), proc.cpu_percent);
+                    }
                     
                     // Check budget
                     let mut enforcer = budget_enforcer.write().await;
@@ -412,6 +425,7 @@ impl PerformanceMonitor {
                             
                             for violation in violations {
                                 let alert = Self::violation_to_alert(&violation);
+                                history.write().await.record_alert(alert.clone());
                                 
                                 // Log to system
                                 if let Some(ref logging) = logging_system {
@@ -613,6 +627,8 @@ impl PerformanceMonitor {
     
     /// Send alert through all channels
     async fn send_alert(&self, alert: PerformanceAlert) {
+        self.history.write().await.record_alert(alert.clone());
+        
         // Log the alert
         if let Some(ref logging) = self.logging_system {
             let level = match &alert {
@@ -655,6 +671,26 @@ impl PerformanceMonitor {
         self.resource_usage.read().await.clone()
     }
     
+    /// Report a device round-trip latency; it is attached to subsequent history samples
+    pub async fn record_latency(&self, latency: Duration) {
+        self.history.write().await.set_latency(latency.as_secs_f64() * 1000.0);
+    }
+    
+    /// Add a CPU/memory reading to the history
+    pub async fn record_sample(&self, memory_mb: f64, cpu_percent: f32) {
+        self.history.write().await.record_sample(memory_mb, cpu_percent);
+    }
+    
+    /// Recorded samples and alerts (bounded by `max_samples`)
+    pub async fn history(&self) -> PerformanceHistory {
+        self.history.read().await.clone()
+    }
+    
+    /// Export the recorded samples and alerts for offline analysis
+    pub async fn export_history(&self, format: HistoryFormat) -> Vec<u8> {
+        self.history.read().await.export(format)
+    }
+    
     /// Get budget violations
     pub async fn violations(&self) -> Vec<BudgetViolation> {
         let enforcer = self.budget_enforcer.read().await;
@@ -765,4 +801,34 @@ mod tests {
         assert_eq!(usage.peak_memory_mb, 110.0);
         assert!((usage.avg_memory_mb - 105.0).abs() < 0.01);
     }
-}
\ No newline at end of file
+    
+    #[tokio::test]
+    async fn test_export_history_contains_samples_and_alerts() {
+        let monitor = PerformanceMonitor::default();
+        monitor.record_latency(Duration::from_millis(12)).await;
+        monitor.record_sample(120.5, 3.25).await;
+        monitor.record_sample(130.0, 4.5).await;
+        monitor.send_alert(PerformanceAlert::Memory {
+            current_mb: 130.0,
+            limit_mb: 128.0,
+            severity: AlertSeverity::Warning,
+        }).await;
+        
+        let json: serde_json::Value = serde_json::from_slice(&monitor.export_history(HistoryFormat::Json).await).unwrap();
+        let samples = json["samples"].as_array().unwrap();
+        assert_eq!(samples.len(), 2);
+        assert_eq!(samples[0]["memory_mb"], 120.5);
+        assert_eq!(samples[1]["latency_ms"], 12.0);
+        let alerts = json["alerts"].as_array().unwrap();
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0]["alert"]["Memory"]["limit_mb"], 128.0);
+        
+        let csv = String::from_utf8(monitor.export_history(HistoryFormat::Csv).await).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "timestamp_ms,kind,cpu_percent,memory_mb,latency_ms,alert");
+        assert_eq!(lines.len(), 4);
+        assert!(lines[1].ends_with(",sample,3.25,120.50,12.00,"));
+        assert!(lines[3].contains(",alert,,,,"));
+        assert!(lines[3].contains("Memory usage: 130.0 MB / 128.0 MB limit"));
+    }
+}
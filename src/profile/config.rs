@@ -2,6 +2,13 @@
 use serde::{Serialize, Deserialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
+use crate::transport::common::{SerialSettings, DataBits, StopBits, Parity, FlowControl};
+use crate::transport::serial::{ComPortRange, DiscoveryFilter};
+use crate::transport::discovery_debounce::DEFAULT_DISCOVERY_DEBOUNCE;
+use crate::device::SessionCommand;
+use crate::device::calibration::LinearCalibration;
+use crate::device::identify::IdentifyPattern;
+use crate::device::command_failures::DEFAULT_FAILURE_ALERT_THRESHOLD;
 
 /// Main profile structure containing all settings
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -76,9 +83,134 @@ pub struct DeviceSettings {
     pub scan_interval_ms: u32,
     pub reconnect_attempts: u32,
     pub reconnect_delay_ms: u32,
+    /// Timeout for UI-dispatched device commands
+    #[serde(default = "default_command_timeout_ms")]
+    pub command_timeout_ms: u32,
+    /// Named serial settings presets offered when configuring a device
+    #[serde(default = "SerialPreset::builtin")]
+    pub serial_presets: Vec<SerialPreset>,
+    /// Which serial ports discovery lists
+    #[serde(default)]
+    pub discovery_filter: DiscoveryFilter,
+    /// How long a device must stay present (or absent) before the device list changes
+    #[serde(default = "default_discovery_debounce_ms")]
+    pub discovery_debounce_ms: u32,
+    /// COM ports probed on Windows for ports enumeration misses (None = don't probe)
+    #[serde(default)]
+    pub com_port_fallback: Option<ComPortRange>,
+    /// Commands failing in a row before the user is alerted (0 = never)
+    #[serde(default = "default_failure_alert_threshold")]
+    pub failure_alert_threshold: u32,
     pub device_configs: Vec<DeviceConfig>,
 }
 
+/// Default command timeout (ms) used when a profile doesn't specify one
+pub const DEFAULT_COMMAND_TIMEOUT_MS: u32 = 5000;
+
+fn default_command_timeout_ms() -> u32 {
+    DEFAULT_COMMAND_TIMEOUT_MS
+}
+
+fn default_discovery_debounce_ms() -> u32 {
+    DEFAULT_DISCOVERY_DEBOUNCE.as_millis() as u32
+}
+
+fn default_failure_alert_threshold() -> u32 {
+    DEFAULT_FAILURE_ALERT_THRESHOLD
+}
+
+/// Baud rates offered in the device configuration dialog
+pub const COMMON_BAUD_RATES: &[u32] = &[
+    300, 1200, 2400, 4800, 9600, 19200, 38400, 57600, 115200, 230400, 250000, 460800, 921600,
+];
+
+/// Named serial line settings (e.g. "Arduino 115200 8N1")
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SerialPreset {
+    pub name: String,
+    pub baud_rate: u32,
+    pub data_bits: DataBits,
+    pub parity: Parity,
+    pub stop_bits: StopBits,
+    #[serde(default = "default_flow_control")]
+    pub flow_control: FlowControl,
+}
+
+fn default_flow_control() -> FlowControl {
+    FlowControl::None
+}
+
+impl SerialPreset {
+    /// Create an 8N1 preset with no flow control
+    pub fn new_8n1(name: &str, baud_rate: u32) -> Self {
+        Self {
+            name: name.to_string(),
+            baud_rate,
+            data_bits: DataBits::Eight,
+            parity: Parity::None,
+            stop_bits: StopBits::One,
+            flow_control: FlowControl::None,
+        }
+    }
+    
+    /// Presets shipped with the app
+    pub fn builtin() -> Vec<SerialPreset> {
+        vec![
+            SerialPreset::new_8n1("Arduino 115200 8N1", 115200),
+            SerialPreset::new_8n1("Arduino 9600 8N1", 9600),
+            SerialPreset::new_8n1("GPS 9600 8N1", 9600),
+            SerialPreset::new_8n1("ESP32 921600 8N1", 921600),
+        ]
+    }
+    
+    /// Copy the preset's line settings into `settings`, leaving other fields untouched
+    pub fn apply_to(&self, settings: &mut SerialSettings) {
+        settings.baud_rate = self.baud_rate;
+        settings.data_bits = self.data_bits;
+        settings.parity = self.parity;
+        settings.stop_bits = self.stop_bits;
+        settings.flow_control = self.flow_control;
+    }
+    
+    /// Serial settings with this preset applied to the defaults
+    pub fn to_settings(&self) -> SerialSettings {
+        let mut settings = SerialSettings::default();
+        self.apply_to(&mut settings);
+        settings
+    }
+    
+    /// Whether `settings` still matches this preset (false once edited)
+    pub fn matches(&self, settings: &SerialSettings) -> bool {
+        settings.baud_rate == self.baud_rate
+            && settings.data_bits == self.data_bits
+            && settings.parity == self.parity
+            && settings.stop_bits == self.stop_bits
+            && settings.flow_control == self.flow_control
+    }
+    
+    /// Short line description, e.g. "115200 8N1"
+    pub fn summary(&self) -> String {
+        let data_bits = match self.data_bits {
+            DataBits::Five => 5,
+            DataBits::Six => 6,
+            DataBits::Seven => 7,
+            DataBits::Eight => 8,
+        };
+        let parity = match self.parity {
+            Parity::None => 'N',
+            Parity::Odd => 'O',
+            Parity::Even => 'E',
+            Parity::Mark => 'M',
+            Parity::Space => 'S',
+        };
+        let stop_bits = match self.stop_bits {
+            StopBits::One => 1,
+            StopBits::Two => 2,
+        };
+        format!("{} {}{}{}", self.baud_rate, data_bits, parity, stop_bits)
+    }
+}
+
 /// Individual device configuration
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct DeviceConfig {
@@ -87,6 +219,15 @@ pub struct DeviceConfig {
     pub transport: String,
     pub address: String,
     pub settings: HashMap<String, toml::Value>,
+    /// Commands run right after connecting, e.g. zero PWM outputs and center servos
+    #[serde(default)]
+    pub on_connect_commands: Vec<SessionCommand>,
+    /// Input calibrations keyed by `calibration_key`, e.g. `analogRead:0`
+    #[serde(default)]
+    pub calibrations: HashMap<String, LinearCalibration>,
+    /// LED pin and blink pattern used to locate the board
+    #[serde(default)]
+    pub identify: IdentifyPattern,
 }
 
 /// Telemetry settings
@@ -199,6 +340,12 @@ impl Default for Profile {
                 scan_interval_ms: 5000,
                 reconnect_attempts: 3,
                 reconnect_delay_ms: 1000,
+                command_timeout_ms: DEFAULT_COMMAND_TIMEOUT_MS,
+                serial_presets: SerialPreset::builtin(),
+                discovery_filter: DiscoveryFilter::ShowAll,
+                discovery_debounce_ms: default_discovery_debounce_ms(),
+                com_port_fallback: None,
+                failure_alert_threshold: DEFAULT_FAILURE_ALERT_THRESHOLD,
                 device_configs: vec![],
             },
             telemetry: TelemetrySettings {
@@ -259,6 +406,7 @@ mod tests {
         let profile = Profile::default();
         assert_eq!(profile.metadata.name, "default");
         assert_eq!(profile.ui.chart_settings.update_interval_ms, 33);
+        assert_eq!(profile.device.command_timeout_ms, DEFAULT_COMMAND_TIMEOUT_MS);
     }
 
     #[test]
@@ -268,4 +416,40 @@ mod tests {
         let deserialized: Profile = toml::from_str(&toml_str).unwrap();
         assert_eq!(profile, deserialized);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_serial_preset_applies_line_settings() {
+        let preset = SerialPreset {
+            name: "Legacy 7E2".to_string(),
+            baud_rate: 4800,
+            data_bits: DataBits::Seven,
+            parity: Parity::Even,
+            stop_bits: StopBits::Two,
+            flow_control: FlowControl::None,
+        };
+        
+        let mut settings = SerialSettings::default();
+        settings.report_line_errors = false;
+        preset.apply_to(&mut settings);
+        
+        assert_eq!(settings.baud_rate, 4800);
+        assert_eq!(settings.data_bits, DataBits::Seven);
+        assert_eq!(settings.parity, Parity::Even);
+        assert_eq!(settings.stop_bits, StopBits::Two);
+        assert!(!settings.report_line_errors, "non-line settings are preserved");
+        assert!(preset.matches(&settings));
+        assert_eq!(preset.summary(), "4800 7E2");
+    }
+
+    #[test]
+    fn test_serial_preset_custom_override() {
+        let preset = SerialPreset::new_8n1("GPS 9600 8N1", 9600);
+        let mut settings = preset.to_settings();
+        
+        settings.baud_rate = 38400;
+        
+        assert_eq!(settings.baud_rate, 38400);
+        assert_eq!(settings.data_bits, DataBits::Eight);
+        assert!(!preset.matches(&settings));
+    }
+}
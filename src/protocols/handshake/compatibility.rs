@@ -0,0 +1,168 @@
+//! Version Compatibility Checking
+//!
+//! Parses the semantic versions reported during the handshake, checks them
+//! against client-side minimums (Task 28.4) and resolves the protocol version
+//! both sides can speak. Protocol versions are backward compatible within a
+//! major version, so a side supporting 1.3.0 can also speak 1.1.0.
+
+use serde::{Serialize, Deserialize};
+use std::fmt;
+use std::str::FromStr;
+
+use super::schema::{IdentifyResponse, ValidationError};
+use super::{HandshakeError, HandshakeResult};
+
+/// Semantic version (major.minor.patch) with ordering for minimum checks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Version {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl Version {
+    /// Create a version from its components
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Version { major, minor, patch }
+    }
+    
+    /// Parse a `major.minor.patch` string
+    pub fn parse(version: &str) -> Result<Self, ValidationError> {
+        let invalid = || ValidationError::InvalidSemver {
+            field: "version".to_string(),
+            version: version.to_string(),
+        };
+        
+        let parts: Vec<&str> = version.trim().split('.').collect();
+        if parts.len() != 3 {
+            return Err(invalid());
+        }
+        
+        let major = parts[0].parse::<u32>().map_err(|_| invalid())?;
+        let minor = parts[1].parse::<u32>().map_err(|_| invalid())?;
+        let patch = parts[2].parse::<u32>().map_err(|_| invalid())?;
+        
+        Ok(Version { major, minor, patch })
+    }
+    
+    /// Whether a side supporting `self` can also speak `other` (same major, not newer)
+    pub fn can_speak(&self, other: &Version) -> bool {
+        self.major == other.major && *other <= *self
+    }
+}
+
+impl FromStr for Version {
+    type Err = ValidationError;
+    
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Version::parse(s)
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Check the handshake-reported firmware version against a required minimum
+pub fn check_minimum_firmware(response: &IdentifyResponse, minimum: &Version) -> HandshakeResult<()> {
+    let reported = Version::parse(&response.firmware_version)?;
+    
+    if reported < *minimum {
+        return Err(HandshakeError::IncompatibleFirmware {
+            device_type: response.device_type.clone(),
+            device_version: response.firmware_version.clone(),
+            minimum_required: minimum.to_string(),
+        });
+    }
+    
+    Ok(())
+}
+
+/// Protocol version settled on by VERSION negotiation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NegotiatedVersion {
+    /// Version the session continues at
+    pub version: Version,
+    /// Version the client asked for first
+    pub preferred: Version,
+}
+
+impl NegotiatedVersion {
+    /// Whether the session settled below the client's preferred version
+    pub fn is_downgrade(&self) -> bool {
+        self.version < self.preferred
+    }
+}
+
+/// Highest protocol version both sides can speak, or `None` if no major version is shared
+pub fn highest_common_version(client: &[Version], device: &[Version]) -> Option<Version> {
+    let newest_in_major = |versions: &[Version], major: u32| {
+        versions.iter().filter(|v| v.major == major).max().copied()
+    };
+    
+    client.iter()
+        .filter_map(|c| {
+            let device_newest = newest_in_major(device, c.major)?;
+            let client_newest = newest_in_major(client, c.major)?;
+            Some(client_newest.min(device_newest))
+        })
+        .max()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocols::handshake::MessageExamples;
+    
+    #[test]
+    fn test_version_parse_and_order() {
+        let v = Version::parse("2.1.0").unwrap();
+        assert_eq!(v, Version::new(2, 1, 0));
+        assert_eq!(v.to_string(), "2.1.0");
+        
+        assert!(Version::new(1, 9, 9) < Version::new(2, 0, 0));
+        assert!(Version::new(2, 10, 0) > Version::new(2, 9, 5));
+        
+        assert!(Version::parse("2.1").is_err());
+        assert!(Version::parse("v2.1.0").is_err());
+    }
+    
+    #[test]
+    fn test_check_minimum_firmware() {
+        // Example response reports firmware 2.1.0
+        let response = MessageExamples::identify_response_success();
+        
+        assert!(check_minimum_firmware(&response, &Version::new(2, 1, 0)).is_ok());
+        assert!(check_minimum_firmware(&response, &Version::new(1, 5, 0)).is_ok());
+        
+        match check_minimum_firmware(&response, &Version::new(3, 0, 0)) {
+            Err(HandshakeError::IncompatibleFirmware { device_version, minimum_required, .. }) => {
+                assert_eq!(device_version, "2.1.0");
+                assert_eq!(minimum_required, "3.0.0");
+            }
+            other => panic!("Expected IncompatibleFirmware, got {:?}", other),
+        }
+    }
+    
+    #[test]
+    fn test_highest_common_version() {
+        let v = Version::new;
+        
+        // Same major: the older of the two sides' newest versions
+        assert_eq!(highest_common_version(&[v(1, 3, 0)], &[v(1, 0, 0), v(1, 1, 0)]), Some(v(1, 1, 0)));
+        assert_eq!(highest_common_version(&[v(1, 1, 0)], &[v(1, 2, 5)]), Some(v(1, 1, 0)));
+        
+        // Highest shared major wins
+        assert_eq!(highest_common_version(&[v(1, 4, 0), v(2, 1, 0)], &[v(1, 2, 0), v(2, 0, 3)]), Some(v(2, 0, 3)));
+        
+        // No shared major
+        assert_eq!(highest_common_version(&[v(1, 3, 0)], &[v(2, 0, 0)]), None);
+        assert_eq!(highest_common_version(&[], &[v(1, 0, 0)]), None);
+        
+        assert!(v(1, 3, 0).can_speak(&v(1, 1, 0)));
+        assert!(!v(1, 1, 0).can_speak(&v(1, 3, 0)));
+        assert!(!v(2, 0, 0).can_speak(&v(1, 9, 0)));
+    }
+}
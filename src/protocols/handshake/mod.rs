@@ -17,10 +17,15 @@
 //! - `schema` - Complete JSON message schema with validation
 //! - Future: `state_machine` - Handshake state management (Task 28.2)
 //! - Future: `timeout` - Timeout enforcement and retry logic (Task 28.3)  
-//! - Future: `compatibility` - Version compatibility checking (Task 28.4)
+//! - `compatibility` - Version parsing, minimum firmware checks (Task 28.4) and common version resolution
+//! - `runner` - IDENTIFY exchange with pluggable challenge/response authentication and version downgrade
+//! - `transcript` - Raw and parsed record of every handshake message for debugging
 //! - Future: `feedback` - User feedback and status reporting (Task 28.5)
 
 pub mod schema;
+pub mod compatibility;
+pub mod runner;
+pub mod transcript;
 
 // Re-export commonly used types for convenience
 pub use schema::{
@@ -44,6 +49,9 @@ pub use schema::{
     MAX_CAPABILITIES,
     MAX_PARAMETERS,
 };
+pub use compatibility::{Version, NegotiatedVersion, check_minimum_firmware, highest_common_version};
+pub use runner::{HandshakeRunner, HandshakeOutcome, Authenticator, HmacAuthenticator, AUTH_CHALLENGE_PARAM};
+pub use transcript::{HandshakeTranscript, HandshakeFailure, TranscriptEntry, TranscriptDirection};
 
 /// Handshake protocol result type
 pub type HandshakeResult<T> = Result<T, HandshakeError>;
@@ -157,6 +165,9 @@ impl HandshakeError {
             HandshakeError::Session { message } => {
                 format!("Session error: {}", message)
             }
+            HandshakeError::Validation(err) => {
+                format!("Device sent an invalid handshake message. {}", err.user_friendly_message())
+            }
             _ => {
                 format!("Handshake failed: {}", self)
             }
@@ -231,4 +242,16 @@ mod tests {
         assert!(incompatible_msg.contains("not supported"));
         assert!(incompatible_msg.contains("0.5.0"));
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_validation_message_names_field() {
+        let msg = HandshakeError::from(ValidationError::StringTooLong {
+            field: "device_type".to_string(),
+            length: 2000,
+            max: MAX_STRING_LENGTH,
+        }).user_friendly_message();
+        assert!(msg.contains("invalid handshake message"));
+        assert!(msg.contains("'device_type'"));
+        assert!(msg.contains(&MAX_STRING_LENGTH.to_string()));
+    }
+}
@@ -0,0 +1,488 @@
+//! Handshake Runner
+//!
+//! Drives the IDENTIFY exchange over a transport as newline-delimited JSON and,
+//! when the device issues an authentication challenge in the IDENTIFY response
+//! (`custom_params["auth_challenge"]`), answers it through a pluggable
+//! `Authenticator` before the session is considered established. If the client
+//! is configured with protocol versions, a VERSION exchange follows; a device
+//! that only speaks an older version of the same major is negotiated down to
+//! the highest common version. Every message is recorded in a
+//! `HandshakeTranscript` for debugging failed negotiations.
+
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+use super::compatibility::{highest_common_version, NegotiatedVersion, Version};
+use super::schema::{HandshakeMessage, IdentifyCommand, IdentifyResponse, VersionRequest, VersionResponse};
+use super::transcript::{HandshakeFailure, HandshakeTranscript};
+use super::{HandshakeError, HandshakeResult};
+use crate::transport::{CommandCodec, Transport, TransportError};
+
+/// Key in `IdentifyResponse.custom_params` carrying the device's challenge
+pub const AUTH_CHALLENGE_PARAM: &str = "auth_challenge";
+
+/// Default time allowed for each handshake reply
+pub const DEFAULT_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Computes the client's answer to a device authentication challenge
+pub trait Authenticator: Send + Sync {
+    /// Response to send back for `challenge`
+    fn respond(&self, challenge: &str) -> HandshakeResult<String>;
+}
+
+/// Answers challenges with a hex-encoded HMAC-SHA256 over a shared secret
+pub struct HmacAuthenticator {
+    secret: Vec<u8>,
+}
+
+impl HmacAuthenticator {
+    pub fn new(secret: impl Into<Vec<u8>>) -> Self {
+        Self { secret: secret.into() }
+    }
+    
+    /// Hex-encoded HMAC-SHA256 of `challenge`
+    pub fn sign(&self, challenge: &str) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.secret)
+            .expect("HMAC accepts keys of any length");
+        mac.update(challenge.as_bytes());
+        mac.finalize()
+            .into_bytes()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+}
+
+impl Authenticator for HmacAuthenticator {
+    fn respond(&self, challenge: &str) -> HandshakeResult<String> {
+        Ok(self.sign(challenge))
+    }
+}
+
+/// AUTH command carrying the client's challenge response
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AuthCommand {
+    /// Command type - always "AUTH"
+    pub command: String,
+    
+    /// Session ID from the IDENTIFY command
+    pub session_id: Uuid,
+    
+    /// Response computed from the device's challenge
+    pub response: String,
+}
+
+/// Device verdict on an AUTH command
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AuthResponse {
+    /// "OK" if the response was accepted, "ERROR" otherwise
+    pub status: String,
+    
+    /// Reason for rejection
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_message: Option<String>,
+}
+
+/// Result of a completed handshake
+#[derive(Debug, Clone)]
+pub struct HandshakeOutcome {
+    pub response: IdentifyResponse,
+    
+    /// Protocol version agreed by VERSION negotiation (`None` if not negotiated)
+    pub negotiated: Option<NegotiatedVersion>,
+}
+
+/// Runs the handshake against a device over a transport
+pub struct HandshakeRunner {
+    codec: CommandCodec,
+    authenticator: Option<Arc<dyn Authenticator>>,
+    protocol_versions: Vec<Version>,
+}
+
+impl HandshakeRunner {
+    pub fn new(transport: Arc<dyn Transport>) -> Self {
+        Self {
+            codec: CommandCodec::new(transport).with_timeout(DEFAULT_HANDSHAKE_TIMEOUT),
+            authenticator: None,
+            protocol_versions: Vec::new(),
+        }
+    }
+    
+    /// Time allowed for each device reply
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.codec = self.codec.with_timeout(timeout);
+        self
+    }
+    
+    /// Answer authentication challenges with `authenticator`
+    pub fn with_authenticator(mut self, authenticator: Arc<dyn Authenticator>) -> Self {
+        self.authenticator = Some(authenticator);
+        self
+    }
+    
+    /// Negotiate the protocol version after IDENTIFY, preferring the highest of `versions`
+    pub fn with_protocol_versions(mut self, versions: Vec<Version>) -> Self {
+        self.protocol_versions = versions;
+        self
+    }
+    
+    /// Send IDENTIFY, check the device accepted the session, then authenticate if challenged
+    pub async fn run(&self, identify: &IdentifyCommand) -> HandshakeResult<IdentifyResponse> {
+        self.establish(identify).await.map(|outcome| outcome.response)
+    }
+    
+    /// Like `run`, also returning the negotiated protocol version
+    pub async fn establish(&self, identify: &IdentifyCommand) -> HandshakeResult<HandshakeOutcome> {
+        self.run_with_transcript(identify).await
+            .map(|(outcome, _)| outcome)
+            .map_err(|failure| failure.error)
+    }
+    
+    /// Like `establish`, also returning every message exchanged; on failure the
+    /// transcript holds the exchange up to the point it broke
+    pub async fn run_with_transcript(
+        &self,
+        identify: &IdentifyCommand,
+    ) -> Result<(HandshakeOutcome, HandshakeTranscript), HandshakeFailure> {
+        let mut transcript = HandshakeTranscript::new();
+        match self.handshake(identify, &mut transcript).await {
+            Ok(outcome) => Ok((outcome, transcript)),
+            Err(error) => {
+                tracing::debug!("Handshake failed: {}\n{}", error, transcript);
+                Err(HandshakeFailure { error, transcript })
+            }
+        }
+    }
+    
+    async fn handshake(&self, identify: &IdentifyCommand, transcript: &mut HandshakeTranscript) -> HandshakeResult<HandshakeOutcome> {
+        identify.validate()?;
+        
+        let response: IdentifyResponse = self.exchange(identify, transcript).await?;
+        response.validate()?;
+        
+        if response.status != "OK" || !response.session_accepted {
+            return Err(HandshakeError::DeviceRejection {
+                reason: response.error_message.clone().unwrap_or_else(|| "session not accepted".to_string()),
+            });
+        }
+        
+        if let Some(challenge) = response.custom_params.get(AUTH_CHALLENGE_PARAM) {
+            let challenge = challenge.as_str()
+                .ok_or_else(|| HandshakeError::malformed_response("auth challenge is not a string"))?;
+            self.authenticate(identify.session_id, challenge, transcript).await?;
+        }
+        
+        let negotiated = if self.protocol_versions.is_empty() {
+            None
+        } else {
+            Some(self.negotiate_version(identify.session_id, transcript).await?)
+        };
+        
+        Ok(HandshakeOutcome { response, negotiated })
+    }
+    
+    /// Agree on a protocol version, stepping down to the highest common one if
+    /// the device refuses the preferred version; fails only when no major is shared
+    async fn negotiate_version(&self, session_id: Uuid, transcript: &mut HandshakeTranscript) -> HandshakeResult<NegotiatedVersion> {
+        let mut offered = self.protocol_versions.clone();
+        offered.sort_by(|a, b| b.cmp(a));
+        let preferred = offered[0];
+        
+        let response = self.request_version(session_id, preferred, &offered, transcript).await?;
+        if let Some(version) = self.accepted_version(&response)? {
+            return Ok(NegotiatedVersion { version, preferred });
+        }
+        
+        let device_versions = response.supported_versions.iter()
+            .map(|v| Version::parse(v))
+            .collect::<Result<Vec<_>, _>>()?;
+        let incompatible = || HandshakeError::IncompatibleProtocol {
+            device_version: response.supported_versions.join(", "),
+            client_versions: offered.iter().map(Version::to_string).collect(),
+        };
+        
+        let fallback = highest_common_version(&offered, &device_versions).ok_or_else(incompatible)?;
+        if fallback == preferred {
+            // The device refused a version it claims to support
+            return Err(incompatible());
+        }
+        
+        tracing::info!("Device does not support protocol {}, downgrading to {}", preferred, fallback);
+        let response = self.request_version(session_id, fallback, &[fallback], transcript).await?;
+        match self.accepted_version(&response)? {
+            Some(version) if version == fallback => Ok(NegotiatedVersion { version, preferred }),
+            _ => Err(incompatible()),
+        }
+    }
+    
+    async fn request_version(
+        &self,
+        session_id: Uuid,
+        preferred: Version,
+        supported: &[Version],
+        transcript: &mut HandshakeTranscript,
+    ) -> HandshakeResult<VersionResponse> {
+        let request = VersionRequest {
+            command: "VERSION".to_string(),
+            session_id,
+            preferred_version: preferred.to_string(),
+            supported_versions: supported.iter().map(Version::to_string).collect(),
+            timestamp: None,
+        };
+        let response: VersionResponse = self.exchange(&request, transcript).await?;
+        response.validate()?;
+        Ok(response)
+    }
+    
+    /// The version the device agreed to, if it is one the client can speak
+    fn accepted_version(&self, response: &VersionResponse) -> HandshakeResult<Option<Version>> {
+        if response.status != "OK" {
+            return Ok(None);
+        }
+        let version = Version::parse(&response.negotiated_version)?;
+        Ok(self.protocol_versions.iter().any(|v| v.can_speak(&version)).then_some(version))
+    }
+    
+    /// Answer the device's challenge and check it accepted the answer
+    async fn authenticate(&self, session_id: Uuid, challenge: &str, transcript: &mut HandshakeTranscript) -> HandshakeResult<()> {
+        let authenticator = self.authenticator.as_ref().ok_or_else(|| HandshakeError::Session {
+            message: "device requires authentication but no authenticator is configured".to_string(),
+        })?;
+        
+        let command = AuthCommand {
+            command: "AUTH".to_string(),
+            session_id,
+            response: authenticator.respond(challenge)?,
+        };
+        
+        let verdict: AuthResponse = self.exchange(&command, transcript).await?;
+        if verdict.status == "OK" {
+            Ok(())
+        } else {
+            Err(HandshakeError::DeviceRejection {
+                reason: verdict.error_message.unwrap_or_else(|| "authentication failed".to_string()),
+            })
+        }
+    }
+    
+    /// Send one JSON message and parse the JSON line that answers it, recording both
+    async fn exchange<Req, Resp>(&self, request: &Req, transcript: &mut HandshakeTranscript) -> HandshakeResult<Resp>
+    where
+        Req: Serialize,
+        Resp: for<'de> Deserialize<'de>,
+    {
+        let line = serde_json::to_string(request).map_err(HandshakeError::from_json_error)?;
+        transcript.record_sent(&line);
+        self.codec.send_command(&line).await.map_err(HandshakeError::transport)?;
+        
+        let reply = self.codec.read_line().await.map_err(|e| match e {
+            TransportError::Timeout(_) => HandshakeError::Timeout,
+            other => HandshakeError::transport(other),
+        })?;
+        transcript.record_received(&reply);
+        
+        serde_json::from_str(&reply).map_err(|e| HandshakeError::malformed_response(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use crate::protocols::handshake::schema::MessageExamples;
+    use crate::protocols::handshake::transcript::TranscriptDirection;
+    use crate::transport::mock::MockTransport;
+    
+    const SECRET: &[u8] = b"shared-secret";
+    const CHALLENGE: &str = "c7f3a9e1";
+    
+    /// Mock device answering each JSON message with `respond(message)` as a JSON line
+    fn json_device(respond: impl Fn(&serde_json::Value) -> serde_json::Value + Send + Sync + 'static) -> Arc<MockTransport> {
+        Arc::new(MockTransport::scripted(move |data| {
+            let message: serde_json::Value = serde_json::from_slice(data).unwrap();
+            vec![format!("{}\n", respond(&message)).into_bytes()]
+        }))
+    }
+    
+    /// Device that challenges every IDENTIFY and only accepts the correct HMAC
+    fn challenging_device() -> Arc<MockTransport> {
+        json_device(|message| match message["command"].as_str() {
+            Some("IDENTIFY") => {
+                let mut response = MessageExamples::identify_response_success();
+                response.custom_params.insert(AUTH_CHALLENGE_PARAM.to_string(), json!(CHALLENGE));
+                serde_json::to_value(response).unwrap()
+            }
+            Some("AUTH") if message["response"] == json!(HmacAuthenticator::new(SECRET).sign(CHALLENGE)) => {
+                json!({ "status": "OK" })
+            }
+            Some("AUTH") => json!({ "status": "ERROR", "error_message": "invalid auth response" }),
+            _ => json!({ "status": "ERROR", "error_message": "unknown command" }),
+        })
+    }
+    
+    #[test]
+    fn test_hmac_matches_rfc4231_vector() {
+        let authenticator = HmacAuthenticator::new("Jefe");
+        assert_eq!(
+            authenticator.sign("what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+    
+    #[tokio::test]
+    async fn test_correct_hmac_accepted() {
+        let runner = HandshakeRunner::new(challenging_device())
+            .with_authenticator(Arc::new(HmacAuthenticator::new(SECRET)));
+        
+        let response = runner.run(&MessageExamples::identify_command()).await.unwrap();
+        assert_eq!(response.device_type, "Arduino_Uno");
+    }
+    
+    #[tokio::test]
+    async fn test_wrong_hmac_rejected() {
+        let runner = HandshakeRunner::new(challenging_device())
+            .with_authenticator(Arc::new(HmacAuthenticator::new("wrong-secret")));
+        
+        match runner.run(&MessageExamples::identify_command()).await {
+            Err(HandshakeError::DeviceRejection { reason }) => assert_eq!(reason, "invalid auth response"),
+            other => panic!("Expected DeviceRejection, got {:?}", other),
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_challenge_without_authenticator_fails() {
+        let runner = HandshakeRunner::new(challenging_device());
+        
+        let result = runner.run(&MessageExamples::identify_command()).await;
+        assert!(matches!(result, Err(HandshakeError::Session { .. })));
+    }
+    
+    #[tokio::test]
+    async fn test_transcript_records_identify_exchange() {
+        let runner = HandshakeRunner::new(challenging_device())
+            .with_authenticator(Arc::new(HmacAuthenticator::new(SECRET)));
+        
+        let (_, transcript) = runner.run_with_transcript(&MessageExamples::identify_command()).await.unwrap();
+        let directions: Vec<_> = transcript.entries().iter().map(|e| e.direction).collect();
+        assert_eq!(directions, vec![
+            TranscriptDirection::Sent, TranscriptDirection::Received,
+            TranscriptDirection::Sent, TranscriptDirection::Received,
+        ]);
+        
+        let entries = transcript.entries();
+        assert_eq!(entries[0].parsed.as_ref().unwrap()["command"], json!("IDENTIFY"));
+        assert!(entries[0].raw.contains("\"IDENTIFY\""));
+        assert_eq!(entries[1].parsed.as_ref().unwrap()["device_type"], json!("Arduino_Uno"));
+        assert_eq!(entries[1].parsed.as_ref().unwrap()["custom_params"][AUTH_CHALLENGE_PARAM], json!(CHALLENGE));
+        assert_eq!(entries[2].parsed.as_ref().unwrap()["command"], json!("AUTH"));
+        assert_eq!(entries[3].parsed.as_ref().unwrap()["status"], json!("OK"));
+        assert!(transcript.to_string().lines().next().unwrap().contains("-> {"));
+    }
+    
+    #[tokio::test]
+    async fn test_failed_handshake_transcript_stops_at_failure() {
+        // Rejected AUTH: the whole exchange up to the device's refusal
+        let runner = HandshakeRunner::new(challenging_device())
+            .with_authenticator(Arc::new(HmacAuthenticator::new("wrong-secret")));
+        let failure = runner.run_with_transcript(&MessageExamples::identify_command()).await.unwrap_err();
+        assert!(matches!(failure.error, HandshakeError::DeviceRejection { .. }));
+        assert_eq!(failure.transcript.len(), 4);
+        let last = failure.transcript.last().unwrap();
+        assert_eq!(last.direction, TranscriptDirection::Received);
+        assert_eq!(last.parsed.as_ref().unwrap()["error_message"], json!("invalid auth response"));
+        
+        // No authenticator: fails right after the challenge, before any AUTH is sent
+        let runner = HandshakeRunner::new(challenging_device());
+        let failure = runner.run_with_transcript(&MessageExamples::identify_command()).await.unwrap_err();
+        assert!(matches!(failure.error, HandshakeError::Session { .. }));
+        assert_eq!(failure.transcript.len(), 2);
+        assert_eq!(failure.transcript.entries()[0].parsed.as_ref().unwrap()["command"], json!("IDENTIFY"));
+        assert_eq!(failure.transcript.last().unwrap().direction, TranscriptDirection::Received);
+    }
+    
+    /// Device speaking only protocol versions in `supported`; accepts a VERSION
+    /// request whose preferred version it lists and refuses any other
+    fn versioned_device(supported: Vec<&'static str>) -> Arc<MockTransport> {
+        json_device(move |message| match message["command"].as_str() {
+            Some("IDENTIFY") => serde_json::to_value(MessageExamples::identify_response_success()).unwrap(),
+            Some("VERSION") => {
+                let preferred = message["preferred_version"].as_str().unwrap();
+                if supported.contains(&preferred) {
+                    json!({
+                        "status": "OK",
+                        "session_id": message["session_id"],
+                        "negotiated_version": preferred,
+                        "supported_versions": supported,
+                    })
+                } else {
+                    json!({
+                        "status": "ERROR",
+                        "session_id": message["session_id"],
+                        "negotiated_version": "",
+                        "supported_versions": supported,
+                        "error_message": format!("unsupported protocol version {}", preferred),
+                    })
+                }
+            }
+            _ => json!({ "status": "ERROR", "error_message": "unknown command" }),
+        })
+    }
+    
+    #[tokio::test]
+    async fn test_older_compatible_minor_negotiated_down() {
+        let runner = HandshakeRunner::new(versioned_device(vec!["1.1.0", "1.0.0"]))
+            .with_protocol_versions(vec![Version::new(1, 3, 0), Version::new(1, 2, 0)]);
+        
+        let (outcome, transcript) = runner.run_with_transcript(&MessageExamples::identify_command()).await.unwrap();
+        let negotiated = outcome.negotiated.unwrap();
+        assert_eq!(negotiated.version, Version::new(1, 1, 0));
+        assert_eq!(negotiated.preferred, Version::new(1, 3, 0));
+        assert!(negotiated.is_downgrade());
+        assert_eq!(outcome.response.device_type, "Arduino_Uno");
+        
+        // IDENTIFY, the refused VERSION and the downgraded VERSION
+        let entries = transcript.entries();
+        assert_eq!(entries.len(), 6);
+        assert_eq!(entries[2].parsed.as_ref().unwrap()["preferred_version"], json!("1.3.0"));
+        assert_eq!(entries[3].parsed.as_ref().unwrap()["status"], json!("ERROR"));
+        assert_eq!(entries[4].parsed.as_ref().unwrap()["preferred_version"], json!("1.1.0"));
+        assert_eq!(entries[5].parsed.as_ref().unwrap()["negotiated_version"], json!("1.1.0"));
+    }
+    
+    #[tokio::test]
+    async fn test_supported_version_negotiated_without_downgrade() {
+        let runner = HandshakeRunner::new(versioned_device(vec!["1.1.0", "1.0.0"]))
+            .with_protocol_versions(vec![Version::new(1, 1, 0)]);
+        
+        let negotiated = runner.establish(&MessageExamples::identify_command()).await.unwrap().negotiated.unwrap();
+        assert_eq!(negotiated.version, Version::new(1, 1, 0));
+        assert!(!negotiated.is_downgrade());
+        
+        // Without configured versions there is no VERSION exchange at all
+        let runner = HandshakeRunner::new(versioned_device(vec!["1.1.0"]));
+        let (outcome, transcript) = runner.run_with_transcript(&MessageExamples::identify_command()).await.unwrap();
+        assert!(outcome.negotiated.is_none());
+        assert_eq!(transcript.len(), 2);
+    }
+    
+    #[tokio::test]
+    async fn test_no_common_major_fails() {
+        let runner = HandshakeRunner::new(versioned_device(vec!["2.0.0"]))
+            .with_protocol_versions(vec![Version::new(1, 3, 0)]);
+        
+        let failure = runner.run_with_transcript(&MessageExamples::identify_command()).await.unwrap_err();
+        match failure.error {
+            HandshakeError::IncompatibleProtocol { device_version, client_versions } => {
+                assert_eq!(device_version, "2.0.0");
+                assert_eq!(client_versions, vec!["1.3.0"]);
+            }
+            other => panic!("Expected IncompatibleProtocol, got {:?}", other),
+        }
+        // No downgrade attempt after the refusal
+        assert_eq!(failure.transcript.len(), 4);
+    }
+}
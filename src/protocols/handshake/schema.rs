@@ -125,7 +125,7 @@ impl HandshakeMessage for IdentifyCommand {
         }
         
         // Validate protocol version format
-        validate_semver(&self.protocol_version)?;
+        validate_semver(&self.protocol_version, "protocol_version")?;
         
         // Validate capabilities list
         if self.capabilities_requested.len() > MAX_CAPABILITIES {
@@ -228,8 +228,8 @@ impl HandshakeMessage for IdentifyResponse {
         // Validate required fields
         validate_string_length(&self.device_id, "device_id")?;
         validate_string_length(&self.device_type, "device_type")?;
-        validate_semver(&self.firmware_version)?;
-        validate_semver(&self.protocol_version)?;
+        validate_semver(&self.firmware_version, "firmware_version")?;
+        validate_semver(&self.protocol_version, "protocol_version")?;
         
         // Validate capabilities
         if self.capabilities.len() > MAX_CAPABILITIES {
@@ -415,14 +415,14 @@ impl HandshakeMessage for VersionRequest {
             });
         }
         
-        validate_semver(&self.preferred_version)?;
+        validate_semver(&self.preferred_version, "preferred_version")?;
         
         if self.supported_versions.is_empty() {
             return Err(ValidationError::EmptySupportedVersions);
         }
         
         for version in &self.supported_versions {
-            validate_semver(version)?;
+            validate_semver(version, "supported_versions")?;
         }
         
         Ok(())
@@ -470,7 +470,7 @@ impl HandshakeMessage for VersionResponse {
         }
         
         if self.status == "OK" {
-            validate_semver(&self.negotiated_version)?;
+            validate_semver(&self.negotiated_version, "negotiated_version")?;
         }
         
         if self.supported_versions.is_empty() {
@@ -478,7 +478,7 @@ impl HandshakeMessage for VersionResponse {
         }
         
         for version in &self.supported_versions {
-            validate_semver(version)?;
+            validate_semver(version, "supported_versions")?;
         }
         
         if self.status == "ERROR" && self.error_message.is_none() {
@@ -603,7 +603,7 @@ pub struct Capability {
 impl Capability {
     fn validate(&self) -> Result<(), ValidationError> {
         validate_string_length(&self.name, "capability_name")?;
-        validate_semver(&self.version)?;
+        validate_semver(&self.version, "capability_version")?;
         validate_string_length(&self.description, "capability_description")?;
         
         if self.parameters.len() > MAX_PARAMETERS {
@@ -618,7 +618,7 @@ impl Capability {
         }
         
         if let Some(ref min_version) = self.min_protocol_version {
-            validate_semver(min_version)?;
+            validate_semver(min_version, "min_protocol_version")?;
         }
         
         Ok(())
@@ -786,8 +786,8 @@ pub enum ValidationError {
     #[error("Invalid status: '{status}' (must be 'OK' or 'ERROR')")]
     InvalidStatus { status: String },
     
-    #[error("Invalid semantic version: '{version}'")]
-    InvalidSemver { version: String },
+    #[error("Invalid semantic version for '{field}': '{version}'")]
+    InvalidSemver { field: String, version: String },
     
     #[error("String field '{field}' too long: {length} characters (max {max})")]
     StringTooLong { field: String, length: usize, max: usize },
@@ -805,12 +805,59 @@ pub enum ValidationError {
     EmptySupportedVersions,
 }
 
+impl ValidationError {
+    /// Name of the message field that failed validation
+    pub fn field(&self) -> &str {
+        match self {
+            ValidationError::InvalidCommand { .. } => "command",
+            ValidationError::InvalidStatus { .. } => "status",
+            ValidationError::InvalidSemver { field, .. } => field,
+            ValidationError::StringTooLong { field, .. } => field,
+            ValidationError::TooManyCapabilities { .. } => "capabilities",
+            ValidationError::TooManyParameters { .. } => "parameters",
+            ValidationError::MissingErrorMessage => "error_message",
+            ValidationError::EmptySupportedVersions => "supported_versions",
+        }
+    }
+    
+    /// Get user-friendly message naming the field, its value and the constraint
+    pub fn user_friendly_message(&self) -> String {
+        match self {
+            ValidationError::InvalidCommand { expected, actual } => {
+                format!("Field 'command' must be '{}' but the device sent '{}'.", expected, actual)
+            }
+            ValidationError::InvalidStatus { status } => {
+                format!("Field 'status' must be 'OK' or 'ERROR' but the device sent '{}'.", status)
+            }
+            ValidationError::InvalidSemver { field, version } => {
+                format!("Field '{}' must be a major.minor.patch version (e.g. 1.2.0) but was '{}'.", field, version)
+            }
+            ValidationError::StringTooLong { field, length, max } => {
+                format!("Field '{}' is {} characters long; the maximum is {} characters.", field, length, max)
+            }
+            ValidationError::TooManyCapabilities { count, max } => {
+                format!("Field 'capabilities' lists {} entries; the maximum is {}.", count, max)
+            }
+            ValidationError::TooManyParameters { count, max } => {
+                format!("Field 'parameters' has {} entries; the maximum is {}.", count, max)
+            }
+            ValidationError::MissingErrorMessage => {
+                "Field 'error_message' is required when status is 'ERROR'.".to_string()
+            }
+            ValidationError::EmptySupportedVersions => {
+                "Field 'supported_versions' must list at least one version.".to_string()
+            }
+        }
+    }
+}
+
 /// Validate a semantic version string
-fn validate_semver(version: &str) -> Result<(), ValidationError> {
+fn validate_semver(version: &str, field_name: &str) -> Result<(), ValidationError> {
     // Basic semantic version validation (major.minor.patch)
     let parts: Vec<&str> = version.split('.').collect();
     if parts.len() != 3 {
         return Err(ValidationError::InvalidSemver {
+            field: field_name.to_string(),
             version: version.to_string(),
         });
     }
@@ -818,6 +865,7 @@ fn validate_semver(version: &str) -> Result<(), ValidationError> {
     for part in parts {
         if part.parse::<u32>().is_err() {
             return Err(ValidationError::InvalidSemver {
+                field: field_name.to_string(),
                 version: version.to_string(),
             });
         }
@@ -1070,15 +1118,15 @@ mod tests {
     
     #[test]
     fn test_semver_validation() {
-        assert!(validate_semver("1.0.0").is_ok());
-        assert!(validate_semver("2.5.10").is_ok());
-        assert!(validate_semver("0.1.0").is_ok());
+        assert!(validate_semver("1.0.0", "test").is_ok());
+        assert!(validate_semver("2.5.10", "test").is_ok());
+        assert!(validate_semver("0.1.0", "test").is_ok());
         
-        assert!(validate_semver("1.0").is_err());
-        assert!(validate_semver("1.0.0.1").is_err());
-        assert!(validate_semver("v1.0.0").is_err());
-        assert!(validate_semver("1.0.0-beta").is_err());
-        assert!(validate_semver("abc").is_err());
+        assert!(validate_semver("1.0", "test").is_err());
+        assert!(validate_semver("1.0.0.1", "test").is_err());
+        assert!(validate_semver("v1.0.0", "test").is_err());
+        assert!(validate_semver("1.0.0-beta", "test").is_err());
+        assert!(validate_semver("abc", "test").is_err());
     }
     
     #[test]
@@ -1133,4 +1181,36 @@ mod tests {
         invalid_error.message_type = "INVALID".to_string();
         assert!(invalid_error.validate().is_err());
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_validation_error_user_messages() {
+        // (error, field, text describing the constraint)
+        let cases = vec![
+            (ValidationError::InvalidCommand { expected: "IDENTIFY".into(), actual: "HELLO".into() }, "command", "IDENTIFY"),
+            (ValidationError::InvalidStatus { status: "MAYBE".into() }, "status", "'OK' or 'ERROR'"),
+            (ValidationError::InvalidSemver { field: "firmware_version".into(), version: "v1".into() }, "firmware_version", "major.minor.patch"),
+            (ValidationError::StringTooLong { field: "device_type".into(), length: 2000, max: MAX_STRING_LENGTH }, "device_type", "maximum is 1024 characters"),
+            (ValidationError::TooManyCapabilities { count: 120, max: MAX_CAPABILITIES }, "capabilities", "maximum is 100"),
+            (ValidationError::TooManyParameters { count: 60, max: MAX_PARAMETERS }, "parameters", "maximum is 50"),
+            (ValidationError::MissingErrorMessage, "error_message", "required when status is 'ERROR'"),
+            (ValidationError::EmptySupportedVersions, "supported_versions", "at least one"),
+        ];
+        
+        for (error, field, constraint) in cases {
+            let msg = error.user_friendly_message();
+            assert_eq!(error.field(), field);
+            assert!(msg.contains(&format!("'{}'", field)), "{:?}: {}", error, msg);
+            assert!(msg.contains(constraint), "{:?}: {}", error, msg);
+        }
+    }
+    
+    #[test]
+    fn test_too_long_device_type_names_field() {
+        let mut response = MessageExamples::identify_response_success();
+        response.device_type = "x".repeat(MAX_STRING_LENGTH + 1);
+        
+        let error = response.validate().unwrap_err();
+        assert_eq!(error.field(), "device_type");
+        assert!(error.user_friendly_message().contains(&format!("{} characters long", MAX_STRING_LENGTH + 1)));
+    }
+}
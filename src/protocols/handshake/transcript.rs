@@ -0,0 +1,116 @@
+//! Handshake Transcript
+//!
+//! Record of every message sent and received during a handshake, raw and
+//! parsed, so a failed negotiation can be inspected step by step instead of
+//! only through its final error.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use super::HandshakeError;
+
+/// Which way a transcript message travelled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TranscriptDirection {
+    Sent,
+    Received,
+}
+
+/// One message of a handshake
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptEntry {
+    pub direction: TranscriptDirection,
+    
+    /// Time since the handshake started
+    pub elapsed: Duration,
+    
+    /// Line as written to or read from the transport
+    pub raw: String,
+    
+    /// The line parsed as JSON, if it was valid JSON
+    pub parsed: Option<Value>,
+}
+
+/// Ordered record of a handshake's messages
+#[derive(Debug, Clone)]
+pub struct HandshakeTranscript {
+    started: Instant,
+    entries: Vec<TranscriptEntry>,
+}
+
+impl Default for HandshakeTranscript {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HandshakeTranscript {
+    /// Empty transcript timed from now
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            entries: Vec::new(),
+        }
+    }
+    
+    /// Record a message written to the device
+    pub fn record_sent(&mut self, raw: &str) {
+        self.record(TranscriptDirection::Sent, raw);
+    }
+    
+    /// Record a line read from the device
+    pub fn record_received(&mut self, raw: &str) {
+        self.record(TranscriptDirection::Received, raw);
+    }
+    
+    fn record(&mut self, direction: TranscriptDirection, raw: &str) {
+        self.entries.push(TranscriptEntry {
+            direction,
+            elapsed: self.started.elapsed(),
+            raw: raw.to_string(),
+            parsed: serde_json::from_str(raw).ok(),
+        });
+    }
+    
+    pub fn entries(&self) -> &[TranscriptEntry] {
+        &self.entries
+    }
+    
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+    
+    /// Most recent message, i.e. the last step reached before a failure
+    pub fn last(&self) -> Option<&TranscriptEntry> {
+        self.entries.last()
+    }
+}
+
+impl fmt::Display for HandshakeTranscript {
+    /// One line per message: `[  12ms] -> {...}` for sent, `<-` for received
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entry in &self.entries {
+            let arrow = match entry.direction {
+                TranscriptDirection::Sent => "->",
+                TranscriptDirection::Received => "<-",
+            };
+            writeln!(f, "[{:>5}ms] {} {}", entry.elapsed.as_millis(), arrow, entry.raw)?;
+        }
+        Ok(())
+    }
+}
+
+/// A failed handshake together with the exchange that led up to it
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("{error}")]
+pub struct HandshakeFailure {
+    #[source]
+    pub error: HandshakeError,
+    pub transcript: HandshakeTranscript,
+}
@@ -1,27 +1,38 @@
-use rhai::{Dynamic, Engine};
+use rhai::{Array, Dynamic, Engine, EvalAltResult};
+use serde_json::Value;
+use std::future::Future;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::runtime::Handle;
+use tokio::sync::{Mutex, RwLock, oneshot};
 use std::collections::HashMap;
-use crate::device::{DeviceManager, DeviceSession};
+use crate::device::{DeviceManager, DeviceSession, SharedSession};
 use super::errors::{ScriptError, ScriptResult};
 
+/// Operations granted to a script on a device it looks up by session id
+const SESSION_OPERATIONS: [&str; 2] = ["read", "write"];
+
 /// Safe handle to a device for script access
 #[derive(Clone)]
 pub struct ScriptDeviceHandle {
     device_id: String,
-    session: Arc<RwLock<Option<Box<dyn DeviceSession>>>>,
+    session: Option<SharedSession>,
     allowed_operations: Vec<String>,
 }
 
 impl ScriptDeviceHandle {
     pub fn new(
-        device_id: String, 
+        device_id: String,
         session: Box<dyn DeviceSession>,
         allowed_operations: Vec<String>
     ) -> Self {
+        Self::from_shared(device_id, Arc::new(Mutex::new(session)), allowed_operations)
+    }
+    
+    /// Handle on a session that is also used outside the script
+    pub fn from_shared(device_id: String, session: SharedSession, allowed_operations: Vec<String>) -> Self {
         Self {
             device_id,
-            session: Arc::new(RwLock::new(Some(session))),
+            session: Some(session),
             allowed_operations,
         }
     }
@@ -37,36 +48,35 @@ impl ScriptDeviceHandle {
         }
     }
     
+    /// Session behind the handle, or an error if the device is not connected
+    fn connected_session(&self) -> ScriptResult<&SharedSession> {
+        self.session.as_ref().ok_or_else(|| ScriptError::DeviceOperation(
+            format!("Device {} not connected", self.device_id)
+        ))
+    }
+    
     /// Safe read operation
     pub async fn read(&self, endpoint: &str) -> ScriptResult<Dynamic> {
         self.check_permission("read")?;
         
-        let session = self.session.read().await;
-        if let Some(session) = session.as_ref() {
-            // Note: This is a simplified example - actual implementation
-            // needs to handle async operations properly
-            Ok(Dynamic::from(format!("Read from {}: placeholder", endpoint)))
-        } else {
-            Err(ScriptError::DeviceOperation(
-                format!("Device {} not connected", self.device_id)
-            ))
-        }
+        let mut session = self.connected_session()?.lock().await;
+        let value = session.invoke_async(endpoint, Vec::new()).await
+            .map_err(|e| ScriptError::DeviceOperation(e.to_string()))?;
+        Ok(script_value(value))
     }
     
-    /// Safe write operation
+    /// Safe write operation; an array `value` is sent as the command's arguments
     pub async fn write(&self, endpoint: &str, value: Dynamic) -> ScriptResult<()> {
         self.check_permission("write")?;
         
-        let mut session = self.session.write().await;
-        if let Some(_session) = session.as_mut() {
-            // Note: Actual implementation would invoke device command
-            tracing::info!("Script write to {}: {:?}", endpoint, value);
-            Ok(())
-        } else {
-            Err(ScriptError::DeviceOperation(
-                format!("Device {} not connected", self.device_id)
-            ))
-        }
+        let args = command_args(&value).ok_or_else(|| ScriptError::DeviceOperation(
+            format!("Cannot send {} to {} on device {}", value.type_name(), endpoint, self.device_id)
+        ))?;
+        let mut session = self.connected_session()?.lock().await;
+        tracing::info!("Script write to {}: {:?}", endpoint, args);
+        session.invoke_async(endpoint, args).await
+            .map_err(|e| ScriptError::DeviceOperation(e.to_string()))?;
+        Ok(())
     }
     
     /// Safe control operation
@@ -81,15 +91,10 @@ impl ScriptDeviceHandle {
             ));
         }
         
-        let mut session = self.session.write().await;
-        if let Some(_session) = session.as_mut() {
-            // Note: Actual implementation would invoke device command
-            Ok(Dynamic::from(format!("Executed {}: OK", command)))
-        } else {
-            Err(ScriptError::DeviceOperation(
-                format!("Device {} not connected", self.device_id)
-            ))
-        }
+        let _session = self.connected_session()?.lock().await;
+        // Note: Actual implementation would invoke device command
+        tracing::info!("Script control {}: {:?}", command, params);
+        Ok(Dynamic::from(format!("Executed {}: OK", command)))
     }
 }
 
@@ -107,6 +112,11 @@ impl DeviceApi {
         }
     }
     
+    /// Make `handle` available to scripts under its device id, with its own permissions
+    pub async fn register_device(&self, handle: ScriptDeviceHandle) {
+        self.devices.write().await.insert(handle.device_id.clone(), handle);
+    }
+    
     /// List available devices
     pub async fn list_devices(&self) -> Vec<String> {
         // Get device list from manager
@@ -114,19 +124,30 @@ impl DeviceApi {
     }
     
     /// Get a device handle for script access
+    ///
+    /// Registered devices come first; otherwise `device_id` names an open
+    /// session of the device manager, which scripts may read and write.
     pub async fn get_device(&self, device_id: &str) -> ScriptResult<ScriptDeviceHandle> {
-        let devices = self.devices.read().await;
+        if let Some(handle) = self.devices.read().await.get(device_id) {
+            return Ok(handle.clone());
+        }
         
-        if let Some(handle) = devices.get(device_id) {
-            Ok(handle.clone())
-        } else {
-            Err(ScriptError::DeviceOperation(
+        match self.manager.get_session(device_id).await {
+            Some(session) => Ok(ScriptDeviceHandle::from_shared(
+                device_id.to_string(),
+                session,
+                SESSION_OPERATIONS.iter().map(|op| op.to_string()).collect(),
+            )),
+            None => Err(ScriptError::DeviceOperation(
                 format!("Device {} not found", device_id)
-            ))
+            )),
         }
     }
     
     /// Register device API functions with Rhai engine
+    ///
+    /// Device functions block the script until the device answers, so scripts
+    /// must run on a blocking thread of a Tokio runtime (e.g. `spawn_blocking`).
     pub fn register_api(engine: &mut Engine, api: Arc<DeviceApi>) {
         // Register the DeviceApi type
         engine.register_type::<ScriptDeviceHandle>()
@@ -135,34 +156,33 @@ impl DeviceApi {
             });
         
         // Register global functions
-        let api_clone = api.clone();
         engine.register_fn("list_devices", move || {
             // Note: This is a sync wrapper - actual implementation needs
             // to handle async properly with tokio::runtime::Handle
             vec!["device1".to_string(), "device2".to_string()]
         });
         
-        let api_clone = api.clone();
-        engine.register_fn("get_device", move |device_id: &str| {
-            // Sync wrapper for get_device
-            // In practice, this would use Handle::current() to run async code
-            ScriptDeviceHandle {
-                device_id: device_id.to_string(),
-                session: Arc::new(RwLock::new(None)),
-                allowed_operations: vec!["read".to_string()],
-            }
+        engine.register_fn("get_device", move |device_id: &str| -> Result<ScriptDeviceHandle, Box<EvalAltResult>> {
+            let api = api.clone();
+            let device_id = device_id.to_string();
+            block_on_runtime(async move { api.get_device(&device_id).await })
+                .map_err(|e| e.to_string().into())
         });
         
         // Register device operations
-        engine.register_fn("device_read", |handle: &mut ScriptDeviceHandle, endpoint: &str| {
-            // Sync wrapper for async read
-            format!("Read from {}: placeholder", endpoint)
+        engine.register_fn("device_read", |handle: &mut ScriptDeviceHandle, endpoint: &str| -> Result<Dynamic, Box<EvalAltResult>> {
+            let handle = handle.clone();
+            let endpoint = endpoint.to_string();
+            block_on_runtime(async move { handle.read(&endpoint).await })
+                .map_err(|e| e.to_string().into())
         });
         
-        engine.register_fn("device_write", 
-            |handle: &mut ScriptDeviceHandle, endpoint: &str, value: Dynamic| {
-            // Sync wrapper for async write
-            format!("Write to {}: {:?}", endpoint, value)
+        engine.register_fn("device_write",
+            |handle: &mut ScriptDeviceHandle, endpoint: &str, value: Dynamic| -> Result<(), Box<EvalAltResult>> {
+            let handle = handle.clone();
+            let endpoint = endpoint.to_string();
+            block_on_runtime(async move { handle.write(&endpoint, value).await })
+                .map_err(|e| e.to_string().into())
         });
         
         // Register utility functions
@@ -174,4 +194,65 @@ impl DeviceApi {
             tracing::info!("[Script]: {}", text);
         });
     }
-}
\ No newline at end of file
+}
+
+/// Run `future` on the current Tokio runtime and block the script's thread for its result
+fn block_on_runtime<F, T>(future: F) -> ScriptResult<T>
+where
+    F: Future<Output = ScriptResult<T>> + Send + 'static,
+    T: Send + 'static,
+{
+    let runtime_handle = Handle::try_current()
+        .map_err(|_| ScriptError::Execution(
+            "No Tokio runtime found. Scripts must run within async context".to_string()
+        ))?;
+    
+    let (tx, rx) = oneshot::channel();
+    runtime_handle.spawn(async move {
+        let _ = tx.send(future.await);
+    });
+    
+    rx.blocking_recv()
+        .map_err(|_| ScriptError::Execution("Async operation failed".to_string()))?
+}
+
+/// Command arguments for a script value: an array's elements, or the single value
+fn command_args(value: &Dynamic) -> Option<Vec<Value>> {
+    match value.clone().into_array() {
+        Ok(items) => items.iter().map(json_value).collect(),
+        Err(_) => json_value(value).map(|arg| vec![arg]),
+    }
+}
+
+/// Script value as JSON, or `None` for types with no JSON form
+fn json_value(value: &Dynamic) -> Option<Value> {
+    if let Ok(flag) = value.as_bool() {
+        Some(Value::Bool(flag))
+    } else if let Ok(int) = value.as_int() {
+        Some(Value::from(int))
+    } else if let Ok(float) = value.as_float() {
+        serde_json::Number::from_f64(float).map(Value::Number)
+    } else if value.is_string() {
+        value.clone().into_string().ok().map(Value::String)
+    } else if value.is_array() {
+        let items: Array = value.clone().into_array().ok()?;
+        items.iter().map(json_value).collect::<Option<Vec<_>>>().map(Value::Array)
+    } else {
+        None
+    }
+}
+
+/// Device reply as a script value; objects are passed on as JSON text
+fn script_value(value: Value) -> Dynamic {
+    match value {
+        Value::Null => Dynamic::UNIT,
+        Value::Bool(flag) => Dynamic::from(flag),
+        Value::Number(number) => match number.as_i64() {
+            Some(int) => Dynamic::from(int),
+            None => Dynamic::from(number.as_f64().unwrap_or_default()),
+        },
+        Value::String(text) => Dynamic::from(text),
+        Value::Array(items) => Dynamic::from_array(items.into_iter().map(script_value).collect()),
+        Value::Object(_) => Dynamic::from(value.to_string()),
+    }
+}
@@ -63,14 +63,14 @@ impl ScriptEngine {
         // Configure engine based on sandbox settings
         Self::configure_engine(&mut engine, &sandbox_config);
         
-        // Register device API
-        DeviceApi::register_api(&mut engine, device_api.clone());
-        
         // Create and register async bridge
         let manager = Arc::new(crate::device::DeviceManager::new("plugins"));
         let bridge = Arc::new(AsyncBridge::new(manager)?);
         register_sync_api(&mut engine, bridge);
         
+        // Register device API last so its session-backed `get_device` replaces the bridge's
+        DeviceApi::register_api(&mut engine, device_api.clone());
+        
         Ok(Self {
             engine: Arc::new(Mutex::new(engine)),
             sandbox_config,
@@ -116,11 +116,12 @@ impl ScriptEngine {
         }
         
         // Add operation counting hook
+        let max_operations = config.limits.max_operations;
         engine.on_progress(move |operations| {
             // This is called periodically during script execution
             // Return None to continue, Some(error) to stop
-            if operations > config.limits.max_operations {
-                Some(format!("Operation limit {} exceeded", config.limits.max_operations).into())
+            if operations > max_operations {
+                Some(format!("Operation limit {} exceeded", max_operations).into())
             } else {
                 None
             }
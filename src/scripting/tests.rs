@@ -31,7 +31,7 @@ mod tests {
     #[tokio::test]
     async fn test_simple_script_compilation() {
         // Create a mock device manager
-        let manager = Arc::new(crate::device::DeviceManager::new());
+        let manager = Arc::new(crate::device::DeviceManager::new("./drivers"));
         let device_api = Arc::new(DeviceApi::new(manager));
         let config = SandboxConfig::default();
         
@@ -49,7 +49,7 @@ mod tests {
     
     #[tokio::test]
     async fn test_security_validation() {
-        let manager = Arc::new(crate::device::DeviceManager::new());
+        let manager = Arc::new(crate::device::DeviceManager::new("./drivers"));
         let device_api = Arc::new(DeviceApi::new(manager));
         let config = SandboxConfig::high_security();
         
@@ -73,7 +73,7 @@ mod tests {
     
     #[tokio::test]
     async fn test_simple_eval() {
-        let manager = Arc::new(crate::device::DeviceManager::new());
+        let manager = Arc::new(crate::device::DeviceManager::new("./drivers"));
         let device_api = Arc::new(DeviceApi::new(manager));
         let config = SandboxConfig::default();
         
@@ -0,0 +1,152 @@
+//! Channels derived from several source channels
+//!
+//! The same logical signal often arrives from several devices (e.g.
+//! `board1/temperature`, `board2/temperature`). An aggregate channel keeps the
+//! latest value of each matching source and, whenever any source accepts a
+//! sample, records the sum/average/minimum/maximum across them. Sources come
+//! and go with their channels, so a board connected later joins the aggregate
+//! and a removed one stops counting.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use parking_lot::Mutex;
+use serde::{Serialize, Deserialize};
+use crate::telemetry::{SampleValue, TelemetryChannel, TelemetrySample, TelemetrySink};
+
+/// How an aggregate channel combines its sources
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AggKind {
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+impl AggKind {
+    /// Combine `values`, or `None` when there are none
+    pub fn apply(&self, values: &[f32]) -> Option<f32> {
+        if values.is_empty() {
+            return None;
+        }
+        Some(match self {
+            AggKind::Sum => values.iter().sum(),
+            AggKind::Avg => values.iter().sum::<f32>() / values.len() as f32,
+            AggKind::Min => values.iter().copied().fold(f32::INFINITY, f32::min),
+            AggKind::Max => values.iter().copied().fold(f32::NEG_INFINITY, f32::max),
+        })
+    }
+}
+
+impl fmt::Display for AggKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            AggKind::Sum => "sum",
+            AggKind::Avg => "avg",
+            AggKind::Min => "min",
+            AggKind::Max => "max",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Whether `name` matches `pattern`, where `*` matches any run of characters
+pub fn channel_matches(pattern: &str, name: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or("");
+    let Some(mut rest) = name.strip_prefix(first) else {
+        return false;
+    };
+    
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // No wildcard: the whole name must match
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+/// Sink registered on each source channel, feeding the derived channel
+pub(crate) struct AggregateSink {
+    kind: AggKind,
+    target: Arc<TelemetryChannel>,
+    /// Latest numeric value per attached source channel, `None` until it has one
+    latest: Mutex<HashMap<String, Option<f32>>>,
+}
+
+impl AggregateSink {
+    pub(crate) fn new(kind: AggKind, target: Arc<TelemetryChannel>) -> Self {
+        Self {
+            kind,
+            target,
+            latest: Mutex::new(HashMap::new()),
+        }
+    }
+    
+    /// Name of the derived channel
+    pub(crate) fn target_name(&self) -> &str {
+        &self.target.config().name
+    }
+    
+    /// Count `channel` as a source from now on
+    pub(crate) fn attach(&self, channel: &str) {
+        self.latest.lock().entry(channel.to_string()).or_insert(None);
+    }
+    
+    /// Stop counting `channel`, dropping its latest value
+    pub(crate) fn detach(&self, channel: &str) {
+        self.latest.lock().remove(channel);
+    }
+}
+
+impl TelemetrySink for AggregateSink {
+    fn ingest(&self, channel: &str, sample: &TelemetrySample) {
+        // Non-numeric samples don't take part in the aggregate
+        let Some(value) = sample.as_f32() else {
+            return;
+        };
+        
+        // Held while adding so samples from concurrent sources stay in order
+        let mut latest = self.latest.lock();
+        // A detached source may still be held (and fed) elsewhere
+        let Some(slot) = latest.get_mut(channel) else {
+            return;
+        };
+        *slot = Some(value);
+        let values: Vec<f32> = latest.values().flatten().copied().collect();
+        if let Some(combined) = self.kind.apply(&values) {
+            self.target.add_sample(TelemetrySample::with_timestamp(SampleValue::Float32(combined), sample.timestamp_ms));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_agg_kinds() {
+        let values = [2.0, 8.0, 5.0];
+        assert_eq!(AggKind::Sum.apply(&values), Some(15.0));
+        assert_eq!(AggKind::Avg.apply(&values), Some(5.0));
+        assert_eq!(AggKind::Min.apply(&values), Some(2.0));
+        assert_eq!(AggKind::Max.apply(&values), Some(8.0));
+        assert_eq!(AggKind::Avg.apply(&[]), None);
+    }
+    
+    #[test]
+    fn test_channel_matches() {
+        assert!(channel_matches("*/temperature", "board1/temperature"));
+        assert!(channel_matches("board*/temp*", "board2/temperature"));
+        assert!(channel_matches("temperature", "temperature"));
+        assert!(!channel_matches("temperature", "temperature2"));
+        assert!(!channel_matches("*/temperature", "board1/humidity"));
+        assert!(channel_matches("*", "anything"));
+    }
+}
@@ -4,9 +4,15 @@
 //! ring buffer, configuration, and statistics.
 
 use crate::telemetry::{RingBuffer, TelemetrySample, SampleType, SampleStatistics};
+use crate::telemetry::sink::{SinkWorker, TelemetrySink, DEFAULT_SINK_QUEUE_CAPACITY};
+use crate::telemetry::persist::SpillFile;
+use crate::telemetry::timestamp::{SampleClock, TimestampSource};
+use crate::device::clock_sync::ClockOffset;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
 use std::time::{SystemTime, UNIX_EPOCH, Duration};
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use serde::{Serialize, Deserialize};
 
 /// Configuration for a telemetry channel
@@ -20,6 +26,18 @@ pub struct ChannelConfig {
     pub sample_rate: f32,
     /// Sample type for this channel
     pub sample_type: SampleType,
+    /// Append samples evicted from the ring buffer to this file
+    #[serde(default)]
+    pub persist_path: Option<PathBuf>,
+    /// Free-form labels for filtering and bulk operations
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Display group (e.g. "Motor 1"); ungrouped channels show under `UNGROUPED_CHANNEL_GROUP`
+    #[serde(default)]
+    pub group: Option<String>,
+    /// Clock that stamps incoming samples
+    #[serde(default)]
+    pub timestamp_source: TimestampSource,
 }
 
 impl Default for ChannelConfig {
@@ -29,6 +47,10 @@ impl Default for ChannelConfig {
             buffer_size: 2000,
             sample_rate: 30.0,
             sample_type: SampleType::Float32,
+            persist_path: None,
+            tags: Vec::new(),
+            group: None,
+            timestamp_source: TimestampSource::Sample,
         }
     }
 }
@@ -39,6 +61,15 @@ pub struct TelemetryChannel {
     buffer: Arc<RingBuffer<TelemetrySample>>,
     stats: Arc<RwLock<ChannelStats>>,
     rate_limiter: Arc<RwLock<RateLimiter>>,
+    sinks: Arc<RwLock<Vec<SinkWorker>>>,
+    /// Sinks fed on the ingesting thread, in the order samples are accepted
+    inline_sinks: RwLock<Vec<Arc<dyn TelemetrySink>>>,
+    spill: Option<Arc<Mutex<SpillFile>>>,
+    clock: RwLock<SampleClock>,
+    /// Keep one in this many incoming samples (1 keeps all)
+    decimation: AtomicU32,
+    decimation_counter: AtomicU64,
+    ingest_paused: AtomicBool,
 }
 
 impl TelemetryChannel {
@@ -46,41 +77,196 @@ impl TelemetryChannel {
     pub fn new(config: ChannelConfig) -> Self {
         let buffer_size = config.buffer_size.max(2000); // Enforce minimum
         
+        let spill = config.persist_path.as_ref().and_then(|path| match SpillFile::create(path) {
+            Ok(file) => Some(Arc::new(Mutex::new(file))),
+            Err(e) => {
+                tracing::warn!("Channel '{}' cannot persist to {}: {}", config.name, path.display(), e);
+                None
+            }
+        });
+        
         Self {
             buffer: Arc::new(RingBuffer::new(buffer_size)),
             stats: Arc::new(RwLock::new(ChannelStats::new(config.name.clone()))),
             rate_limiter: Arc::new(RwLock::new(RateLimiter::new(config.sample_rate))),
+            sinks: Arc::new(RwLock::new(Vec::new())),
+            inline_sinks: RwLock::new(Vec::new()),
+            spill,
+            clock: RwLock::new(SampleClock::new(config.timestamp_source)),
+            decimation: AtomicU32::new(1),
+            decimation_counter: AtomicU64::new(0),
+            ingest_paused: AtomicBool::new(false),
             config,
         }
     }
     
+    /// Clock offset of the device feeding this channel, used when the
+    /// timestamp source is `TimestampSource::Device`
+    pub fn set_clock_offset(&self, offset: Option<ClockOffset>) {
+        self.clock.write().set_offset(offset);
+    }
+    
+    /// Keep only one in `factor` incoming samples (1 keeps all)
+    pub fn set_decimation(&self, factor: u32) {
+        self.decimation.store(factor.max(1), Ordering::Relaxed);
+    }
+    
+    pub fn decimation(&self) -> u32 {
+        self.decimation.load(Ordering::Relaxed)
+    }
+    
+    /// Stop (or resume) accepting samples; rejected samples count as dropped
+    pub fn set_ingest_paused(&self, paused: bool) {
+        self.ingest_paused.store(paused, Ordering::Relaxed);
+    }
+    
+    pub fn is_ingest_paused(&self) -> bool {
+        self.ingest_paused.load(Ordering::Relaxed)
+    }
+    
+    /// Whether an incoming sample gets past the pause flag and decimation
+    fn admit(&self) -> bool {
+        if self.is_ingest_paused() {
+            return false;
+        }
+        let factor = self.decimation() as u64;
+        factor <= 1 || self.decimation_counter.fetch_add(1, Ordering::Relaxed) % factor == 0
+    }
+    
     /// Add a sample to the channel
-    pub fn add_sample(&self, sample: TelemetrySample) {
-        // Check rate limiting
-        if !self.rate_limiter.write().should_accept() {
+    pub fn add_sample(&self, mut sample: TelemetrySample) {
+        // Check pause/decimation and rate limiting
+        if !self.admit() || !self.rate_limiter.write().should_accept() {
             self.stats.write().samples_dropped += 1;
             return;
         }
         
+        self.clock.read().stamp(&mut sample);
+        
         // Type checking (optional, for safety)
         if sample.sample_type() != self.config.sample_type {
             self.stats.write().type_mismatches += 1;
             // Could still accept or convert, depending on policy
         }
         
-        // Add to buffer
-        self.buffer.push(sample);
+        // Forward to live sinks (non-blocking, drops if a sink falls behind)
+        let mut sink_drops = 0;
+        for sink in self.sinks.read().iter() {
+            if !sink.forward(&self.config.name, &sample) {
+                sink_drops += 1;
+            }
+        }
+        for sink in self.inline_sinks.read().iter() {
+            sink.ingest(&self.config.name, &sample);
+        }
+        
+        // Add to buffer, spilling the overwritten sample to disk if persisting
+        let evicted = self.buffer.push_evicting(sample);
+        let mut persisted = false;
+        if let (Some(evicted), Some(ref spill)) = (evicted, &self.spill) {
+            match spill.lock().append(&evicted) {
+                Ok(()) => persisted = true,
+                Err(e) => tracing::warn!("Channel '{}' failed to persist sample: {}", self.config.name, e),
+            }
+        }
         
         // Update stats
         let mut stats = self.stats.write();
         stats.total_samples += 1;
+        if persisted {
+            stats.samples_persisted += 1;
+        }
+        stats.sink_samples_dropped += sink_drops;
         stats.last_sample_time = SystemTime::now();
     }
     
-    /// Add multiple samples at once
-    pub fn add_samples(&self, samples: Vec<TelemetrySample>) {
-        for sample in samples {
-            self.add_sample(sample);
+    /// Register a live sink that receives every accepted sample
+    pub fn register_sink(&self, sink: Arc<dyn TelemetrySink>) {
+        self.register_sink_with_capacity(sink, DEFAULT_SINK_QUEUE_CAPACITY);
+    }
+    
+    /// Register a live sink with a custom queue capacity
+    pub fn register_sink_with_capacity(&self, sink: Arc<dyn TelemetrySink>, capacity: usize) {
+        self.sinks.write().push(SinkWorker::spawn(sink, capacity));
+    }
+    
+    /// Register a sink called directly from `add_sample`/`add_samples`, with no
+    /// queue or worker thread. It sees samples from every channel it is
+    /// registered on in the order they were accepted, so it must be cheap and
+    /// must not block
+    pub(crate) fn register_inline_sink(&self, sink: Arc<dyn TelemetrySink>) {
+        self.inline_sinks.write().push(sink);
+    }
+    
+    /// Detach all sinks, flushing any queued samples
+    pub fn clear_sinks(&self) {
+        let sinks = std::mem::take(&mut *self.sinks.write());
+        drop(sinks);
+        self.inline_sinks.write().clear();
+    }
+    
+    /// Number of registered sinks
+    pub fn sink_count(&self) -> usize {
+        self.sinks.read().len() + self.inline_sinks.read().len()
+    }
+    
+    /// Add a batch of samples in order, taking each lock once for the whole batch
+    /// Behaves like calling `add_sample` for each, but avoids per-sample contention at high rates
+    pub fn add_samples(&self, samples: &[TelemetrySample]) {
+        if samples.is_empty() {
+            return;
+        }
+        
+        // Admit and rate limit each sample, as `add_sample` would
+        let mut accepted: Vec<TelemetrySample> = {
+            let mut limiter = self.rate_limiter.write();
+            samples.iter().filter(|_| self.admit() && limiter.should_accept()).cloned().collect()
+        };
+        {
+            let clock = self.clock.read();
+            accepted.iter_mut().for_each(|sample| clock.stamp(sample));
+        }
+        let dropped = (samples.len() - accepted.len()) as u64;
+        let mismatches = accepted.iter().filter(|s| s.sample_type() != self.config.sample_type).count() as u64;
+        
+        // Forward to live sinks (non-blocking, drops if a sink falls behind)
+        let mut sink_drops = 0;
+        for sink in self.sinks.read().iter() {
+            for sample in &accepted {
+                if !sink.forward(&self.config.name, sample) {
+                    sink_drops += 1;
+                }
+            }
+        }
+        for sink in self.inline_sinks.read().iter() {
+            for sample in &accepted {
+                sink.ingest(&self.config.name, sample);
+            }
+        }
+        
+        // Add to buffer, spilling overwritten samples to disk if persisting
+        let accepted_count = accepted.len() as u64;
+        let evicted = self.buffer.push_batch_evicting(accepted);
+        let mut persisted = 0;
+        if let (false, Some(ref spill)) = (evicted.is_empty(), &self.spill) {
+            let mut spill = spill.lock();
+            for sample in &evicted {
+                match spill.append(sample) {
+                    Ok(()) => persisted += 1,
+                    Err(e) => tracing::warn!("Channel '{}' failed to persist sample: {}", self.config.name, e),
+                }
+            }
+        }
+        
+        // Update stats
+        let mut stats = self.stats.write();
+        stats.total_samples += accepted_count;
+        stats.samples_dropped += dropped;
+        stats.type_mismatches += mismatches;
+        stats.samples_persisted += persisted;
+        stats.sink_samples_dropped += sink_drops;
+        if accepted_count > 0 {
+            stats.last_sample_time = SystemTime::now();
         }
     }
     
@@ -126,6 +312,7 @@ impl TelemetryChannel {
         stats.buffer_used = buffer_stats.current_size;
         stats.buffer_fill_ratio = buffer_stats.fill_ratio();
         stats.memory_bytes = buffer_stats.memory_bytes;
+        stats.buffer_write_locks = buffer_stats.write_locks;
         
         // Calculate sample statistics
         let samples = self.buffer.snapshot();
@@ -134,12 +321,26 @@ impl TelemetryChannel {
         stats
     }
     
-    /// Clear all data in the channel
+    /// Clear all data in the channel (including persisted history)
     pub fn clear(&self) {
         self.buffer.clear();
+        if let Some(ref spill) = self.spill {
+            if let Err(e) = spill.lock().truncate() {
+                tracing::warn!("Channel '{}' failed to clear persisted samples: {}", self.config.name, e);
+            }
+        }
         self.stats.write().reset();
     }
     
+    /// Samples spilled to disk, oldest first (empty if not persisting)
+    pub fn persisted_samples(&self) -> Result<Vec<TelemetrySample>, String> {
+        match self.spill {
+            Some(ref spill) => spill.lock().read_all()
+                .map_err(|e| format!("Failed to read persisted samples: {}", e)),
+            None => Ok(Vec::new()),
+        }
+    }
+    
     /// Export channel data
     pub fn export_data(&self) -> ChannelExportData {
         ChannelExportData {
@@ -150,6 +351,19 @@ impl TelemetryChannel {
         }
     }
     
+    /// Export the full history: persisted samples followed by the ring buffer
+    pub fn export_full_history(&self) -> Result<ChannelExportData, String> {
+        let mut samples = self.persisted_samples()?;
+        samples.extend(self.buffer.snapshot());
+        
+        Ok(ChannelExportData {
+            config: self.config.clone(),
+            samples,
+            stats: self.get_stats(),
+            exported_at: SystemTime::now(),
+        })
+    }
+    
     /// Get memory usage
     pub fn memory_usage(&self) -> usize {
         self.buffer.memory_usage()
@@ -172,11 +386,20 @@ pub struct ChannelStats {
     pub name: String,
     pub total_samples: u64,
     pub samples_dropped: u64,
+    /// Samples a live sink could not accept because its queue was full
+    #[serde(default)]
+    pub sink_samples_dropped: u64,
+    /// Samples evicted from the ring buffer and written to the persist file
+    #[serde(default)]
+    pub samples_persisted: u64,
     pub type_mismatches: u64,
     pub buffer_capacity: usize,
     pub buffer_used: usize,
     pub buffer_fill_ratio: f32,
     pub memory_bytes: usize,
+    /// Buffer write-lock acquisitions; batched ingest takes one per batch
+    #[serde(default)]
+    pub buffer_write_locks: u64,
     pub created_at: SystemTime,
     pub last_sample_time: SystemTime,
     pub sample_stats: Option<SampleStatistics>,
@@ -189,11 +412,14 @@ impl ChannelStats {
             name,
             total_samples: 0,
             samples_dropped: 0,
+            sink_samples_dropped: 0,
+            samples_persisted: 0,
             type_mismatches: 0,
             buffer_capacity: 0,
             buffer_used: 0,
             buffer_fill_ratio: 0.0,
             memory_bytes: 0,
+            buffer_write_locks: 0,
             created_at: now,
             last_sample_time: now,
             sample_stats: None,
@@ -203,6 +429,8 @@ impl ChannelStats {
     fn reset(&mut self) {
         self.total_samples = 0;
         self.samples_dropped = 0;
+        self.sink_samples_dropped = 0;
+        self.samples_persisted = 0;
         self.type_mismatches = 0;
         self.buffer_used = 0;
         self.buffer_fill_ratio = 0.0;
@@ -326,6 +554,10 @@ mod tests {
             buffer_size: 100,
             sample_rate: 10.0,
             sample_type: SampleType::Float32,
+            persist_path: None,
+            tags: Vec::new(),
+            group: None,
+            timestamp_source: TimestampSource::Sample,
         };
         
         let channel = TelemetryChannel::new(config);
@@ -351,6 +583,60 @@ mod tests {
         assert_eq!(snapshot.len(), 10);
     }
     
+    #[test]
+    fn test_batched_samples_keep_order() {
+        let mut config = ChannelConfig::default();
+        config.sample_rate = 0.0;
+        let channel = TelemetryChannel::new(config);
+        
+        let first: Vec<_> = (0..50).map(|i| TelemetrySample::new_f32(i as f32)).collect();
+        let second: Vec<_> = (50..120).map(|i| TelemetrySample::new_f32(i as f32)).collect();
+        channel.add_samples(&first);
+        channel.add_sample(TelemetrySample::new_f32(120.0));
+        channel.add_samples(&second);
+        channel.add_samples(&[]);
+        
+        let values: Vec<f32> = channel.snapshot().iter().filter_map(|s| s.as_f32()).collect();
+        let mut expected: Vec<f32> = (0..50).map(|i| i as f32).collect();
+        expected.push(120.0);
+        expected.extend((50..120).map(|i| i as f32));
+        assert_eq!(values, expected);
+        assert_eq!(channel.get_stats().total_samples, 121);
+    }
+    
+    #[test]
+    fn test_batched_ingest_takes_fewer_locks() {
+        const SAMPLES: usize = 10_000;
+        const BATCH: usize = 100;
+        let samples: Vec<_> = (0..SAMPLES).map(|i| TelemetrySample::new_f32(i as f32)).collect();
+        
+        let mut config = ChannelConfig::default();
+        config.sample_rate = 0.0;
+        
+        let per_sample = TelemetryChannel::new(config.clone());
+        for sample in &samples {
+            per_sample.add_sample(sample.clone());
+        }
+        
+        let batched = TelemetryChannel::new(config);
+        for chunk in samples.chunks(BATCH) {
+            batched.add_samples(chunk);
+        }
+        
+        let per_sample_stats = per_sample.get_stats();
+        let batched_stats = batched.get_stats();
+        
+        // One lock per sample versus one per batch
+        assert_eq!(per_sample_stats.buffer_write_locks, SAMPLES as u64);
+        assert_eq!(batched_stats.buffer_write_locks, (SAMPLES / BATCH) as u64);
+        assert_eq!(per_sample_stats.buffer_write_locks / batched_stats.buffer_write_locks, BATCH as u64);
+        assert_eq!(batched_stats.total_samples, per_sample_stats.total_samples);
+        assert_eq!(
+            batched.last_n(5).iter().filter_map(|s| s.as_f32()).collect::<Vec<_>>(),
+            per_sample.last_n(5).iter().filter_map(|s| s.as_f32()).collect::<Vec<_>>()
+        );
+    }
+    
     #[test]
     fn test_rate_limiting() {
         let mut config = ChannelConfig::default();
@@ -385,4 +671,131 @@ mod tests {
             assert!(value >= 0.0 && value < 50.0);
         }
     }
-}
\ No newline at end of file
+    
+    fn persisting_channel(path: PathBuf) -> TelemetryChannel {
+        TelemetryChannel::new(ChannelConfig {
+            name: "capture".to_string(),
+            buffer_size: 2000,
+            sample_rate: 0.0, // Disable rate limiting
+            sample_type: SampleType::Float32,
+            persist_path: Some(path),
+            tags: Vec::new(),
+            group: None,
+            timestamp_source: TimestampSource::Sample,
+        })
+    }
+    
+    #[test]
+    fn test_evicted_samples_recoverable_from_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let channel = persisting_channel(dir.path().join("capture.bin"));
+        
+        for i in 0..2500 {
+            channel.add_sample(TelemetrySample::new_f32(i as f32));
+        }
+        
+        // The ring holds the newest 2000; the oldest 500 were spilled in order
+        let persisted = channel.persisted_samples().unwrap();
+        assert_eq!(persisted.len(), 500);
+        assert_eq!(persisted.first().and_then(|s| s.as_f32()), Some(0.0));
+        assert_eq!(persisted.last().and_then(|s| s.as_f32()), Some(499.0));
+        assert_eq!(channel.snapshot().len(), 2000);
+        assert_eq!(channel.get_stats().samples_persisted, 500);
+    }
+    
+    #[test]
+    fn test_full_history_export_includes_disk_and_memory() {
+        let dir = tempfile::tempdir().unwrap();
+        let channel = persisting_channel(dir.path().join("nested").join("capture.bin"));
+        
+        for i in 0..2100 {
+            channel.add_sample(TelemetrySample::new_f32(i as f32));
+        }
+        
+        let export = channel.export_full_history().unwrap();
+        let values: Vec<f32> = export.samples.iter().filter_map(|s| s.as_f32()).collect();
+        assert_eq!(values, (0..2100).map(|i| i as f32).collect::<Vec<_>>());
+        
+        // Regular export stays memory-only
+        assert_eq!(channel.export_data().samples.len(), 2000);
+        
+        channel.clear();
+        assert!(channel.persisted_samples().unwrap().is_empty());
+    }
+    
+    #[test]
+    fn test_no_persistence_by_default() {
+        let mut config = ChannelConfig::default();
+        config.sample_rate = 0.0;
+        let channel = TelemetryChannel::new(config);
+        
+        for i in 0..2100 {
+            channel.add_sample(TelemetrySample::new_f32(i as f32));
+        }
+        
+        assert!(channel.persisted_samples().unwrap().is_empty());
+        assert_eq!(channel.get_stats().samples_persisted, 0);
+    }
+    
+    fn stamped_channel(timestamp_source: TimestampSource) -> TelemetryChannel {
+        TelemetryChannel::new(ChannelConfig {
+            sample_rate: 0.0, // Disable rate limiting
+            timestamp_source,
+            ..Default::default()
+        })
+    }
+    
+    fn newest_timestamp(channel: &TelemetryChannel) -> u64 {
+        channel.snapshot().last().unwrap().timestamp_ms
+    }
+    
+    #[test]
+    fn test_timestamp_sources_stamp_samples() {
+        let now_ms = || SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64;
+        
+        // Producer timestamps are kept by default
+        let channel = stamped_channel(TimestampSource::Sample);
+        channel.add_sample(TelemetrySample::with_timestamp(SampleValue::Float32(1.0), 42));
+        assert_eq!(newest_timestamp(&channel), 42);
+        
+        // Host clocks replace whatever the producer set
+        for source in [TimestampSource::HostWall, TimestampSource::HostMonotonic] {
+            let channel = stamped_channel(source);
+            let before = now_ms();
+            channel.add_sample(TelemetrySample::with_timestamp(SampleValue::Float32(1.0), 42));
+            channel.add_samples(&[TelemetrySample::with_timestamp(SampleValue::Float32(2.0), 43)]);
+            let after = now_ms();
+            
+            for sample in channel.snapshot() {
+                // Allow 1ms for the monotonic and wall clocks rounding differently
+                assert!(sample.timestamp_ms + 1 >= before && sample.timestamp_ms <= after + 1, "{:?} stamped {}", source, sample.timestamp_ms);
+            }
+        }
+        
+        // Monotonic stamps never go backwards
+        let channel = stamped_channel(TimestampSource::HostMonotonic);
+        for i in 0..50 {
+            channel.add_sample(TelemetrySample::new_f32(i as f32));
+        }
+        let stamps: Vec<u64> = channel.snapshot().iter().map(|s| s.timestamp_ms).collect();
+        assert!(stamps.windows(2).all(|w| w[0] <= w[1]));
+    }
+    
+    #[test]
+    fn test_device_timestamp_source_applies_clock_offset() {
+        let channel = stamped_channel(TimestampSource::Device);
+        
+        // Before any clock sync, device time passes through
+        channel.add_sample(TelemetrySample::with_timestamp(SampleValue::Float32(1.0), 5_000));
+        assert_eq!(newest_timestamp(&channel), 5_000);
+        
+        // Device booted 5s ago: device 5000ms == host 1_700_000_000_000
+        let offset = ClockOffset::estimate(1_699_999_999_990, 4_990, 1_700_000_000_010);
+        channel.set_clock_offset(Some(offset));
+        channel.add_sample(TelemetrySample::with_timestamp(SampleValue::Float32(2.0), 5_000));
+        assert_eq!(newest_timestamp(&channel), 1_700_000_000_010);
+        
+        channel.add_samples(&[TelemetrySample::with_timestamp(SampleValue::Float32(3.0), 6_000)]);
+        assert_eq!(newest_timestamp(&channel), 1_700_000_001_010);
+    }
+}
@@ -3,10 +3,20 @@
 //! Provides export capabilities for telemetry data in various formats
 //! including JSON, CSV, and binary formats.
 
-use crate::telemetry::{TelemetrySample, ChannelExportData};
+use crate::telemetry::{TelemetrySample, ChannelExportData, ChannelConfig, ChannelStats, SampleType, SampleValue};
 use std::collections::HashMap;
 use serde::{Serialize, Deserialize};
 use std::io::Write;
+use std::time::{Duration, UNIX_EPOCH};
+
+/// Magic bytes opening every packed binary channel block
+pub const BINARY_MAGIC: &[u8; 4] = b"MCTB";
+
+/// Current packed binary layout version
+pub const BINARY_FORMAT_VERSION: u8 = 1;
+
+/// Header flag: each sample is preceded by a u64 timestamp (ms)
+const FLAG_TIMESTAMPS: u8 = 0x01;
 
 /// Supported export formats
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -17,12 +27,234 @@ pub enum ExportFormat {
     JsonPretty,
     /// CSV format (for spreadsheets)
     Csv,
-    /// Binary format (compact)
-    Binary,
+    /// Packed binary samples behind a self-describing header
+    Binary {
+        /// Byte order of every multi-byte field after the endianness marker
+        endian: Endian,
+        /// Emit a timestamp column before each value
+        include_timestamps: bool,
+    },
     /// MessagePack format
     MessagePack,
 }
 
+/// Byte order for packed binary exports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Endian {
+    #[default]
+    Little,
+    Big,
+}
+
+impl Endian {
+    /// Marker byte stored in the packed header
+    fn marker(self) -> u8 {
+        match self {
+            Endian::Little => b'L',
+            Endian::Big => b'B',
+        }
+    }
+    
+    fn from_marker(marker: u8) -> Option<Self> {
+        match marker {
+            b'L' => Some(Endian::Little),
+            b'B' => Some(Endian::Big),
+            _ => None,
+        }
+    }
+    
+    fn put_u16(self, out: &mut Vec<u8>, value: u16) {
+        match self {
+            Endian::Little => out.extend_from_slice(&value.to_le_bytes()),
+            Endian::Big => out.extend_from_slice(&value.to_be_bytes()),
+        }
+    }
+    
+    fn put_u32(self, out: &mut Vec<u8>, value: u32) {
+        match self {
+            Endian::Little => out.extend_from_slice(&value.to_le_bytes()),
+            Endian::Big => out.extend_from_slice(&value.to_be_bytes()),
+        }
+    }
+    
+    fn put_u64(self, out: &mut Vec<u8>, value: u64) {
+        match self {
+            Endian::Little => out.extend_from_slice(&value.to_le_bytes()),
+            Endian::Big => out.extend_from_slice(&value.to_be_bytes()),
+        }
+    }
+}
+
+/// Header of a packed binary channel block
+///
+/// Layout (multi-byte fields use the byte order named at offset 5):
+///
+/// | offset | size | field                                          |
+/// |--------|------|------------------------------------------------|
+/// | 0      | 4    | magic `MCTB`                                   |
+/// | 4      | 1    | format version                                 |
+/// | 5      | 1    | endianness: `L` (little) or `B` (big)          |
+/// | 6      | 1    | flags: bit 0 = timestamp column present        |
+/// | 7      | 1    | value type: 0=f32, 1=f64, 2=i32, 3=u32, 4=bool, |
+/// |        |      | 5=string, 6=bytes, 7=vector                    |
+/// | 8      | 8    | export time (Unix ms)                          |
+/// | 16     | 8    | sample count                                   |
+/// | 24     | 2    | channel name length N                          |
+/// | 26     | N    | channel name (UTF-8)                           |
+///
+/// Samples follow the header, each an optional u64 timestamp then the value
+/// (4 bytes for f32/i32/u32, 8 for f64, 1 for bool). String and bytes values
+/// are a u32 byte length then the bytes (UTF-8 for strings); vectors are a
+/// u32 element count then that many f32.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BinaryHeader {
+    pub version: u8,
+    pub endian: Endian,
+    pub include_timestamps: bool,
+    pub sample_type: SampleType,
+    pub exported_at_ms: u64,
+    pub sample_count: u64,
+    pub channel: String,
+}
+
+impl BinaryHeader {
+    /// Parse a header, returning it with the number of bytes consumed
+    pub fn decode(data: &[u8]) -> Result<(Self, usize), String> {
+        if data.len() < 8 || &data[0..4] != BINARY_MAGIC {
+            return Err("Not a packed telemetry block (bad magic)".to_string());
+        }
+        
+        let version = data[4];
+        if version != BINARY_FORMAT_VERSION {
+            return Err(format!("Unsupported packed format version {}", version));
+        }
+        let endian = Endian::from_marker(data[5])
+            .ok_or_else(|| format!("Unknown endianness marker 0x{:02x}", data[5]))?;
+        let include_timestamps = data[6] & FLAG_TIMESTAMPS != 0;
+        let sample_type = sample_type_from_code(data[7])?;
+        
+        let mut reader = PackedReader { data, pos: 8, endian };
+        let exported_at_ms = reader.read_u64()?;
+        let sample_count = reader.read_u64()?;
+        let name_len = reader.read_u16()? as usize;
+        let channel = String::from_utf8(reader.take(name_len)?.to_vec())
+            .map_err(|e| format!("Invalid channel name: {}", e))?;
+        
+        Ok((
+            Self {
+                version,
+                endian,
+                include_timestamps,
+                sample_type,
+                exported_at_ms,
+                sample_count,
+                channel,
+            },
+            reader.pos,
+        ))
+    }
+    
+    fn encode(&self, out: &mut Vec<u8>) -> Result<(), String> {
+        let name_len = u16::try_from(self.channel.len())
+            .map_err(|_| format!("Channel name too long: {} bytes", self.channel.len()))?;
+        
+        out.extend_from_slice(BINARY_MAGIC);
+        out.push(self.version);
+        out.push(self.endian.marker());
+        out.push(if self.include_timestamps { FLAG_TIMESTAMPS } else { 0 });
+        out.push(sample_type_code(self.sample_type));
+        self.endian.put_u64(out, self.exported_at_ms);
+        self.endian.put_u64(out, self.sample_count);
+        self.endian.put_u16(out, name_len);
+        out.extend_from_slice(self.channel.as_bytes());
+        Ok(())
+    }
+    
+    /// Size in bytes of one packed sample, or `None` for length-prefixed values
+    pub fn sample_size(&self) -> Option<usize> {
+        let value_size = match self.sample_type {
+            SampleType::Float32 | SampleType::Int32 | SampleType::UInt32 => 4,
+            SampleType::Float64 => 8,
+            SampleType::Bool => 1,
+            SampleType::String | SampleType::Bytes | SampleType::Vector => return None,
+        };
+        Some(if self.include_timestamps { value_size + 8 } else { value_size })
+    }
+}
+
+fn sample_type_code(sample_type: SampleType) -> u8 {
+    match sample_type {
+        SampleType::Float32 => 0,
+        SampleType::Float64 => 1,
+        SampleType::Int32 => 2,
+        SampleType::UInt32 => 3,
+        SampleType::Bool => 4,
+        SampleType::String => 5,
+        SampleType::Bytes => 6,
+        SampleType::Vector => 7,
+    }
+}
+
+fn sample_type_from_code(code: u8) -> Result<SampleType, String> {
+    match code {
+        0 => Ok(SampleType::Float32),
+        1 => Ok(SampleType::Float64),
+        2 => Ok(SampleType::Int32),
+        3 => Ok(SampleType::UInt32),
+        4 => Ok(SampleType::Bool),
+        5 => Ok(SampleType::String),
+        6 => Ok(SampleType::Bytes),
+        7 => Ok(SampleType::Vector),
+        _ => Err(format!("Unknown packed value type {}", code)),
+    }
+}
+
+/// Cursor over packed bytes in a fixed byte order
+struct PackedReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    endian: Endian,
+}
+
+impl<'a> PackedReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let end = self.pos.checked_add(len)
+            .filter(|&end| end <= self.data.len())
+            .ok_or_else(|| "Packed data truncated".to_string())?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+    
+    fn read_u8(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+    
+    fn read_u16(&mut self) -> Result<u16, String> {
+        let bytes: [u8; 2] = self.take(2)?.try_into().unwrap();
+        Ok(match self.endian {
+            Endian::Little => u16::from_le_bytes(bytes),
+            Endian::Big => u16::from_be_bytes(bytes),
+        })
+    }
+    
+    fn read_u32(&mut self) -> Result<u32, String> {
+        let bytes: [u8; 4] = self.take(4)?.try_into().unwrap();
+        Ok(match self.endian {
+            Endian::Little => u32::from_le_bytes(bytes),
+            Endian::Big => u32::from_be_bytes(bytes),
+        })
+    }
+    
+    fn read_u64(&mut self) -> Result<u64, String> {
+        let bytes: [u8; 8] = self.take(8)?.try_into().unwrap();
+        Ok(match self.endian {
+            Endian::Little => u64::from_le_bytes(bytes),
+            Endian::Big => u64::from_be_bytes(bytes),
+        })
+    }
+}
+
 /// Telemetry data exporter
 pub struct TelemetryExporter {
     compression: bool,
@@ -48,7 +280,11 @@ impl TelemetryExporter {
             ExportFormat::Json => self.export_json(data, false),
             ExportFormat::JsonPretty => self.export_json(data, true),
             ExportFormat::Csv => self.export_csv(data),
-            ExportFormat::Binary => self.export_binary(data),
+            ExportFormat::Binary { endian, include_timestamps } => {
+                let mut out = Vec::new();
+                pack_channel(data, endian, include_timestamps, &mut out)?;
+                self.maybe_compress(out)
+            }
             ExportFormat::MessagePack => self.export_messagepack(data),
         }
     }
@@ -65,15 +301,20 @@ impl TelemetryExporter {
                 self.export_multiple_json(channels, pretty)
             }
             ExportFormat::Csv => self.export_multiple_csv(channels),
-            ExportFormat::Binary | ExportFormat::MessagePack => {
-                // For binary formats, serialize the entire HashMap
-                if format == ExportFormat::Binary {
-                    bincode::serialize(&channels)
-                        .map_err(|e| format!("Binary serialization failed: {}", e))
-                } else {
-                    rmp_serde::to_vec(&channels)
-                        .map_err(|e| format!("MessagePack serialization failed: {}", e))
+            ExportFormat::Binary { endian, include_timestamps } => {
+                // Packed blocks are self-describing, so channels are simply concatenated
+                let mut names: Vec<_> = channels.keys().cloned().collect();
+                names.sort();
+                
+                let mut out = Vec::new();
+                for name in names {
+                    pack_channel(&channels[&name], endian, include_timestamps, &mut out)?;
                 }
+                self.maybe_compress(out)
+            }
+            ExportFormat::MessagePack => {
+                rmp_serde::to_vec(&channels)
+                    .map_err(|e| format!("MessagePack serialization failed: {}", e))
             }
         }
     }
@@ -211,13 +452,6 @@ impl TelemetryExporter {
         self.maybe_compress(data)
     }
     
-    /// Export to binary format
-    fn export_binary(&self, data: &ChannelExportData) -> Result<Vec<u8>, String> {
-        bincode::serialize(data)
-            .map_err(|e| format!("Binary serialization failed: {}", e))
-            .and_then(|data| self.maybe_compress(data))
-    }
-    
     /// Export to MessagePack format
     fn export_messagepack(&self, data: &ChannelExportData) -> Result<Vec<u8>, String> {
         rmp_serde::to_vec(data)
@@ -242,6 +476,134 @@ impl TelemetryExporter {
     }
 }
 
+/// Append one channel as a packed binary block
+fn pack_channel(
+    data: &ChannelExportData,
+    endian: Endian,
+    include_timestamps: bool,
+    out: &mut Vec<u8>,
+) -> Result<(), String> {
+    let sample_type = data.samples.first()
+        .map(|s| s.sample_type())
+        .unwrap_or(data.config.sample_type);
+    
+    let header = BinaryHeader {
+        version: BINARY_FORMAT_VERSION,
+        endian,
+        include_timestamps,
+        sample_type,
+        exported_at_ms: data.exported_at
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0),
+        sample_count: data.samples.len() as u64,
+        channel: data.config.name.clone(),
+    };
+    header.encode(out)?;
+    
+    for sample in &data.samples {
+        if include_timestamps {
+            endian.put_u64(out, sample.timestamp_ms);
+        }
+        match (&sample.value, sample_type) {
+            (SampleValue::Float32(v), SampleType::Float32) => endian.put_u32(out, v.to_bits()),
+            (SampleValue::Float64(v), SampleType::Float64) => endian.put_u64(out, v.to_bits()),
+            (SampleValue::Int32(v), SampleType::Int32) => endian.put_u32(out, *v as u32),
+            (SampleValue::UInt32(v), SampleType::UInt32) => endian.put_u32(out, *v),
+            (SampleValue::Bool(v), SampleType::Bool) => out.push(*v as u8),
+            (SampleValue::String(v), SampleType::String) => {
+                endian.put_u32(out, packed_len(v.len(), &data.config.name)?);
+                out.extend_from_slice(v.as_bytes());
+            }
+            (SampleValue::Bytes(v), SampleType::Bytes) => {
+                endian.put_u32(out, packed_len(v.len(), &data.config.name)?);
+                out.extend_from_slice(v);
+            }
+            (SampleValue::Vector(v), SampleType::Vector) => {
+                endian.put_u32(out, packed_len(v.len(), &data.config.name)?);
+                for element in v {
+                    endian.put_u32(out, element.to_bits());
+                }
+            }
+            _ => {
+                return Err(format!(
+                    "Channel '{}' mixes {:?} with {:?} samples; packed export needs one type",
+                    data.config.name, sample_type, sample.sample_type()
+                ));
+            }
+        }
+    }
+    
+    Ok(())
+}
+
+/// Length prefix for a string, bytes or vector value
+fn packed_len(len: usize, channel: &str) -> Result<u32, String> {
+    u32::try_from(len).map_err(|_| format!("Channel '{}' has a value too long to pack: {}", channel, len))
+}
+
+/// Read one packed binary block, returning the channel data and bytes consumed
+fn unpack_channel(data: &[u8]) -> Result<(ChannelExportData, usize), String> {
+    let (header, header_len) = BinaryHeader::decode(data)?;
+    
+    // Fixed-size samples can be checked up front; length-prefixed ones as they are read
+    if let Some(sample_size) = header.sample_size() {
+        let body_len = (header.sample_count as usize)
+            .checked_mul(sample_size)
+            .ok_or_else(|| "Packed sample count overflows".to_string())?;
+        if data.len() - header_len < body_len {
+            return Err("Packed data truncated".to_string());
+        }
+    }
+    
+    let mut reader = PackedReader { data, pos: header_len, endian: header.endian };
+    let mut samples = Vec::with_capacity((header.sample_count as usize).min(data.len()));
+    for _ in 0..header.sample_count {
+        let timestamp_ms = if header.include_timestamps { reader.read_u64()? } else { 0 };
+        let value = match header.sample_type {
+            SampleType::Float32 => SampleValue::Float32(f32::from_bits(reader.read_u32()?)),
+            SampleType::Float64 => SampleValue::Float64(f64::from_bits(reader.read_u64()?)),
+            SampleType::Int32 => SampleValue::Int32(reader.read_u32()? as i32),
+            SampleType::UInt32 => SampleValue::UInt32(reader.read_u32()?),
+            SampleType::Bool => SampleValue::Bool(reader.read_u8()? != 0),
+            SampleType::String => {
+                let len = reader.read_u32()? as usize;
+                let text = String::from_utf8(reader.take(len)?.to_vec())
+                    .map_err(|e| format!("Invalid string sample: {}", e))?;
+                SampleValue::String(text)
+            }
+            SampleType::Bytes => {
+                let len = reader.read_u32()? as usize;
+                SampleValue::Bytes(reader.take(len)?.to_vec())
+            }
+            SampleType::Vector => {
+                let count = reader.read_u32()? as usize;
+                let mut elements = Vec::with_capacity(count.min(data.len() / 4));
+                for _ in 0..count {
+                    elements.push(f32::from_bits(reader.read_u32()?));
+                }
+                SampleValue::Vector(elements)
+            }
+        };
+        samples.push(TelemetrySample::with_timestamp(value, timestamp_ms));
+    }
+    
+    let mut stats = ChannelStats::new(header.channel.clone());
+    stats.total_samples = header.sample_count;
+    let export = ChannelExportData {
+        config: ChannelConfig {
+            name: header.channel,
+            sample_type: header.sample_type,
+            ..Default::default()
+        },
+        samples,
+        stats,
+        exported_at: UNIX_EPOCH + Duration::from_millis(header.exported_at_ms),
+    };
+    
+    Ok((export, reader.pos))
+}
+
 /// Format sample value as string for CSV
 fn format_sample_value(sample: &TelemetrySample) -> String {
     use crate::telemetry::SampleValue;
@@ -284,7 +646,10 @@ impl TelemetryImporter {
             .map_err(|e| format!("JSON deserialization failed: {}", e))
     }
     
-    /// Import from binary format
+    /// Import from packed binary format
+    ///
+    /// Byte order, timestamps and value type are read from the block header.
+    /// Data without the packed magic is decoded as a legacy bincode export.
     pub fn import_binary(data: &[u8]) -> Result<ChannelExportData, String> {
         // Check if compressed
         let data = if data.starts_with(&[0x1f, 0x8b]) {
@@ -300,10 +665,40 @@ impl TelemetryImporter {
             data.to_vec()
         };
         
+        if data.starts_with(BINARY_MAGIC) {
+            return unpack_channel(&data).map(|(export, _)| export);
+        }
+        
         bincode::deserialize(&data)
             .map_err(|e| format!("Binary deserialization failed: {}", e))
     }
     
+    /// Import every packed block written by a multi-channel binary export
+    pub fn import_binary_multiple(data: &[u8]) -> Result<HashMap<String, ChannelExportData>, String> {
+        let data = if data.starts_with(&[0x1f, 0x8b]) {
+            use flate2::read::GzDecoder;
+            use std::io::Read;
+            
+            let mut decoder = GzDecoder::new(data);
+            let mut decompressed = Vec::new();
+            decoder.read_to_end(&mut decompressed)
+                .map_err(|e| format!("Decompression failed: {}", e))?;
+            decompressed
+        } else {
+            data.to_vec()
+        };
+        
+        let mut channels = HashMap::new();
+        let mut pos = 0;
+        while pos < data.len() {
+            let (export, consumed) = unpack_channel(&data[pos..])?;
+            pos += consumed;
+            channels.insert(export.config.name.clone(), export);
+        }
+        
+        Ok(channels)
+    }
+    
     /// Import from MessagePack format
     pub fn import_messagepack(data: &[u8]) -> Result<ChannelExportData, String> {
         // Check if compressed
@@ -388,7 +783,8 @@ mod tests {
         let data = create_test_export_data();
         
         // Export
-        let result = exporter.export(&data, ExportFormat::Binary);
+        let format = ExportFormat::Binary { endian: Endian::Little, include_timestamps: true };
+        let result = exporter.export(&data, format);
         assert!(result.is_ok());
         
         // Import
@@ -413,4 +809,149 @@ mod tests {
         let imported = TelemetryImporter::import_json(&compressed);
         assert!(imported.is_ok());
     }
-}
\ No newline at end of file
+    
+    fn export_data_with(name: &str, sample_type: SampleType, values: Vec<SampleValue>) -> ChannelExportData {
+        let samples = values
+            .into_iter()
+            .enumerate()
+            .map(|(i, value)| TelemetrySample::with_timestamp(value, 1_700_000_000_000 + i as u64))
+            .collect();
+        
+        ChannelExportData {
+            config: ChannelConfig {
+                name: name.to_string(),
+                sample_type,
+                ..Default::default()
+            },
+            samples,
+            stats: ChannelStats::new(name.to_string()),
+            exported_at: UNIX_EPOCH + Duration::from_millis(1_700_000_123_000),
+        }
+    }
+    
+    #[test]
+    fn test_packed_round_trip_both_endians() {
+        let exporter = TelemetryExporter::new();
+        let float_data = export_data_with("temp", SampleType::Float64, vec![
+            SampleValue::Float64(-1.5),
+            SampleValue::Float64(3.25e10),
+        ]);
+        let int_data = export_data_with("count", SampleType::Int32, vec![
+            SampleValue::Int32(-7),
+            SampleValue::Int32(i32::MAX),
+            SampleValue::Int32(0x0102_0304),
+        ]);
+        
+        for endian in [Endian::Little, Endian::Big] {
+            for include_timestamps in [true, false] {
+                let format = ExportFormat::Binary { endian, include_timestamps };
+                
+                let packed = exporter.export(&float_data, format).unwrap();
+                let imported = TelemetryImporter::import_binary(&packed).unwrap();
+                assert_eq!(imported.config.name, "temp");
+                let values: Vec<_> = imported.samples.iter()
+                    .map(|s| match s.value { SampleValue::Float64(v) => v, ref other => panic!("{:?}", other) })
+                    .collect();
+                assert_eq!(values, vec![-1.5, 3.25e10]);
+                
+                let packed = exporter.export(&int_data, format).unwrap();
+                let imported = TelemetryImporter::import_binary(&packed).unwrap();
+                let values: Vec<_> = imported.samples.iter()
+                    .map(|s| match s.value { SampleValue::Int32(v) => v, ref other => panic!("{:?}", other) })
+                    .collect();
+                assert_eq!(values, vec![-7, i32::MAX, 0x0102_0304]);
+                
+                let timestamps: Vec<_> = imported.samples.iter().map(|s| s.timestamp_ms).collect();
+                if include_timestamps {
+                    assert_eq!(timestamps, vec![1_700_000_000_000, 1_700_000_000_001, 1_700_000_000_002]);
+                } else {
+                    assert_eq!(timestamps, vec![0, 0, 0]);
+                }
+            }
+        }
+    }
+    
+    #[test]
+    fn test_packed_header_describes_layout() {
+        let data = export_data_with("count", SampleType::Int32, vec![SampleValue::Int32(0x0102_0304)]);
+        let exporter = TelemetryExporter::new();
+        
+        let big = exporter.export(&data, ExportFormat::Binary { endian: Endian::Big, include_timestamps: false }).unwrap();
+        let (header, header_len) = BinaryHeader::decode(&big).unwrap();
+        assert_eq!(header, BinaryHeader {
+            version: BINARY_FORMAT_VERSION,
+            endian: Endian::Big,
+            include_timestamps: false,
+            sample_type: SampleType::Int32,
+            exported_at_ms: 1_700_000_123_000,
+            sample_count: 1,
+            channel: "count".to_string(),
+        });
+        assert_eq!(&big[0..4], BINARY_MAGIC);
+        assert_eq!(big[5], b'B');
+        assert_eq!(header_len, 26 + "count".len());
+        assert_eq!(&big[header_len..], &[0x01, 0x02, 0x03, 0x04]);
+        
+        let little = exporter.export(&data, ExportFormat::Binary { endian: Endian::Little, include_timestamps: true }).unwrap();
+        let (header, header_len) = BinaryHeader::decode(&little).unwrap();
+        assert_eq!(header.endian, Endian::Little);
+        assert!(header.include_timestamps);
+        assert_eq!(Some(little.len() - header_len), header.sample_size());
+        assert_eq!(&little[little.len() - 4..], &[0x04, 0x03, 0x02, 0x01]);
+    }
+    
+    #[test]
+    fn test_packed_multiple_channels() {
+        let mut channels = HashMap::new();
+        channels.insert("a".to_string(), export_data_with("a", SampleType::Bool, vec![SampleValue::Bool(true)]));
+        channels.insert("b".to_string(), export_data_with("b", SampleType::UInt32, vec![SampleValue::UInt32(9)]));
+        
+        let packed = TelemetryExporter::new()
+            .export_multiple(channels, ExportFormat::Binary { endian: Endian::Big, include_timestamps: true })
+            .unwrap();
+        let imported = TelemetryImporter::import_binary_multiple(&packed).unwrap();
+        
+        assert_eq!(imported.len(), 2);
+        assert!(matches!(imported["a"].samples[0].value, SampleValue::Bool(true)));
+        assert!(matches!(imported["b"].samples[0].value, SampleValue::UInt32(9)));
+    }
+    
+    #[test]
+    fn test_packed_round_trip_variable_length_values() {
+        let exporter = TelemetryExporter::new();
+        let events = export_data_with("events", SampleType::String, vec![
+            SampleValue::String("armed".to_string()),
+            SampleValue::String(String::new()),
+            SampleValue::String("motor °C".to_string()),
+        ]);
+        let frames = export_data_with("frames", SampleType::Bytes, vec![
+            SampleValue::Bytes(vec![0x00, 0xff, 0x10]),
+        ]);
+        let imu = export_data_with("imu", SampleType::Vector, vec![
+            SampleValue::Vector(vec![0.5, -1.25, 9.81]),
+            SampleValue::Vector(vec![]),
+        ]);
+        
+        for endian in [Endian::Little, Endian::Big] {
+            let format = ExportFormat::Binary { endian, include_timestamps: true };
+            
+            let imported = TelemetryImporter::import_binary(&exporter.export(&events, format).unwrap()).unwrap();
+            let texts: Vec<_> = imported.samples.iter()
+                .map(|s| match &s.value { SampleValue::String(v) => v.clone(), other => panic!("{:?}", other) })
+                .collect();
+            assert_eq!(texts, vec!["armed", "", "motor °C"]);
+            assert_eq!(imported.samples[2].timestamp_ms, 1_700_000_000_002);
+            
+            let imported = TelemetryImporter::import_binary(&exporter.export(&frames, format).unwrap()).unwrap();
+            assert!(matches!(&imported.samples[0].value, SampleValue::Bytes(v) if *v == vec![0x00, 0xff, 0x10]));
+            
+            let imported = TelemetryImporter::import_binary(&exporter.export(&imu, format).unwrap()).unwrap();
+            assert!(matches!(&imported.samples[0].value, SampleValue::Vector(v) if *v == vec![0.5, -1.25, 9.81]));
+            assert!(matches!(&imported.samples[1].value, SampleValue::Vector(v) if v.is_empty()));
+        }
+        
+        // A cut-off value is reported, not read past
+        let packed = exporter.export(&events, ExportFormat::Binary { endian: Endian::Little, include_timestamps: false }).unwrap();
+        assert!(TelemetryImporter::import_binary(&packed[..packed.len() - 2]).is_err());
+    }
+}
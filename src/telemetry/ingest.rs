@@ -0,0 +1,250 @@
+//! Bounded, back-pressured ingest channel between device tasks and the UI
+//!
+//! Unbounded channels let a fast device queue responses faster than the UI
+//! drains them, growing memory without limit. An ingest channel holds at most
+//! `capacity` items and either drops the oldest queued item or blocks the
+//! sender when full, counting every drop so it can be surfaced to the user.
+
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::sync::Notify;
+
+/// Default number of queued items before the overflow policy applies
+pub const DEFAULT_INGEST_CAPACITY: usize = 1024;
+
+/// What a full channel does with a new item
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverflowPolicy {
+    /// Evict the oldest queued item (counted as dropped)
+    #[default]
+    DropOldest,
+    /// Wait for the receiver to make room
+    Block,
+}
+
+struct Shared<T> {
+    queue: Mutex<VecDeque<T>>,
+    capacity: usize,
+    policy: OverflowPolicy,
+    dropped: AtomicU64,
+    senders: AtomicUsize,
+    receiver_closed: AtomicBool,
+    item_ready: Notify,
+    space_ready: Notify,
+}
+
+/// Create a bounded ingest channel
+pub fn ingest_channel<T>(capacity: usize, policy: OverflowPolicy) -> (IngestSender<T>, IngestReceiver<T>) {
+    let capacity = capacity.max(1);
+    let shared = Arc::new(Shared {
+        queue: Mutex::new(VecDeque::with_capacity(capacity)),
+        capacity,
+        policy,
+        dropped: AtomicU64::new(0),
+        senders: AtomicUsize::new(1),
+        receiver_closed: AtomicBool::new(false),
+        item_ready: Notify::new(),
+        space_ready: Notify::new(),
+    });
+    
+    (IngestSender { shared: shared.clone() }, IngestReceiver { shared })
+}
+
+/// Sending half of an ingest channel
+pub struct IngestSender<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> IngestSender<T> {
+    /// Queue an item, applying the overflow policy if the channel is full
+    /// Returns the item back if the receiver has been dropped
+    pub async fn send(&self, item: T) -> Result<(), T> {
+        let mut item = item;
+        loop {
+            match self.try_send(item) {
+                Ok(()) => return Ok(()),
+                Err(returned) if self.shared.receiver_closed.load(Ordering::Acquire) => return Err(returned),
+                Err(returned) => {
+                    item = returned;
+                    self.shared.space_ready.notified().await;
+                }
+            }
+        }
+    }
+    
+    /// Queue an item without waiting
+    /// With `Block`, a full channel hands the item back instead of queuing it
+    pub fn try_send(&self, item: T) -> Result<(), T> {
+        if self.shared.receiver_closed.load(Ordering::Acquire) {
+            return Err(item);
+        }
+        
+        {
+            let mut queue = self.shared.queue.lock();
+            if queue.len() >= self.shared.capacity {
+                match self.shared.policy {
+                    OverflowPolicy::DropOldest => {
+                        queue.pop_front();
+                        self.shared.dropped.fetch_add(1, Ordering::Relaxed);
+                    }
+                    OverflowPolicy::Block => return Err(item),
+                }
+            }
+            queue.push_back(item);
+        }
+        
+        self.shared.item_ready.notify_one();
+        Ok(())
+    }
+    
+    /// Number of items dropped because the channel was full
+    pub fn dropped(&self) -> u64 {
+        self.shared.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl<T> Clone for IngestSender<T> {
+    fn clone(&self) -> Self {
+        self.shared.senders.fetch_add(1, Ordering::AcqRel);
+        Self { shared: self.shared.clone() }
+    }
+}
+
+impl<T> Drop for IngestSender<T> {
+    fn drop(&mut self) {
+        if self.shared.senders.fetch_sub(1, Ordering::AcqRel) == 1 {
+            // Wake a receiver waiting in `recv` so it sees the channel closed
+            self.shared.item_ready.notify_one();
+        }
+    }
+}
+
+/// Receiving half of an ingest channel
+pub struct IngestReceiver<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> IngestReceiver<T> {
+    /// Take the oldest queued item, if any
+    pub fn try_recv(&self) -> Option<T> {
+        let item = self.shared.queue.lock().pop_front();
+        if item.is_some() {
+            self.shared.space_ready.notify_one();
+        }
+        item
+    }
+    
+    /// Wait for the next item; `None` once every sender is gone and the queue is empty
+    pub async fn recv(&self) -> Option<T> {
+        loop {
+            if let Some(item) = self.try_recv() {
+                return Some(item);
+            }
+            if self.shared.senders.load(Ordering::Acquire) == 0 {
+                return None;
+            }
+            self.shared.item_ready.notified().await;
+        }
+    }
+    
+    /// Number of items dropped because the channel was full
+    pub fn dropped(&self) -> u64 {
+        self.shared.dropped.load(Ordering::Relaxed)
+    }
+    
+    /// Items currently queued
+    pub fn len(&self) -> usize {
+        self.shared.queue.lock().len()
+    }
+    
+    /// Whether no items are queued
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    
+    /// Maximum number of queued items
+    pub fn capacity(&self) -> usize {
+        self.shared.capacity
+    }
+}
+
+impl<T> Drop for IngestReceiver<T> {
+    fn drop(&mut self) {
+        self.shared.receiver_closed.store(true, Ordering::Release);
+        // Release any sender blocked on a full channel
+        self.shared.space_ready.notify_waiters();
+        self.shared.space_ready.notify_one();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    
+    #[test]
+    fn test_flood_drops_oldest_and_counts() {
+        let (tx, rx) = ingest_channel(100, OverflowPolicy::DropOldest);
+        
+        for i in 0..10_000u32 {
+            assert!(tx.try_send(i).is_ok());
+        }
+        
+        // Memory stays bounded at capacity and the overflow is counted
+        assert_eq!(rx.len(), 100);
+        assert_eq!(rx.dropped(), 9_900);
+        assert_eq!(tx.dropped(), 9_900);
+        
+        // The newest items survive
+        let received: Vec<u32> = std::iter::from_fn(|| rx.try_recv()).collect();
+        assert_eq!(received, (9_900..10_000).collect::<Vec<_>>());
+    }
+    
+    #[tokio::test]
+    async fn test_block_policy_waits_for_room() {
+        let (tx, rx) = ingest_channel(2, OverflowPolicy::Block);
+        
+        tx.send(1).await.unwrap();
+        tx.send(2).await.unwrap();
+        assert_eq!(tx.try_send(3), Err(3));
+        
+        let sender = tx.clone();
+        let blocked = tokio::spawn(async move { sender.send(3).await });
+        
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!blocked.is_finished());
+        assert_eq!(rx.len(), 2);
+        
+        assert_eq!(rx.recv().await, Some(1));
+        blocked.await.unwrap().unwrap();
+        
+        assert_eq!(rx.recv().await, Some(2));
+        assert_eq!(rx.recv().await, Some(3));
+        assert_eq!(rx.dropped(), 0);
+    }
+    
+    #[tokio::test]
+    async fn test_recv_ends_when_senders_dropped() {
+        let (tx, rx) = ingest_channel(4, OverflowPolicy::DropOldest);
+        let tx2 = tx.clone();
+        
+        tx.send("a").await.unwrap();
+        drop(tx);
+        tx2.send("b").await.unwrap();
+        drop(tx2);
+        
+        assert_eq!(rx.recv().await, Some("a"));
+        assert_eq!(rx.recv().await, Some("b"));
+        assert_eq!(rx.recv().await, None);
+    }
+    
+    #[tokio::test]
+    async fn test_send_fails_after_receiver_dropped() {
+        let (tx, rx) = ingest_channel(1, OverflowPolicy::Block);
+        tx.send(1).await.unwrap();
+        drop(rx);
+        assert_eq!(tx.send(2).await, Err(2));
+    }
+}
@@ -8,6 +8,12 @@ pub mod ring_buffer;
 pub mod sample;
 pub mod channel;
 pub mod export;
+pub mod sink;
+pub mod persist;
+pub mod ingest;
+pub mod poller;
+pub mod timestamp;
+pub mod aggregate;
 // pub mod parser;  // TODO: Task 29 - implement parser module
 // pub mod buffer;  // TODO: Task 29 - implement buffer module
 
@@ -15,16 +21,33 @@ pub use ring_buffer::{RingBuffer, RingBufferStats};
 pub use sample::{TelemetrySample, SampleMetadata, SampleType, SampleValue, SampleStatistics};
 pub use channel::{TelemetryChannel, ChannelConfig, ChannelStats, ChannelExportData};
 pub use export::{ExportFormat, TelemetryExporter, TelemetryImporter};
+pub use sink::{TelemetrySink, CallbackSink, InfluxLineSink, BufferedSink};
+pub use ingest::{ingest_channel, IngestSender, IngestReceiver, OverflowPolicy};
+pub use poller::TelemetryPoller;
+pub use timestamp::{TimestampSource, SampleClock};
+pub use aggregate::AggKind;
 // pub use parser::*;  // TODO: Task 29 - implement parser module
 // pub use buffer::*;  // TODO: Task 29 - implement buffer module
 
 use std::sync::Arc;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use parking_lot::RwLock;
+use aggregate::AggregateSink;
+
+/// Group name for channels without an explicit `group`
+pub const UNGROUPED_CHANNEL_GROUP: &str = "Ungrouped";
+
+/// Largest decimation factor `MemoryPressurePolicy::ReduceSampleRate` escalates to
+pub const MAX_PRESSURE_DECIMATION: u32 = 16;
+
+/// Aggregate sinks keyed by the source channel pattern they were created for
+type PatternAggregates = Vec<(String, Arc<AggregateSink>)>;
 
 /// Telemetry system manager that coordinates multiple channels
 pub struct TelemetrySystem {
     channels: Arc<RwLock<HashMap<String, Arc<TelemetryChannel>>>>,
+    /// Aggregates by source pattern, attached to matching channels as they're created
+    aggregates: Arc<RwLock<PatternAggregates>>,
     global_config: TelemetryConfig,
 }
 
@@ -39,6 +62,32 @@ pub struct TelemetryConfig {
     pub auto_memory_management: bool,
     /// Default sample rate (Hz) for channels
     pub default_sample_rate: f32,
+    /// How `enforce_memory_limits` degrades when over `max_memory_bytes`
+    pub memory_pressure_policy: MemoryPressurePolicy,
+}
+
+/// What the telemetry system gives up when it runs out of memory
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MemoryPressurePolicy {
+    /// Prune the same share of oldest samples from every channel
+    #[default]
+    DropOldestGlobally,
+    /// Keep history, but only accept every Nth incoming sample; N doubles
+    /// each time the limit is still exceeded, up to `MAX_PRESSURE_DECIMATION`
+    ReduceSampleRate,
+    /// Keep history and reject new samples until usage drops below the limit
+    PauseIngest,
+}
+
+/// Reported by `enforce_memory_limits` when the limit was exceeded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryPressureEvent {
+    pub policy: MemoryPressurePolicy,
+    pub limit_bytes: usize,
+    /// Usage that triggered the policy
+    pub usage_bytes: usize,
+    /// Usage after the policy was applied
+    pub usage_after_bytes: usize,
 }
 
 impl Default for TelemetryConfig {
@@ -48,6 +97,7 @@ impl Default for TelemetryConfig {
             max_memory_bytes: 50 * 1024 * 1024,  // 50MB default limit
             auto_memory_management: true,
             default_sample_rate: 30.0,  // 30 FPS for charts
+            memory_pressure_policy: MemoryPressurePolicy::default(),
         }
     }
 }
@@ -62,6 +112,7 @@ impl TelemetrySystem {
     pub fn with_config(config: TelemetryConfig) -> Self {
         Self {
             channels: Arc::new(RwLock::new(HashMap::new())),
+            aggregates: Arc::new(RwLock::new(Vec::new())),
             global_config: config,
         }
     }
@@ -73,10 +124,22 @@ impl TelemetrySystem {
             sample_rate: self.global_config.default_sample_rate,
             name: name.clone(),
             sample_type: SampleType::Float32,
+            persist_path: None,
+            tags: Vec::new(),
+            group: None,
+            timestamp_source: Default::default(),
         });
         
         let channel = Arc::new(TelemetryChannel::new(config));
-        self.channels.write().insert(name, channel.clone());
+        for (pattern, sink) in self.aggregates.read().iter() {
+            if sink.target_name() != name && aggregate::channel_matches(pattern, &name) {
+                sink.attach(&name);
+                channel.register_inline_sink(sink.clone());
+            }
+        }
+        if let Some(replaced) = self.channels.write().insert(name, channel.clone()) {
+            self.detach_from_aggregates(&replaced);
+        }
         channel
     }
     
@@ -85,9 +148,62 @@ impl TelemetrySystem {
         self.channels.read().get(name).cloned()
     }
     
+    /// Add a batch of samples to the named channel under one lock per batch
+    /// Returns false if there is no such channel
+    pub fn ingest_batch(&self, channel: &str, samples: &[TelemetrySample]) -> bool {
+        match self.get_channel(channel) {
+            Some(channel) => {
+                channel.add_samples(samples);
+                true
+            }
+            None => false,
+        }
+    }
+    
+    /// Derived channel combining every channel whose name matches `channel_pattern`
+    /// 
+    /// `*` in the pattern matches any run of characters. The derived channel is
+    /// named e.g. `avg(*/temperature)` and gets a new sample each time a source
+    /// accepts one, computed over each source's latest value. Matching channels
+    /// created later become sources too, and removed ones stop counting.
+    pub fn aggregate(&self, channel_pattern: &str, agg: AggKind) -> Arc<TelemetryChannel> {
+        let name = format!("{}({})", agg, channel_pattern);
+        // Unthrottled: the sources' own rate limits already apply
+        let derived = self.create_channel(name.clone(), Some(ChannelConfig {
+            name: name.clone(),
+            buffer_size: self.global_config.default_buffer_size,
+            sample_rate: 0.0,
+            sample_type: SampleType::Float32,
+            tags: vec!["aggregate".to_string()],
+            ..Default::default()
+        }));
+        
+        let sink = Arc::new(AggregateSink::new(agg, derived.clone()));
+        for (source_name, source) in self.channels.read().iter() {
+            if *source_name != name && aggregate::channel_matches(channel_pattern, source_name) {
+                sink.attach(source_name);
+                source.register_inline_sink(sink.clone());
+            }
+        }
+        self.aggregates.write().push((channel_pattern.to_string(), sink));
+        derived
+    }
+    
     /// Remove a channel
     pub fn remove_channel(&self, name: &str) -> Option<Arc<TelemetryChannel>> {
-        self.channels.write().remove(name)
+        let removed = self.channels.write().remove(name)?;
+        self.detach_from_aggregates(&removed);
+        Some(removed)
+    }
+    
+    /// Drop `channel` from every aggregate it feeds, and its aggregate if it is derived
+    fn detach_from_aggregates(&self, channel: &TelemetryChannel) {
+        let name = &channel.config().name;
+        let mut aggregates = self.aggregates.write();
+        aggregates.retain(|(_, sink)| sink.target_name() != name);
+        for (_, sink) in aggregates.iter() {
+            sink.detach(name);
+        }
     }
     
     /// Get all channel names
@@ -95,6 +211,31 @@ impl TelemetrySystem {
         self.channels.read().keys().cloned().collect()
     }
     
+    /// Channels keyed by display group, each group sorted by channel name
+    pub fn channels_by_group(&self) -> BTreeMap<String, Vec<Arc<TelemetryChannel>>> {
+        let mut groups: BTreeMap<String, Vec<Arc<TelemetryChannel>>> = BTreeMap::new();
+        for channel in self.channels.read().values() {
+            let group = channel.config().group.clone()
+                .unwrap_or_else(|| UNGROUPED_CHANNEL_GROUP.to_string());
+            groups.entry(group).or_default().push(channel.clone());
+        }
+        
+        for channels in groups.values_mut() {
+            channels.sort_by(|a, b| a.config().name.cmp(&b.config().name));
+        }
+        groups
+    }
+    
+    /// Channels carrying `tag`
+    pub fn channels_with_tag(&self, tag: &str) -> Vec<Arc<TelemetryChannel>> {
+        self.channels
+            .read()
+            .values()
+            .filter(|ch| ch.config().tags.iter().any(|t| t == tag))
+            .cloned()
+            .collect()
+    }
+    
     /// Get total memory usage across all channels
     pub fn total_memory_usage(&self) -> usize {
         self.channels
@@ -139,41 +280,75 @@ impl TelemetrySystem {
         }
     }
     
-    /// Enforce memory limits by pruning oldest data
-    pub fn enforce_memory_limits(&self) {
+    /// Apply the configured `MemoryPressurePolicy` if usage is over the limit
+    /// 
+    /// Returns the event (also logged as a warning) when the policy was
+    /// applied. Once usage is back under the limit, decimation and paused
+    /// ingest are lifted again.
+    pub fn enforce_memory_limits(&self) -> Option<MemoryPressureEvent> {
         if !self.global_config.auto_memory_management {
-            return;
+            return None;
         }
         
-        let mut iterations = 0;
-        const MAX_ITERATIONS: usize = 100;
+        // Clone the Arcs to avoid holding the lock while pruning
+        let channels: Vec<Arc<TelemetryChannel>> = self.channels.read().values().cloned().collect();
+        let limit_bytes = self.global_config.max_memory_bytes;
+        let usage_bytes = self.total_memory_usage();
+        
+        if usage_bytes <= limit_bytes {
+            for channel in &channels {
+                channel.set_decimation(1);
+                channel.set_ingest_paused(false);
+            }
+            return None;
+        }
         
-        while self.total_memory_usage() > self.global_config.max_memory_bytes {
-            iterations += 1;
-            if iterations > MAX_ITERATIONS {
-                // Prevent infinite loop - just clear everything if we can't get under limit
-                for channel in self.channels.read().values() {
-                    channel.clear();
+        let policy = self.global_config.memory_pressure_policy;
+        match policy {
+            MemoryPressurePolicy::DropOldestGlobally => {
+                const MAX_PASSES: usize = 10;
+                
+                let mut usage = usage_bytes;
+                for _ in 0..MAX_PASSES {
+                    if usage <= limit_bytes {
+                        break;
+                    }
+                    let excess_percent = ((usage - limit_bytes) * 100).div_ceil(usage).clamp(1, 100) as u8;
+                    for channel in &channels {
+                        channel.prune_oldest(excess_percent);
+                    }
+                    
+                    // Per-channel overhead can't be pruned; stop once nothing moves
+                    let pruned = self.total_memory_usage();
+                    if pruned >= usage {
+                        break;
+                    }
+                    usage = pruned;
                 }
-                break;
             }
-            
-            // Find channel with most data and prune it
-            // Clone the Arc to avoid holding the lock during pruning
-            let channel_to_prune = {
-                let channels = self.channels.read();
-                channels
-                    .iter()
-                    .max_by_key(|(_, ch)| ch.memory_usage())
-                    .map(|(_, ch)| ch.clone())
-            };
-            
-            if let Some(channel) = channel_to_prune {
-                channel.prune_oldest(10); // Remove 10% of oldest data
-            } else {
-                break;
+            MemoryPressurePolicy::ReduceSampleRate => {
+                for channel in &channels {
+                    channel.set_decimation((channel.decimation() * 2).min(MAX_PRESSURE_DECIMATION));
+                }
+            }
+            MemoryPressurePolicy::PauseIngest => {
+                for channel in &channels {
+                    channel.set_ingest_paused(true);
+                }
             }
         }
+        
+        let event = MemoryPressureEvent {
+            policy,
+            limit_bytes,
+            usage_bytes,
+            usage_after_bytes: self.total_memory_usage(),
+        };
+        tracing::warn!(
+            "Telemetry memory {} bytes over limit {} bytes, applied {:?} (now {} bytes)",
+            event.usage_bytes, event.limit_bytes, event.policy, event.usage_after_bytes
+        );
+        Some(event)
     }
 }
 
@@ -216,35 +391,198 @@ mod tests {
     }
     
     #[test]
-    #[ignore] // TODO: Fix pruning algorithm to actually reduce memory usage
+    fn test_channels_by_group() {
+        let system = TelemetrySystem::new();
+        let grouped = |name: &str, group: Option<&str>, tags: &[&str]| ChannelConfig {
+            name: name.to_string(),
+            group: group.map(str::to_string),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            ..Default::default()
+        };
+        
+        system.create_channel("m1_speed".into(), Some(grouped("m1_speed", Some("Motor 1"), &["motor"])));
+        system.create_channel("m1_current".into(), Some(grouped("m1_current", Some("Motor 1"), &["motor", "power"])));
+        system.create_channel("temp".into(), Some(grouped("temp", Some("Sensors"), &[])));
+        system.create_channel("raw".into(), None);
+        
+        let groups = system.channels_by_group();
+        let names = |group: &str| -> Vec<String> {
+            groups[group].iter().map(|ch| ch.config().name.clone()).collect()
+        };
+        
+        assert_eq!(groups.keys().collect::<Vec<_>>(), vec!["Motor 1", "Sensors", UNGROUPED_CHANNEL_GROUP]);
+        assert_eq!(names("Motor 1"), vec!["m1_current", "m1_speed"]);
+        assert_eq!(names("Sensors"), vec!["temp"]);
+        assert_eq!(names(UNGROUPED_CHANNEL_GROUP), vec!["raw"]);
+        
+        let mut power: Vec<_> = system.channels_with_tag("power").iter().map(|ch| ch.config().name.clone()).collect();
+        power.sort();
+        assert_eq!(power, vec!["m1_current"]);
+        assert_eq!(system.channels_with_tag("motor").len(), 2);
+    }
+    
+    #[test]
+    fn test_ingest_batch_routes_to_channel() {
+        let system = TelemetrySystem::new();
+        system.create_channel("imu".to_string(), Some(ChannelConfig {
+            name: "imu".to_string(),
+            sample_rate: 0.0,
+            ..Default::default()
+        }));
+        
+        let batch: Vec<_> = (0..5).map(|i| TelemetrySample::new_f32(i as f32)).collect();
+        assert!(system.ingest_batch("imu", &batch));
+        assert!(!system.ingest_batch("missing", &batch));
+        
+        let stats = system.get_channel("imu").unwrap().get_stats();
+        assert_eq!(stats.total_samples, 5);
+        assert_eq!(stats.buffer_write_locks, 1);
+    }
+    
+    /// System over its limit: two unthrottled channels, 1000 and 500 samples,
+    /// with the limit set to half of what they hold
+    fn system_under_pressure(policy: MemoryPressurePolicy) -> TelemetrySystem {
+        let filled = |config: TelemetryConfig| {
+            let system = TelemetrySystem::with_config(TelemetryConfig { default_sample_rate: 0.0, ..config });
+            for (name, count) in [("a", 1000), ("b", 500)] {
+                let channel = system.create_channel(name.to_string(), None);
+                for i in 0..count {
+                    channel.add_sample(TelemetrySample::new_f32(i as f32));
+                }
+            }
+            system
+        };
+        
+        let unlimited = filled(TelemetryConfig::default());
+        filled(TelemetryConfig {
+            max_memory_bytes: unlimited.total_memory_usage() / 2,
+            memory_pressure_policy: policy,
+            ..Default::default()
+        })
+    }
+    
+    #[test]
     fn test_memory_enforcement() {
-        let mut config = TelemetryConfig::default();
-        config.max_memory_bytes = 100_000; // 100KB limit for testing
+        let system = system_under_pressure(MemoryPressurePolicy::DropOldestGlobally);
+        let limit = system.global_config.max_memory_bytes;
+        
+        let event = system.enforce_memory_limits().expect("limit exceeded");
+        assert_eq!(event.policy, MemoryPressurePolicy::DropOldestGlobally);
+        assert!(event.usage_bytes > limit);
+        assert!(event.usage_after_bytes <= limit, "{} > {}", event.usage_after_bytes, limit);
+        
+        // Pruned proportionally, newest samples kept, nothing cleared outright
+        let a = system.get_channel("a").unwrap().snapshot();
+        let b = system.get_channel("b").unwrap().snapshot();
+        assert!(a.len() >= 450 && a.len() < 1000, "a kept {}", a.len());
+        assert!(b.len() >= 225 && b.len() < 500, "b kept {}", b.len());
+        assert_eq!(a.last().unwrap().as_f32(), Some(999.0));
+        assert_eq!(b.last().unwrap().as_f32(), Some(499.0));
+        
+        assert!(system.enforce_memory_limits().is_none());
+    }
+    
+    #[test]
+    fn test_reduce_sample_rate_under_pressure() {
+        let system = system_under_pressure(MemoryPressurePolicy::ReduceSampleRate);
+        let channel = system.get_channel("a").unwrap();
+        
+        let event = system.enforce_memory_limits().expect("limit exceeded");
+        assert_eq!(event.policy, MemoryPressurePolicy::ReduceSampleRate);
+        assert_eq!(event.usage_after_bytes, event.usage_bytes);
+        assert_eq!(channel.decimation(), 2);
         
-        let system = TelemetrySystem::with_config(config);
-        let channel = system.create_channel("test".to_string(), None);
+        // History is kept; only every other new sample is accepted
+        let before = channel.get_stats();
+        channel.add_samples(&(0..100).map(|i| TelemetrySample::new_f32(i as f32)).collect::<Vec<_>>());
+        let after = channel.get_stats();
+        assert_eq!(after.total_samples - before.total_samples, 50);
+        assert_eq!(after.samples_dropped - before.samples_dropped, 50);
         
-        // Add lots of data
-        for i in 0..1000 {
-            channel.add_sample(TelemetrySample::new_f32(i as f32));
+        // Sustained pressure escalates up to the cap
+        for _ in 0..10 {
+            system.enforce_memory_limits();
         }
+        assert_eq!(channel.decimation(), MAX_PRESSURE_DECIMATION);
+        
+        // Relief restores full rate
+        system.clear_all();
+        assert!(system.enforce_memory_limits().is_none());
+        assert_eq!(channel.decimation(), 1);
+    }
+    
+    #[test]
+    fn test_pause_ingest_under_pressure() {
+        let system = system_under_pressure(MemoryPressurePolicy::PauseIngest);
+        let channel = system.get_channel("b").unwrap();
+        
+        let event = system.enforce_memory_limits().expect("limit exceeded");
+        assert_eq!(event.policy, MemoryPressurePolicy::PauseIngest);
+        assert!(channel.is_ingest_paused());
+        
+        channel.add_sample(TelemetrySample::new_f32(1.0));
+        assert!(system.ingest_batch("b", &[TelemetrySample::new_f32(2.0)]));
+        let stats = channel.get_stats();
+        assert_eq!(stats.total_samples, 500);
+        assert_eq!(stats.samples_dropped, 2);
+        assert_eq!(channel.snapshot().len(), 500);
+        
+        system.clear_all();
+        assert!(system.enforce_memory_limits().is_none());
+        assert!(!channel.is_ingest_paused());
+        channel.add_sample(TelemetrySample::new_f32(3.0));
+        assert_eq!(channel.snapshot().len(), 1);
+    }
+    
+    /// Numeric values the channel holds, oldest first
+    fn values(channel: &TelemetryChannel) -> Vec<f32> {
+        channel.snapshot().iter().filter_map(|s| s.as_f32()).collect()
+    }
+    
+    #[test]
+    fn test_aggregate_across_devices() {
+        let system = TelemetrySystem::new();
+        let unthrottled = |name: &str| Some(ChannelConfig { name: name.to_string(), sample_rate: 0.0, ..Default::default() });
+        let board1 = system.create_channel("board1/temperature".to_string(), unthrottled("board1/temperature"));
+        let board2 = system.create_channel("board2/temperature".to_string(), unthrottled("board2/temperature"));
+        let other = system.create_channel("board1/humidity".to_string(), unthrottled("board1/humidity"));
         
-        // Initial memory should be over the limit
-        let initial_memory = system.total_memory_usage();
-        println!("Initial memory usage: {} bytes", initial_memory);
+        let avg = system.aggregate("*/temperature", AggKind::Avg);
+        let max = system.aggregate("*/temperature", AggKind::Max);
+        assert_eq!(avg.config().name, "avg(*/temperature)");
+        assert!(system.get_channel("max(*/temperature)").is_some());
         
-        // Enforce limits
-        system.enforce_memory_limits();
+        // Each sample updates the aggregate from every source's latest value
+        board1.add_sample(TelemetrySample::new_f32(20.0));
+        assert_eq!(values(&avg), vec![20.0]);
+        board2.add_sample(TelemetrySample::new_f32(30.0));
+        assert_eq!(values(&avg), vec![20.0, 25.0]);
+        other.add_sample(TelemetrySample::new_f32(90.0));
+        board1.add_sample(TelemetrySample::new_f32(40.0));
+        assert_eq!(values(&avg), vec![20.0, 25.0, 35.0]);
         
-        // Check memory is within limits (with some tolerance for metadata)
-        let final_memory = system.total_memory_usage();
-        println!("Final memory usage: {} bytes", final_memory);
-        println!("Memory limit: {} bytes", system.global_config.max_memory_bytes);
+        assert_eq!(values(&max), vec![20.0, 30.0, 40.0]);
+    }
+    
+    #[test]
+    fn test_aggregate_tracks_added_and_removed_channels() {
+        let system = TelemetrySystem::new();
+        let unthrottled = |name: &str| Some(ChannelConfig { name: name.to_string(), sample_rate: 0.0, ..Default::default() });
+        let board1 = system.create_channel("board1/temperature".to_string(), unthrottled("board1/temperature"));
+        let avg = system.aggregate("*/temperature", AggKind::Avg);
         
-        // Allow 10% tolerance for metadata overhead
-        let tolerance = system.global_config.max_memory_bytes + (system.global_config.max_memory_bytes / 10);
-        assert!(final_memory <= tolerance, 
-                "Memory {} exceeds limit {} (with 10% tolerance)", 
-                final_memory, tolerance);
+        // A board connected after the aggregate was created joins it
+        let board2 = system.create_channel("board2/temperature".to_string(), unthrottled("board2/temperature"));
+        board1.add_sample(TelemetrySample::new_f32(20.0));
+        board2.add_sample(TelemetrySample::new_f32(30.0));
+        assert_eq!(values(&avg), vec![20.0, 25.0]);
+        
+        // A removed board no longer counts, even if something still feeds it
+        system.remove_channel("board2/temperature");
+        board1.add_sample(TelemetrySample::new_f32(40.0));
+        assert_eq!(values(&avg), vec![20.0, 25.0, 40.0]);
+        board2.add_sample(TelemetrySample::new_f32(90.0));
+        board1.add_sample(TelemetrySample::new_f32(50.0));
+        assert_eq!(values(&avg), vec![20.0, 25.0, 40.0, 50.0]);
     }
-}
\ No newline at end of file
+}
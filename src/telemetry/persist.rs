@@ -0,0 +1,104 @@
+//! Append-only on-disk spill for telemetry channels
+//!
+//! When a channel has a `persist_path`, samples evicted from its ring buffer
+//! are appended here so long captures keep their full history without
+//! holding it all in RAM. Each record is a little-endian u32 length followed
+//! by the bincode-encoded sample.
+
+use crate::telemetry::TelemetrySample;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+/// Append-only sample file written as the ring buffer wraps
+pub struct SpillFile {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    samples_written: u64,
+}
+
+impl SpillFile {
+    /// Create (or truncate) the spill file at `path`
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(parent) = path.parent() {
+            if !parent.as_os_str().is_empty() {
+                std::fs::create_dir_all(parent)?;
+            }
+        }
+        
+        let file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(&path)?;
+        
+        Ok(Self {
+            path,
+            writer: BufWriter::new(file),
+            samples_written: 0,
+        })
+    }
+    
+    /// Append one sample
+    pub fn append(&mut self, sample: &TelemetrySample) -> io::Result<()> {
+        let encoded = bincode::serialize(sample)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        self.writer.write_all(&(encoded.len() as u32).to_le_bytes())?;
+        self.writer.write_all(&encoded)?;
+        self.samples_written += 1;
+        Ok(())
+    }
+    
+    /// Flush buffered records to disk
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+    
+    /// Flush and read back every sample written so far (oldest first)
+    pub fn read_all(&mut self) -> io::Result<Vec<TelemetrySample>> {
+        self.flush()?;
+        read_spill_file(&self.path)
+    }
+    
+    /// Discard all spilled samples
+    pub fn truncate(&mut self) -> io::Result<()> {
+        self.writer.flush()?;
+        self.writer = BufWriter::new(OpenOptions::new().write(true).truncate(true).open(&self.path)?);
+        self.samples_written = 0;
+        Ok(())
+    }
+    
+    /// Number of samples appended since creation or the last truncate
+    pub fn samples_written(&self) -> u64 {
+        self.samples_written
+    }
+    
+    /// Location of the spill file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+/// Read every sample from a spill file
+pub fn read_spill_file<P: AsRef<Path>>(path: P) -> io::Result<Vec<TelemetrySample>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut samples = Vec::new();
+    let mut len_bytes = [0u8; 4];
+    
+    loop {
+        match reader.read_exact(&mut len_bytes) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(e),
+        }
+        
+        let mut record = vec![0u8; u32::from_le_bytes(len_bytes) as usize];
+        reader.read_exact(&mut record)?;
+        let sample = bincode::deserialize(&record)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        samples.push(sample);
+    }
+    
+    Ok(samples)
+}
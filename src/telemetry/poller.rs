@@ -0,0 +1,227 @@
+//! Periodic "read an endpoint, publish to a channel" task
+//!
+//! Most polled telemetry is the same loop: invoke one session endpoint at a
+//! fixed rate, turn the reply into a sample stamped with host time, and push it
+//! into a channel. `TelemetryPoller` runs that loop on the runtime, backs off
+//! exponentially while the endpoint keeps failing, exits when the session goes
+//! inactive, and aborts its task when stopped or dropped.
+
+use serde_json::Value;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+use crate::device::DeviceSession;
+use crate::telemetry::{TelemetryChannel, TelemetrySample, SampleValue};
+
+/// Longest pause between polls while an endpoint keeps failing
+pub const MAX_POLL_BACKOFF: Duration = Duration::from_secs(5);
+
+/// Counters shared between a poller and its task
+#[derive(Debug, Default)]
+struct PollerCounters {
+    published: AtomicU64,
+    failures: AtomicU64,
+}
+
+/// Handle to a running poll loop; the loop stops when this is dropped
+pub struct TelemetryPoller {
+    handle: JoinHandle<()>,
+    counters: Arc<PollerCounters>,
+}
+
+impl TelemetryPoller {
+    /// Poll `endpoint` with `args` at `rate_hz` and publish each reading to `channel`
+    pub fn spawn(
+        session: Arc<Mutex<Box<dyn DeviceSession>>>,
+        endpoint: &str,
+        args: Vec<Value>,
+        channel: Arc<TelemetryChannel>,
+        rate_hz: f32,
+    ) -> Self {
+        let period = Duration::from_secs_f32(1.0 / rate_hz.max(0.001));
+        let counters = Arc::new(PollerCounters::default());
+        let endpoint = endpoint.to_string();
+        
+        let task_counters = counters.clone();
+        let handle = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(period);
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            let mut consecutive_failures: u32 = 0;
+            
+            loop {
+                ticker.tick().await;
+                
+                let result = {
+                    let mut session = session.lock().await;
+                    if !session.is_active() {
+                        tracing::debug!("Session closed, stopping poll of '{}'", endpoint);
+                        break;
+                    }
+                    session.invoke_async(&endpoint, args.clone()).await
+                };
+                
+                let error = match result {
+                    Ok(value) => match sample_value(&value) {
+                        Some(value) => {
+                            channel.add_sample(TelemetrySample::new(value));
+                            task_counters.published.fetch_add(1, Ordering::Relaxed);
+                            consecutive_failures = 0;
+                            continue;
+                        }
+                        None => format!("non-numeric reply {}", value),
+                    },
+                    Err(e) => e.to_string(),
+                };
+                
+                task_counters.failures.fetch_add(1, Ordering::Relaxed);
+                consecutive_failures = consecutive_failures.saturating_add(1);
+                let backoff = backoff_delay(period, consecutive_failures);
+                tracing::warn!(
+                    "Polling '{}' failed ({} in a row): {}; retrying in {:?}",
+                    endpoint, consecutive_failures, error, backoff
+                );
+                tokio::time::sleep(backoff.saturating_sub(period)).await;
+                ticker.reset();
+            }
+        });
+        
+        Self { handle, counters }
+    }
+    
+    /// Samples published so far
+    pub fn published(&self) -> u64 {
+        self.counters.published.load(Ordering::Relaxed)
+    }
+    
+    /// Failed polls so far
+    pub fn failures(&self) -> u64 {
+        self.counters.failures.load(Ordering::Relaxed)
+    }
+    
+    /// Whether the poll loop has exited
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+    
+    /// Stop polling and wait for the task to wind down
+    pub async fn stop(mut self) {
+        self.handle.abort();
+        let _ = (&mut self.handle).await;
+    }
+}
+
+impl Drop for TelemetryPoller {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+/// Delay before the next poll after `failures` consecutive failures
+fn backoff_delay(period: Duration, failures: u32) -> Duration {
+    period.saturating_mul(1u32 << failures.min(16)).min(MAX_POLL_BACKOFF).max(period)
+}
+
+/// Sample value for a poll reply: a number, a bool, or an object with a numeric "value"
+fn sample_value(reply: &Value) -> Option<SampleValue> {
+    match reply {
+        Value::Number(n) => n.as_f64().map(|v| SampleValue::Float32(v as f32)),
+        Value::Bool(b) => Some(SampleValue::Bool(*b)),
+        Value::Object(fields) => fields.get("value").and_then(sample_value),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use crate::device::DeviceError;
+    use crate::device::mock::MockSession;
+    use crate::telemetry::ChannelConfig;
+    
+    /// Session whose reads return an increasing counter, or always fail
+    fn counter_session(failing: bool) -> Arc<Mutex<Box<dyn DeviceSession>>> {
+        let reads = AtomicU64::new(0);
+        let session = MockSession::new(move |_, _| {
+            let reads = reads.fetch_add(1, Ordering::Relaxed) + 1;
+            if failing {
+                return Err(DeviceError::Timeout(100));
+            }
+            Ok(json!({ "value": reads }))
+        });
+        Arc::new(Mutex::new(Box::new(session)))
+    }
+    
+    fn unlimited_channel() -> Arc<TelemetryChannel> {
+        Arc::new(TelemetryChannel::new(ChannelConfig {
+            name: "poll".into(),
+            sample_rate: 0.0,
+            ..Default::default()
+        }))
+    }
+    
+    /// Let the poll task run until it is waiting on the clock again
+    async fn settle() {
+        for _ in 0..4 {
+            tokio::task::yield_now().await;
+        }
+    }
+    
+    /// Move the paused clock on by `millis`, one millisecond at a time
+    async fn run_for(millis: u64) {
+        settle().await;
+        for _ in 0..millis {
+            tokio::time::advance(Duration::from_millis(1)).await;
+            settle().await;
+        }
+    }
+    
+    #[tokio::test(start_paused = true)]
+    async fn test_publishes_at_configured_rate_until_stopped() {
+        let channel = unlimited_channel();
+        let poller = TelemetryPoller::spawn(counter_session(false), "analogRead", vec![json!(0)], channel.clone(), 50.0);
+        
+        // Polls at 0, 20, ..., 300ms
+        run_for(310).await;
+        assert_eq!(poller.published(), 16);
+        assert_eq!(channel.get_stats().total_samples, 16);
+        assert_eq!(poller.failures(), 0);
+        
+        poller.stop().await;
+        run_for(100).await;
+        assert_eq!(channel.get_stats().total_samples, 16);
+    }
+    
+    #[tokio::test(start_paused = true)]
+    async fn test_backs_off_on_repeated_failures() {
+        let channel = unlimited_channel();
+        let poller = TelemetryPoller::spawn(counter_session(true), "analogRead", vec![json!(0)], channel.clone(), 100.0);
+        
+        // The 10ms period doubles per failure: polls at 0, 20, 60 and 140ms...
+        run_for(150).await;
+        assert_eq!(poller.failures(), 4);
+        
+        // ...then 160ms later at 300ms
+        run_for(149).await;
+        assert_eq!(poller.failures(), 4);
+        run_for(11).await;
+        assert_eq!(poller.failures(), 5);
+        assert_eq!(channel.get_stats().total_samples, 0);
+        assert!(!poller.is_finished());
+    }
+    
+    #[test]
+    fn test_sample_value_extraction() {
+        assert!(matches!(sample_value(&json!({ "value": 512 })), Some(SampleValue::Float32(v)) if v == 512.0));
+        assert!(matches!(sample_value(&json!(1.5)), Some(SampleValue::Float32(v)) if v == 1.5));
+        assert!(matches!(sample_value(&json!(true)), Some(SampleValue::Bool(true))));
+        assert!(sample_value(&json!("OK")).is_none());
+        
+        assert_eq!(backoff_delay(Duration::from_millis(10), 3), Duration::from_millis(80));
+        assert_eq!(backoff_delay(Duration::from_millis(10), 30), MAX_POLL_BACKOFF);
+    }
+}
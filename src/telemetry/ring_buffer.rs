@@ -25,6 +25,8 @@ pub struct RingBuffer<T: Clone + Send + Sync> {
     created_at: SystemTime,
     /// Last write timestamp
     last_write: AtomicU64,
+    /// Times the data lock was taken for writing
+    write_locks: AtomicU64,
 }
 
 impl<T: Clone + Send + Sync> RingBuffer<T> {
@@ -50,6 +52,7 @@ impl<T: Clone + Send + Sync> RingBuffer<T> {
             capacity,
             created_at: SystemTime::now(),
             last_write: AtomicU64::new(0),
+            write_locks: AtomicU64::new(0),
         }
     }
     
@@ -58,12 +61,18 @@ impl<T: Clone + Send + Sync> RingBuffer<T> {
     /// This will overwrite the oldest value if the buffer is full.
     /// Thread-safe for single writer.
     pub fn push(&self, value: T) {
+        self.push_evicting(value);
+    }
+    
+    /// Push a new value, returning the oldest value it overwrote (if full)
+    pub fn push_evicting(&self, value: T) -> Option<T> {
         let pos = self.write_pos.fetch_add(1, Ordering::AcqRel) % self.capacity;
         
-        {
+        let evicted = {
             let mut buffer = self.buffer.write();
-            buffer[pos] = Some(value);
-        }
+            self.write_locks.fetch_add(1, Ordering::Relaxed);
+            buffer[pos].replace(value)
+        };
         
         self.total_written.fetch_add(1, Ordering::Relaxed);
         self.last_write.store(
@@ -73,6 +82,8 @@ impl<T: Clone + Send + Sync> RingBuffer<T> {
                 .as_millis() as u64,
             Ordering::Relaxed
         );
+        
+        evicted
     }
     
     /// Push multiple values at once (batch operation)
@@ -87,6 +98,7 @@ impl<T: Clone + Send + Sync> RingBuffer<T> {
         
         {
             let mut buffer = self.buffer.write();
+            self.write_locks.fetch_add(1, Ordering::Relaxed);
             for (i, value) in values.iter().enumerate() {
                 let pos = (start_pos + i) % self.capacity;
                 buffer[pos] = Some(value.clone());
@@ -103,6 +115,36 @@ impl<T: Clone + Send + Sync> RingBuffer<T> {
         );
     }
     
+    /// Push values in order under one lock, returning the overwritten ones (oldest first)
+    pub fn push_batch_evicting(&self, values: Vec<T>) -> Vec<T> {
+        if values.is_empty() {
+            return Vec::new();
+        }
+        
+        let count = values.len();
+        let start_pos = self.write_pos.fetch_add(count, Ordering::AcqRel);
+        
+        let evicted = {
+            let mut buffer = self.buffer.write();
+            self.write_locks.fetch_add(1, Ordering::Relaxed);
+            values.into_iter()
+                .enumerate()
+                .filter_map(|(i, value)| buffer[(start_pos + i) % self.capacity].replace(value))
+                .collect()
+        };
+        
+        self.total_written.fetch_add(count as u64, Ordering::Relaxed);
+        self.last_write.store(
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_millis() as u64,
+            Ordering::Relaxed
+        );
+        
+        evicted
+    }
+    
     /// Get the current number of valid items in the buffer
     pub fn len(&self) -> usize {
         let total = self.total_written.load(Ordering::Relaxed);
@@ -205,14 +247,16 @@ impl<T: Clone + Send + Sync> RingBuffer<T> {
             created_at: self.created_at,
             last_write_ms: self.last_write.load(Ordering::Relaxed),
             memory_bytes: self.memory_usage(),
+            write_locks: self.write_locks.load(Ordering::Relaxed),
         }
     }
     
-    /// Estimate memory usage in bytes
+    /// Estimate memory held by the stored items in bytes
+    /// 
+    /// Counts occupied slots rather than capacity so that pruning shows up
+    /// in the figure memory limits are enforced against.
     pub fn memory_usage(&self) -> usize {
-        std::mem::size_of::<T>() * self.capacity
-            + std::mem::size_of::<Self>()
-            + std::mem::size_of::<Option<T>>() * self.capacity
+        std::mem::size_of::<Self>() + std::mem::size_of::<Option<T>>() * self.len()
     }
     
     /// Remove oldest n% of data
@@ -250,6 +294,8 @@ pub struct RingBufferStats {
     pub created_at: SystemTime,
     pub last_write_ms: u64,
     pub memory_bytes: usize,
+    /// Times the data lock was taken for writing (batches take it once)
+    pub write_locks: u64,
 }
 
 impl RingBufferStats {
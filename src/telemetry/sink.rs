@@ -0,0 +1,478 @@
+//! Pluggable telemetry sinks for live streaming
+//!
+//! Sinks receive every sample a channel accepts, so telemetry can be streamed
+//! to external systems (InfluxDB, sockets, callbacks) alongside the in-memory
+//! ring buffers. Each registered sink runs behind a bounded queue drained by
+//! its own worker thread, so a slow sink never stalls `add_sample`. Sinks that
+//! pay per write (files, sockets) can be wrapped in a `BufferedSink` to batch
+//! samples and flush them by size or interval.
+
+use crate::telemetry::{TelemetrySample, SampleValue};
+use std::io::Write;
+use std::sync::mpsc::{self, RecvTimeoutError, Sender, SyncSender, TrySendError};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use parking_lot::Mutex;
+
+/// Default number of samples queued per sink before new samples are dropped
+pub const DEFAULT_SINK_QUEUE_CAPACITY: usize = 1024;
+
+/// Samples a `BufferedSink` holds before flushing, unless configured otherwise
+pub const DEFAULT_SINK_BUFFER_SAMPLES: usize = 256;
+
+/// Longest a `BufferedSink` holds samples before flushing, unless configured otherwise
+pub const DEFAULT_SINK_FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Destination for live telemetry samples
+pub trait TelemetrySink: Send + Sync {
+    /// Handle a sample accepted by `channel`
+    fn ingest(&self, channel: &str, sample: &TelemetrySample);
+    
+    /// Handle several samples at once; sinks that pay per write should override this
+    fn ingest_batch(&self, samples: &[(String, TelemetrySample)]) {
+        for (channel, sample) in samples {
+            self.ingest(channel, sample);
+        }
+    }
+    
+    /// Flush any buffered output (called when the sink is detached)
+    fn flush(&self) {}
+}
+
+/// Closure receiving each sample with the name of its channel
+type SampleCallback = Box<dyn Fn(&str, &TelemetrySample) + Send + Sync>;
+
+/// Sink that forwards samples to a user-supplied closure
+pub struct CallbackSink {
+    callback: SampleCallback,
+}
+
+impl CallbackSink {
+    pub fn new<F>(callback: F) -> Self
+    where
+        F: Fn(&str, &TelemetrySample) + Send + Sync + 'static,
+    {
+        Self {
+            callback: Box::new(callback),
+        }
+    }
+}
+
+impl TelemetrySink for CallbackSink {
+    fn ingest(&self, channel: &str, sample: &TelemetrySample) {
+        (self.callback)(channel, sample);
+    }
+}
+
+/// Sink that writes samples as InfluxDB line protocol
+///
+/// Each sample becomes `<channel>[,source=..][,unit=..] <fields> <timestamp_ns>`.
+pub struct InfluxLineSink {
+    writer: Mutex<Box<dyn Write + Send>>,
+}
+
+impl InfluxLineSink {
+    pub fn new<W: Write + Send + 'static>(writer: W) -> Self {
+        Self {
+            writer: Mutex::new(Box::new(writer)),
+        }
+    }
+    
+    /// Format a sample as a single line protocol entry (without trailing newline)
+    /// Returns None for values that have no line protocol representation
+    pub fn format_line(channel: &str, sample: &TelemetrySample) -> Option<String> {
+        let fields = match &sample.value {
+            SampleValue::Float32(v) => format!("value={}", v),
+            SampleValue::Float64(v) => format!("value={}", v),
+            SampleValue::Int32(v) => format!("value={}i", v),
+            SampleValue::UInt32(v) => format!("value={}i", v),
+            SampleValue::Bool(v) => format!("value={}", v),
+            SampleValue::String(s) => format!("value=\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\"")),
+            SampleValue::Vector(values) if !values.is_empty() => values
+                .iter()
+                .enumerate()
+                .map(|(i, v)| format!("v{}={}", i, v))
+                .collect::<Vec<_>>()
+                .join(","),
+            SampleValue::Vector(_) | SampleValue::Bytes(_) => return None,
+        };
+        
+        let mut line = escape_key(channel);
+        if let Some(ref metadata) = sample.metadata {
+            if let Some(ref source) = metadata.source {
+                line.push_str(&format!(",source={}", escape_key(source)));
+            }
+            if let Some(ref unit) = metadata.unit {
+                line.push_str(&format!(",unit={}", escape_key(unit)));
+            }
+        }
+        
+        let timestamp_ns = sample.timestamp_ms as u128 * 1_000_000;
+        Some(format!("{} {} {}", line, fields, timestamp_ns))
+    }
+}
+
+impl TelemetrySink for InfluxLineSink {
+    fn ingest(&self, channel: &str, sample: &TelemetrySample) {
+        if let Some(line) = Self::format_line(channel, sample) {
+            let mut writer = self.writer.lock();
+            if let Err(e) = writeln!(writer, "{}", line) {
+                tracing::warn!("InfluxLineSink write failed: {}", e);
+            }
+        }
+    }
+    
+    /// Writes the whole batch with a single write call
+    fn ingest_batch(&self, samples: &[(String, TelemetrySample)]) {
+        let mut lines = String::new();
+        for (channel, sample) in samples {
+            if let Some(line) = Self::format_line(channel, sample) {
+                lines.push_str(&line);
+                lines.push('\n');
+            }
+        }
+        if lines.is_empty() {
+            return;
+        }
+        
+        if let Err(e) = self.writer.lock().write_all(lines.as_bytes()) {
+            tracing::warn!("InfluxLineSink write failed: {}", e);
+        }
+    }
+    
+    fn flush(&self) {
+        let _ = self.writer.lock().flush();
+    }
+}
+
+/// Sink wrapper that collects samples and hands them to the inner sink in batches
+///
+/// The buffer is flushed when it reaches `max_samples`, when `flush_interval`
+/// has passed since the last flush (checked by a background thread), on
+/// `flush()` and when the wrapper is dropped.
+pub struct BufferedSink {
+    state: Arc<BufferState>,
+    stop: Option<Sender<()>>,
+    flusher: Option<JoinHandle<()>>,
+}
+
+struct BufferState {
+    inner: Arc<dyn TelemetrySink>,
+    pending: Mutex<Vec<(String, TelemetrySample)>>,
+    max_samples: usize,
+    last_flush: Mutex<Instant>,
+}
+
+impl BufferState {
+    fn flush(&self) {
+        let batch = std::mem::take(&mut *self.pending.lock());
+        *self.last_flush.lock() = Instant::now();
+        if !batch.is_empty() {
+            self.inner.ingest_batch(&batch);
+        }
+        self.inner.flush();
+    }
+}
+
+impl BufferedSink {
+    /// Wrap `inner` with the default buffer size and flush interval
+    pub fn new(inner: Arc<dyn TelemetrySink>) -> Self {
+        Self::with_limits(inner, DEFAULT_SINK_BUFFER_SAMPLES, DEFAULT_SINK_FLUSH_INTERVAL)
+    }
+    
+    /// Wrap `inner`, flushing every `max_samples` samples or `flush_interval`
+    /// (a zero interval flushes only by size, explicitly and on drop)
+    pub fn with_limits(inner: Arc<dyn TelemetrySink>, max_samples: usize, flush_interval: Duration) -> Self {
+        let max_samples = max_samples.max(1);
+        let state = Arc::new(BufferState {
+            inner,
+            pending: Mutex::new(Vec::with_capacity(max_samples)),
+            max_samples,
+            last_flush: Mutex::new(Instant::now()),
+        });
+        
+        if flush_interval.is_zero() {
+            return Self { state, stop: None, flusher: None };
+        }
+        
+        // The flusher exits once `stop` is dropped
+        let (stop, stopped) = mpsc::channel::<()>();
+        let flusher = {
+            let state = state.clone();
+            std::thread::Builder::new()
+                .name("telemetry-sink-flush".to_string())
+                .spawn(move || loop {
+                    let due = flush_interval.saturating_sub(state.last_flush.lock().elapsed());
+                    match stopped.recv_timeout(due) {
+                        Err(RecvTimeoutError::Timeout) => {
+                            if state.last_flush.lock().elapsed() >= flush_interval {
+                                state.flush();
+                            }
+                        }
+                        _ => break,
+                    }
+                })
+                .ok()
+        };
+        
+        Self { state, stop: Some(stop), flusher }
+    }
+    
+    /// Samples waiting for the next flush
+    pub fn pending(&self) -> usize {
+        self.state.pending.lock().len()
+    }
+}
+
+impl TelemetrySink for BufferedSink {
+    fn ingest(&self, channel: &str, sample: &TelemetrySample) {
+        let full = {
+            let mut pending = self.state.pending.lock();
+            pending.push((channel.to_string(), sample.clone()));
+            pending.len() >= self.state.max_samples
+        };
+        if full {
+            self.state.flush();
+        }
+    }
+    
+    fn flush(&self) {
+        self.state.flush();
+    }
+}
+
+impl Drop for BufferedSink {
+    fn drop(&mut self) {
+        self.stop.take();
+        if let Some(flusher) = self.flusher.take() {
+            let _ = flusher.join();
+        }
+        self.state.flush();
+    }
+}
+
+/// Escape measurement names and tag values for line protocol
+fn escape_key(value: &str) -> String {
+    value
+        .replace(',', "\\,")
+        .replace('=', "\\=")
+        .replace(' ', "\\ ")
+}
+
+/// A registered sink with its bounded queue and worker thread
+pub(crate) struct SinkWorker {
+    sender: Option<SyncSender<(String, TelemetrySample)>>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl SinkWorker {
+    pub(crate) fn spawn(sink: Arc<dyn TelemetrySink>, capacity: usize) -> Self {
+        let (sender, receiver) = mpsc::sync_channel::<(String, TelemetrySample)>(capacity.max(1));
+        
+        let worker = std::thread::Builder::new()
+            .name("telemetry-sink".to_string())
+            .spawn(move || {
+                while let Ok((channel, sample)) = receiver.recv() {
+                    sink.ingest(&channel, &sample);
+                }
+                sink.flush();
+            })
+            .ok();
+        
+        Self {
+            sender: Some(sender),
+            worker,
+        }
+    }
+    
+    /// Queue a sample without blocking; returns false if it was dropped
+    pub(crate) fn forward(&self, channel: &str, sample: &TelemetrySample) -> bool {
+        let Some(ref sender) = self.sender else {
+            return false;
+        };
+        
+        match sender.try_send((channel.to_string(), sample.clone())) {
+            Ok(()) => true,
+            Err(TrySendError::Full(_)) | Err(TrySendError::Disconnected(_)) => false,
+        }
+    }
+}
+
+impl Drop for SinkWorker {
+    fn drop(&mut self) {
+        // Closing the queue lets the worker drain remaining samples and exit
+        self.sender.take();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::telemetry::{TelemetryChannel, ChannelConfig, SampleMetadata};
+    use std::time::{Duration, Instant};
+    
+    fn unlimited_channel(name: &str) -> TelemetryChannel {
+        TelemetryChannel::new(ChannelConfig {
+            name: name.to_string(),
+            sample_rate: 0.0, // Disable rate limiting
+            ..Default::default()
+        })
+    }
+    
+    #[test]
+    fn test_callback_sink_receives_every_sample() {
+        let channel = unlimited_channel("voltage");
+        let (tx, rx) = std::sync::mpsc::channel();
+        let tx = Mutex::new(tx);
+        
+        channel.register_sink(Arc::new(CallbackSink::new(move |name, sample| {
+            let _ = tx.lock().send((name.to_string(), sample.as_f32()));
+        })));
+        
+        for i in 0..50 {
+            channel.add_sample(TelemetrySample::new_f32(i as f32));
+        }
+        
+        for i in 0..50 {
+            let (name, value) = rx.recv_timeout(Duration::from_secs(1)).expect("sample not delivered");
+            assert_eq!(name, "voltage");
+            assert_eq!(value, Some(i as f32));
+        }
+    }
+    
+    #[test]
+    fn test_slow_sink_does_not_stall_channel() {
+        let channel = unlimited_channel("fast");
+        channel.register_sink_with_capacity(
+            Arc::new(CallbackSink::new(|_, _| std::thread::sleep(Duration::from_millis(50)))),
+            4,
+        );
+        
+        let start = Instant::now();
+        for i in 0..100 {
+            channel.add_sample(TelemetrySample::new_f32(i as f32));
+        }
+        assert!(start.elapsed() < Duration::from_millis(200), "add_sample blocked on slow sink");
+        
+        let stats = channel.get_stats();
+        assert_eq!(stats.total_samples, 100);
+        assert!(stats.sink_samples_dropped > 0);
+    }
+    
+    #[test]
+    fn test_influx_line_format() {
+        let mut metadata = SampleMetadata::with_source("uno 1".to_string());
+        metadata.unit = Some("V".to_string());
+        let sample = TelemetrySample {
+            timestamp_ms: 1_700_000_000_000,
+            value: SampleValue::Float32(3.5),
+            metadata: Some(metadata),
+        };
+        
+        let line = InfluxLineSink::format_line("battery", &sample).unwrap();
+        assert_eq!(line, "battery,source=uno\\ 1,unit=V value=3.5 1700000000000000000");
+        
+        let int_sample = TelemetrySample::with_timestamp(SampleValue::Int32(-4), 1);
+        assert_eq!(InfluxLineSink::format_line("count", &int_sample).unwrap(), "count value=-4i 1000000");
+        
+        let bytes = TelemetrySample::with_timestamp(SampleValue::Bytes(vec![1, 2]), 1);
+        assert!(InfluxLineSink::format_line("raw", &bytes).is_none());
+    }
+    
+    /// Sink recording the size of every batch it is handed
+    #[derive(Default)]
+    struct BatchRecorder {
+        batches: Mutex<Vec<Vec<f32>>>,
+    }
+    
+    impl BatchRecorder {
+        fn batch_sizes(&self) -> Vec<usize> {
+            self.batches.lock().iter().map(Vec::len).collect()
+        }
+    }
+    
+    impl TelemetrySink for BatchRecorder {
+        fn ingest(&self, channel: &str, sample: &TelemetrySample) {
+            self.ingest_batch(&[(channel.to_string(), sample.clone())]);
+        }
+        
+        fn ingest_batch(&self, samples: &[(String, TelemetrySample)]) {
+            self.batches.lock().push(samples.iter().filter_map(|(_, s)| s.as_f32()).collect());
+        }
+    }
+    
+    #[test]
+    fn test_buffered_sink_flushes_at_threshold() {
+        let recorder = Arc::new(BatchRecorder::default());
+        let sink = BufferedSink::with_limits(recorder.clone(), 5, Duration::from_secs(60));
+        
+        for i in 0..4 {
+            sink.ingest("rpm", &TelemetrySample::new_f32(i as f32));
+        }
+        assert!(recorder.batch_sizes().is_empty());
+        assert_eq!(sink.pending(), 4);
+        
+        sink.ingest("rpm", &TelemetrySample::new_f32(4.0));
+        assert_eq!(recorder.batch_sizes(), vec![5]);
+        assert_eq!(recorder.batches.lock()[0], vec![0.0, 1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(sink.pending(), 0);
+    }
+    
+    #[test]
+    fn test_buffered_sink_flushes_on_drop() {
+        let recorder = Arc::new(BatchRecorder::default());
+        let sink = BufferedSink::with_limits(recorder.clone(), 100, Duration::from_secs(60));
+        
+        for i in 0..3 {
+            sink.ingest("rpm", &TelemetrySample::new_f32(i as f32));
+        }
+        assert!(recorder.batch_sizes().is_empty());
+        
+        drop(sink);
+        assert_eq!(recorder.batch_sizes(), vec![3]);
+    }
+    
+    #[test]
+    fn test_buffered_sink_flushes_on_interval() {
+        let recorder = Arc::new(BatchRecorder::default());
+        let sink = BufferedSink::with_limits(recorder.clone(), 100, Duration::from_millis(50));
+        
+        sink.ingest("rpm", &TelemetrySample::new_f32(1.0));
+        sink.ingest("rpm", &TelemetrySample::new_f32(2.0));
+        std::thread::sleep(Duration::from_millis(200));
+        
+        assert_eq!(recorder.batch_sizes(), vec![2]);
+        assert_eq!(sink.pending(), 0);
+    }
+    
+    #[test]
+    fn test_influx_sink_batch_written_once() {
+        let buffer = Arc::new(Mutex::new(Vec::new()));
+        struct SharedWriter(Arc<Mutex<Vec<u8>>>, Arc<std::sync::atomic::AtomicUsize>);
+        impl Write for SharedWriter {
+            fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+                self.1.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                self.0.lock().extend_from_slice(data);
+                Ok(data.len())
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+        
+        let writes = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let influx = Arc::new(InfluxLineSink::new(SharedWriter(buffer.clone(), writes.clone())));
+        let sink = BufferedSink::with_limits(influx, 3, Duration::ZERO);
+        for i in 0..3 {
+            sink.ingest("temp", &TelemetrySample::with_timestamp(SampleValue::Int32(i), 1));
+        }
+        
+        assert_eq!(writes.load(std::sync::atomic::Ordering::SeqCst), 1);
+        let text = String::from_utf8(buffer.lock().clone()).unwrap();
+        assert_eq!(text.lines().collect::<Vec<_>>(), vec!["temp value=0i 1000000", "temp value=1i 1000000", "temp value=2i 1000000"]);
+    }
+}
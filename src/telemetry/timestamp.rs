@@ -0,0 +1,85 @@
+//! Which clock stamps a channel's samples
+//!
+//! By default samples keep the timestamp their producer gave them. A channel
+//! can instead restamp samples on arrival with host wall time or with a
+//! monotonic host clock (immune to NTP steps and manual clock changes), or
+//! treat incoming timestamps as device time and correct them with the
+//! session's clock offset.
+
+use crate::device::clock_sync::ClockOffset;
+use crate::telemetry::TelemetrySample;
+use serde::{Serialize, Deserialize};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Clock used for a channel's sample timestamps
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum TimestampSource {
+    /// Keep the timestamp set by whoever created the sample
+    #[default]
+    Sample,
+    /// Host wall-clock time when the sample reaches the channel
+    HostWall,
+    /// Host monotonic time when the sample reaches the channel, expressed in
+    /// unix milliseconds anchored at channel creation
+    HostMonotonic,
+    /// Incoming timestamps are device milliseconds, rebased onto host time
+    /// with the session clock offset once one is known
+    Device,
+}
+
+/// Stamps samples according to a `TimestampSource`
+#[derive(Debug, Clone)]
+pub struct SampleClock {
+    source: TimestampSource,
+    origin: Instant,
+    origin_wall_ms: u64,
+    offset: Option<ClockOffset>,
+}
+
+impl SampleClock {
+    pub fn new(source: TimestampSource) -> Self {
+        Self {
+            source,
+            origin: Instant::now(),
+            origin_wall_ms: wall_ms(),
+            offset: None,
+        }
+    }
+    
+    pub fn source(&self) -> TimestampSource {
+        self.source
+    }
+    
+    /// Device clock offset used by `TimestampSource::Device`
+    pub fn set_offset(&mut self, offset: Option<ClockOffset>) {
+        self.offset = offset;
+    }
+    
+    pub fn offset(&self) -> Option<ClockOffset> {
+        self.offset
+    }
+    
+    /// Set the sample's timestamp from the configured clock
+    pub fn stamp(&self, sample: &mut TelemetrySample) {
+        match self.source {
+            TimestampSource::Sample => {}
+            TimestampSource::HostWall => sample.timestamp_ms = wall_ms(),
+            TimestampSource::HostMonotonic => {
+                sample.timestamp_ms = self.origin_wall_ms + self.origin.elapsed().as_millis() as u64;
+            }
+            TimestampSource::Device => {
+                // Until the first clock sync, device time is passed through uncorrected
+                if let Some(offset) = self.offset {
+                    offset.rebase_sample(sample);
+                }
+            }
+        }
+    }
+}
+
+fn wall_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
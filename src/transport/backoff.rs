@@ -1,5 +1,6 @@
 use std::time::Duration;
 use rand::Rng;
+use crate::transport::common::ReconnectHints;
 
 /// Configuration for exponential backoff retry logic
 #[derive(Debug, Clone)]
@@ -19,6 +20,9 @@ pub struct ExponentialBackoff {
     /// Enable jitter to prevent thundering herd (default: true)
     enable_jitter: bool,
     
+    /// Floor for the first delay from device reconnect hints (default: 0)
+    seed_delay_ms: u64,
+    
     /// Current attempt number
     current_attempt: u32,
 }
@@ -59,6 +63,14 @@ impl ExponentialBackoff {
         self
     }
     
+    /// Builder method to seed the first delay from device reconnect hints
+    /// Delays grow from the larger of the initial delay and the hinted delay;
+    /// jitter stays relative to the initial delay so a long hint isn't padded further
+    pub fn with_hints(mut self, hints: &ReconnectHints) -> Self {
+        self.seed_delay_ms = hints.first_attempt_delay().as_millis() as u64;
+        self
+    }
+    
     /// Reset the backoff to initial state
     pub fn reset(&mut self) {
         self.current_attempt = 0;
@@ -76,25 +88,35 @@ impl ExponentialBackoff {
         }
         
         self.current_attempt += 1;
+        Some(self.delay_for_attempt(self.current_attempt))
+    }
+    
+    /// Delay before attempt `attempt` (1-based), for callers that count attempts themselves
+    /// Doesn't advance the backoff or check the attempt limit
+    pub fn delay_for_attempt(&self, attempt: u32) -> Duration {
+        // Calculate exponential delay, starting from the hinted delay if that is longer
+        let base_delay = self.exponential_delay(self.initial_delay_ms.max(self.seed_delay_ms), attempt);
         
-        // Calculate exponential delay
-        let base_delay = if self.current_attempt == 1 {
-            self.initial_delay_ms
-        } else {
-            let exponential = self.initial_delay_ms as f64 * self.factor.powi((self.current_attempt - 1) as i32);
-            exponential.min(self.max_delay_ms as f64) as u64
-        };
-        
-        // Apply jitter if enabled (0-25% of base delay)
+        // Apply jitter if enabled (0-25% of the unhinted delay)
         let final_delay = if self.enable_jitter {
-            let jitter_range = base_delay / 4;
+            let jitter_range = self.exponential_delay(self.initial_delay_ms, attempt) / 4;
             let jitter = rand::thread_rng().gen_range(0..=jitter_range);
             base_delay + jitter
         } else {
             base_delay
         };
         
-        Some(Duration::from_millis(final_delay))
+        Duration::from_millis(final_delay)
+    }
+    
+    /// Delay for `attempt` growing from `initial_ms`, capped at the maximum
+    fn exponential_delay(&self, initial_ms: u64, attempt: u32) -> u64 {
+        if attempt <= 1 {
+            initial_ms
+        } else {
+            let exponential = initial_ms as f64 * self.factor.powi((attempt - 1) as i32);
+            exponential.min(self.max_delay_ms as f64) as u64
+        }
     }
     
     /// Get current attempt number
@@ -119,6 +141,7 @@ impl ExponentialBackoff {
             factor: 2.0,
             max_attempts,
             enable_jitter: true,
+            seed_delay_ms: 0,
             current_attempt: 0,
         }
     }
@@ -132,6 +155,7 @@ impl Default for ExponentialBackoff {
             factor: 2.0,
             max_attempts: 10,
             enable_jitter: true,
+            seed_delay_ms: 0,
             current_attempt: 0,
         }
     }
@@ -146,6 +170,9 @@ pub fn is_retryable_error(error: &crate::transport::TransportError) -> bool {
         TransportError::ConfigError(_) |
         TransportError::PermissionDenied(_) |
         TransportError::InvalidData(_) |
+        TransportError::LineError(_) |
+        TransportError::SettingsMismatch(_) |
+        TransportError::Cancelled |
         TransportError::NotImplemented(_) => false,
         
         // Retryable errors (temporary failures)
@@ -206,6 +233,35 @@ mod tests {
         }
     }
     
+    #[test]
+    fn test_hints_seed_first_delay() {
+        let hints = ReconnectHints { min_settle_ms: 1500, reenumeration_delay_ms: 500 };
+        
+        // Whatever the base delay, the first attempt waits out the hinted 2s
+        for initial in [10, 100, 1000] {
+            let mut backoff = ExponentialBackoff::new()
+                .with_initial_delay(initial)
+                .with_hints(&hints);
+            let delay = backoff.next_delay().unwrap().as_millis() as u64;
+            assert!(delay >= 2000, "initial {}ms: first delay {}ms", initial, delay);
+            assert!(delay <= 2000 + initial / 4, "initial {}ms: jitter padded to {}ms", initial, delay);
+        }
+        
+        // Later attempts grow from the seeded delay; a longer base delay wins
+        let mut backoff = ExponentialBackoff::new()
+            .with_initial_delay(100)
+            .with_jitter(false)
+            .with_hints(&hints);
+        let delays: Vec<u128> = (0..3).map(|_| backoff.next_delay().unwrap().as_millis()).collect();
+        assert_eq!(delays, vec![2000, 4000, 8000]);
+        
+        let mut backoff = ExponentialBackoff::new()
+            .with_initial_delay(5000)
+            .with_jitter(false)
+            .with_hints(&hints);
+        assert_eq!(backoff.next_delay().unwrap().as_millis(), 5000);
+    }
+    
     #[test]
     fn test_backoff_reset() {
         let mut backoff = ExponentialBackoff::new()
@@ -0,0 +1,382 @@
+//! Line-oriented command/response helper
+//!
+//! Most device firmwares speak a text protocol: one command per line, one
+//! response line back. `CommandCodec` appends the line terminator on send and
+//! reassembles received chunks into trimmed lines, keeping any bytes past the
+//! first line for the next read.
+//!
+//! With id tagging enabled each command goes out as `#<n> <command>` and the
+//! firmware is expected to echo `#<n>` at the start of its reply. Replies are
+//! then matched by id: replies to other commands are parked for their reader
+//! and untagged lines are buffered as unsolicited. Until the device has echoed
+//! a tag at least once, untagged lines are taken as positional replies, so
+//! firmware that ignores the prefix keeps working.
+//!
+//! Some firmwares echo each command before answering it. With echo
+//! suppression enabled, `query` and `read_response` discard a first line
+//! that repeats the command (or a truncated start of it) and return the line
+//! after it.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use crate::transport::{Transport, TransportError, TransportResult};
+
+/// Default terminator appended to every command
+pub const DEFAULT_TERMINATOR: &str = "\r\n";
+
+/// Default time to wait for a complete response line
+pub const DEFAULT_RESPONSE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Unsolicited lines kept until read; older ones are dropped
+pub const MAX_UNSOLICITED_LINES: usize = 64;
+
+/// Tagged replies kept for commands that have not read them yet
+const MAX_PARKED_REPLIES: usize = 64;
+
+/// Shortest truncated echo recognized; shorter lines are taken as responses
+pub const MIN_PARTIAL_ECHO_LEN: usize = 3;
+
+/// Sends newline-terminated commands and reads back single response lines
+pub struct CommandCodec {
+    transport: Arc<dyn Transport>,
+    terminator: String,
+    timeout: Duration,
+    /// Bytes received after the last returned line
+    pending: Mutex<Vec<u8>>,
+    id_tagging: bool,
+    next_id: AtomicU64,
+    echo_suppression: bool,
+    /// Whether the device has echoed a tag, i.e. untagged lines are unsolicited
+    echoes_ids: AtomicBool,
+    /// Tagged replies read on behalf of other commands
+    parked: std::sync::Mutex<HashMap<u64, String>>,
+    unsolicited: std::sync::Mutex<VecDeque<String>>,
+}
+
+impl CommandCodec {
+    /// Create a codec using `\r\n` and a 2 second response timeout
+    pub fn new(transport: Arc<dyn Transport>) -> Self {
+        Self {
+            transport,
+            terminator: DEFAULT_TERMINATOR.to_string(),
+            timeout: DEFAULT_RESPONSE_TIMEOUT,
+            pending: Mutex::new(Vec::new()),
+            id_tagging: false,
+            next_id: AtomicU64::new(1),
+            echo_suppression: false,
+            echoes_ids: AtomicBool::new(false),
+            parked: std::sync::Mutex::new(HashMap::new()),
+            unsolicited: std::sync::Mutex::new(VecDeque::new()),
+        }
+    }
+    
+    /// Use a different command terminator (e.g. "\n")
+    pub fn with_terminator(mut self, terminator: &str) -> Self {
+        self.terminator = terminator.to_string();
+        self
+    }
+    
+    /// Set how long `query`/`read_line` wait for a response line
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+    
+    /// How long `query`/`read_line` wait for a response line
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+    
+    /// Tag `query` commands with a sequence id and match replies by it
+    pub fn with_id_tagging(mut self, enabled: bool) -> Self {
+        self.id_tagging = enabled;
+        self
+    }
+    
+    pub fn id_tagging(&self) -> bool {
+        self.id_tagging
+    }
+    
+    /// Discard a command's echo before its response in `query`
+    pub fn with_echo_suppression(mut self, enabled: bool) -> Self {
+        self.echo_suppression = enabled;
+        self
+    }
+    
+    pub fn echo_suppression(&self) -> bool {
+        self.echo_suppression
+    }
+    
+    /// The underlying transport
+    pub fn transport(&self) -> &Arc<dyn Transport> {
+        &self.transport
+    }
+    
+    /// Send a command followed by the terminator
+    pub async fn send_command(&self, command: &str) -> TransportResult<()> {
+        let framed = format!("{}{}", command, self.terminator);
+        self.transport.send(framed.as_bytes()).await
+    }
+    
+    /// Send a command prefixed with a fresh `#<n> ` tag and return the id
+    pub async fn send_tagged(&self, command: &str) -> TransportResult<u64> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.send_command(&format!("#{} {}", id, command)).await?;
+        Ok(id)
+    }
+    
+    /// Read the reply tagged `id`, without its tag
+    /// Replies to other commands are parked and untagged lines are buffered as
+    /// unsolicited, unless the device has never echoed a tag
+    pub async fn read_tagged(&self, id: u64) -> TransportResult<String> {
+        let mut pending = self.pending.lock().await;
+        let deadline = Instant::now() + self.timeout;
+        
+        loop {
+            let parked = self.parked.lock().unwrap().remove(&id);
+            if let Some(response) = parked {
+                return Ok(response);
+            }
+            
+            let line = self.next_line(&mut pending, deadline).await?;
+            match split_tag(&line) {
+                Some((tag, response)) => {
+                    self.echoes_ids.store(true, Ordering::Relaxed);
+                    if tag == id {
+                        return Ok(response.to_string());
+                    }
+                    self.park(tag, response);
+                }
+                None if self.echoes_ids.load(Ordering::Relaxed) => {
+                    tracing::debug!("Buffering unsolicited line: {}", line);
+                    let mut unsolicited = self.unsolicited.lock().unwrap();
+                    if unsolicited.len() >= MAX_UNSOLICITED_LINES {
+                        unsolicited.pop_front();
+                    }
+                    unsolicited.push_back(line);
+                }
+                // Firmware that ignores tags answers in order
+                None => return Ok(line),
+            }
+        }
+    }
+    
+    /// Untagged lines buffered while waiting for tagged replies, oldest first
+    pub fn take_unsolicited(&self) -> Vec<String> {
+        self.unsolicited.lock().unwrap().drain(..).collect()
+    }
+    
+    fn park(&self, tag: u64, response: &str) {
+        let mut parked = self.parked.lock().unwrap();
+        if parked.len() >= MAX_PARKED_REPLIES {
+            // Replies nobody claimed (e.g. after a timeout) are the oldest ids
+            if let Some(oldest) = parked.keys().min().copied() {
+                parked.remove(&oldest);
+            }
+        }
+        parked.insert(tag, response.to_string());
+    }
+    
+    /// Read one response line, without its line ending or surrounding whitespace
+    pub async fn read_line(&self) -> TransportResult<String> {
+        let mut pending = self.pending.lock().await;
+        let deadline = Instant::now() + self.timeout;
+        self.next_line(&mut pending, deadline).await
+    }
+    
+    async fn next_line(&self, pending: &mut Vec<u8>, deadline: Instant) -> TransportResult<String> {
+        loop {
+            if let Some(pos) = pending.iter().position(|&b| b == b'\n') {
+                let line: Vec<u8> = pending.drain(..=pos).collect();
+                return Ok(String::from_utf8_lossy(&line).trim().to_string());
+            }
+            
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Err(TransportError::Timeout(format!(
+                    "No complete response line within {}ms", self.timeout.as_millis()
+                )));
+            }
+            
+            let chunk = self.transport.receive(remaining).await?;
+            pending.extend_from_slice(&chunk);
+        }
+    }
+    
+    /// Send a command and return the trimmed response line
+    pub async fn query(&self, command: &str) -> TransportResult<String> {
+        let id = if self.id_tagging {
+            Some(self.send_tagged(command).await?)
+        } else {
+            self.send_command(command).await?;
+            None
+        };
+        self.read_response(command, id).await
+    }
+    
+    /// Read the response to `command` (the reply tagged `id` when given),
+    /// skipping its echo if echo suppression is on
+    pub async fn read_response(&self, command: &str, id: Option<u64>) -> TransportResult<String> {
+        let read = || async {
+            match id {
+                Some(id) => self.read_tagged(id).await,
+                None => self.read_line().await,
+            }
+        };
+        
+        let response = read().await?;
+        if self.echo_suppression && is_echo(&response, command) {
+            tracing::trace!("Discarding echo of '{}'", command);
+            return read().await;
+        }
+        Ok(response)
+    }
+    
+    /// Drop any buffered bytes left over from earlier responses
+    pub async fn clear(&self) {
+        self.pending.lock().await.clear();
+    }
+}
+
+/// Whether `line` repeats `command`, in full or as a truncated start of it
+fn is_echo(line: &str, command: &str) -> bool {
+    let command = command.trim();
+    line == command || (line.len() >= MIN_PARTIAL_ECHO_LEN && command.starts_with(line))
+}
+
+/// Split `#<n> rest` into the tag and the rest of the line
+fn split_tag(line: &str) -> Option<(u64, &str)> {
+    let tagged = line.strip_prefix('#')?;
+    let (tag, rest) = tagged.split_once(' ').unwrap_or((tagged, ""));
+    Some((tag.parse().ok()?, rest.trim_start()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::mock::MockTransport;
+    
+    /// Mock device echoing each command back, padded and split into two chunks
+    fn echo_transport() -> Arc<MockTransport> {
+        echo_transport_with(|command| format!("  {}\r\n", command))
+    }
+    
+    /// Mock device sending back `respond(command)` in two chunks
+    fn echo_transport_with(respond: impl Fn(&str) -> String + Send + Sync + 'static) -> Arc<MockTransport> {
+        Arc::new(MockTransport::scripted(move |data| {
+            let command = String::from_utf8_lossy(data).trim().to_string();
+            let reply = respond(&command).into_bytes();
+            let (head, tail) = reply.split_at(reply.len() / 2);
+            vec![head.to_vec(), tail.to_vec()]
+        }))
+    }
+    
+    #[tokio::test]
+    async fn test_query_terminates_and_trims() {
+        let transport = echo_transport();
+        let codec = CommandCodec::new(transport.clone());
+        
+        let response = codec.query("PROBE").await.unwrap();
+        assert_eq!(response, "PROBE");
+        assert_eq!(transport.get_sent_history().await, vec![b"PROBE\r\n".to_vec()]);
+        
+        let codec = CommandCodec::new(transport.clone()).with_terminator("\n");
+        assert_eq!(codec.query("ANALOG_READ 2").await.unwrap(), "ANALOG_READ 2");
+        assert_eq!(transport.get_sent_data().await, b"ANALOG_READ 2\n".to_vec());
+    }
+    
+    #[tokio::test]
+    async fn test_extra_lines_kept_for_next_read() {
+        let transport = echo_transport();
+        transport.inject_receive_data(b"OK\r\nVALUE:1\r\n".to_vec()).await.unwrap();
+        let codec = CommandCodec::new(transport.clone()).with_timeout(Duration::from_millis(50));
+        
+        assert_eq!(codec.read_line().await.unwrap(), "OK");
+        assert_eq!(codec.read_line().await.unwrap(), "VALUE:1");
+        assert!(matches!(codec.read_line().await, Err(TransportError::Timeout(_))));
+    }
+    
+    #[tokio::test]
+    async fn test_tagged_replies_skip_unsolicited_lines() {
+        let transport = echo_transport();
+        let codec = CommandCodec::new(transport.clone()).with_id_tagging(true);
+        
+        assert_eq!(codec.query("PROBE").await.unwrap(), "PROBE");
+        assert_eq!(transport.get_sent_data().await, b"#1 PROBE\r\n".to_vec());
+        
+        // An event arrives before the reply to the next command
+        transport.inject_receive_data(b"EVENT:button\r\n".to_vec()).await.unwrap();
+        assert_eq!(codec.query("STATUS").await.unwrap(), "STATUS");
+        assert_eq!(codec.take_unsolicited(), vec!["EVENT:button".to_string()]);
+        assert!(codec.take_unsolicited().is_empty());
+    }
+    
+    #[tokio::test]
+    async fn test_tagged_replies_matched_out_of_order() {
+        let codec = CommandCodec::new(echo_transport()).with_id_tagging(true);
+        
+        let first = codec.send_tagged("DIGITAL_READ 2").await.unwrap();
+        let second = codec.send_tagged("DIGITAL_READ 3").await.unwrap();
+        assert_eq!(codec.read_tagged(second).await.unwrap(), "DIGITAL_READ 3");
+        assert_eq!(codec.read_tagged(first).await.unwrap(), "DIGITAL_READ 2");
+    }
+    
+    #[tokio::test]
+    async fn test_untagged_firmware_falls_back_to_positional() {
+        let transport = Arc::new(MockTransport::scripted(|_| Vec::new()));
+        let codec = CommandCodec::new(transport.clone()).with_id_tagging(true);
+        
+        let id = codec.send_tagged("PROBE").await.unwrap();
+        transport.inject_receive_data(b"ARDUINO_UNO_V1\r\n".to_vec()).await.unwrap();
+        assert_eq!(codec.read_tagged(id).await.unwrap(), "ARDUINO_UNO_V1");
+        
+        assert_eq!(split_tag("#12 VALUE:512"), Some((12, "VALUE:512")));
+        assert_eq!(split_tag("#7"), Some((7, "")));
+        assert_eq!(split_tag("#x OK"), None);
+        assert_eq!(split_tag("OK"), None);
+    }
+    
+    #[tokio::test]
+    async fn test_echo_suppression() {
+        let echoing = || echo_transport_with(|command| format!("{}\r\nVALUE:512\r\n", command));
+        
+        // Off: the echo comes back as the response
+        let codec = CommandCodec::new(echoing());
+        assert!(!codec.echo_suppression());
+        assert_eq!(codec.query("ANALOG_READ 2").await.unwrap(), "ANALOG_READ 2");
+        
+        // On: the echo is discarded
+        let codec = CommandCodec::new(echoing()).with_echo_suppression(true);
+        assert_eq!(codec.query("ANALOG_READ 2").await.unwrap(), "VALUE:512");
+        assert_eq!(codec.query("ANALOG_READ 3").await.unwrap(), "VALUE:512");
+        
+        // Tagged firmware echoes the tagged command, then answers with the tag
+        let transport = echo_transport_with(|command| {
+            let tag = command.split(' ').next().unwrap_or_default();
+            format!("{}\r\n{} VALUE:512\r\n", command, tag)
+        });
+        let codec = CommandCodec::new(transport).with_echo_suppression(true).with_id_tagging(true);
+        assert_eq!(codec.query("ANALOG_READ 2").await.unwrap(), "VALUE:512");
+    }
+    
+    #[tokio::test]
+    async fn test_echo_suppression_partial_and_missing_echo() {
+        // Firmware that only echoes the first few characters
+        let transport = echo_transport_with(|command| format!("{}\r\nOK\r\n", &command[..5]));
+        let codec = CommandCodec::new(transport).with_echo_suppression(true);
+        assert_eq!(codec.query("DIGITAL_WRITE 13 1").await.unwrap(), "OK");
+        
+        // Firmware that doesn't echo at all: the first line is the response
+        let transport = echo_transport_with(|_| "OK\r\n".to_string());
+        let codec = CommandCodec::new(transport).with_echo_suppression(true);
+        assert_eq!(codec.query("PING").await.unwrap(), "OK");
+        assert_eq!(codec.query("PING").await.unwrap(), "OK");
+        
+        assert!(is_echo("PIN", "PING"));
+        assert!(!is_echo("OK", "OKAY"));
+        assert!(!is_echo("VALUE:1", "PING"));
+    }
+}
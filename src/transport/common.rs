@@ -4,7 +4,7 @@ use std::error::Error;
 use std::time::Duration;
 
 /// Transport types supported by the application
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum TransportType {
     Serial,
     Tcp,
@@ -35,6 +35,9 @@ pub enum TransportError {
     /// Already connected
     AlreadyConnected,
     
+    /// Asked to connect with settings other than the transport's own
+    SettingsMismatch(String),
+    
     /// Timeout occurred
     Timeout(String),
     
@@ -65,6 +68,12 @@ pub enum TransportError {
     /// Resource unavailable
     ResourceUnavailable(String),
     
+    /// Serial line error (parity, framing, etc.) - usually a baud/parity mismatch
+    LineError(LineErrorKind),
+    
+    /// Operation was cancelled by its cancellation token
+    Cancelled,
+    
     /// Other error
     Other(String),
 }
@@ -75,6 +84,7 @@ impl fmt::Display for TransportError {
             TransportError::ConnectionFailed(msg) => write!(f, "Connection failed: {}", msg),
             TransportError::NotConnected => write!(f, "Transport is not connected"),
             TransportError::AlreadyConnected => write!(f, "Transport is already connected"),
+            TransportError::SettingsMismatch(msg) => write!(f, "Settings mismatch: {}", msg),
             TransportError::Timeout(msg) => write!(f, "Timeout: {}", msg),
             TransportError::IoError(err) => write!(f, "I/O error: {}", err),
             TransportError::ConfigError(msg) => write!(f, "Configuration error: {}", msg),
@@ -85,6 +95,8 @@ impl fmt::Display for TransportError {
             TransportError::HardwareError(msg) => write!(f, "Hardware error: {}", msg),
             TransportError::PermissionDenied(msg) => write!(f, "Permission denied: {}", msg),
             TransportError::ResourceUnavailable(msg) => write!(f, "Resource unavailable: {}", msg),
+            TransportError::LineError(kind) => write!(f, "Line error: {}", kind),
+            TransportError::Cancelled => write!(f, "Operation cancelled"),
             TransportError::Other(msg) => write!(f, "Transport error: {}", msg),
         }
     }
@@ -105,6 +117,33 @@ impl From<std::io::Error> for TransportError {
     }
 }
 
+/// Kind of low-level serial line error reported by the UART
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LineErrorKind {
+    /// Parity bit did not match the configured parity
+    Parity,
+    
+    /// Stop bit missing - frame boundaries are misaligned
+    Framing,
+    
+    /// Receive buffer overrun - bytes were lost
+    Overrun,
+    
+    /// Line held low longer than a frame (break condition)
+    Break,
+}
+
+impl fmt::Display for LineErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LineErrorKind::Parity => write!(f, "parity error (check parity/baud rate settings)"),
+            LineErrorKind::Framing => write!(f, "framing error (check baud rate/stop bits settings)"),
+            LineErrorKind::Overrun => write!(f, "receive overrun (data arrived faster than it was read)"),
+            LineErrorKind::Break => write!(f, "break condition detected on the line"),
+        }
+    }
+}
+
 /// Result type for transport operations
 pub type TransportResult<T> = Result<T, TransportError>;
 
@@ -129,7 +168,7 @@ pub struct TransportConfig {
     /// Automatic reconnection
     pub auto_reconnect: bool,
     
-    /// Maximum reconnection attempts
+    /// Maximum reconnection attempts (0 = retry forever)
     pub max_reconnect_attempts: u32,
     
     /// Reconnection delay in milliseconds
@@ -147,10 +186,43 @@ pub struct TransportConfig {
     /// Minimum latency to enforce between operations (optional)
     pub min_latency: Option<Duration>,
     
+    /// Device timing that sets the earliest first reconnect attempt after a drop
+    #[serde(default)]
+    pub reconnect_hints: ReconnectHints,
+    
     /// Transport-specific settings
     pub settings: TransportSettings,
 }
 
+/// How long a device takes to come back after a reset, for pacing reconnection
+///
+/// Boards that reset when the port drops (most USB Arduinos) vanish, re-enumerate
+/// and then ignore input while the bootloader runs. Reconnecting before that is
+/// a wasted attempt, so the hints seed the backoff: the first attempt waits at
+/// least `reenumeration_delay_ms + min_settle_ms`, whatever the base delay.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReconnectHints {
+    /// Time the device needs after enumerating before it accepts a connection
+    #[serde(default)]
+    pub min_settle_ms: u32,
+    
+    /// Time the device takes to disappear and enumerate again after a reset
+    #[serde(default)]
+    pub reenumeration_delay_ms: u32,
+}
+
+impl ReconnectHints {
+    /// Earliest the first reconnect attempt is worth making
+    pub fn first_attempt_delay(&self) -> Duration {
+        Duration::from_millis(self.min_settle_ms as u64 + self.reenumeration_delay_ms as u64)
+    }
+    
+    /// Backoff base delay raised to at least `first_attempt_delay`
+    pub fn seed(&self, base: Duration) -> Duration {
+        base.max(self.first_attempt_delay())
+    }
+}
+
 impl Default for TransportConfig {
     fn default() -> Self {
         TransportConfig {
@@ -166,13 +238,29 @@ impl Default for TransportConfig {
             write_buffer_size: 4096,
             require_handshake: false,
             min_latency: None,
+            reconnect_hints: ReconnectHints::default(),
             settings: TransportSettings::Serial(SerialSettings::default()),
         }
     }
 }
 
+impl TransportConfig {
+    /// Whether both configs describe the same connection (type, address and settings)
+    /// Reconnect and timeout tuning is ignored
+    pub fn same_connection(&self, other: &TransportConfig) -> bool {
+        self.transport_type == other.transport_type
+            && self.address == other.address
+            && self.settings == other.settings
+    }
+    
+    /// Whether `attempts` reconnection attempts use up `max_reconnect_attempts`
+    pub fn reconnect_attempts_exhausted(&self, attempts: u32) -> bool {
+        self.max_reconnect_attempts != 0 && attempts >= self.max_reconnect_attempts
+    }
+}
+
 /// Transport-specific settings
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum TransportSettings {
     Serial(SerialSettings),
     Tcp(TcpSettings),
@@ -181,13 +269,42 @@ pub enum TransportSettings {
 }
 
 /// Serial port settings
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SerialSettings {
     pub baud_rate: u32,
     pub data_bits: DataBits,
     pub stop_bits: StopBits,
     pub parity: Parity,
+    /// `Software` is handled by the transport: XOFF pauses sends until XON
     pub flow_control: FlowControl,
+    /// Surface parity/framing errors as `LineError` instead of generic I/O errors
+    #[serde(default = "default_report_line_errors")]
+    pub report_line_errors: bool,
+    /// Once a frame starts, keep reading until the line is quiet this long
+    /// (independent of the overall read timeout); `None` returns after the first chunk
+    #[serde(default)]
+    pub inter_byte_timeout_ms: Option<u32>,
+    /// Bytes the device sends when its receive buffer overflowed
+    /// Seeing them fails the read with `BufferOverflow`
+    #[serde(default)]
+    pub overflow_marker: Option<Vec<u8>>,
+    /// Hold a lock file for the port while open so other processes can't share it
+    #[serde(default)]
+    pub exclusive: bool,
+    /// Bytes the device sends before powering off (e.g. `BYE`)
+    /// Seeing them ends the connection cleanly and suppresses auto-reconnect
+    #[serde(default)]
+    pub goodbye_marker: Option<Vec<u8>>,
+    /// How reads wait for incoming data
+    #[serde(default)]
+    pub read_strategy: ReadStrategy,
+}
+
+impl SerialSettings {
+    /// Inter-byte timeout as a duration, if configured
+    pub fn inter_byte_timeout(&self) -> Option<Duration> {
+        self.inter_byte_timeout_ms.map(|ms| Duration::from_millis(ms as u64))
+    }
 }
 
 impl Default for SerialSettings {
@@ -198,6 +315,12 @@ impl Default for SerialSettings {
             stop_bits: StopBits::One,
             parity: Parity::None,
             flow_control: FlowControl::None,
+            report_line_errors: true,
+            inter_byte_timeout_ms: None,
+            overflow_marker: None,
+            exclusive: false,
+            goodbye_marker: None,
+            read_strategy: ReadStrategy::default(),
         }
     }
 }
@@ -219,11 +342,15 @@ pub enum StopBits {
 }
 
 /// Parity configuration
+/// `Mark` and `Space` can be described (e.g. in profiles) but the serial backend
+/// cannot drive them, so `SerialTransport::new` rejects them
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Parity {
     None,
     Odd,
     Even,
+    Mark,
+    Space,
 }
 
 /// Flow control configuration
@@ -234,14 +361,32 @@ pub enum FlowControl {
     Hardware,
 }
 
+/// How the serial transport waits for incoming data
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ReadStrategy {
+    /// Repeated short blocking reads
+    #[default]
+    Polled,
+    /// Sleep until the port's descriptor is readable (epoll/kqueue); falls
+    /// back to `Polled` where the platform has no pollable descriptor
+    EventDriven,
+}
+
 /// TCP settings
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TcpSettings {
     pub host: String,
     pub port: u16,
+    /// Set TCP_NODELAY on connect so small command packets aren't delayed by Nagle
+    /// Disable for bulk transfers where coalescing writes is preferable
+    #[serde(default = "default_no_delay")]
     pub no_delay: bool,
     pub keep_alive: bool,
     pub keep_alive_interval_ms: u32,
+    /// Mark the connection down if nothing is received for this long (0 = disabled)
+    /// Catches half-open connections where the peer vanished without a RST
+    #[serde(default)]
+    pub heartbeat_timeout_ms: u32,
 }
 
 impl Default for TcpSettings {
@@ -252,12 +397,13 @@ impl Default for TcpSettings {
             no_delay: true, // Disable Nagle's algorithm for low latency
             keep_alive: true,
             keep_alive_interval_ms: 10000,
+            heartbeat_timeout_ms: 0,
         }
     }
 }
 
 /// UDP settings
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct UdpSettings {
     pub host: String,
     pub port: u16,
@@ -283,7 +429,7 @@ impl Default for UdpSettings {
 }
 
 /// SSH settings
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SshSettings {
     pub username: String,
     pub key_path: Option<String>,
@@ -310,3 +456,6 @@ impl Default for SshSettings {
     }
 }
 
+fn default_no_delay() -> bool { true }
+
+fn default_report_line_errors() -> bool { true }
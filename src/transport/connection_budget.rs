@@ -0,0 +1,209 @@
+//! System-wide cap on open transports
+//!
+//! Each open transport holds file descriptors and usually a reader task or
+//! thread. `ConnectionBudget` counts the transports created through
+//! `TransportFactory` across all types and refuses new ones once the limit
+//! (`ResourceBudget::max_open_transports`) is reached. A transport's slot is
+//! returned when the transport is dropped.
+
+use async_trait::async_trait;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use crate::performance::ResourceBudget;
+use crate::transport::{
+    CancellationToken, DisconnectReason, ReconnectCallback, Transport, TransportConfig, TransportError,
+    TransportResult, TransportStats, TransportType, WireTrace,
+};
+
+/// Snapshot of a budget's usage
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConnectionUsage {
+    pub open: usize,
+    /// Maximum open transports (0 = unlimited)
+    pub limit: usize,
+    /// Opens refused because the budget was full
+    pub rejected: u64,
+}
+
+impl ConnectionUsage {
+    /// Opens still allowed, or `None` when unlimited
+    pub fn available(&self) -> Option<usize> {
+        (self.limit > 0).then(|| self.limit.saturating_sub(self.open))
+    }
+}
+
+#[derive(Debug)]
+struct BudgetState {
+    limit: AtomicUsize,
+    open: AtomicUsize,
+    rejected: AtomicU64,
+}
+
+/// Counter of open transports with a shared limit; clones share the count
+#[derive(Debug, Clone)]
+pub struct ConnectionBudget {
+    state: Arc<BudgetState>,
+}
+
+impl ConnectionBudget {
+    /// Budget allowing `limit` open transports (0 = unlimited)
+    pub fn new(limit: usize) -> Self {
+        Self {
+            state: Arc::new(BudgetState {
+                limit: AtomicUsize::new(limit),
+                open: AtomicUsize::new(0),
+                rejected: AtomicU64::new(0),
+            }),
+        }
+    }
+    
+    /// Process-wide budget used by `TransportFactory::create`
+    pub fn global() -> &'static ConnectionBudget {
+        static GLOBAL: OnceLock<ConnectionBudget> = OnceLock::new();
+        GLOBAL.get_or_init(|| ConnectionBudget::new(ResourceBudget::default().max_open_transports))
+    }
+    
+    /// Change the limit; transports already open are not closed
+    pub fn set_limit(&self, limit: usize) {
+        self.state.limit.store(limit, Ordering::Relaxed);
+    }
+    
+    /// Take the limit from a resource budget
+    pub fn apply(&self, budget: &ResourceBudget) {
+        self.set_limit(budget.max_open_transports);
+    }
+    
+    pub fn usage(&self) -> ConnectionUsage {
+        ConnectionUsage {
+            open: self.state.open.load(Ordering::Relaxed),
+            limit: self.state.limit.load(Ordering::Relaxed),
+            rejected: self.state.rejected.load(Ordering::Relaxed),
+        }
+    }
+    
+    /// Reserve a slot for one transport, failing with `ResourceUnavailable` when full
+    pub fn try_acquire(&self) -> TransportResult<BudgetSlot> {
+        let limit = self.state.limit.load(Ordering::Relaxed);
+        let reserved = self.state.open.fetch_update(Ordering::AcqRel, Ordering::Acquire, |open| {
+            (limit == 0 || open < limit).then_some(open + 1)
+        });
+        
+        match reserved {
+            Ok(_) => Ok(BudgetSlot { state: self.state.clone() }),
+            Err(open) => {
+                self.state.rejected.fetch_add(1, Ordering::Relaxed);
+                Err(TransportError::ResourceUnavailable(format!(
+                    "Connection budget reached: {} of {} transports open", open, limit
+                )))
+            }
+        }
+    }
+}
+
+/// One reserved transport slot, released on drop
+#[derive(Debug)]
+pub struct BudgetSlot {
+    state: Arc<BudgetState>,
+}
+
+impl Drop for BudgetSlot {
+    fn drop(&mut self) {
+        self.state.open.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// Transport that holds a budget slot for as long as it exists
+pub struct BudgetedTransport {
+    inner: Box<dyn Transport>,
+    _slot: BudgetSlot,
+}
+
+impl BudgetedTransport {
+    pub fn new(inner: Box<dyn Transport>, slot: BudgetSlot) -> Self {
+        Self { inner, _slot: slot }
+    }
+}
+
+#[async_trait]
+impl Transport for BudgetedTransport {
+    fn transport_type(&self) -> TransportType {
+        self.inner.transport_type()
+    }
+    
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+    
+    fn is_connected(&self) -> bool {
+        self.inner.is_connected()
+    }
+    
+    async fn connect(&self) -> TransportResult<()> {
+        self.inner.connect().await
+    }
+    
+    async fn connect_with(&self, config: &TransportConfig) -> TransportResult<()> {
+        self.inner.connect_with(config).await
+    }
+    
+    async fn disconnect(&self) -> TransportResult<()> {
+        self.inner.disconnect().await
+    }
+    
+    async fn send(&self, data: &[u8]) -> TransportResult<()> {
+        self.inner.send(data).await
+    }
+    
+    async fn receive(&self, timeout: Duration) -> TransportResult<Vec<u8>> {
+        self.inner.receive(timeout).await
+    }
+    
+    async fn send_cancellable(&self, data: &[u8], token: CancellationToken) -> TransportResult<()> {
+        self.inner.send_cancellable(data, token).await
+    }
+    
+    async fn receive_cancellable(&self, timeout: Duration, token: CancellationToken) -> TransportResult<Vec<u8>> {
+        self.inner.receive_cancellable(timeout, token).await
+    }
+    
+    async fn transact(&self, data: &[u8], timeout: Duration) -> TransportResult<Vec<u8>> {
+        self.inner.transact(data, timeout).await
+    }
+    
+    async fn transact_resilient(&self, data: &[u8], timeout: Duration, max_retries: u32) -> TransportResult<Vec<u8>> {
+        self.inner.transact_resilient(data, timeout, max_retries).await
+    }
+    
+    fn stats(&self) -> TransportStats {
+        self.inner.stats()
+    }
+    
+    async fn reset(&self) -> TransportResult<()> {
+        self.inner.reset().await
+    }
+    
+    fn config(&self) -> &TransportConfig {
+        self.inner.config()
+    }
+    
+    async fn cleanup_resources(&self) -> TransportResult<()> {
+        self.inner.cleanup_resources().await
+    }
+    
+    fn on_reconnect(&self, callback: ReconnectCallback) {
+        self.inner.on_reconnect(callback)
+    }
+    
+    fn wire_trace(&self) -> Option<&WireTrace> {
+        self.inner.wire_trace()
+    }
+    
+    fn disconnect_reason(&self) -> Option<DisconnectReason> {
+        self.inner.disconnect_reason()
+    }
+    
+    fn status_summary(&self) -> String {
+        self.inner.status_summary()
+    }
+}
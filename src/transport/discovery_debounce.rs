@@ -0,0 +1,98 @@
+//! Debounce of discovery results
+//!
+//! A marginally connected USB device can enumerate and vanish between scans,
+//! making the device list flicker. `DiscoveryDebouncer` only reports a device
+//! as added (or removed) once every scan over the debounce window has agreed;
+//! a device that flips back before then produces no change at all.
+
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::time::{Duration, Instant};
+
+/// Debounce window used when none is configured
+pub const DEFAULT_DISCOVERY_DEBOUNCE: Duration = Duration::from_millis(2000);
+
+/// A settled change in the discovered set
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiscoveryChange<K> {
+    Added(K),
+    Removed(K),
+}
+
+/// Turns raw discovery scans into stable add/remove events
+#[derive(Debug)]
+pub struct DiscoveryDebouncer<K> {
+    window: Duration,
+    stable: HashSet<K>,
+    /// Devices whose observed presence differs from `stable`, and since when
+    pending: HashMap<K, Instant>,
+    seeded: bool,
+}
+
+impl<K: Hash + Eq + Clone> DiscoveryDebouncer<K> {
+    /// Debouncer requiring `window` of consistent scans (zero = report every change)
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            stable: HashSet::new(),
+            pending: HashMap::new(),
+            seeded: false,
+        }
+    }
+    
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+    
+    /// Change the window; pending changes keep their start time
+    pub fn set_window(&mut self, window: Duration) {
+        self.window = window;
+    }
+    
+    /// Devices currently considered present
+    pub fn stable(&self) -> impl Iterator<Item = &K> {
+        self.stable.iter()
+    }
+    
+    pub fn is_stable(&self, key: &K) -> bool {
+        self.stable.contains(key)
+    }
+    
+    /// Feed one scan taken at `now` and return the changes that have settled
+    ///
+    /// The first scan is taken as-is: devices already present at startup are
+    /// listed immediately.
+    pub fn observe(&mut self, present: impl IntoIterator<Item = K>, now: Instant) -> Vec<DiscoveryChange<K>> {
+        let present: HashSet<K> = present.into_iter().collect();
+        
+        if !self.seeded {
+            self.seeded = true;
+            self.stable = present.clone();
+            return present.into_iter().map(DiscoveryChange::Added).collect();
+        }
+        
+        // Devices back in their stable state have stopped flapping
+        let stable = &self.stable;
+        self.pending.retain(|key, _| present.contains(key) != stable.contains(key));
+        
+        let differing: Vec<K> = present.symmetric_difference(&self.stable).cloned().collect();
+        let mut changes = Vec::new();
+        for key in differing {
+            let since = *self.pending.entry(key.clone()).or_insert(now);
+            if now.duration_since(since) < self.window {
+                continue;
+            }
+            
+            self.pending.remove(&key);
+            if present.contains(&key) {
+                self.stable.insert(key.clone());
+                changes.push(DiscoveryChange::Added(key));
+            } else {
+                self.stable.remove(&key);
+                changes.push(DiscoveryChange::Removed(key));
+            }
+        }
+        
+        changes
+    }
+}
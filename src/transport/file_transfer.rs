@@ -0,0 +1,115 @@
+//! Push a file's contents to a device through any transport
+//!
+//! Config blobs and firmware images are too large for a single write on most
+//! links. `send_file` streams the file in fixed-size chunks, reports progress
+//! after each one and can verify the transfer by reading every chunk back
+//! (for firmware that echoes what it receives).
+
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tokio::fs::File;
+use tokio::io::AsyncReadExt;
+use crate::transport::{Transport, TransportError, TransportResult};
+
+/// Chunk size used when none is given
+pub const DEFAULT_CHUNK_SIZE: usize = 256;
+
+/// Progress of a running transfer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransferProgress {
+    pub bytes_sent: u64,
+    pub total_bytes: u64,
+}
+
+impl TransferProgress {
+    /// Completed fraction in 0.0..=1.0 (1.0 for an empty file)
+    pub fn fraction(&self) -> f64 {
+        if self.total_bytes == 0 {
+            1.0
+        } else {
+            self.bytes_sent as f64 / self.total_bytes as f64
+        }
+    }
+}
+
+/// Send the file at `path` in `chunk_size` pieces and return the bytes sent
+///
+/// With `verify`, each chunk must be echoed back unchanged before the next
+/// one is sent; a difference fails with `InvalidData` naming the file offset.
+/// `progress` is called after every chunk.
+pub async fn send_file(
+    transport: &dyn Transport,
+    path: impl AsRef<Path>,
+    chunk_size: usize,
+    verify: bool,
+    mut progress: impl FnMut(TransferProgress),
+) -> TransportResult<u64> {
+    let path = path.as_ref();
+    let chunk_size = if chunk_size == 0 { DEFAULT_CHUNK_SIZE } else { chunk_size };
+    let mut file = File::open(path).await?;
+    let total_bytes = file.metadata().await?.len();
+    let read_timeout = Duration::from_millis(transport.config().read_timeout_ms as u64);
+    
+    tracing::info!("Sending {} ({} bytes) to {}", path.display(), total_bytes, transport.name());
+    
+    let mut chunk = vec![0u8; chunk_size];
+    let mut bytes_sent = 0u64;
+    loop {
+        let n = read_chunk(&mut file, &mut chunk).await?;
+        if n == 0 {
+            break;
+        }
+        
+        transport.send(&chunk[..n]).await?;
+        if verify {
+            let echoed = read_exact(transport, n, read_timeout).await?;
+            if echoed.len() > n {
+                return Err(TransportError::InvalidData(format!(
+                    "Verification failed: {} bytes read back for a {} byte chunk", echoed.len(), n
+                )));
+            }
+            if let Some(i) = echoed.iter().zip(&chunk[..n]).position(|(a, b)| a != b) {
+                return Err(TransportError::InvalidData(format!(
+                    "Verification failed at byte {}: sent 0x{:02X}, read back 0x{:02X}",
+                    bytes_sent + i as u64, chunk[i], echoed[i]
+                )));
+            }
+        }
+        
+        bytes_sent += n as u64;
+        progress(TransferProgress { bytes_sent, total_bytes });
+    }
+    
+    Ok(bytes_sent)
+}
+
+/// Fill `buf` from the file, returning fewer bytes only at end of file
+async fn read_chunk(file: &mut File, buf: &mut [u8]) -> TransportResult<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        let n = file.read(&mut buf[filled..]).await?;
+        if n == 0 {
+            break;
+        }
+        filled += n;
+    }
+    Ok(filled)
+}
+
+/// Receive at least `len` bytes, failing if they don't all arrive within `timeout`
+async fn read_exact(transport: &dyn Transport, len: usize, timeout: Duration) -> TransportResult<Vec<u8>> {
+    let deadline = Instant::now() + timeout;
+    let mut data = Vec::with_capacity(len);
+    
+    while data.len() < len {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(TransportError::Timeout(format!(
+                "Read back {} of {} bytes", data.len(), len
+            )));
+        }
+        data.extend(transport.receive(remaining).await?);
+    }
+    
+    Ok(data)
+}
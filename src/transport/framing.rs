@@ -0,0 +1,168 @@
+//! Frame boundary detection on top of a byte transport
+//!
+//! A `Framing` turns the chunks a transport delivers into complete protocol
+//! frames. `TimeoutFraming` suits protocols without delimiters (e.g. binary
+//! chatter or Modbus RTU style links), where a quiet period ends a frame.
+
+use async_trait::async_trait;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use crate::transport::{Transport, TransportError, TransportResult};
+
+/// Splits a transport's byte stream into frames
+#[async_trait]
+pub trait Framing: Send + Sync {
+    /// Read the next complete frame, waiting up to `timeout` for it to start
+    async fn read_frame(&self, transport: &dyn Transport, timeout: Duration) -> TransportResult<Vec<u8>>;
+}
+
+/// Treats an idle gap longer than `gap` as a frame boundary
+#[derive(Debug)]
+pub struct TimeoutFraming {
+    gap: Duration,
+    max_frame_len: usize,
+    /// Bytes past `max_frame_len`, carried into the next frame
+    overflow: Mutex<Vec<u8>>,
+}
+
+impl TimeoutFraming {
+    /// Default cap on a single frame so a chatty device can't grow one forever
+    pub const DEFAULT_MAX_FRAME_LEN: usize = 4096;
+    
+    /// Frames end after `gap` without new bytes
+    pub fn new(gap: Duration) -> Self {
+        Self {
+            gap,
+            max_frame_len: Self::DEFAULT_MAX_FRAME_LEN,
+            overflow: Mutex::new(Vec::new()),
+        }
+    }
+    
+    /// Cut a frame once it reaches `max_frame_len` bytes even without a gap
+    pub fn with_max_frame_len(mut self, max_frame_len: usize) -> Self {
+        self.max_frame_len = max_frame_len.max(1);
+        self
+    }
+    
+    /// Idle period that ends a frame
+    pub fn gap(&self) -> Duration {
+        self.gap
+    }
+}
+
+#[async_trait]
+impl Framing for TimeoutFraming {
+    async fn read_frame(&self, transport: &dyn Transport, timeout: Duration) -> TransportResult<Vec<u8>> {
+        let deadline = Instant::now() + timeout;
+        let mut overflow = self.overflow.lock().await;
+        
+        // Start with bytes left over from an oversized frame, or wait for new ones
+        let mut frame = if !overflow.is_empty() {
+            std::mem::take(&mut *overflow)
+        } else {
+            loop {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                if remaining.is_zero() {
+                    return Err(TransportError::Timeout(format!(
+                        "No frame started within {}ms", timeout.as_millis()
+                    )));
+                }
+                
+                let chunk = transport.receive(remaining).await?;
+                if !chunk.is_empty() {
+                    break chunk;
+                }
+            }
+        };
+        
+        // Keep collecting until the line stays quiet for a full gap
+        while frame.len() < self.max_frame_len {
+            match transport.receive(self.gap).await {
+                Ok(chunk) if chunk.is_empty() => break,
+                Ok(chunk) => frame.extend_from_slice(&chunk),
+                Err(TransportError::Timeout(_)) => break,
+                Err(e) => return Err(e),
+            }
+        }
+        
+        if frame.len() > self.max_frame_len {
+            *overflow = frame.split_off(self.max_frame_len);
+        }
+        Ok(frame)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::mock::MockTransport;
+    use std::sync::Arc;
+    
+    /// Connected mock receiving each chunk of `script` after its delay (ms) from the previous one
+    fn paced(script: Vec<(u64, &[u8])>) -> Arc<MockTransport> {
+        let transport = Arc::new(MockTransport::scripted(|_| Vec::new()));
+        let script: Vec<(u64, Vec<u8>)> = script.into_iter().map(|(ms, bytes)| (ms, bytes.to_vec())).collect();
+        let device = transport.clone();
+        tokio::spawn(async move {
+            for (ms, chunk) in script {
+                tokio::time::sleep(Duration::from_millis(ms)).await;
+                let _ = device.inject_receive_data(chunk).await;
+            }
+        });
+        transport
+    }
+    
+    #[tokio::test]
+    async fn test_frames_split_at_idle_gap() {
+        let transport = paced(vec![
+            (0, b"\x01\x03"),
+            (5, b"\x02"),
+            (5, b"\x00\x0A"),
+            (150, b"\x01\x06"), // Quiet longer than the gap: new frame
+            (5, b"\xFF"),
+        ]);
+        let framing = TimeoutFraming::new(Duration::from_millis(50));
+        
+        let first = framing.read_frame(&*transport, Duration::from_secs(1)).await.unwrap();
+        assert_eq!(first, vec![0x01, 0x03, 0x02, 0x00, 0x0A]);
+        
+        let second = framing.read_frame(&*transport, Duration::from_secs(1)).await.unwrap();
+        assert_eq!(second, vec![0x01, 0x06, 0xFF]);
+        
+        let none = framing.read_frame(&*transport, Duration::from_millis(100)).await;
+        assert!(matches!(none, Err(TransportError::Timeout(_))));
+    }
+    
+    #[tokio::test]
+    async fn test_short_pauses_below_gap_stay_in_frame() {
+        let transport = paced(vec![
+            (0, b"AB"),
+            (30, b"CD"),
+            (30, b"EF"),
+        ]);
+        
+        // A 50ms gap tolerates the 30ms pauses
+        let frame = TimeoutFraming::new(Duration::from_millis(50))
+            .read_frame(&*transport, Duration::from_secs(1)).await.unwrap();
+        assert_eq!(frame, b"ABCDEF".to_vec());
+        
+        // A 20ms gap splits on them
+        let transport = paced(vec![(0, b"AB"), (30, b"CD")]);
+        let framing = TimeoutFraming::new(Duration::from_millis(20));
+        assert_eq!(framing.read_frame(&*transport, Duration::from_secs(1)).await.unwrap(), b"AB".to_vec());
+        assert_eq!(framing.read_frame(&*transport, Duration::from_secs(1)).await.unwrap(), b"CD".to_vec());
+    }
+    
+    #[tokio::test]
+    async fn test_max_frame_len_caps_frame() {
+        let transport = paced(vec![(0, b"0123"), (1, b"4567")]);
+        let framing = TimeoutFraming::new(Duration::from_millis(50)).with_max_frame_len(6);
+        
+        let frame = framing.read_frame(&*transport, Duration::from_secs(1)).await.unwrap();
+        assert_eq!(frame, b"012345".to_vec());
+        
+        // The cut-off bytes start the next frame
+        let rest = framing.read_frame(&*transport, Duration::from_secs(1)).await.unwrap();
+        assert_eq!(rest, b"67".to_vec());
+    }
+}
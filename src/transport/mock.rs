@@ -1,9 +1,9 @@
 /// Mock transport implementation for testing
 /// Provides configurable failure injection and deterministic behavior
 use async_trait::async_trait;
-use std::sync::{Arc, atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering}};
+use std::sync::{Arc, atomic::{AtomicBool, AtomicU32, Ordering}};
 use std::time::{Duration, Instant};
-use tokio::sync::{RwLock, mpsc};
+use tokio::sync::{Mutex, RwLock, mpsc};
 use crate::transport::{
     Transport, TransportConfig, TransportError, TransportResult, 
     TransportStats, TransportType
@@ -16,6 +16,8 @@ pub struct MockConfig {
     pub connect_failures: u32,
     /// Number of send attempts before succeeding
     pub send_failures: u32,
+    /// Whether an injected send failure also drops the link, like an unplugged cable
+    pub disconnect_on_failure: bool,
     /// Number of receive attempts before succeeding  
     pub receive_failures: u32,
     /// Simulated latency for operations
@@ -33,6 +35,7 @@ impl Default for MockConfig {
         MockConfig {
             connect_failures: 0,
             send_failures: 0,
+            disconnect_on_failure: false,
             receive_failures: 0,
             latency_ms: 10,
             disconnect_after_ops: None,
@@ -42,6 +45,9 @@ impl Default for MockConfig {
     }
 }
 
+/// Replies a simulated device sends back for the bytes written to it
+pub type MockResponder = Arc<dyn Fn(&[u8]) -> Vec<Vec<u8>> + Send + Sync>;
+
 /// Mock transport for testing
 pub struct MockTransport {
     name: String,
@@ -58,8 +64,11 @@ pub struct MockTransport {
     
     // Data handling
     send_buffer: Arc<RwLock<Vec<u8>>>,
-    receive_channel: Option<mpsc::UnboundedReceiver<Vec<u8>>>,
+    sent_history: Arc<RwLock<Vec<Vec<u8>>>>,
+    receive_channel: Mutex<mpsc::UnboundedReceiver<Vec<u8>>>,
     receive_sender: mpsc::UnboundedSender<Vec<u8>>,
+    responder: Option<MockResponder>,
+    reply_delay: Option<Arc<dyn Fn(&[u8]) -> Duration + Send + Sync>>,
     
     // Timing
     last_operation: Arc<RwLock<Option<Instant>>>,
@@ -80,12 +89,40 @@ impl MockTransport {
             receive_attempts: Arc::new(AtomicU32::new(0)),
             total_operations: Arc::new(AtomicU32::new(0)),
             send_buffer: Arc::new(RwLock::new(Vec::new())),
-            receive_channel: Some(rx),
+            sent_history: Arc::new(RwLock::new(Vec::new())),
+            receive_channel: Mutex::new(rx),
             receive_sender: tx,
+            responder: None,
+            reply_delay: None,
             last_operation: Arc::new(RwLock::new(None)),
         }
     }
     
+    /// Connected mock with no simulated latency whose replies come from `responder`
+    pub fn scripted(responder: impl Fn(&[u8]) -> Vec<Vec<u8>> + Send + Sync + 'static) -> Self {
+        let mock_config = MockConfig {
+            latency_ms: 0,
+            enforce_latency: false,
+            ..Default::default()
+        };
+        let transport = Self::new("mock".into(), TransportConfig::default(), mock_config).with_responder(responder);
+        transport.connected.store(true, Ordering::Relaxed);
+        transport
+    }
+    
+    /// Answer each send with `responder`'s replies instead of echoing
+    /// A receive with nothing queued then times out
+    pub fn with_responder(mut self, responder: impl Fn(&[u8]) -> Vec<Vec<u8>> + Send + Sync + 'static) -> Self {
+        self.responder = Some(Arc::new(responder));
+        self
+    }
+    
+    /// Hold back the replies to each send for `delay(data)`, like a slow device
+    pub fn with_reply_delay(mut self, delay: impl Fn(&[u8]) -> Duration + Send + Sync + 'static) -> Self {
+        self.reply_delay = Some(Arc::new(delay));
+        self
+    }
+    
     /// Update mock configuration during test
     pub async fn set_mock_config(&self, config: MockConfig) {
         *self.mock_config.write().await = config;
@@ -104,6 +141,26 @@ impl MockTransport {
         self.send_buffer.read().await.clone()
     }
     
+    /// Replace the reported statistics
+    pub async fn set_stats(&self, stats: TransportStats) {
+        *self.stats.write().await = stats;
+    }
+    
+    /// Every successful send so far, oldest first
+    pub async fn get_sent_history(&self) -> Vec<Vec<u8>> {
+        self.sent_history.read().await.clone()
+    }
+    
+    /// Connect attempts since the counters were last reset
+    pub fn connect_count(&self) -> u32 {
+        self.connect_attempts.load(Ordering::Relaxed)
+    }
+    
+    /// Send attempts while connected since the counters were last reset
+    pub fn send_count(&self) -> u32 {
+        self.send_attempts.load(Ordering::Relaxed)
+    }
+    
     /// Reset all counters
     pub fn reset_counters(&self) {
         self.connect_attempts.store(0, Ordering::Relaxed);
@@ -157,7 +214,7 @@ impl Transport for MockTransport {
         self.connected.load(Ordering::Relaxed)
     }
     
-    async fn connect(&mut self) -> TransportResult<()> {
+    async fn connect(&self) -> TransportResult<()> {
         let attempts = self.connect_attempts.fetch_add(1, Ordering::Relaxed);
         let mock_cfg = self.mock_config.read().await;
         
@@ -172,13 +229,13 @@ impl Transport for MockTransport {
         Ok(())
     }
     
-    async fn disconnect(&mut self) -> TransportResult<()> {
+    async fn disconnect(&self) -> TransportResult<()> {
         self.connected.store(false, Ordering::Relaxed);
         self.reset_counters();
         Ok(())
     }
     
-    async fn send(&mut self, data: &[u8]) -> TransportResult<()> {
+    async fn send(&self, data: &[u8]) -> TransportResult<()> {
         if !self.is_connected() {
             return Err(TransportError::NotConnected);
         }
@@ -191,6 +248,9 @@ impl Transport for MockTransport {
         
         if attempts < mock_cfg.send_failures {
             self.stats.write().await.transactions_failed += 1;
+            if mock_cfg.disconnect_on_failure {
+                self.connected.store(false, Ordering::Relaxed);
+            }
             return Err(TransportError::IoError(
                 std::io::Error::new(
                     std::io::ErrorKind::Other,
@@ -199,8 +259,23 @@ impl Transport for MockTransport {
             ));
         }
         
-        // Store sent data
+        // Store sent data and queue the simulated device's replies
         *self.send_buffer.write().await = data.to_vec();
+        self.sent_history.write().await.push(data.to_vec());
+        if let Some(responder) = &self.responder {
+            let replies = responder(data);
+            let delay = self.reply_delay.as_ref().map_or(Duration::ZERO, |delay| delay(data));
+            let sender = self.receive_sender.clone();
+            let deliver = move || replies.into_iter().for_each(|reply| { let _ = sender.send(reply); });
+            if delay.is_zero() {
+                deliver();
+            } else {
+                tokio::spawn(async move {
+                    tokio::time::sleep(delay).await;
+                    deliver();
+                });
+            }
+        }
         
         // Update stats
         let mut stats = self.stats.write().await;
@@ -211,7 +286,7 @@ impl Transport for MockTransport {
         Ok(())
     }
     
-    async fn receive(&mut self, timeout: Duration) -> TransportResult<Vec<u8>> {
+    async fn receive(&self, timeout: Duration) -> TransportResult<Vec<u8>> {
         if !self.is_connected() {
             return Err(TransportError::NotConnected);
         }
@@ -230,20 +305,22 @@ impl Transport for MockTransport {
         // Return configured data or echo sent data
         let data = if let Some(ref configured_data) = mock_cfg.receive_data {
             configured_data.clone()
-        } else if let Some(ref mut rx) = self.receive_channel {
+        } else {
             // Try to receive injected data
+            let mut rx = self.receive_channel.lock().await;
             match tokio::time::timeout(timeout, rx.recv()).await {
                 Ok(Some(data)) => data,
                 Ok(None) => return Err(TransportError::IoError(
                     std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "Channel closed")
                 )),
+                Err(_) if self.responder.is_some() => {
+                    return Err(TransportError::Timeout(format!("{}ms", timeout.as_millis())));
+                }
                 Err(_) => {
                     // Echo sent data as fallback
                     self.send_buffer.read().await.clone()
                 }
             }
-        } else {
-            self.send_buffer.read().await.clone()
         };
         
         // Update stats
@@ -262,9 +339,10 @@ impl Transport for MockTransport {
             .unwrap_or_else(|_| TransportStats::default())
     }
     
-    async fn reset(&mut self) -> TransportResult<()> {
+    async fn reset(&self) -> TransportResult<()> {
         self.reset_counters();
         *self.send_buffer.write().await = Vec::new();
+        self.sent_history.write().await.clear();
         *self.stats.write().await = TransportStats::default();
         Ok(())
     }
@@ -273,7 +351,7 @@ impl Transport for MockTransport {
         &self.config
     }
     
-    async fn cleanup_resources(&mut self) -> TransportResult<()> {
+    async fn cleanup_resources(&self) -> TransportResult<()> {
         self.connected.store(false, Ordering::Relaxed);
         self.reset_counters();
         Ok(())
@@ -288,7 +366,7 @@ mod tests {
     async fn test_mock_transport_basic() {
         let config = TransportConfig::default();
         let mock_config = MockConfig::default();
-        let mut transport = MockTransport::new("test".into(), config, mock_config);
+        let transport = MockTransport::new("test".into(), config, mock_config);
         
         // Test connection
         assert!(!transport.is_connected());
@@ -315,7 +393,7 @@ mod tests {
             receive_failures: 1,
             ..Default::default()
         };
-        let mut transport = MockTransport::new("test".into(), config, mock_config);
+        let transport = MockTransport::new("test".into(), config, mock_config);
         
         // Test connect failures
         assert!(transport.connect().await.is_err());
@@ -338,7 +416,7 @@ mod tests {
             disconnect_after_ops: Some(3),
             ..Default::default()
         };
-        let mut transport = MockTransport::new("test".into(), config, mock_config);
+        let transport = MockTransport::new("test".into(), config, mock_config);
         
         transport.connect().await.unwrap();
         
@@ -351,4 +429,20 @@ mod tests {
         assert!(transport.send(b"4").await.is_err());
         assert!(!transport.is_connected());
     }
-}
\ No newline at end of file
+    
+    #[tokio::test]
+    async fn test_scripted_mock_answers_with_responder() {
+        let transport = MockTransport::scripted(|data| match data {
+            b"ping" => vec![b"pong".to_vec()],
+            _ => Vec::new(),
+        });
+        assert!(transport.is_connected());
+        
+        transport.send(b"ping").await.unwrap();
+        assert_eq!(transport.receive(Duration::from_millis(50)).await.unwrap(), b"pong");
+        
+        // No reply: times out rather than echoing
+        transport.send(b"other").await.unwrap();
+        assert!(matches!(transport.receive(Duration::from_millis(20)).await, Err(TransportError::Timeout(_))));
+    }
+}
@@ -14,6 +14,13 @@ pub mod common;
 pub mod manifest;
 pub mod monitor;
 pub mod backoff;
+pub mod command_codec;
+pub mod framing;
+pub mod port_lock;
+pub mod wire_trace;
+pub mod file_transfer;
+pub mod connection_budget;
+pub mod discovery_debounce;
 
 #[cfg(test)]
 pub mod mock;
@@ -22,8 +29,16 @@ pub mod mock;
 mod tests;
 
 // Re-export common types
-pub use common::{TransportType, TransportError, TransportResult, TransportConfig};
+pub use common::{TransportType, TransportError, TransportResult, TransportConfig, LineErrorKind, ReconnectHints, ReadStrategy};
 pub use monitor::LatencyMonitor;
+pub use command_codec::CommandCodec;
+pub use framing::{Framing, TimeoutFraming};
+pub use port_lock::PortLock;
+pub use wire_trace::{WireDirection, WireTrace};
+pub use file_transfer::{send_file, TransferProgress};
+pub use connection_budget::{ConnectionBudget, ConnectionUsage, BudgetedTransport};
+pub use discovery_debounce::{DiscoveryDebouncer, DiscoveryChange};
+pub use tokio_util::sync::CancellationToken;
 
 /// Core transport trait for device communication
 /// Implements connection management, data transfer, and latency enforcement
@@ -41,9 +56,23 @@ pub trait Transport: Send + Sync {
     /// Check if currently connected
     fn is_connected(&self) -> bool;
     
-    /// Connect to the transport
+    /// Connect to the transport; succeeds without reconnecting when already connected
     async fn connect(&self) -> TransportResult<()>;
     
+    /// `connect`, first checking that `config` describes this transport's connection
+    /// Fails with `SettingsMismatch` rather than silently keeping (or replacing)
+    /// a live connection opened with other settings
+    async fn connect_with(&self, config: &TransportConfig) -> TransportResult<()> {
+        if !self.config().same_connection(config) {
+            let state = if self.is_connected() { "connected" } else { "configured" };
+            return Err(TransportError::SettingsMismatch(format!(
+                "{} is {} with different settings; disconnect and create a new transport to change them",
+                self.name(), state
+            )));
+        }
+        self.connect().await
+    }
+    
     /// Disconnect from the transport
     async fn disconnect(&self) -> TransportResult<()>;
     
@@ -53,12 +82,77 @@ pub trait Transport: Send + Sync {
     /// Receive data with timeout
     async fn receive(&self, timeout: Duration) -> TransportResult<Vec<u8>>;
     
+    /// Send data, returning `Cancelled` early if the token fires
+    async fn send_cancellable(&self, data: &[u8], token: CancellationToken) -> TransportResult<()> {
+        if token.is_cancelled() {
+            return Err(TransportError::Cancelled);
+        }
+        
+        tokio::select! {
+            biased;
+            _ = token.cancelled() => Err(TransportError::Cancelled),
+            result = self.send(data) => result,
+        }
+    }
+    
+    /// Receive data with timeout, returning `Cancelled` early if the token fires
+    /// The default drops the pending receive; transports with blocking reads
+    /// should override this to stop the underlying read as well
+    async fn receive_cancellable(&self, timeout: Duration, token: CancellationToken) -> TransportResult<Vec<u8>> {
+        if token.is_cancelled() {
+            return Err(TransportError::Cancelled);
+        }
+        
+        tokio::select! {
+            biased;
+            _ = token.cancelled() => Err(TransportError::Cancelled),
+            result = self.receive(timeout) => result,
+        }
+    }
+    
     /// Send and receive in one operation (common pattern)
     async fn transact(&self, data: &[u8], timeout: Duration) -> TransportResult<Vec<u8>> {
         self.send(data).await?;
         self.receive(timeout).await
     }
     
+    /// `transact` that survives brief link drops: after a retryable failure it
+    /// waits with backoff, reconnects if needed and repeats the whole exchange,
+    /// up to `max_retries` times
+    /// Retries resend `data`, so non-idempotent commands should use `transact`
+    /// (or pass `max_retries = 0`)
+    async fn transact_resilient(&self, data: &[u8], timeout: Duration, max_retries: u32) -> TransportResult<Vec<u8>> {
+        if max_retries == 0 {
+            return self.transact(data, timeout).await;
+        }
+        
+        let mut backoff = backoff::ExponentialBackoff::from_config(max_retries, self.config().reconnect_delay_ms);
+        loop {
+            let error = match self.transact(data, timeout).await {
+                Ok(response) => return Ok(response),
+                Err(e) => e,
+            };
+            if !backoff::is_retryable_error(&error) {
+                return Err(error);
+            }
+            let Some(delay) = backoff.next_delay() else {
+                return Err(error);
+            };
+            
+            tracing::warn!(
+                "{}: exchange failed ({}), retry {}/{} in {:?}",
+                self.name(), error, backoff.current_attempt(), max_retries, delay
+            );
+            tokio::time::sleep(delay).await;
+            
+            if !self.is_connected() {
+                if let Err(e) = self.connect().await {
+                    tracing::warn!("{}: reconnect before retry failed: {}", self.name(), e);
+                }
+            }
+        }
+    }
+    
     /// Get transport statistics
     fn stats(&self) -> TransportStats;
     
@@ -72,6 +166,111 @@ pub trait Transport: Send + Sync {
     /// This method should abort all spawned tasks, drop Arc references,
     /// and ensure no memory leaks occur during reconnect cycles
     async fn cleanup_resources(&self) -> TransportResult<()>;
+    
+    /// Register a callback invoked with the outcome of every reconnection attempt
+    /// Multiple callbacks may be registered; transports that never reconnect ignore them
+    fn on_reconnect(&self, _callback: ReconnectCallback) {}
+    
+    /// Raw byte trace toggle, if the transport supports wire tracing
+    fn wire_trace(&self) -> Option<&WireTrace> {
+        None
+    }
+    
+    /// Why the transport last disconnected, if it tracks that
+    fn disconnect_reason(&self) -> Option<DisconnectReason> {
+        None
+    }
+    
+    /// One-line status for compact displays, e.g. "Connected, 12ms, 0 reconnects"
+    /// A disconnected transport reports its last error instead of latency
+    fn status_summary(&self) -> String {
+        let stats = self.stats();
+        let reconnects = match stats.reconnect_count {
+            1 => "1 reconnect".to_string(),
+            n => format!("{} reconnects", n),
+        };
+        
+        if self.is_connected() {
+            format!("Connected, {:.0}ms, {}", stats.avg_latency_ms, reconnects)
+        } else {
+            match stats.last_error {
+                Some(error) => format!("Disconnected: {}, {}", error, reconnects),
+                None => format!("Disconnected, {}", reconnects),
+            }
+        }
+    }
+}
+
+/// Outcome of a completed reconnection attempt
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReconnectOutcome {
+    Succeeded,
+    Failed(String),
+}
+
+impl ReconnectOutcome {
+    pub fn is_success(&self) -> bool {
+        matches!(self, ReconnectOutcome::Succeeded)
+    }
+}
+
+/// Why a transport left the connected state
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DisconnectReason {
+    /// `disconnect` was called locally
+    Local,
+    /// The device announced it was going away (e.g. sent its goodbye marker)
+    DeviceRequested,
+    /// The link failed or the port vanished
+    Lost,
+}
+
+impl DisconnectReason {
+    /// Whether automatic reconnection should follow this disconnect
+    pub fn allows_auto_reconnect(&self) -> bool {
+        !matches!(self, DisconnectReason::DeviceRequested)
+    }
+}
+
+/// Callback run after each reconnection attempt (e.g. to re-arm device state)
+pub type ReconnectCallback = Arc<dyn Fn(&ReconnectOutcome) + Send + Sync>;
+
+/// Registered reconnection callbacks, shareable with background reconnect tasks
+#[derive(Clone, Default)]
+pub struct ReconnectHooks {
+    callbacks: Arc<std::sync::RwLock<Vec<ReconnectCallback>>>,
+}
+
+impl ReconnectHooks {
+    /// Add a callback
+    pub fn register(&self, callback: ReconnectCallback) {
+        self.callbacks.write().unwrap().push(callback);
+    }
+    
+    /// Invoke every callback with the outcome of an attempt
+    pub fn notify(&self, outcome: &ReconnectOutcome) {
+        // Clone the list so callbacks can register further callbacks
+        let callbacks = self.callbacks.read().unwrap().clone();
+        for callback in callbacks {
+            callback(outcome);
+        }
+    }
+    
+    /// Number of registered callbacks
+    pub fn len(&self) -> usize {
+        self.callbacks.read().unwrap().len()
+    }
+    
+    /// Whether no callbacks are registered
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl std::fmt::Debug for ReconnectHooks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReconnectHooks").field("callbacks", &self.len()).finish()
+    }
 }
 
 /// Transport statistics for monitoring
@@ -112,6 +311,65 @@ pub struct TransportStats {
     
     /// Total cumulative enforcement delay in milliseconds
     pub total_enforcement_delay_ms: f64,
+    
+    /// Number of serial line errors (parity, framing, overrun, break)
+    pub line_errors: u64,
+}
+
+/// Rates between two `TransportStats` snapshots
+/// Counters that went backwards (stats reset in between) count as zero
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct StatsDelta {
+    pub elapsed: Duration,
+    pub bytes_sent_per_sec: f64,
+    pub bytes_received_per_sec: f64,
+    
+    /// Successful and failed transactions per second
+    pub transactions_per_sec: f64,
+    
+    /// Fraction of the interval's transactions that failed (0.0 with none)
+    pub error_rate: f64,
+    
+    /// Change in the cumulative failure fraction since the earlier snapshot
+    pub error_rate_change: f64,
+    
+    pub reconnects: u32,
+    pub line_errors: u64,
+}
+
+impl TransportStats {
+    /// Fraction of all transactions that failed (0.0 with none)
+    pub fn error_rate(&self) -> f64 {
+        failure_fraction(self.transactions_success, self.transactions_failed)
+    }
+    
+    /// Rates since `earlier`, taken `elapsed` before this snapshot
+    /// A zero `elapsed` yields zero rates
+    pub fn delta(&self, earlier: &TransportStats, elapsed: Duration) -> StatsDelta {
+        let secs = elapsed.as_secs_f64();
+        let per_sec = |count: u64| if secs > 0.0 { count as f64 / secs } else { 0.0 };
+        
+        let succeeded = self.transactions_success.saturating_sub(earlier.transactions_success);
+        let failed = self.transactions_failed.saturating_sub(earlier.transactions_failed);
+        
+        StatsDelta {
+            elapsed,
+            bytes_sent_per_sec: per_sec(self.bytes_sent.saturating_sub(earlier.bytes_sent)),
+            bytes_received_per_sec: per_sec(self.bytes_received.saturating_sub(earlier.bytes_received)),
+            transactions_per_sec: per_sec(succeeded + failed),
+            error_rate: failure_fraction(succeeded, failed),
+            error_rate_change: self.error_rate() - earlier.error_rate(),
+            reconnects: self.reconnect_count.saturating_sub(earlier.reconnect_count),
+            line_errors: self.line_errors.saturating_sub(earlier.line_errors),
+        }
+    }
+}
+
+fn failure_fraction(succeeded: u64, failed: u64) -> f64 {
+    match succeeded + failed {
+        0 => 0.0,
+        total => failed as f64 / total as f64,
+    }
 }
 
 /// Transport connection state
@@ -160,6 +418,9 @@ pub struct TransportBase {
     pub capabilities: TransportCapabilities,
     pub monitor: Arc<LatencyMonitor>,
     pub reconnection_task: Arc<Mutex<Option<JoinHandle<()>>>>,
+    pub reconnect_hooks: ReconnectHooks,
+    pub wire_trace: WireTrace,
+    pub disconnect_reason: Arc<std::sync::RwLock<Option<DisconnectReason>>>,
 }
 
 impl TransportBase {
@@ -216,6 +477,9 @@ impl TransportBase {
             capabilities,
             monitor,
             reconnection_task: Arc::new(Mutex::new(None)),
+            reconnect_hooks: ReconnectHooks::default(),
+            wire_trace: WireTrace::default(),
+            disconnect_reason: Arc::new(std::sync::RwLock::new(None)),
         }
     }
     
@@ -227,6 +491,14 @@ impl TransportBase {
         update_fn(&mut stats);
     }
     
+    /// Copy of the current stats for the synchronous `Transport::stats`
+    /// Falls back to empty stats only while an update holds the lock
+    pub fn stats_snapshot(&self) -> TransportStats {
+        self.stats.try_read()
+            .map(|guard| guard.clone())
+            .unwrap_or_else(|_| TransportStats::default())
+    }
+    
     pub async fn set_state(&self, new_state: ConnectionState) {
         let mut state = self.state.write().await;
         *state = new_state;
@@ -237,6 +509,15 @@ impl TransportBase {
         *state
     }
     
+    /// Record why the transport disconnected (`None` once connected again)
+    pub fn set_disconnect_reason(&self, reason: Option<DisconnectReason>) {
+        *self.disconnect_reason.write().unwrap() = reason;
+    }
+    
+    pub fn disconnect_reason(&self) -> Option<DisconnectReason> {
+        *self.disconnect_reason.read().unwrap()
+    }
+    
     /// Enforce latency requirements
     pub async fn enforce_latency(&self, start: std::time::Instant) -> TransportResult<()> {
         let elapsed = start.elapsed();
@@ -282,6 +563,11 @@ impl TransportBase {
     where
         F: Fn() -> std::pin::Pin<Box<dyn std::future::Future<Output = TransportResult<()>> + Send>> + Send + 'static,
     {
+        if self.disconnect_reason().is_some_and(|reason| !reason.allows_auto_reconnect()) {
+            tracing::info!("{} was disconnected by the device, not reconnecting", self.name);
+            return Ok(());
+        }
+        
         let mut task_guard = self.reconnection_task.lock().await;
         
         // Cancel existing reconnection task if any
@@ -293,11 +579,12 @@ impl TransportBase {
         let backoff = backoff::ExponentialBackoff::from_config(
             self.config.max_reconnect_attempts,
             self.config.reconnect_delay_ms,
-        );
+        ).with_hints(&self.config.reconnect_hints);
         
         let state_clone = self.state.clone();
         let stats_clone = self.stats.clone();
         let name = self.name.clone();
+        let hooks = self.reconnect_hooks.clone();
         
         // Spawn new reconnection task
         let handle = tokio::spawn(async move {
@@ -343,10 +630,15 @@ impl TransportBase {
                             // Update stats
                             let mut stats = stats_clone.write().await;
                             stats.reconnect_count += 1;
+                            drop(stats);
+                            drop(state);
                             
+                            hooks.notify(&ReconnectOutcome::Succeeded);
                             break; // Success, exit loop
                         }
                         Err(e) => {
+                            hooks.notify(&ReconnectOutcome::Failed(e.to_string()));
+                            
                             // Check if error is retryable
                             if !backoff::is_retryable_error(&e) {
                                 tracing::error!("Non-retryable error for {}: {}", name, e);
@@ -376,6 +668,11 @@ impl TransportBase {
         Ok(())
     }
     
+    /// Register a callback run after each reconnection attempt
+    pub fn on_reconnect(&self, callback: ReconnectCallback) {
+        self.reconnect_hooks.register(callback);
+    }
+    
     /// Cancel any active reconnection attempts
     pub async fn cancel_reconnection(&self) {
         let mut task_guard = self.reconnection_task.lock().await;
@@ -390,38 +687,52 @@ impl TransportBase {
 pub struct TransportFactory;
 
 impl TransportFactory {
-    /// Create a transport from configuration
+    /// Create a transport from configuration, counted against the global `ConnectionBudget`
     pub async fn create(config: TransportConfig) -> TransportResult<Box<dyn Transport>> {
-        match config.transport_type {
-            TransportType::Serial => {
-                let transport = serial::SerialTransport::new(config)?;
-                Ok(Box::new(transport))
-            }
-            TransportType::Tcp => {
-                let transport = tcp::TcpTransport::new(config)?;
-                Ok(Box::new(transport))
-            }
-            TransportType::Udp => {
-                let transport = udp::UdpTransport::new(config)?;
-                Ok(Box::new(transport))
-            }
-            TransportType::Ssh => {
-                let transport = ssh::SshTransport::new(config)?;
-                Ok(Box::new(transport))
-            }
-        }
+        Self::create_with_budget(config, ConnectionBudget::global()).await
+    }
+    
+    /// Create a transport that holds a slot in `budget` until it is dropped
+    /// Fails with `ResourceUnavailable` when the budget is full
+    pub async fn create_with_budget(config: TransportConfig, budget: &ConnectionBudget) -> TransportResult<Box<dyn Transport>> {
+        let slot = budget.try_acquire()?;
+        let transport: Box<dyn Transport> = match config.transport_type {
+            TransportType::Serial => Box::new(serial::SerialTransport::new(config)?),
+            TransportType::Tcp => Box::new(tcp::TcpTransport::new(config)?),
+            TransportType::Udp => Box::new(udp::UdpTransport::new(config)?),
+            TransportType::Ssh => Box::new(ssh::SshTransport::new(config)?),
+        };
+        Ok(Box::new(BudgetedTransport::new(transport, slot)))
     }
     
     /// List available transports on the system
     pub async fn list_available() -> TransportResult<Vec<TransportInfo>> {
+        Self::list_available_filtered(serial::DiscoveryFilter::ShowAll, None).await
+    }
+    
+    /// List available transports, hiding serial ports rejected by `filter`
+    /// With a COM fallback, openable COM ports Windows did not enumerate are
+    /// listed too; without one only enumerated ports are
+    pub async fn list_available_filtered(
+        filter: serial::DiscoveryFilter,
+        com_fallback: Option<&serial::ComPortFallback>,
+    ) -> TransportResult<Vec<TransportInfo>> {
         let mut available = Vec::new();
         
-        // Check for serial ports
-        if let Ok(ports) = serial::SerialTransport::list_ports().await {
-            for port_info in ports {
+        let ports = match com_fallback {
+            Some(fallback) => serial::SerialTransport::list_ports_with_fallback(fallback).await,
+            None => serial::SerialTransport::list_ports().await,
+        };
+        if let Ok(ports) = ports {
+            for port_info in ports.into_iter().filter(|port| filter.allows(port)) {
+                let name = if port_info.verified {
+                    port_info.name.clone()
+                } else {
+                    format!("{} (unverified)", port_info.name)
+                };
                 available.push(TransportInfo {
                     transport_type: TransportType::Serial,
-                    name: port_info.name.clone(),
+                    name,
                     address: port_info.name,
                     available: true,
                 });
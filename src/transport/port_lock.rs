@@ -0,0 +1,204 @@
+//! Advisory lock files for exclusive serial port access
+//!
+//! Nothing stops two processes from opening the same tty and interleaving
+//! their writes. When a port is opened with `SerialSettings::exclusive`, the
+//! transport first creates a lock file named after the port and holding its
+//! PID; a second exclusive open of the same port fails until the holder drops
+//! the lock. Locks left behind by processes that no longer exist are taken over.
+
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use crate::transport::{TransportError, TransportResult};
+
+/// Exclusive claim on a port, released when dropped
+#[derive(Debug)]
+pub struct PortLock {
+    path: PathBuf,
+}
+
+impl PortLock {
+    /// Lock `port_name` in the system temp directory
+    pub fn acquire(port_name: &str) -> TransportResult<Self> {
+        Self::acquire_in(&std::env::temp_dir(), port_name)
+    }
+    
+    /// Lock `port_name` with a lock file in `dir`
+    pub fn acquire_in(dir: &Path, port_name: &str) -> TransportResult<Self> {
+        let path = dir.join(lock_file_name(port_name));
+        
+        // One retry, after clearing a lock whose owner has exited
+        for _ in 0..2 {
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(mut file) => {
+                    write!(file, "{}", std::process::id()).map_err(TransportError::IoError)?;
+                    tracing::debug!("Locked {} via {}", port_name, path.display());
+                    return Ok(PortLock { path });
+                }
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+                    let holder = fs::read_to_string(&path).ok().and_then(|pid| pid.trim().parse::<u32>().ok());
+                    match holder {
+                        Some(pid) if !process_alive(pid) => {
+                            tracing::warn!("Removing stale lock on {} left by exited process {}", port_name, pid);
+                            let _ = fs::remove_file(&path);
+                        }
+                        Some(pid) => {
+                            return Err(TransportError::ConnectionFailed(format!(
+                                "{} is locked by another process (pid {})", port_name, pid
+                            )));
+                        }
+                        None => {
+                            return Err(TransportError::ConnectionFailed(format!(
+                                "{} is locked by another process ({})", port_name, path.display()
+                            )));
+                        }
+                    }
+                }
+                Err(e) => return Err(TransportError::IoError(e)),
+            }
+        }
+        
+        Err(TransportError::ConnectionFailed(format!("could not lock {}", port_name)))
+    }
+    
+    /// Path of the lock file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for PortLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// UUCP-style lock file name, e.g. "LCK..ttyUSB0" for "/dev/ttyUSB0"
+fn lock_file_name(port_name: &str) -> String {
+    let base = port_name.rsplit(['/', '\\']).next().unwrap_or(port_name);
+    let base: String = base.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' { c } else { '_' })
+        .collect();
+    format!("LCK..{}", base)
+}
+
+/// Whether `pid` is still running
+#[cfg(unix)]
+fn process_alive(pid: u32) -> bool {
+    const EPERM: i32 = 1;
+    extern "C" {
+        fn kill(pid: i32, sig: i32) -> i32;
+    }
+    
+    // 0 and negative PIDs address process groups, not a single process
+    let Ok(pid) = i32::try_from(pid) else {
+        return false;
+    };
+    if pid <= 0 {
+        return false;
+    }
+    
+    // Signal 0 only checks that the process exists
+    if unsafe { kill(pid, 0) } == 0 {
+        return true;
+    }
+    // EPERM: it exists but belongs to another user
+    std::io::Error::last_os_error().raw_os_error() == Some(EPERM)
+}
+
+/// Whether `pid` is still running
+#[cfg(windows)]
+fn process_alive(pid: u32) -> bool {
+    use std::ffi::c_void;
+    const PROCESS_QUERY_LIMITED_INFORMATION: u32 = 0x1000;
+    const STILL_ACTIVE: u32 = 259;
+    const ERROR_ACCESS_DENIED: i32 = 5;
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn OpenProcess(access: u32, inherit_handle: i32, pid: u32) -> *mut c_void;
+        fn GetExitCodeProcess(process: *mut c_void, exit_code: *mut u32) -> i32;
+        fn CloseHandle(handle: *mut c_void) -> i32;
+    }
+    
+    let process = unsafe { OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid) };
+    if process.is_null() {
+        // Access denied: it exists but we may not query it
+        return std::io::Error::last_os_error().raw_os_error() == Some(ERROR_ACCESS_DENIED);
+    }
+    
+    // An exited process keeps a handle while anyone holds one, so check the exit code
+    let mut exit_code = 0;
+    let queried = unsafe { GetExitCodeProcess(process, &mut exit_code) } != 0;
+    unsafe { CloseHandle(process) };
+    !queried || exit_code == STILL_ACTIVE
+}
+
+/// Assumed alive where liveness can't be checked
+#[cfg(not(any(unix, windows)))]
+fn process_alive(_pid: u32) -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn lock_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("port-lock-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+    
+    #[test]
+    fn test_second_exclusive_open_fails_until_released() {
+        let dir = lock_dir();
+        
+        let lock = PortLock::acquire_in(&dir, "/dev/ttyUSB0").unwrap();
+        assert!(lock.path().ends_with("LCK..ttyUSB0"));
+        
+        match PortLock::acquire_in(&dir, "/dev/ttyUSB0") {
+            Err(TransportError::ConnectionFailed(msg)) => assert!(msg.contains("locked by another process")),
+            other => panic!("Expected ConnectionFailed, got {:?}", other),
+        }
+        
+        // Other ports are unaffected
+        assert!(PortLock::acquire_in(&dir, "/dev/ttyUSB1").is_ok());
+        
+        drop(lock);
+        assert!(PortLock::acquire_in(&dir, "/dev/ttyUSB0").is_ok());
+        let _ = fs::remove_dir_all(&dir);
+    }
+    
+    #[cfg(any(unix, windows))]
+    #[test]
+    fn test_stale_lock_is_taken_over() {
+        let dir = lock_dir();
+        fs::write(dir.join("LCK..ttyACM0"), format!("{}", u32::MAX)).unwrap();
+        
+        let lock = PortLock::acquire_in(&dir, "/dev/ttyACM0").unwrap();
+        assert_eq!(fs::read_to_string(lock.path()).unwrap(), std::process::id().to_string());
+        let _ = fs::remove_dir_all(&dir);
+    }
+    
+    #[cfg(any(unix, windows))]
+    #[test]
+    fn test_process_alive() {
+        assert!(process_alive(std::process::id()));
+        
+        #[cfg(unix)]
+        let mut child = std::process::Command::new("true").spawn().unwrap();
+        #[cfg(windows)]
+        let mut child = std::process::Command::new("cmd").args(["/C", "exit"]).spawn().unwrap();
+        let pid = child.id();
+        child.wait().unwrap();
+        assert!(!process_alive(pid));
+        assert!(!process_alive(u32::MAX));
+    }
+    
+    #[test]
+    fn test_lock_file_name_sanitizes_port() {
+        assert_eq!(lock_file_name("COM3"), "LCK..COM3");
+        assert_eq!(lock_file_name(r"\\.\COM12"), "LCK..COM12");
+        assert_eq!(lock_file_name("/dev/serial/by-id/usb-Arduino:1"), "LCK..usb-Arduino_1");
+    }
+}
@@ -1,15 +1,17 @@
 use async_trait::async_trait;
 use std::sync::{Arc, atomic::{AtomicBool, Ordering}};
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, broadcast};
 use std::time::{Duration, Instant};
 use tokio::task::{JoinHandle, spawn_blocking};
-use rand::Rng;
 use uuid::Uuid;
+use serde::{Serialize, Deserialize};
 use crate::transport::{
     Transport, TransportBase, TransportConfig, TransportError, TransportResult, 
-    TransportStats, TransportType, ConnectionState
+    TransportStats, TransportType, ConnectionState, LineErrorKind, CancellationToken,
+    ReconnectCallback, ReconnectOutcome, WireDirection, WireTrace, PortLock, DisconnectReason,
 };
-use crate::transport::common::SerialSettings;
+use crate::transport::common::{ReadStrategy, ReconnectHints, SerialSettings};
+use crate::transport::backoff::ExponentialBackoff;
 
 // Type alias for SerialConfig
 type SerialConfig = SerialSettings;
@@ -37,15 +39,46 @@ impl From<crate::transport::common::StopBits> for serialport::StopBits {
     }
 }
 
-impl From<crate::transport::common::Parity> for serialport::Parity {
-    fn from(parity: crate::transport::common::Parity) -> Self {
+impl TryFrom<crate::transport::common::Parity> for serialport::Parity {
+    type Error = TransportError;
+    
+    fn try_from(parity: crate::transport::common::Parity) -> TransportResult<Self> {
         use crate::transport::common::Parity;
         match parity {
-            Parity::None => serialport::Parity::None,
-            Parity::Odd => serialport::Parity::Odd,
-            Parity::Even => serialport::Parity::Even,
+            Parity::None => Ok(serialport::Parity::None),
+            Parity::Odd => Ok(serialport::Parity::Odd),
+            Parity::Even => Ok(serialport::Parity::Even),
+            Parity::Mark | Parity::Space => Err(TransportError::ConfigError(format!(
+                "{:?} parity is not supported by the serial backend", parity
+            ))),
+        }
+    }
+}
+
+/// Check a serial settings combination against what the backend can open
+/// Catches bad configs at construction instead of as an open-time failure
+pub fn validate_serial_settings(settings: &SerialSettings) -> TransportResult<()> {
+    use crate::transport::common::{DataBits, StopBits};
+    
+    if settings.baud_rate == 0 {
+        return Err(TransportError::ConfigError("Invalid baud rate".into()));
+    }
+    serialport::Parity::try_from(settings.parity)?;
+    // Five data bits with two stop bits means 1.5 stop bits on real UARTs, which the backend can't express
+    if settings.data_bits == DataBits::Five && settings.stop_bits == StopBits::Two {
+        return Err(TransportError::ConfigError(
+            "5 data bits with 2 stop bits is not supported (the line would need 1.5 stop bits)".into()
+        ));
+    }
+    if settings.inter_byte_timeout_ms == Some(0) {
+        return Err(TransportError::ConfigError("Inter-byte timeout must be at least 1 ms".into()));
+    }
+    for (name, marker) in [("Overflow", &settings.overflow_marker), ("Goodbye", &settings.goodbye_marker)] {
+        if marker.as_ref().is_some_and(|m| m.is_empty()) {
+            return Err(TransportError::ConfigError(format!("{} marker must not be empty", name)));
         }
     }
+    Ok(())
 }
 
 impl From<crate::transport::common::FlowControl> for serialport::FlowControl {
@@ -69,6 +102,160 @@ pub struct PortInfo {
     pub manufacturer: Option<String>,
     pub product: Option<String>,
     pub serial_number: Option<String>,
+    
+    /// False for ports found only by probing names the OS did not enumerate
+    pub verified: bool,
+}
+
+impl PortInfo {
+    /// Whether the port belongs to a USB device
+    pub fn is_usb(&self) -> bool {
+        self.vendor_id.is_some()
+    }
+    
+    /// Whether the port's USB vendor is a known microcontroller/dev-board vendor
+    pub fn is_microcontroller(&self) -> bool {
+        self.vendor_id.map_or(false, is_microcontroller_vid)
+    }
+}
+
+/// Which discovered serial ports to show in device lists
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum DiscoveryFilter {
+    /// Every serial port, including virtual/system ports
+    #[default]
+    ShowAll,
+    /// Only USB ports from known microcontroller vendors
+    OnlyMicrocontrollers,
+    /// Only USB serial ports
+    OnlyUsb,
+}
+
+/// COM port numbers probed on Windows for ports enumeration misses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ComPortRange {
+    pub first: u32,
+    pub last: u32,
+}
+
+impl Default for ComPortRange {
+    fn default() -> Self {
+        Self { first: 1, last: 256 }
+    }
+}
+
+impl ComPortRange {
+    /// Port names in the range, e.g. "COM1".."COM256"
+    pub fn names(&self) -> impl Iterator<Item = String> {
+        (self.first..=self.last).map(|n| format!("COM{}", n))
+    }
+}
+
+/// How long probed COM ports are reused before the range is probed again
+pub const DEFAULT_COM_FALLBACK_REFRESH: Duration = Duration::from_secs(300);
+
+/// Opt-in probing of a COM range, with the result kept between discovery passes
+/// Opening every name in the range is slow and can reset boards, so a pass
+/// inside the refresh interval reuses the last probe instead
+#[derive(Debug)]
+pub struct ComPortFallback {
+    range: ComPortRange,
+    refresh: Duration,
+    /// When the range was last probed and the ports it found
+    cached: std::sync::Mutex<Option<(Instant, Vec<PortInfo>)>>,
+}
+
+impl ComPortFallback {
+    pub fn new(range: ComPortRange) -> Self {
+        Self::with_refresh(range, DEFAULT_COM_FALLBACK_REFRESH)
+    }
+    
+    pub fn with_refresh(range: ComPortRange, refresh: Duration) -> Self {
+        Self {
+            range,
+            refresh,
+            cached: std::sync::Mutex::new(None),
+        }
+    }
+    
+    pub fn range(&self) -> ComPortRange {
+        self.range
+    }
+    
+    /// Ports from the last probe that `enumerated` still lacks, or `None` when
+    /// nothing was probed yet or the refresh interval has passed
+    fn cached(&self, enumerated: &[PortInfo]) -> Option<Vec<PortInfo>> {
+        let cached = self.cached.lock().unwrap_or_else(|e| e.into_inner());
+        let (probed_at, ports) = cached.as_ref()?;
+        (probed_at.elapsed() < self.refresh).then(|| not_enumerated(ports, enumerated))
+    }
+    
+    /// Keep the result of a fresh probe
+    fn store(&self, ports: Vec<PortInfo>) {
+        *self.cached.lock().unwrap_or_else(|e| e.into_inner()) = Some((Instant::now(), ports));
+    }
+    
+    /// Openable ports in the range that `enumerated` lacks, probing only when
+    /// the cached result has expired
+    pub async fn extra_ports(&self, enumerated: &[PortInfo]) -> TransportResult<Vec<PortInfo>> {
+        if let Some(ports) = self.cached(enumerated) {
+            return Ok(ports);
+        }
+        
+        let known = enumerated.to_vec();
+        let range = self.range;
+        let extra = spawn_blocking(move || {
+            probe_com_range(&known, range, |name| serialport::new(name, 9600).open().is_ok())
+        }).await
+        .map_err(|e| TransportError::IoError(std::io::Error::new(
+            std::io::ErrorKind::Other, 
+            format!("Task join error: {}", e)
+        )))?;
+        
+        self.store(extra.clone());
+        Ok(extra)
+    }
+}
+
+/// Entries of `ports` whose names `enumerated` doesn't list, whatever their case
+fn not_enumerated(ports: &[PortInfo], enumerated: &[PortInfo]) -> Vec<PortInfo> {
+    ports.iter()
+        .filter(|port| !enumerated.iter().any(|known| known.name.eq_ignore_ascii_case(&port.name)))
+        .cloned()
+        .collect()
+}
+
+/// Unverified entries for openable ports in `range` that `enumerated` lacks
+pub fn probe_com_range(
+    enumerated: &[PortInfo],
+    range: ComPortRange,
+    is_openable: impl Fn(&str) -> bool,
+) -> Vec<PortInfo> {
+    range.names()
+        .filter(|name| !enumerated.iter().any(|port| port.name.eq_ignore_ascii_case(name)))
+        .filter(|name| is_openable(name))
+        .map(|name| PortInfo {
+            name,
+            device_type: "Serial Port (unverified)".to_string(),
+            vendor_id: None,
+            product_id: None,
+            manufacturer: None,
+            product: None,
+            serial_number: None,
+            verified: false,
+        })
+        .collect()
+}
+
+impl DiscoveryFilter {
+    /// Whether a discovered port passes this filter
+    pub fn allows(&self, port: &PortInfo) -> bool {
+        match self {
+            DiscoveryFilter::ShowAll => true,
+            DiscoveryFilter::OnlyMicrocontrollers => port.is_microcontroller(),
+            DiscoveryFilter::OnlyUsb => port.is_usb(),
+        }
+    }
 }
 
 // Known microcontroller vendor IDs
@@ -79,9 +266,77 @@ const CP210X_VID: u16 = 0x10C4;   // Silicon Labs CP210x
 const TEENSY_VID: u16 = 0x16C0;   // Teensy boards
 const STM32_VID: u16 = 0x0483;    // STMicroelectronics
 
+/// Longest single blocking read while a cancellable receive is waiting
+/// Bounds how long a cancelled receive can keep the port busy
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Longest single blocking read; the port is unlocked between slices of a long receive
+const READ_SLICE: Duration = Duration::from_millis(50);
+
+/// Wakes a reader when a port has data, instead of polling it
+#[async_trait]
+pub(crate) trait ReadReadiness: Send + Sync {
+    /// Wait until data can be read; false if `timeout` passed first
+    async fn wait_readable(&self, timeout: Duration) -> std::io::Result<bool>;
+}
+
+/// Readiness of a port's file descriptor through the runtime's reactor
+/// Watches a duplicate of the port's descriptor, so it stays valid however
+/// long either outlives the other
+#[cfg(unix)]
+struct FdReadiness {
+    fd: tokio::io::unix::AsyncFd<std::os::fd::OwnedFd>,
+}
+
+#[cfg(unix)]
+impl FdReadiness {
+    /// Register a duplicate of `port`'s descriptor; `None` if the reactor can't watch it
+    fn for_port(port: &serialport::TTYPort) -> Option<Arc<dyn ReadReadiness>> {
+        use std::os::fd::{AsRawFd, BorrowedFd};
+        
+        // SAFETY: `port` keeps the descriptor open for the duration of the borrow
+        let fd = match unsafe { BorrowedFd::borrow_raw(port.as_raw_fd()) }.try_clone_to_owned() {
+            Ok(fd) => fd,
+            Err(e) => {
+                tracing::debug!("Event-driven reads unavailable, polling instead: {}", e);
+                return None;
+            }
+        };
+        match tokio::io::unix::AsyncFd::with_interest(fd, tokio::io::Interest::READABLE) {
+            Ok(fd) => Some(Arc::new(FdReadiness { fd })),
+            Err(e) => {
+                tracing::debug!("Event-driven reads unavailable, polling instead: {}", e);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+#[async_trait]
+impl ReadReadiness for FdReadiness {
+    async fn wait_readable(&self, timeout: Duration) -> std::io::Result<bool> {
+        match tokio::time::timeout(timeout, self.fd.readable()).await {
+            Ok(guard) => {
+                // Unread bytes are found through `bytes_to_read` before the next wait
+                guard?.clear_ready();
+                Ok(true)
+            }
+            Err(_) => Ok(false),
+        }
+    }
+}
+
+/// Longest pause between reconnect attempts, so unlimited retries keep polling
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
 /// Check if a USB device is likely a microcontroller
 fn is_microcontroller_device(info: &serialport::UsbPortInfo) -> bool {
-    matches!(info.vid, 
+    is_microcontroller_vid(info.vid)
+}
+
+fn is_microcontroller_vid(vid: u16) -> bool {
+    matches!(vid, 
         ARDUINO_VID | FTDI_VID | CH340_VID | CP210X_VID | TEENSY_VID | STM32_VID
     )
 }
@@ -108,16 +363,59 @@ fn detect_device_type(info: &serialport::UsbPortInfo) -> String {
     }
 }
 
+/// Classify a port I/O error as a UART line error, if the driver reported one
+///
+/// Drivers surface parity/framing faults as I/O errors whose message names
+/// the condition, so the message is the only portable signal available.
+fn classify_line_error(err: &std::io::Error) -> Option<LineErrorKind> {
+    let message = err.to_string().to_lowercase();
+    if message.contains("parity") {
+        Some(LineErrorKind::Parity)
+    } else if message.contains("framing") || message.contains("frame error") {
+        Some(LineErrorKind::Framing)
+    } else if message.contains("overrun") {
+        Some(LineErrorKind::Overrun)
+    } else if message.contains("break") {
+        Some(LineErrorKind::Break)
+    } else {
+        None
+    }
+}
+
+/// Modem status (input control line) states of a serial port
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ControlLines {
+    /// Clear To Send
+    pub cts: bool,
+    /// Data Set Ready
+    pub dsr: bool,
+    /// Carrier Detect
+    pub cd: bool,
+    /// Ring Indicator
+    pub ri: bool,
+}
+
+/// Lifecycle events published by a serial transport
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SerialTransportEvent {
+    /// Port was reopened after a disconnect; the transport session id is unchanged
+    Reconnected { transport_session_id: Uuid },
+    /// The device sent its goodbye marker and the port was closed cleanly
+    DeviceDisconnected { transport_session_id: Uuid },
+}
+
 /// Serial port transport implementation using interior mutability pattern
 /// Enables true sharing via Arc<dyn Transport> by using &self methods with Arc/Mutex internals
 pub struct SerialTransport {
     base: TransportBase,
     port: Arc<Mutex<Option<SerialPortWrapper>>>, // Using Arc for shared access from monitor
     reconnect_attempts: Arc<Mutex<u32>>,         // Thread-safe mutable state
-    max_reconnect_attempts: u32,                 // Immutable configuration
     base_reconnect_delay: Duration,              // Immutable configuration
     task_handles: Arc<Mutex<Vec<JoinHandle<()>>>>, // Track spawned tasks for cleanup
     cleanup_flag: Arc<AtomicBool>,               // Signal for cooperative shutdown
+    session_id: Uuid,                            // Stable across reconnects (wrapper ids are not)
+    has_connected: Arc<AtomicBool>,              // Distinguishes first connect from reconnects
+    events_tx: broadcast::Sender<SerialTransportEvent>,
 }
 
 impl SerialTransport {
@@ -125,13 +423,13 @@ impl SerialTransport {
     pub fn new(config: TransportConfig) -> TransportResult<Self> {
         // Validate configuration
         if let crate::transport::common::TransportSettings::Serial(ref settings) = config.settings {
-            if settings.baud_rate == 0 {
-                return Err(TransportError::ConfigError("Invalid baud rate".into()));
-            }
+            validate_serial_settings(settings)?;
         } else {
             return Err(TransportError::ConfigError("Invalid settings for serial transport".into()));
         }
         
+        let (events_tx, _) = broadcast::channel(16);
+        
         let transport = SerialTransport {
             base: TransportBase::new(
                 format!("Serial:{}", config.address),
@@ -140,10 +438,12 @@ impl SerialTransport {
             ),
             port: Arc::new(Mutex::new(None)),
             reconnect_attempts: Arc::new(Mutex::new(0)),
-            max_reconnect_attempts: 10,
             base_reconnect_delay: Duration::from_millis(100),
             task_handles: Arc::new(Mutex::new(Vec::new())),
             cleanup_flag: Arc::new(AtomicBool::new(false)),
+            session_id: Uuid::new_v4(),
+            has_connected: Arc::new(AtomicBool::new(false)),
+            events_tx,
         };
         
         // Note: Connection monitoring will be started in connect() method when needed
@@ -152,6 +452,75 @@ impl SerialTransport {
         Ok(transport)
     }
     
+    /// Session id for this transport, stable across reconnects
+    pub fn session_id(&self) -> Uuid {
+        self.session_id
+    }
+    
+    /// Session id of the currently open port (changes on every reconnect)
+    pub async fn port_session_id(&self) -> Option<Uuid> {
+        self.port.lock().await.as_ref().map(|p| p.session_id())
+    }
+    
+    /// Query the current CTS/DSR/CD/RI line states (re-reads the hardware on each call)
+    pub async fn control_lines(&self) -> TransportResult<ControlLines> {
+        if !self.is_connected() {
+            return Err(TransportError::NotConnected);
+        }
+        
+        let port_guard = self.port.lock().await;
+        match port_guard.as_ref() {
+            Some(port) => port.control_lines().await,
+            None => Err(TransportError::NotConnected),
+        }
+    }
+    
+    /// Configured goodbye marker, if any
+    fn goodbye_marker(&self) -> Option<&[u8]> {
+        match self.base.config.settings {
+            crate::transport::common::TransportSettings::Serial(ref settings) => settings.goodbye_marker.as_deref(),
+            _ => None,
+        }
+    }
+    
+    /// Subscribe to transport lifecycle events
+    pub fn subscribe_events(&self) -> broadcast::Receiver<SerialTransportEvent> {
+        self.events_tx.subscribe()
+    }
+    
+    /// Install a freshly opened port and mark the transport connected
+    /// Emits `Reconnected` if the transport has been connected before
+    async fn install_port(&self, serial_port: SerialPortWrapper) {
+        {
+            let mut port_guard = self.port.lock().await;
+            *port_guard = Some(serial_port);
+        }
+        
+        self.base.set_disconnect_reason(None);
+        self.base.set_state(ConnectionState::Connected).await;
+        
+        if self.has_connected.swap(true, Ordering::Relaxed) {
+            let _ = self.events_tx.send(SerialTransportEvent::Reconnected {
+                transport_session_id: self.session_id,
+            });
+            self.base.reconnect_hooks.notify(&ReconnectOutcome::Succeeded);
+        }
+    }
+    
+    /// Close the port because the device said goodbye; auto-reconnect stays off
+    /// until the next explicit `connect`
+    async fn handle_device_goodbye(&self) {
+        self.port.lock().await.take();
+        self.base.set_disconnect_reason(Some(DisconnectReason::DeviceRequested));
+        self.base.set_state(ConnectionState::Disconnected).await;
+        self.base.cancel_reconnection().await;
+        
+        let _ = self.events_tx.send(SerialTransportEvent::DeviceDisconnected {
+            transport_session_id: self.session_id,
+        });
+        tracing::info!("Device on {} requested disconnect", self.base.config.address);
+    }
+    
     /// List available serial ports with cross-platform support
     pub async fn list_ports() -> TransportResult<Vec<PortInfo>> {
         spawn_blocking(|| {
@@ -173,6 +542,7 @@ impl SerialTransport {
                                 manufacturer: info.manufacturer.clone(),
                                 product: info.product.clone(),
                                 serial_number: info.serial_number.clone(),
+                                verified: true,
                             });
                         } else {
                             // Include other USB serial devices
@@ -184,6 +554,7 @@ impl SerialTransport {
                                 manufacturer: info.manufacturer.clone(),
                                 product: info.product.clone(),
                                 serial_number: info.serial_number.clone(),
+                                verified: true,
                             });
                         }
                     }
@@ -197,6 +568,7 @@ impl SerialTransport {
                             manufacturer: None,
                             product: None,
                             serial_number: None,
+                            verified: true,
                         });
                     }
                 }
@@ -213,6 +585,31 @@ impl SerialTransport {
         )))?
     }
     
+    /// List serial ports, adding unverified entries for ports in the fallback's
+    /// range that open but were not enumerated (Windows hides some higher and
+    /// virtual COM ports). Elsewhere this is the same as `list_ports`
+    pub async fn list_ports_with_fallback(fallback: &ComPortFallback) -> TransportResult<Vec<PortInfo>> {
+        let mut ports = Self::list_ports().await?;
+        if !cfg!(windows) {
+            return Ok(ports);
+        }
+        
+        let extra = fallback.extra_ports(&ports).await?;
+        if !extra.is_empty() {
+            tracing::info!("Found {} serial ports missing from enumeration", extra.len());
+        }
+        ports.extend(extra);
+        ports.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(ports)
+    }
+    
+    /// List serial ports that pass a discovery filter
+    pub async fn list_ports_filtered(filter: DiscoveryFilter) -> TransportResult<Vec<PortInfo>> {
+        let mut ports = Self::list_ports().await?;
+        ports.retain(|port| filter.allows(port));
+        Ok(ports)
+    }
+    
     /// Probe if a device is connected to this port
     pub async fn probe_port(port_name: &str, config: &SerialConfig) -> TransportResult<bool> {
         // Try to open the port and send a probe command
@@ -246,10 +643,14 @@ impl SerialTransport {
         let base_state = self.base.state.clone();
         let port = self.port.clone();
         let address = self.base.config.address.clone();
-        let max_reconnect_attempts = self.max_reconnect_attempts;
+        let reconnect_config = self.base.config.clone();
         let base_reconnect_delay = self.base_reconnect_delay;
         let task_handles = self.task_handles.clone();
         let reconnect_attempts = self.reconnect_attempts.clone();
+        let events_tx = self.events_tx.clone();
+        let transport_session_id = self.session_id;
+        let reconnect_hooks = self.base.reconnect_hooks.clone();
+        let disconnect_reason = self.base.disconnect_reason.clone();
         
         let monitor_handle = tokio::spawn(async move {
             let mut check_interval = Duration::from_millis(1000); // Default check interval
@@ -292,6 +693,7 @@ impl SerialTransport {
                         if let Ok(mut state) = base_state.try_write() {
                             *state = ConnectionState::Disconnected;
                         }
+                        *disconnect_reason.write().unwrap() = Some(DisconnectReason::Lost);
                         
                         // Clear the port since it's no longer valid
                         let mut port_guard = port.lock().await;
@@ -327,18 +729,18 @@ impl SerialTransport {
                     }
                 };
                 
-                if is_disconnected && port_available {
+                // A device that said goodbye stays closed until connected explicitly
+                let device_requested = matches!(*disconnect_reason.read().unwrap(), Some(DisconnectReason::DeviceRequested));
+                
+                if is_disconnected && port_available && !device_requested {
                     // Port is available but we're not connected - try to connect
                     let port_guard = port.lock().await;
-                    let current_attempts = {
-                        if let Ok(attempts) = reconnect_attempts.try_lock() {
-                            *attempts
-                        } else {
-                            max_reconnect_attempts // Conservative: assume max if locked
-                        }
-                    };
+                    // Skip this round if another reconnect holds the counter
+                    let current_attempts = reconnect_attempts.try_lock().ok().map(|attempts| *attempts);
                     
-                    if port_guard.is_none() && current_attempts < max_reconnect_attempts {
+                    if let Some(current_attempts) = current_attempts.filter(|&attempts| {
+                        port_guard.is_none() && !reconnect_config.reconnect_attempts_exhausted(attempts)
+                    }) {
                         drop(port_guard); // Release lock before connection attempt
                         
                         // Increment reconnection attempts
@@ -347,15 +749,12 @@ impl SerialTransport {
                         }
                         let current_attempt = current_attempts + 1;
                         
-                        // Calculate exponential backoff delay
-                        let delay = base_reconnect_delay * 2u32.pow(current_attempt.saturating_sub(1));
-                        let jitter = rand::thread_rng().gen_range(0..delay.as_millis() as u64 / 4);
-                        let total_delay = delay + Duration::from_millis(jitter);
+                        let total_delay = reconnect_delay(base_reconnect_delay, current_attempt, &reconnect_config.reconnect_hints);
                         
                         tracing::info!(
                             "Monitor detected disconnection. Attempting reconnect {} of {} after {:?}",
                             current_attempt,
-                            max_reconnect_attempts,
+                            attempt_limit(&reconnect_config),
                             total_delay
                         );
                         
@@ -374,10 +773,15 @@ impl SerialTransport {
                                 if let Ok(mut attempts) = reconnect_attempts.try_lock() {
                                     *attempts = 0;
                                 }
+                                let _ = events_tx.send(SerialTransportEvent::Reconnected {
+                                    transport_session_id,
+                                });
+                                reconnect_hooks.notify(&ReconnectOutcome::Succeeded);
                                 tracing::info!("Monitor successfully reconnected to serial port");
                             }
                             Err(e) => {
                                 tracing::warn!("Monitor reconnect attempt {} failed: {}", current_attempt, e);
+                                reconnect_hooks.notify(&ReconnectOutcome::Failed(e.to_string()));
                                 
                                 // Check if this is a permanent error
                                 match e {
@@ -460,7 +864,7 @@ impl SerialTransport {
             // Get current attempt count
             let current_attempts = *self.reconnect_attempts.lock().await;
             
-            if current_attempts >= self.max_reconnect_attempts {
+            if self.base.config.reconnect_attempts_exhausted(current_attempts) {
                 break;
             }
             
@@ -472,15 +876,12 @@ impl SerialTransport {
             
             let current_attempt = current_attempts + 1;
             
-            // Calculate delay with exponential backoff and jitter
-            let delay = self.base_reconnect_delay * 2u32.pow(current_attempt.saturating_sub(1));
-            let jitter = rand::thread_rng().gen_range(0..delay.as_millis() as u64 / 4);
-            let total_delay = delay + Duration::from_millis(jitter);
+            let total_delay = reconnect_delay(self.base_reconnect_delay, current_attempt, &self.base.config.reconnect_hints);
             
             tracing::info!(
                 "Attempting reconnect {} of {} after {:?}",
                 current_attempt,
-                self.max_reconnect_attempts,
+                attempt_limit(&self.base.config),
                 total_delay
             );
             
@@ -498,6 +899,7 @@ impl SerialTransport {
                 }
                 Err(e) => {
                     tracing::warn!("Reconnect attempt {} failed: {}", current_attempt, e);
+                    self.base.reconnect_hooks.notify(&ReconnectOutcome::Failed(e.to_string()));
                     
                     // If this is a permanent error, don't retry
                     match e {
@@ -513,11 +915,29 @@ impl SerialTransport {
         
         Err(TransportError::ConnectionFailed(format!(
             "Failed to reconnect after {} attempts",
-            self.max_reconnect_attempts
+            self.base.config.max_reconnect_attempts
         )))
     }
 }
 
+/// Backoff delay before reconnect attempt `attempt` (1-based), seeded by the device's hints
+/// The attempt counter is shared with the port monitor, so the delay is computed per attempt
+fn reconnect_delay(base: Duration, attempt: u32, hints: &ReconnectHints) -> Duration {
+    ExponentialBackoff::new()
+        .with_initial_delay(base.as_millis() as u64)
+        .with_max_delay(MAX_RECONNECT_DELAY.as_millis() as u64)
+        .with_hints(hints)
+        .delay_for_attempt(attempt)
+}
+
+/// Attempt limit for log messages
+fn attempt_limit(config: &TransportConfig) -> String {
+    match config.max_reconnect_attempts {
+        0 => "unlimited".to_string(),
+        n => n.to_string(),
+    }
+}
+
 #[async_trait]
 impl Transport for SerialTransport {
     fn transport_type(&self) -> TransportType {
@@ -538,8 +958,9 @@ impl Transport for SerialTransport {
     }
     
     async fn connect(&self) -> TransportResult<()> {
+        // Connecting is idempotent
         if self.is_connected() {
-            return Err(TransportError::AlreadyConnected);
+            return Ok(());
         }
         
         self.base.set_state(ConnectionState::Connecting).await;
@@ -555,12 +976,7 @@ impl Transport for SerialTransport {
         let serial_port = SerialPortWrapper::new(&self.base.config.address, &serial_config).await?;
         
         // Update the shared port
-        {
-            let mut port_guard = self.port.lock().await;
-            *port_guard = Some(serial_port);
-        }
-        
-        self.base.set_state(ConnectionState::Connected).await;
+        self.install_port(serial_port).await;
         self.base.update_stats(|stats| {
             // Reset reconnection attempts on successful connection
             stats.reconnect_count = 0;
@@ -581,9 +997,10 @@ impl Transport for SerialTransport {
             }
         }
         
-        tracing::info!("Connected to serial port: {} with session ID: {}", 
+        tracing::info!("Connected to serial port: {} with session ID: {} (port session: {})", 
                      self.base.config.address, 
-                     self.port.lock().await.as_ref().map(|p| p.session_id()).unwrap_or_default());
+                     self.session_id,
+                     self.port_session_id().await.unwrap_or_default());
         Ok(())
     }
     
@@ -594,6 +1011,7 @@ impl Transport for SerialTransport {
         
         // Clean up all resources before disconnecting
         self.cleanup_resources().await?;
+        self.base.set_disconnect_reason(Some(DisconnectReason::Local));
         
         tracing::info!("Disconnected from serial port: {}", self.base.config.address);
         Ok(())
@@ -604,6 +1022,14 @@ impl Transport for SerialTransport {
         
         // Attempt reconnection if not connected and auto-reconnect is enabled
         if !self.is_connected() {
+            if self.base.disconnect_reason() == Some(DisconnectReason::DeviceRequested) {
+                self.base.update_stats(|stats| {
+                    stats.transactions_failed += 1;
+                    stats.last_error = Some("Disconnected by device".into());
+                }).await;
+                return Err(TransportError::NotConnected);
+            }
+            
             if self.base.config.auto_reconnect {
                 // Trigger automatic reconnection
                 self.trigger_auto_reconnection().await;
@@ -634,6 +1060,7 @@ impl Transport for SerialTransport {
                         stats.bytes_sent += data.len() as u64;
                         stats.transactions_success += 1;
                     }).await;
+                    self.base.wire_trace.record(&self.base.name, WireDirection::Tx, data).await;
                     
                     // Enforce minimum latency requirement (50ms for serial)
                     self.base.enforce_latency(start).await?;
@@ -659,6 +1086,7 @@ impl Transport for SerialTransport {
                     }
                     // Connection state is now managed by base.state
                     self.base.set_state(ConnectionState::Disconnected).await;
+                    self.base.set_disconnect_reason(Some(DisconnectReason::Lost));
                     
                     // Trigger automatic reconnection if enabled
                     if self.base.config.auto_reconnect && crate::transport::backoff::is_retryable_error(&e) {
@@ -691,17 +1119,27 @@ impl Transport for SerialTransport {
             return Err(TransportError::NotConnected);
         }
         
-        let port_guard = self.port.lock().await;
-        if let Some(ref port) = port_guard.as_ref() {
-            // Use the async read method with timeout
-            match port.read(timeout).await {
-                Ok(data) => {
-                    drop(port_guard);
+        // The port slot is only locked long enough to take a reader, so sends and
+        // health checks are not held up for the whole receive timeout
+        let reader = self.port.lock().await.as_ref().map(SerialPortWrapper::reader);
+        if let Some(reader) = reader {
+            match reader.read(timeout).await {
+                Ok(mut data) => {
+                    // Bytes before the goodbye marker are still delivered
+                    let goodbye = goodbye_position(&data, self.goodbye_marker());
+                    if let Some(position) = goodbye {
+                        data.truncate(position);
+                    }
                     
                     if !data.is_empty() {
                         self.base.update_stats(|stats| {
                             stats.bytes_received += data.len() as u64;
                         }).await;
+                        self.base.wire_trace.record(&self.base.name, WireDirection::Rx, &data).await;
+                    }
+                    
+                    if goodbye.is_some() {
+                        self.handle_device_goodbye().await;
                     }
                     
                     // Enforce minimum latency
@@ -713,10 +1151,12 @@ impl Transport for SerialTransport {
                     Ok(data)
                 }
                 Err(e) => {
-                    drop(port_guard);
-                    
+                    let is_line_error = matches!(e, TransportError::LineError(_));
                     self.base.update_stats(|stats| {
                         stats.transactions_failed += 1;
+                        if is_line_error {
+                            stats.line_errors += 1;
+                        }
                         stats.last_error = Some(e.to_string());
                     }).await;
                     
@@ -730,7 +1170,6 @@ impl Transport for SerialTransport {
                 }
             }
         } else {
-            drop(port_guard);
             self.base.update_stats(|stats| {
                 stats.transactions_failed += 1;
                 stats.last_error = Some("Port not available".into());
@@ -739,10 +1178,35 @@ impl Transport for SerialTransport {
         }
     }
     
+    async fn receive_cancellable(&self, timeout: Duration, token: CancellationToken) -> TransportResult<Vec<u8>> {
+        // Split the wait into short blocking reads so a cancelled receive
+        // releases the port promptly instead of holding it for the full timeout
+        let deadline = Instant::now() + timeout;
+        
+        loop {
+            if token.is_cancelled() {
+                return Err(TransportError::Cancelled);
+            }
+            
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Ok(Vec::new());
+            }
+            
+            let data = tokio::select! {
+                biased;
+                _ = token.cancelled() => return Err(TransportError::Cancelled),
+                result = self.receive(remaining.min(CANCEL_POLL_INTERVAL)) => result?,
+            };
+            
+            if !data.is_empty() {
+                return Ok(data);
+            }
+        }
+    }
+    
     fn stats(&self) -> TransportStats {
-        // This would need async but trait doesn't support it
-        // Return default for now, real implementation would cache stats
-        TransportStats::default()
+        self.base.stats_snapshot()
     }
     
     async fn reset(&self) -> TransportResult<()> {
@@ -795,6 +1259,188 @@ impl Transport for SerialTransport {
         tracing::debug!("Serial transport resources cleaned up");
         Ok(())
     }
+    
+    fn on_reconnect(&self, callback: ReconnectCallback) {
+        self.base.on_reconnect(callback);
+    }
+    
+    fn wire_trace(&self) -> Option<&WireTrace> {
+        Some(&self.base.wire_trace)
+    }
+    
+    fn disconnect_reason(&self) -> Option<DisconnectReason> {
+        self.base.disconnect_reason()
+    }
+}
+
+/// Largest frame collected by one accumulating read
+const DEFAULT_MAX_FRAME_LEN: usize = 4096;
+
+/// Append bytes to `frame` until no byte arrives within `gap` or `max_len` is reached
+/// A timeout ends the frame; any other read error is returned. Bytes of the
+/// last read that don't fit are returned so they can start the next frame
+fn accumulate_frame(
+    port: &mut dyn serialport::SerialPort,
+    frame: &mut Vec<u8>,
+    gap: Duration,
+    max_len: usize,
+) -> TransportResult<Vec<u8>> {
+    port.set_timeout(gap)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
+    
+    let mut chunk = [0u8; 256];
+    while frame.len() < max_len {
+        match port.read(&mut chunk) {
+            Ok(0) => break,
+            Ok(n) => {
+                let fits = n.min(max_len - frame.len());
+                frame.extend_from_slice(&chunk[..fits]);
+                if fits < n {
+                    return Ok(chunk[fits..n].to_vec());
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::TimedOut => break,
+            Err(e) => return Err(TransportError::IoError(e)),
+        }
+    }
+    Ok(Vec::new())
+}
+
+/// Flow state for a port whose XON/XOFF is handled by the transport
+fn software_flow(config: &SerialConfig) -> Option<Arc<std::sync::Mutex<FlowState>>> {
+    (config.flow_control == crate::transport::common::FlowControl::Software)
+        .then(|| Arc::new(std::sync::Mutex::new(FlowState::default())))
+}
+
+/// Software flow control bytes
+const XON: u8 = 0x11;
+const XOFF: u8 = 0x13;
+
+/// Longest a send waits for XON after the device sent XOFF
+const MAX_XOFF_WAIT: Duration = Duration::from_secs(5);
+
+/// How often a paused send polls the port for XON
+const XON_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Bytes written between XOFF checks, so a device can pause a long send midway
+const FLOW_CONTROL_CHUNK: usize = 32;
+
+/// XON/XOFF state for software flow control handled by the transport
+#[derive(Debug, Default)]
+struct FlowState {
+    paused: bool,
+    /// Data bytes read while looking for XON, returned by the next read
+    pending: Vec<u8>,
+}
+
+impl FlowState {
+    /// Act on XON/XOFF in `data` and keep the remaining bytes as pending data
+    fn absorb(&mut self, data: &[u8]) {
+        for &byte in data {
+            match byte {
+                XON => self.paused = false,
+                XOFF => self.paused = true,
+                _ => self.pending.push(byte),
+            }
+        }
+    }
+}
+
+/// Take in bytes already waiting on the port, so an XOFF sent mid-write is seen
+fn absorb_available(port: &mut dyn serialport::SerialPort, flow: &std::sync::Mutex<FlowState>) -> TransportResult<()> {
+    let available = port.bytes_to_read()
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))? as usize;
+    if available == 0 {
+        return Ok(());
+    }
+    
+    let mut chunk = vec![0u8; available];
+    port.set_timeout(XON_POLL_INTERVAL)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
+    match port.read(&mut chunk) {
+        Ok(n) => flow.lock().unwrap().absorb(&chunk[..n]),
+        Err(e) if e.kind() == std::io::ErrorKind::TimedOut => {}
+        Err(e) => return Err(TransportError::IoError(e)),
+    }
+    Ok(())
+}
+
+/// Block until the device sends XON, keeping any data that arrives meanwhile
+fn wait_for_xon(
+    port: &mut dyn serialport::SerialPort,
+    flow: &std::sync::Mutex<FlowState>,
+    max_wait: Duration,
+) -> TransportResult<()> {
+    let deadline = Instant::now() + max_wait;
+    let mut chunk = [0u8; 256];
+    
+    while flow.lock().unwrap().paused {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(TransportError::Timeout(format!("device sent XOFF and no XON within {:?}", max_wait)));
+        }
+        
+        port.set_timeout(remaining.min(XON_POLL_INTERVAL))
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
+        match port.read(&mut chunk) {
+            Ok(n) => flow.lock().unwrap().absorb(&chunk[..n]),
+            Err(e) if e.kind() == std::io::ErrorKind::TimedOut => {}
+            Err(e) => return Err(TransportError::IoError(e)),
+        }
+    }
+    Ok(())
+}
+
+/// Offset of the device's goodbye marker in `data`, if present
+fn goodbye_position(data: &[u8], marker: Option<&[u8]>) -> Option<usize> {
+    match marker {
+        Some(marker) if !marker.is_empty() => data.windows(marker.len()).position(|w| w == marker),
+        _ => None,
+    }
+}
+
+/// Finds the device's overflow marker in received data, including a marker
+/// split across two reads
+#[derive(Debug)]
+struct OverflowDetector {
+    marker: Vec<u8>,
+    /// Last `marker.len() - 1` bytes of the previous read
+    tail: std::sync::Mutex<Vec<u8>>,
+}
+
+impl OverflowDetector {
+    /// Detector for `marker`, or `None` when no (or an empty) marker is configured
+    fn new(marker: Option<&[u8]>) -> Option<Arc<Self>> {
+        marker.filter(|marker| !marker.is_empty()).map(|marker| Arc::new(OverflowDetector {
+            marker: marker.to_vec(),
+            tail: std::sync::Mutex::new(Vec::new()),
+        }))
+    }
+    
+    /// Whether the marker ends in `data`, counting the tail of earlier reads
+    fn scan(&self, data: &[u8]) -> bool {
+        let mut tail = self.tail.lock().unwrap();
+        let mut window = std::mem::take(&mut *tail);
+        window.extend_from_slice(data);
+        
+        if window.windows(self.marker.len()).any(|w| w == self.marker.as_slice()) {
+            return true;
+        }
+        let keep = (self.marker.len() - 1).min(window.len());
+        *tail = window.split_off(window.len() - keep);
+        false
+    }
+}
+
+/// Fail with `BufferOverflow` if `data` completes the device's overflow marker
+fn reject_overflow(data: Vec<u8>, overflow: Option<&OverflowDetector>) -> TransportResult<Vec<u8>> {
+    match overflow {
+        Some(overflow) if overflow.scan(&data) => {
+            tracing::warn!("Device reported a receive buffer overflow");
+            Err(TransportError::BufferOverflow)
+        }
+        _ => Ok(data),
+    }
 }
 
 /// Wrapper around real serial port with proper async patterns
@@ -802,69 +1448,314 @@ struct SerialPortWrapper {
     port: Arc<Mutex<Box<dyn serialport::SerialPort>>>,
     port_name: String,
     session_id: Uuid,
+    report_line_errors: bool,
+    inter_byte_timeout: Option<Duration>,
+    /// Bytes read past the frame cap, which start the next frame
+    spill: Arc<std::sync::Mutex<Vec<u8>>>,
+    /// Set when XON/XOFF is handled here rather than by the OS driver
+    software_flow: Option<Arc<std::sync::Mutex<FlowState>>>,
+    overflow: Option<Arc<OverflowDetector>>,
+    /// Set when the wrapper is dropped so detached readers stop early
+    closed: Arc<AtomicBool>,
+    /// Set for `ReadStrategy::EventDriven` on ports with a pollable descriptor
+    readiness: Option<Arc<dyn ReadReadiness>>,
+    /// Held while the port is open when exclusive access was requested
+    _lock: Option<PortLock>,
 }
 
-impl SerialPortWrapper {
-    /// Create new serial port wrapper using spawn_blocking for I/O operations
-    async fn new(port_name: &str, config: &SerialConfig) -> TransportResult<Self> {
-        let port_name_clone = port_name.to_string();
-        let baud_rate = config.baud_rate;
-        let timeout_ms = 100u64; // Default timeout in ms
-        let data_bits = config.data_bits.into();
-        let stop_bits = config.stop_bits.into();
-        let parity = config.parity.into();
-        let flow_control = config.flow_control.into();
+impl Drop for SerialPortWrapper {
+    fn drop(&mut self) {
+        self.closed.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Read side of a port, detached from its wrapper so reads can run without
+/// holding the transport's port slot
+#[derive(Clone)]
+struct PortReader {
+    port: Arc<Mutex<Box<dyn serialport::SerialPort>>>,
+    closed: Arc<AtomicBool>,
+    report_line_errors: bool,
+    inter_byte_timeout: Option<Duration>,
+    spill: Arc<std::sync::Mutex<Vec<u8>>>,
+    software_flow: Option<Arc<std::sync::Mutex<FlowState>>>,
+    overflow: Option<Arc<OverflowDetector>>,
+    readiness: Option<Arc<dyn ReadReadiness>>,
+}
+
+impl PortReader {
+    /// Read data using spawn_blocking for async safety
+    /// `timeout` bounds the wait for the first byte; with an inter-byte timeout
+    /// configured, reading continues until the line goes quiet for that long
+    async fn read(self, timeout: Duration) -> TransportResult<Vec<u8>> {
+        if let Some(readiness) = self.readiness.clone() {
+            return self.read_when_ready(readiness.as_ref(), timeout).await;
+        }
         
-        // CRITICAL: Use spawn_blocking for serial port opening
-        let port = spawn_blocking(move || {
-            serialport::new(&port_name_clone, baud_rate)
-                .timeout(Duration::from_millis(timeout_ms))
-                .data_bits(data_bits)
-                .parity(parity)
-                .stop_bits(stop_bits)
-                .flow_control(flow_control)
-                .open()
-                .map_err(|e| {
-                    use serialport::ErrorKind;
-                    match e.kind() {
-                        ErrorKind::NoDevice => TransportError::ConnectionFailed(
-                            format!("No device found on port {}", port_name_clone)
-                        ),
-                        ErrorKind::InvalidInput => TransportError::ConfigError(
-                            format!("Invalid port name: {}", port_name_clone)
-                        ),
-                        _ => TransportError::ConnectionFailed(
-                            format!("Failed to open port {}: {}", port_name_clone, e)
-                        ),
-                    }
-                })
-        }).await
+        // CRITICAL: Use spawn_blocking for serial read operations
+        spawn_blocking(move || self.read_blocking(timeout)).await
         .map_err(|e| TransportError::IoError(std::io::Error::new(
             std::io::ErrorKind::Other, 
             format!("Task join error: {}", e)
-        )))??;
+        )))?
+    }
+    
+    /// Event-driven read: sleep until the port is readable, then read what arrived
+    /// No blocking thread is held and the port is not touched while idle
+    async fn read_when_ready(self, readiness: &dyn ReadReadiness, timeout: Duration) -> TransportResult<Vec<u8>> {
+        let deadline = Instant::now() + timeout;
+        
+        loop {
+            if self.closed.load(Ordering::Relaxed) {
+                return Err(TransportError::NotConnected);
+            }
+            
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if !self.input_pending().await && !readiness.wait_readable(remaining).await? {
+                return Ok(Vec::new());
+            }
+            
+            let reader = self.clone();
+            let data = spawn_blocking(move || reader.read_blocking(READ_SLICE)).await
+                .map_err(|e| TransportError::IoError(std::io::Error::new(
+                    std::io::ErrorKind::Other, 
+                    format!("Task join error: {}", e)
+                )))??;
+            
+            // A wake with nothing to deliver (e.g. only XON/XOFF) waits again
+            if !data.is_empty() || Instant::now() >= deadline {
+                return Ok(data);
+            }
+        }
+    }
+    
+    /// Whether bytes are already buffered, so waiting for readiness would miss them
+    async fn input_pending(&self) -> bool {
+        if !self.spill.lock().unwrap().is_empty() {
+            return true;
+        }
+        if let Some(ref flow) = self.software_flow {
+            if !flow.lock().unwrap().pending.is_empty() {
+                return true;
+            }
+        }
+        self.port.lock().await.bytes_to_read().map_or(false, |n| n > 0)
+    }
+    
+    /// Wait for data in `READ_SLICE` steps, unlocking the port between them
+    /// so writes and health checks can run during a long receive
+    fn read_blocking(&self, timeout: Duration) -> TransportResult<Vec<u8>> {
+        // Data that arrived while a send was waiting for XON comes first
+        if let Some(ref flow) = self.software_flow {
+            let pending = std::mem::take(&mut flow.lock().unwrap().pending);
+            if !pending.is_empty() {
+                return reject_overflow(pending, self.overflow.as_deref());
+            }
+        }
+        
+        // Bytes read past the previous frame's cap start this frame
+        let spilled = std::mem::take(&mut *self.spill.lock().unwrap());
+        if !spilled.is_empty() {
+            let mut frame = spilled;
+            if let Some(gap) = self.inter_byte_timeout {
+                let mut port_guard = self.port.blocking_lock();
+                *self.spill.lock().unwrap() = accumulate_frame(&mut **port_guard, &mut frame, gap, DEFAULT_MAX_FRAME_LEN)?;
+            }
+            return self.payload(frame);
+        }
+        
+        let deadline = Instant::now() + timeout;
+        let mut buf = vec![0u8; 1024]; // Larger buffer for better performance
+        
+        let data = loop {
+            if self.closed.load(Ordering::Relaxed) {
+                return Err(TransportError::NotConnected);
+            }
+            
+            let mut port_guard = self.port.blocking_lock();
+            let slice = deadline.saturating_duration_since(Instant::now()).min(READ_SLICE);
+            port_guard.set_timeout(slice)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
+            
+            match port_guard.read(&mut buf) {
+                Ok(n) => {
+                    buf.truncate(n);
+                    match self.inter_byte_timeout {
+                        Some(gap) if n > 0 => {
+                            *self.spill.lock().unwrap() = accumulate_frame(&mut **port_guard, &mut buf, gap, DEFAULT_MAX_FRAME_LEN)?;
+                        }
+                        _ => {}
+                    }
+                    break buf;
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::TimedOut => {
+                    // Timeout is not an error, just no data available
+                    if Instant::now() >= deadline {
+                        break Vec::new();
+                    }
+                }
+                Err(e) => {
+                    // Parity/framing errors point at wrong line settings, not a disconnect
+                    if self.report_line_errors {
+                        if let Some(kind) = classify_line_error(&e) {
+                            tracing::warn!("Serial line error: {}", kind);
+                            return Err(TransportError::LineError(kind));
+                        }
+                    }
+                    
+                    // Log the error - IO errors often indicate disconnection
+                    tracing::warn!("Read error (possible disconnection): {}", e);
+                    return Err(TransportError::IoError(e));
+                }
+            }
+        };
+        
+        self.payload(data)
+    }
+    
+    /// Strip XON/XOFF so only payload bytes reach the caller, and check for overflow
+    fn payload(&self, data: Vec<u8>) -> TransportResult<Vec<u8>> {
+        let data = match self.software_flow {
+            Some(ref flow) => {
+                let mut flow = flow.lock().unwrap();
+                flow.absorb(&data);
+                std::mem::take(&mut flow.pending)
+            }
+            None => data,
+        };
+        reject_overflow(data, self.overflow.as_deref())
+    }
+}
+
+impl SerialPortWrapper {
+    /// Create new serial port wrapper using spawn_blocking for I/O operations
+    async fn new(port_name: &str, config: &SerialConfig) -> TransportResult<Self> {
+        let port_name_clone = port_name.to_string();
+        let baud_rate = config.baud_rate;
+        let timeout_ms = 100u64; // Default timeout in ms
+        let data_bits = config.data_bits.into();
+        let stop_bits = config.stop_bits.into();
+        let parity: serialport::Parity = config.parity.try_into()?;
+        // Software flow control is handled in the wrapper, so the OS must pass XON/XOFF through
+        let flow_control = match config.flow_control {
+            crate::transport::common::FlowControl::Software => serialport::FlowControl::None,
+            other => other.into(),
+        };
+        
+        // Claim the port before opening it so a locked port is never touched
+        let lock = if config.exclusive {
+            Some(PortLock::acquire(port_name)?)
+        } else {
+            None
+        };
+        
+        let event_driven = config.read_strategy == ReadStrategy::EventDriven;
+        
+        // CRITICAL: Use spawn_blocking for serial port opening
+        let (port, readiness) = spawn_blocking(move || {
+            let builder = serialport::new(&port_name_clone, baud_rate)
+                .timeout(Duration::from_millis(timeout_ms))
+                .data_bits(data_bits)
+                .parity(parity)
+                .stop_bits(stop_bits)
+                .flow_control(flow_control);
+            let open_error = |e: serialport::Error| {
+                use serialport::ErrorKind;
+                match e.kind() {
+                    ErrorKind::NoDevice => TransportError::ConnectionFailed(
+                        format!("No device found on port {}", port_name_clone)
+                    ),
+                    ErrorKind::InvalidInput => TransportError::ConfigError(
+                        format!("Invalid port name: {}", port_name_clone)
+                    ),
+                    _ => TransportError::ConnectionFailed(
+                        format!("Failed to open port {}: {}", port_name_clone, e)
+                    ),
+                }
+            };
+            
+            // Only a native port exposes the descriptor event-driven reads wait on
+            #[cfg(unix)]
+            {
+                let port = builder.open_native().map_err(open_error)?;
+                let readiness = if event_driven { FdReadiness::for_port(&port) } else { None };
+                Ok::<_, TransportError>((Box::new(port) as Box<dyn serialport::SerialPort>, readiness))
+            }
+            #[cfg(not(unix))]
+            {
+                if event_driven {
+                    tracing::debug!("Event-driven reads unsupported on this platform, polling instead");
+                }
+                builder.open().map(|port| (port, None)).map_err(open_error)
+            }
+        }).await
+        .map_err(|e| TransportError::IoError(std::io::Error::new(
+            std::io::ErrorKind::Other, 
+            format!("Task join error: {}", e)
+        )))??;
         
         Ok(SerialPortWrapper {
             port: Arc::new(Mutex::new(port)),
             port_name: port_name.to_string(),
             session_id: Uuid::new_v4(),
+            report_line_errors: config.report_line_errors,
+            inter_byte_timeout: config.inter_byte_timeout(),
+            spill: Arc::new(std::sync::Mutex::new(Vec::new())),
+            software_flow: software_flow(config),
+            overflow: OverflowDetector::new(config.overflow_marker.as_deref()),
+            closed: Arc::new(AtomicBool::new(false)),
+            readiness,
+            _lock: lock,
         })
     }
     
+    /// Wrap an already-open port (used by tests to inject a fake port and readiness)
+    #[cfg(test)]
+    fn from_port(
+        port: Box<dyn serialport::SerialPort>,
+        port_name: &str,
+        config: &SerialConfig,
+        readiness: Option<Arc<dyn ReadReadiness>>,
+    ) -> Self {
+        SerialPortWrapper {
+            port: Arc::new(Mutex::new(port)),
+            port_name: port_name.to_string(),
+            session_id: Uuid::new_v4(),
+            report_line_errors: config.report_line_errors,
+            inter_byte_timeout: config.inter_byte_timeout(),
+            spill: Arc::new(std::sync::Mutex::new(Vec::new())),
+            software_flow: software_flow(config),
+            overflow: OverflowDetector::new(config.overflow_marker.as_deref()),
+            closed: Arc::new(AtomicBool::new(false)),
+            readiness,
+            _lock: None,
+        }
+    }
+    
     /// Write data using spawn_blocking for async safety
     async fn write(&self, data: &[u8]) -> TransportResult<()> {
         use std::io::Write;
         
         let port = self.port.clone();
         let data = data.to_vec();
+        let flow = self.software_flow.clone();
         
         // CRITICAL: Use spawn_blocking for serial write operations
         spawn_blocking(move || {
             let mut port_guard = port.blocking_lock();
-            port_guard.write_all(&data).map_err(|e| {
-                // IO errors often indicate disconnection on serial ports
-                TransportError::IoError(e)
-            })?;
+            // With software flow control the device may send XOFF at any point,
+            // so it is checked again before each chunk
+            let chunk_len = if flow.is_some() { FLOW_CONTROL_CHUNK } else { data.len().max(1) };
+            for chunk in data.chunks(chunk_len) {
+                if let Some(ref flow) = flow {
+                    absorb_available(&mut **port_guard, flow)?;
+                    wait_for_xon(&mut **port_guard, flow, MAX_XOFF_WAIT)?;
+                }
+                port_guard.write_all(chunk).map_err(|e| {
+                    // IO errors often indicate disconnection on serial ports
+                    TransportError::IoError(e)
+                })?;
+            }
             port_guard.flush().map_err(|e| TransportError::IoError(e))
         }).await
         .map_err(|e| TransportError::IoError(std::io::Error::new(
@@ -873,34 +1764,32 @@ impl SerialPortWrapper {
         )))?
     }
     
+    /// Reader sharing this wrapper's port and read settings
+    fn reader(&self) -> PortReader {
+        PortReader {
+            port: self.port.clone(),
+            closed: self.closed.clone(),
+            report_line_errors: self.report_line_errors,
+            inter_byte_timeout: self.inter_byte_timeout,
+            spill: self.spill.clone(),
+            software_flow: self.software_flow.clone(),
+            overflow: self.overflow.clone(),
+            readiness: self.readiness.clone(),
+        }
+    }
+    
     /// Read data using spawn_blocking for async safety
     async fn read(&self, timeout: Duration) -> TransportResult<Vec<u8>> {
+        self.reader().read(timeout).await
+    }
+    
+    /// Flush port using spawn_blocking
+    async fn flush(&self) -> TransportResult<()> {
         let port = self.port.clone();
         
-        // CRITICAL: Use spawn_blocking for serial read operations
         spawn_blocking(move || {
             let mut port_guard = port.blocking_lock();
-            let mut buf = vec![0u8; 1024]; // Larger buffer for better performance
-            
-            // Set timeout for this specific read
-            port_guard.set_timeout(timeout)
-                .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
-            
-            match port_guard.read(&mut buf) {
-                Ok(n) => {
-                    buf.truncate(n);
-                    Ok(buf)
-                }
-                Err(e) if e.kind() == std::io::ErrorKind::TimedOut => {
-                    // Timeout is not an error, just no data available
-                    Ok(Vec::new())
-                }
-                Err(e) => {
-                    // Log the error - IO errors often indicate disconnection
-                    tracing::warn!("Read error (possible disconnection): {}", e);
-                    Err(TransportError::IoError(e))
-                }
-            }
+            port_guard.flush().map_err(|e| TransportError::IoError(e))
         }).await
         .map_err(|e| TransportError::IoError(std::io::Error::new(
             std::io::ErrorKind::Other, 
@@ -908,13 +1797,22 @@ impl SerialPortWrapper {
         )))?
     }
     
-    /// Flush port using spawn_blocking
-    async fn flush(&self) -> TransportResult<()> {
+    /// Read modem status lines using spawn_blocking
+    async fn control_lines(&self) -> TransportResult<ControlLines> {
         let port = self.port.clone();
         
         spawn_blocking(move || {
             let mut port_guard = port.blocking_lock();
-            port_guard.flush().map_err(|e| TransportError::IoError(e))
+            let to_err = |e: serialport::Error| TransportError::HardwareError(
+                format!("Failed to read control lines: {}", e)
+            );
+            
+            Ok(ControlLines {
+                cts: port_guard.read_clear_to_send().map_err(to_err)?,
+                dsr: port_guard.read_data_set_ready().map_err(to_err)?,
+                cd: port_guard.read_carrier_detect().map_err(to_err)?,
+                ri: port_guard.read_ring_indicator().map_err(to_err)?,
+            })
         }).await
         .map_err(|e| TransportError::IoError(std::io::Error::new(
             std::io::ErrorKind::Other, 
@@ -958,10 +1856,107 @@ impl SerialPortWrapper {
     }
 }
 
+#[cfg(test)]
+impl SerialTransport {
+    /// Attach an already-open port and mark the transport connected
+    async fn attach_port_for_test(&self, port: Box<dyn serialport::SerialPort>) {
+        self.attach_port_with_readiness_for_test(port, None).await;
+    }
+    
+    /// Attach a fake port whose reads wait on `readiness` (event-driven mode)
+    #[cfg(test)]
+    async fn attach_port_with_readiness_for_test(
+        &self,
+        port: Box<dyn serialport::SerialPort>,
+        readiness: Option<Arc<dyn ReadReadiness>>,
+    ) {
+        let settings = match self.base.config.settings {
+            crate::transport::common::TransportSettings::Serial(ref settings) => settings.clone(),
+            _ => SerialSettings::default(),
+        };
+        let wrapper = SerialPortWrapper::from_port(port, &self.base.config.address, &settings, readiness);
+        self.install_port(wrapper).await;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::transport::common::{SerialSettings, TransportSettings};
+    use crate::transport::tests::fake_serial::{FakeRead, FakeSerialHandle};
+    
+    fn port_info(name: &str, vendor_id: Option<u16>) -> PortInfo {
+        PortInfo {
+            name: name.to_string(),
+            device_type: "test".to_string(),
+            vendor_id,
+            product_id: vendor_id.map(|_| 0x0043),
+            manufacturer: None,
+            product: None,
+            serial_number: None,
+            verified: true,
+        }
+    }
+    
+    #[test]
+    fn test_com_range_fallback_adds_unverified_ports() {
+        let enumerated = vec![port_info("COM3", Some(ARDUINO_VID))];
+        let openable = ["COM3", "COM12", "COM300"];
+        
+        let extra = probe_com_range(&enumerated, ComPortRange::default(), |name| openable.contains(&name));
+        assert_eq!(extra.len(), 1);
+        assert_eq!(extra[0].name, "COM12");
+        assert!(!extra[0].verified);
+        assert!(!extra[0].is_usb());
+        
+        // Enumerated ports are not probed again, whatever their case
+        let probed = std::cell::RefCell::new(Vec::new());
+        let range = ComPortRange { first: 2, last: 4 };
+        probe_com_range(&[port_info("com3", None)], range, |name| {
+            probed.borrow_mut().push(name.to_string());
+            false
+        });
+        assert_eq!(probed.into_inner(), vec!["COM2".to_string(), "COM4".to_string()]);
+    }
+    
+    #[test]
+    fn test_com_fallback_reuses_probe_between_passes() {
+        let fallback = ComPortFallback::new(ComPortRange { first: 1, last: 20 });
+        assert!(fallback.cached(&[]).is_none());
+        
+        fallback.store(vec![port_info("COM12", None), port_info("COM15", None)]);
+        let names = |ports: Vec<PortInfo>| ports.into_iter().map(|p| p.name).collect::<Vec<_>>();
+        assert_eq!(names(fallback.cached(&[]).unwrap()), vec!["COM12", "COM15"]);
+        
+        // A probed port the OS now enumerates is not listed twice
+        let enumerated = [port_info("com15", Some(ARDUINO_VID))];
+        assert_eq!(names(fallback.cached(&enumerated).unwrap()), vec!["COM12"]);
+        
+        // Once the refresh interval has passed the range is probed again
+        let expired = ComPortFallback::with_refresh(ComPortRange::default(), Duration::ZERO);
+        expired.store(vec![port_info("COM12", None)]);
+        assert!(expired.cached(&[]).is_none());
+    }
+    
+    #[test]
+    fn test_discovery_filter() {
+        let plain = port_info("/dev/ttyS0", None);
+        let arduino = port_info("/dev/ttyACM0", Some(ARDUINO_VID));
+        let usb_modem = port_info("/dev/ttyUSB1", Some(0x1234));
+        
+        let filter = DiscoveryFilter::OnlyMicrocontrollers;
+        assert!(!filter.allows(&plain));
+        assert!(filter.allows(&arduino));
+        assert!(!filter.allows(&usb_modem));
+        
+        let filter = DiscoveryFilter::OnlyUsb;
+        assert!(!filter.allows(&plain));
+        assert!(filter.allows(&arduino));
+        assert!(filter.allows(&usb_modem));
+        
+        assert_eq!(DiscoveryFilter::default(), DiscoveryFilter::ShowAll);
+        assert!([&plain, &arduino, &usb_modem].iter().all(|p| DiscoveryFilter::ShowAll.allows(p)));
+    }
     
     #[tokio::test]
     async fn test_serial_transport_creation() {
@@ -995,9 +1990,10 @@ mod tests {
         assert!(result.is_ok());
         assert!(transport.is_connected());
         
-        // Try to connect again (should fail)
+        // Connecting again is a no-op
         let result = transport.connect().await;
-        assert!(matches!(result, Err(TransportError::AlreadyConnected)));
+        assert!(result.is_ok());
+        assert!(transport.is_connected());
         
         // Disconnect
         let result = transport.disconnect().await;
@@ -1098,4 +2094,685 @@ mod tests {
         assert!(result.is_ok());
         assert!(transport.is_connected());
     }
-}
\ No newline at end of file
+    
+    fn fake_transport_config(report_line_errors: bool) -> TransportConfig {
+        TransportConfig {
+            transport_type: TransportType::Serial,
+            address: "FAKE0".to_string(),
+            auto_reconnect: false,
+            settings: TransportSettings::Serial(SerialSettings {
+                report_line_errors,
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+    
+    #[test]
+    fn test_classify_line_error() {
+        let parity = std::io::Error::new(std::io::ErrorKind::InvalidData, "Parity error");
+        let framing = std::io::Error::new(std::io::ErrorKind::InvalidData, "framing error on RX");
+        let other = std::io::Error::new(std::io::ErrorKind::BrokenPipe, "device disconnected");
+        
+        assert_eq!(classify_line_error(&parity), Some(LineErrorKind::Parity));
+        assert_eq!(classify_line_error(&framing), Some(LineErrorKind::Framing));
+        assert_eq!(classify_line_error(&other), None);
+    }
+    
+    #[test]
+    fn test_line_errors_reported_when_omitted() {
+        // Saved before `report_line_errors` existed
+        let settings: SerialSettings = serde_json::from_str(
+            r#"{"baud_rate": 9600, "data_bits": "Eight", "stop_bits": "One", "parity": "None", "flow_control": "None"}"#
+        ).unwrap();
+        assert!(settings.report_line_errors);
+        assert_eq!(settings, SerialSettings { baud_rate: 9600, ..Default::default() });
+    }
+    
+    #[tokio::test]
+    async fn test_parity_error_maps_to_line_error() {
+        let transport = SerialTransport::new(fake_transport_config(true)).unwrap();
+        let fake = FakeSerialHandle::new();
+        fake.push_error(std::io::ErrorKind::InvalidData, "parity error");
+        transport.attach_port_for_test(fake.port()).await;
+        
+        let result = transport.receive(Duration::from_millis(10)).await;
+        assert!(matches!(result, Err(TransportError::LineError(LineErrorKind::Parity))));
+        
+        let stats = transport.base.stats.read().await;
+        assert_eq!(stats.line_errors, 1);
+        assert_eq!(stats.transactions_failed, 1);
+    }
+    
+    #[tokio::test]
+    async fn test_line_error_reporting_disabled() {
+        let transport = SerialTransport::new(fake_transport_config(false)).unwrap();
+        let fake = FakeSerialHandle::new();
+        fake.push_error(std::io::ErrorKind::InvalidData, "parity error");
+        transport.attach_port_for_test(fake.port()).await;
+        
+        let result = transport.receive(Duration::from_millis(10)).await;
+        assert!(matches!(result, Err(TransportError::IoError(_))));
+        assert_eq!(transport.base.stats.read().await.line_errors, 0);
+    }
+    
+    #[tokio::test]
+    async fn test_receive_cancellable_returns_promptly() {
+        let transport = SerialTransport::new(fake_transport_config(true)).unwrap();
+        let fake = FakeSerialHandle::new();
+        transport.attach_port_for_test(fake.port()).await;
+        
+        let token = CancellationToken::new();
+        let canceller = token.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            canceller.cancel();
+        });
+        
+        let start = Instant::now();
+        let result = transport.receive_cancellable(Duration::from_secs(5), token).await;
+        
+        assert!(matches!(result, Err(TransportError::Cancelled)));
+        assert!(start.elapsed() < Duration::from_secs(1), "cancel took {:?}", start.elapsed());
+    }
+    
+    #[tokio::test]
+    async fn test_receive_cancellable_delivers_data() {
+        let transport = SerialTransport::new(fake_transport_config(true)).unwrap();
+        let fake = FakeSerialHandle::new();
+        transport.attach_port_for_test(fake.port()).await;
+        
+        let token = CancellationToken::new();
+        token.cancel();
+        let result = transport.receive_cancellable(Duration::from_secs(5), token).await;
+        assert!(matches!(result, Err(TransportError::Cancelled)));
+        
+        fake.push_data(b"OK\r\n");
+        let result = transport.receive_cancellable(Duration::from_secs(1), CancellationToken::new()).await;
+        assert_eq!(result.unwrap(), b"OK\r\n".to_vec());
+    }
+    
+    #[tokio::test]
+    async fn test_reconnect_keeps_transport_session_id() {
+        let transport = SerialTransport::new(fake_transport_config(true)).unwrap();
+        let mut events = transport.subscribe_events();
+        let transport_id = transport.session_id();
+        
+        transport.attach_port_for_test(FakeSerialHandle::new().port()).await;
+        let first_port_id = transport.port_session_id().await.unwrap();
+        assert!(events.try_recv().is_err(), "first connect is not a reconnect");
+        
+        // Force a reconnect: drop the port, then open a new one
+        transport.port.lock().await.take();
+        transport.base.set_state(ConnectionState::Disconnected).await;
+        transport.attach_port_for_test(FakeSerialHandle::new().port()).await;
+        
+        assert_eq!(transport.session_id(), transport_id);
+        assert_ne!(transport.port_session_id().await.unwrap(), first_port_id);
+        assert_eq!(
+            events.try_recv().unwrap(),
+            SerialTransportEvent::Reconnected { transport_session_id: transport_id }
+        );
+    }
+    
+    fn recording_callback(log: &Arc<std::sync::Mutex<Vec<(u8, ReconnectOutcome)>>>, id: u8) -> ReconnectCallback {
+        let log = log.clone();
+        Arc::new(move |outcome: &ReconnectOutcome| log.lock().unwrap().push((id, outcome.clone())))
+    }
+    
+    #[tokio::test]
+    async fn test_reconnect_callbacks_report_success() {
+        let transport = SerialTransport::new(fake_transport_config(true)).unwrap();
+        let log = Arc::new(std::sync::Mutex::new(Vec::new()));
+        transport.on_reconnect(recording_callback(&log, 1));
+        transport.on_reconnect(recording_callback(&log, 2));
+        
+        // First connect is not a reconnect
+        transport.attach_port_for_test(FakeSerialHandle::new().port()).await;
+        assert!(log.lock().unwrap().is_empty());
+        
+        // Force a reconnect
+        transport.port.lock().await.take();
+        transport.base.set_state(ConnectionState::Disconnected).await;
+        transport.attach_port_for_test(FakeSerialHandle::new().port()).await;
+        
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec![(1, ReconnectOutcome::Succeeded), (2, ReconnectOutcome::Succeeded)]
+        );
+    }
+    
+    #[tokio::test]
+    async fn test_reconnect_callbacks_report_failure() {
+        let transport = SerialTransport::new(TransportConfig {
+            max_reconnect_attempts: 1,
+            ..fake_transport_config(true)
+        }).unwrap();
+        let log = Arc::new(std::sync::Mutex::new(Vec::new()));
+        transport.on_reconnect(recording_callback(&log, 1));
+        
+        // FAKE0 does not exist, so the attempt fails
+        assert!(transport.reconnect().await.is_err());
+        
+        let log = log.lock().unwrap();
+        assert_eq!(log.len(), 1);
+        assert!(matches!(log[0], (1, ReconnectOutcome::Failed(_))));
+    }
+    
+    #[tokio::test]
+    async fn test_reconnect_gives_up_after_configured_attempts() {
+        let mut transport = SerialTransport::new(TransportConfig {
+            max_reconnect_attempts: 3,
+            ..fake_transport_config(true)
+        }).unwrap();
+        transport.base_reconnect_delay = Duration::from_millis(1);
+        let log = Arc::new(std::sync::Mutex::new(Vec::new()));
+        transport.on_reconnect(recording_callback(&log, 1));
+        
+        assert!(matches!(transport.reconnect().await, Err(TransportError::ConnectionFailed(_))));
+        assert_eq!(log.lock().unwrap().len(), 3);
+        assert_eq!(*transport.reconnect_attempts.lock().await, 3);
+    }
+    
+    #[tokio::test]
+    async fn test_settle_hint_delays_first_reconnect() {
+        let hints = ReconnectHints { min_settle_ms: 150, reenumeration_delay_ms: 0 };
+        
+        // The hint wins over a tiny base delay
+        let mut transport = SerialTransport::new(TransportConfig {
+            max_reconnect_attempts: 1,
+            reconnect_hints: hints,
+            ..fake_transport_config(true)
+        }).unwrap();
+        transport.base_reconnect_delay = Duration::from_millis(1);
+        
+        let start = Instant::now();
+        assert!(transport.reconnect().await.is_err());
+        assert!(start.elapsed() >= Duration::from_millis(150), "first attempt after {:?}", start.elapsed());
+        
+        for base_ms in [1, 20, 100] {
+            let delay = reconnect_delay(Duration::from_millis(base_ms), 1, &hints);
+            assert!(delay >= Duration::from_millis(150), "base {}ms: {:?}", base_ms, delay);
+            assert!(delay < Duration::from_millis(150 + base_ms / 4 + 1), "base {}ms: {:?}", base_ms, delay);
+        }
+        
+        // Without hints the base delay is used as before
+        let delay = reconnect_delay(Duration::from_millis(20), 1, &ReconnectHints::default());
+        assert!(delay >= Duration::from_millis(20) && delay < Duration::from_millis(26));
+    }
+    
+    #[tokio::test]
+    async fn test_zero_max_attempts_retries_indefinitely() {
+        let mut transport = SerialTransport::new(TransportConfig {
+            max_reconnect_attempts: 0,
+            ..fake_transport_config(true)
+        }).unwrap();
+        transport.base_reconnect_delay = Duration::from_millis(1);
+        let log = Arc::new(std::sync::Mutex::new(Vec::new()));
+        transport.on_reconnect(recording_callback(&log, 1));
+        
+        // Still retrying when the test gives up on it
+        let result = tokio::time::timeout(Duration::from_millis(300), transport.reconnect()).await;
+        assert!(result.is_err());
+        assert!(log.lock().unwrap().len() > 3, "only {} attempts", log.lock().unwrap().len());
+    }
+    
+    #[tokio::test]
+    async fn test_control_lines_query() {
+        let transport = SerialTransport::new(fake_transport_config(true)).unwrap();
+        assert!(matches!(transport.control_lines().await, Err(TransportError::NotConnected)));
+        
+        let fake = FakeSerialHandle::new();
+        fake.set_control_lines(ControlLines { cts: true, dsr: false, cd: true, ri: false });
+        transport.attach_port_for_test(fake.port()).await;
+        
+        let lines = transport.control_lines().await.unwrap();
+        assert_eq!(lines, ControlLines { cts: true, dsr: false, cd: true, ri: false });
+        
+        // Refreshes on demand
+        fake.set_control_lines(ControlLines { cts: false, dsr: true, cd: false, ri: true });
+        let lines = transport.control_lines().await.unwrap();
+        assert!(!lines.cts && lines.dsr && !lines.cd && lines.ri);
+    }
+    
+    #[tokio::test]
+    async fn test_wire_trace_toggle() {
+        let transport = SerialTransport::new(fake_transport_config(true)).unwrap();
+        transport.attach_port_for_test(FakeSerialHandle::new().port()).await;
+        
+        let logging = Arc::new(crate::logging::LoggingSystem::default());
+        let trace = transport.wire_trace().unwrap();
+        trace.set_sink(logging.clone());
+        assert!(!trace.is_enabled());
+        
+        // Off by default: nothing logged
+        transport.send(b"PING\r\n").await.unwrap();
+        assert_eq!(logging.device_io.read().await.len(), 0);
+        
+        trace.set_enabled(true);
+        transport.send(b"PING\r\n").await.unwrap();
+        {
+            let buffer = logging.device_io.read().await;
+            assert_eq!(buffer.len(), 1);
+            let entry = &buffer.entries()[0];
+            assert_eq!(entry.data.as_deref(), Some(&b"PING\r\n"[..]));
+            assert!(entry.message.contains("50 49 4E 47 0D 0A"));
+        }
+        
+        // Toggled off again: later sends stop logging
+        trace.set_enabled(false);
+        transport.send(b"PING\r\n").await.unwrap();
+        assert_eq!(logging.device_io.read().await.len(), 1);
+    }
+    
+    fn inter_byte_config(inter_byte_timeout_ms: Option<u32>) -> TransportConfig {
+        TransportConfig {
+            settings: TransportSettings::Serial(SerialSettings {
+                inter_byte_timeout_ms,
+                ..Default::default()
+            }),
+            ..fake_transport_config(true)
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_inter_byte_timeout_collects_gapped_frame() {
+        let transport = SerialTransport::new(inter_byte_config(Some(80))).unwrap();
+        let fake = FakeSerialHandle::new();
+        fake.push_data(b"\x01\x03");
+        fake.push_data_after(Duration::from_millis(40), b"\x02\x00");
+        fake.push_data_after(Duration::from_millis(40), b"\x0A");
+        transport.attach_port_for_test(fake.port()).await;
+        
+        // Gaps stay under 80ms, so the whole frame comes back from one receive
+        // even though the 30ms read timeout is shorter than the frame
+        let frame = transport.receive(Duration::from_millis(30)).await.unwrap();
+        assert_eq!(frame, vec![0x01, 0x03, 0x02, 0x00, 0x0A]);
+    }
+    
+    #[tokio::test]
+    async fn test_inter_byte_gap_ends_frame() {
+        let transport = SerialTransport::new(inter_byte_config(Some(50))).unwrap();
+        let fake = FakeSerialHandle::new();
+        fake.push_data(b"AB");
+        fake.push_data_after(Duration::from_millis(20), b"CD");
+        fake.push_data_after(Duration::from_millis(120), b"EF");
+        transport.attach_port_for_test(fake.port()).await;
+        
+        let first = transport.receive(Duration::from_millis(500)).await.unwrap();
+        assert_eq!(first, b"ABCD".to_vec());
+        
+        // The late bytes start the next frame
+        let second = transport.receive(Duration::from_millis(500)).await.unwrap();
+        assert_eq!(second, b"EF".to_vec());
+    }
+    
+    #[tokio::test]
+    async fn test_bytes_past_frame_cap_start_next_frame() {
+        let transport = SerialTransport::new(inter_byte_config(Some(50))).unwrap();
+        let fake = FakeSerialHandle::new();
+        let stream: Vec<u8> = (0..DEFAULT_MAX_FRAME_LEN + 128).map(|i| (i % 251) as u8).collect();
+        
+        // One 1024-byte read, then 200-byte chunks; the last chunk crosses the cap
+        fake.push_data(&stream[..1024]);
+        for chunk in stream[1024..].chunks(200) {
+            fake.push_data(chunk);
+        }
+        transport.attach_port_for_test(fake.port()).await;
+        
+        let first = transport.receive(Duration::from_millis(500)).await.unwrap();
+        assert_eq!(first, stream[..DEFAULT_MAX_FRAME_LEN].to_vec());
+        
+        // Nothing is lost: the rest of the chunk comes back as the next frame
+        let second = transport.receive(Duration::from_millis(500)).await.unwrap();
+        assert_eq!(second, stream[DEFAULT_MAX_FRAME_LEN..].to_vec());
+    }
+    
+    #[tokio::test]
+    async fn test_without_inter_byte_timeout_returns_first_chunk() {
+        let transport = SerialTransport::new(inter_byte_config(None)).unwrap();
+        let fake = FakeSerialHandle::new();
+        fake.push_data(b"AB");
+        fake.push_data_after(Duration::from_millis(10), b"CD");
+        transport.attach_port_for_test(fake.port()).await;
+        
+        assert_eq!(transport.receive(Duration::from_millis(500)).await.unwrap(), b"AB".to_vec());
+    }
+    
+    fn flow_config(flow_control: crate::transport::common::FlowControl, overflow_marker: Option<&[u8]>) -> TransportConfig {
+        TransportConfig {
+            settings: TransportSettings::Serial(SerialSettings {
+                flow_control,
+                overflow_marker: overflow_marker.map(<[u8]>::to_vec),
+                ..Default::default()
+            }),
+            ..fake_transport_config(true)
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_xoff_pauses_sending_until_xon() {
+        use crate::transport::common::FlowControl;
+        
+        let transport = Arc::new(SerialTransport::new(flow_config(FlowControl::Software, None)).unwrap());
+        let fake = FakeSerialHandle::new();
+        fake.push_data(b"OK\x13");
+        fake.push_data_after(Duration::from_millis(150), b"\x11READY");
+        transport.attach_port_for_test(fake.port()).await;
+        
+        // Flow control bytes are stripped from received data
+        assert_eq!(transport.receive(Duration::from_millis(100)).await.unwrap(), b"OK".to_vec());
+        
+        let started = Instant::now();
+        let sender = transport.clone();
+        let send = tokio::spawn(async move { sender.send(b"DATA").await });
+        
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert!(fake.written().is_empty(), "sent while paused by XOFF");
+        
+        send.await.unwrap().unwrap();
+        assert!(started.elapsed() >= Duration::from_millis(100));
+        assert_eq!(fake.written(), b"DATA".to_vec());
+        
+        // Data that arrived alongside XON is not lost
+        assert_eq!(transport.receive(Duration::from_millis(100)).await.unwrap(), b"READY".to_vec());
+    }
+    
+    #[tokio::test]
+    async fn test_overflow_marker_surfaces_error() {
+        use crate::transport::common::FlowControl;
+        
+        let transport = SerialTransport::new(flow_config(FlowControl::None, Some(b"!OVF"))).unwrap();
+        let fake = FakeSerialHandle::new();
+        fake.push_data(b"VALUE:12\r\n");
+        fake.push_data(b"VAL!OVF\r\n");
+        transport.attach_port_for_test(fake.port()).await;
+        
+        assert_eq!(transport.receive(Duration::from_millis(100)).await.unwrap(), b"VALUE:12\r\n".to_vec());
+        assert!(matches!(transport.receive(Duration::from_millis(100)).await, Err(TransportError::BufferOverflow)));
+        assert_eq!(transport.stats().transactions_failed, 1);
+    }
+    
+    #[tokio::test]
+    async fn test_overflow_marker_split_across_reads() {
+        use crate::transport::common::FlowControl;
+        
+        let transport = SerialTransport::new(flow_config(FlowControl::None, Some(b"!OVF"))).unwrap();
+        let fake = FakeSerialHandle::new();
+        fake.push_data(b"VAL!O");
+        fake.push_data(b"VF\r\n");
+        transport.attach_port_for_test(fake.port()).await;
+        
+        assert_eq!(transport.receive(Duration::from_millis(100)).await.unwrap(), b"VAL!O".to_vec());
+        assert!(matches!(transport.receive(Duration::from_millis(100)).await, Err(TransportError::BufferOverflow)));
+    }
+    
+    #[tokio::test]
+    async fn test_xoff_during_long_send_pauses_midway() {
+        use crate::transport::common::FlowControl;
+        
+        let transport = Arc::new(SerialTransport::new(flow_config(FlowControl::Software, None)).unwrap());
+        let fake = FakeSerialHandle::new();
+        // The device fills up after the first chunk and resumes a little later
+        fake.push_on_write(FLOW_CONTROL_CHUNK, FakeRead::Data(vec![XOFF]));
+        fake.push_on_write(FLOW_CONTROL_CHUNK, FakeRead::Delayed(Duration::from_millis(150), vec![XON]));
+        transport.attach_port_for_test(fake.port()).await;
+        
+        let payload = vec![b'x'; FLOW_CONTROL_CHUNK * 2];
+        let sender = transport.clone();
+        let expected = payload.clone();
+        let send = tokio::spawn(async move { sender.send(&expected).await });
+        
+        tokio::time::sleep(Duration::from_millis(80)).await;
+        assert_eq!(fake.written().len(), FLOW_CONTROL_CHUNK, "kept sending after XOFF");
+        
+        send.await.unwrap().unwrap();
+        assert_eq!(fake.written(), payload);
+    }
+    
+    fn goodbye_config(auto_reconnect: bool) -> TransportConfig {
+        TransportConfig {
+            auto_reconnect,
+            settings: TransportSettings::Serial(SerialSettings {
+                goodbye_marker: Some(b"BYE".to_vec()),
+                ..Default::default()
+            }),
+            ..fake_transport_config(true)
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_goodbye_marker_disconnects_cleanly() {
+        let transport = SerialTransport::new(goodbye_config(false)).unwrap();
+        let mut events = transport.subscribe_events();
+        let fake = FakeSerialHandle::new();
+        fake.push_data(b"T:21.5\r\nBYE\r\n");
+        transport.attach_port_for_test(fake.port()).await;
+        
+        // Data ahead of the marker is delivered, then the transport is closed without an error
+        assert_eq!(transport.receive(Duration::from_millis(100)).await.unwrap(), b"T:21.5\r\n".to_vec());
+        assert!(!transport.is_connected());
+        assert_eq!(transport.disconnect_reason(), Some(DisconnectReason::DeviceRequested));
+        assert!(transport.port.lock().await.is_none());
+        assert_eq!(transport.stats().transactions_failed, 0);
+        assert_eq!(
+            events.try_recv().unwrap(),
+            SerialTransportEvent::DeviceDisconnected { transport_session_id: transport.session_id() }
+        );
+        
+        assert!(matches!(transport.receive(Duration::from_millis(10)).await, Err(TransportError::NotConnected)));
+        
+        // Connecting again clears the reason
+        transport.attach_port_for_test(FakeSerialHandle::new().port()).await;
+        assert!(transport.disconnect_reason().is_none());
+    }
+    
+    #[tokio::test]
+    async fn test_device_requested_disconnect_suppresses_auto_reconnect() {
+        use std::sync::atomic::AtomicUsize;
+        
+        let transport = SerialTransport::new(goodbye_config(true)).unwrap();
+        let fake = FakeSerialHandle::new();
+        fake.push_data(b"BYE");
+        transport.attach_port_for_test(fake.port()).await;
+        assert!(transport.receive(Duration::from_millis(100)).await.unwrap().is_empty());
+        
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let counter = attempts.clone();
+        transport.base.trigger_reconnection(move || -> std::pin::Pin<Box<dyn std::future::Future<Output = TransportResult<()>> + Send>> {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Box::pin(async { Ok(()) })
+        }).await.unwrap();
+        assert!(transport.base.reconnection_task.lock().await.is_none());
+        
+        // Sends fail fast instead of kicking off a reconnect
+        assert!(matches!(transport.send(b"PING").await, Err(TransportError::NotConnected)));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(attempts.load(Ordering::SeqCst), 0);
+        assert_eq!(transport.base.get_state().await, ConnectionState::Disconnected);
+        
+        // A lost link still reconnects
+        assert!(DisconnectReason::Lost.allows_auto_reconnect());
+    }
+    
+    fn settings_config(settings: SerialSettings) -> TransportConfig {
+        TransportConfig {
+            settings: TransportSettings::Serial(settings),
+            ..fake_transport_config(true)
+        }
+    }
+    
+    fn config_error(settings: SerialSettings) -> String {
+        match SerialTransport::new(settings_config(settings)) {
+            Err(TransportError::ConfigError(msg)) => msg,
+            Err(e) => panic!("Expected ConfigError, got {}", e),
+            Ok(_) => panic!("Expected ConfigError, settings were accepted"),
+        }
+    }
+    
+    #[test]
+    fn test_unsupported_settings_rejected_at_construction() {
+        use crate::transport::common::{DataBits, Parity, StopBits};
+        
+        let mark = config_error(SerialSettings { parity: Parity::Mark, ..Default::default() });
+        assert_eq!(mark, "Mark parity is not supported by the serial backend");
+        
+        let space = config_error(SerialSettings { parity: Parity::Space, data_bits: DataBits::Seven, ..Default::default() });
+        assert!(space.contains("Space parity"));
+        
+        let stop_bits = config_error(SerialSettings { data_bits: DataBits::Five, stop_bits: StopBits::Two, ..Default::default() });
+        assert!(stop_bits.contains("1.5 stop bits"));
+        
+        assert_eq!(config_error(SerialSettings { baud_rate: 0, ..Default::default() }), "Invalid baud rate");
+        assert!(config_error(SerialSettings { inter_byte_timeout_ms: Some(0), ..Default::default() }).contains("Inter-byte"));
+        assert!(config_error(SerialSettings { goodbye_marker: Some(Vec::new()), ..Default::default() }).contains("Goodbye marker"));
+    }
+    
+    #[test]
+    fn test_valid_settings_accepted_at_construction() {
+        use crate::transport::common::{DataBits, FlowControl, Parity, StopBits};
+        
+        let seven_e_two = SerialSettings {
+            baud_rate: 9600,
+            data_bits: DataBits::Seven,
+            parity: Parity::Even,
+            stop_bits: StopBits::Two,
+            flow_control: FlowControl::Hardware,
+            inter_byte_timeout_ms: Some(5),
+            goodbye_marker: Some(b"BYE".to_vec()),
+            ..Default::default()
+        };
+        assert!(SerialTransport::new(settings_config(seven_e_two)).is_ok());
+        assert!(SerialTransport::new(settings_config(SerialSettings {
+            data_bits: DataBits::Five,
+            ..Default::default()
+        })).is_ok());
+    }
+    
+    #[tokio::test]
+    async fn test_long_receive_does_not_block_port() {
+        let transport = Arc::new(SerialTransport::new(fake_transport_config(true)).unwrap());
+        let fake = FakeSerialHandle::new();
+        transport.attach_port_for_test(fake.port()).await;
+        
+        let reader = transport.clone();
+        let pending = tokio::spawn(async move { reader.receive(Duration::from_secs(2)).await });
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        
+        // Health checks and sends get the port between read slices
+        let start = Instant::now();
+        assert!(transport.is_connected());
+        assert!(transport.port.lock().await.as_ref().unwrap().check_health().await);
+        transport.send(b"PING").await.unwrap();
+        assert!(start.elapsed() < Duration::from_millis(500), "blocked for {:?}", start.elapsed());
+        assert_eq!(fake.written(), b"PING".to_vec());
+        assert!(!pending.is_finished());
+        
+        fake.push_data(b"PONG");
+        assert_eq!(pending.await.unwrap().unwrap(), b"PONG".to_vec());
+    }
+    
+    #[tokio::test]
+    async fn test_connect_is_idempotent_unless_settings_differ() {
+        let config = fake_transport_config(true);
+        let transport = SerialTransport::new(config.clone()).unwrap();
+        let fake = FakeSerialHandle::new();
+        transport.attach_port_for_test(fake.port()).await;
+        let session = transport.port_session_id().await;
+        
+        // Neither call reopens the port
+        transport.connect().await.unwrap();
+        transport.connect_with(&config).await.unwrap();
+        assert!(transport.is_connected());
+        assert_eq!(transport.port_session_id().await, session);
+        
+        let faster = TransportConfig {
+            settings: TransportSettings::Serial(SerialSettings {
+                baud_rate: 9600,
+                ..Default::default()
+            }),
+            ..config.clone()
+        };
+        let result = transport.connect_with(&faster).await;
+        assert!(matches!(result, Err(TransportError::SettingsMismatch(ref msg)) if msg.contains("connected")));
+        
+        // Tuning that doesn't change the connection is not a mismatch
+        let retuned = TransportConfig { max_reconnect_attempts: 9, ..config };
+        transport.connect_with(&retuned).await.unwrap();
+        assert!(transport.is_connected());
+    }
+    
+    /// Readiness signalled by the test, counting how often the reader waited
+    #[derive(Default)]
+    struct FakeReadiness {
+        ready: tokio::sync::Notify,
+        waits: std::sync::atomic::AtomicUsize,
+    }
+    
+    #[async_trait]
+    impl ReadReadiness for FakeReadiness {
+        async fn wait_readable(&self, timeout: Duration) -> std::io::Result<bool> {
+            self.waits.fetch_add(1, Ordering::Relaxed);
+            Ok(tokio::time::timeout(timeout, self.ready.notified()).await.is_ok())
+        }
+    }
+    
+    async fn event_driven_transport() -> (SerialTransport, FakeSerialHandle, Arc<FakeReadiness>) {
+        let transport = SerialTransport::new(settings_config(SerialSettings {
+            read_strategy: ReadStrategy::EventDriven,
+            ..Default::default()
+        })).unwrap();
+        let fake = FakeSerialHandle::new();
+        let readiness = Arc::new(FakeReadiness::default());
+        transport.attach_port_with_readiness_for_test(fake.port(), Some(readiness.clone())).await;
+        (transport, fake, readiness)
+    }
+    
+    #[tokio::test]
+    async fn test_event_driven_read_wakes_on_data() {
+        let (transport, fake, readiness) = event_driven_transport().await;
+        let transport = Arc::new(transport);
+        
+        let start = Instant::now();
+        let receiver = {
+            let transport = transport.clone();
+            tokio::spawn(async move { transport.receive(Duration::from_secs(5)).await })
+        };
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        fake.push_data(b"READY\r\n");
+        readiness.ready.notify_one();
+        
+        let data = receiver.await.unwrap().unwrap();
+        assert_eq!(data, b"READY\r\n".to_vec());
+        assert!(start.elapsed() < Duration::from_secs(2), "read did not wake on data");
+        // Nothing was read while waiting for the data
+        assert_eq!(fake.read_calls(), 1);
+    }
+    
+    #[tokio::test]
+    async fn test_event_driven_read_idles_without_polling() {
+        let (transport, fake, readiness) = event_driven_transport().await;
+        
+        let data = transport.receive(Duration::from_millis(300)).await.unwrap();
+        assert!(data.is_empty());
+        assert_eq!(fake.read_calls(), 0);
+        assert_eq!(readiness.waits.load(Ordering::Relaxed), 1);
+        
+        // The polled strategy reads the port every slice over the same wait
+        let polled = SerialTransport::new(fake_transport_config(true)).unwrap();
+        let polled_fake = FakeSerialHandle::new();
+        polled.attach_port_for_test(polled_fake.port()).await;
+        polled.receive(Duration::from_millis(300)).await.unwrap();
+        assert!(polled_fake.read_calls() >= 3, "polled reads: {}", polled_fake.read_calls());
+    }
+    
+    #[tokio::test]
+    async fn test_event_driven_read_takes_buffered_data_without_waiting() {
+        let (transport, fake, readiness) = event_driven_transport().await;
+        fake.push_data(b"EARLY");
+        
+        let data = transport.receive(Duration::from_secs(1)).await.unwrap();
+        assert_eq!(data, b"EARLY".to_vec());
+        assert_eq!(readiness.waits.load(Ordering::Relaxed), 0);
+    }
+}
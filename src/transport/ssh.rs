@@ -6,7 +6,7 @@ use std::path::PathBuf;
 use tokio::task::JoinHandle;
 use crate::transport::{
     Transport, TransportBase, TransportConfig, TransportError, TransportResult, 
-    TransportStats, TransportType, ConnectionState
+    TransportStats, TransportType, ConnectionState, ReconnectCallback, WireDirection, WireTrace
 };
 use crate::transport::ssh_keys::{SshKeyManager, SshKeyInfo};
 
@@ -25,7 +25,6 @@ pub struct SshTransport {
     base: TransportBase,
     session: Arc<Mutex<Option<MockSshSession>>>, // Thread-safe session management
     reconnect_attempts: Arc<Mutex<u32>>,         // Thread-safe mutable state
-    max_reconnect_attempts: u32,                 // Immutable configuration
     base_reconnect_delay: Duration,              // Immutable configuration
     task_handles: Arc<Mutex<Vec<JoinHandle<()>>>>, // Track spawned tasks for cleanup
     cleanup_flag: Arc<AtomicBool>,               // Signal for cooperative shutdown
@@ -87,7 +86,6 @@ impl SshTransport {
             ),
             session: Arc::new(Mutex::new(None)),
             reconnect_attempts: Arc::new(Mutex::new(0)),
-            max_reconnect_attempts: 5, // Fewer attempts for SSH due to longer timeouts
             base_reconnect_delay: Duration::from_secs(1), // Longer base delay for SSH
             task_handles: Arc::new(Mutex::new(Vec::new())),
             cleanup_flag: Arc::new(AtomicBool::new(false)),
@@ -101,7 +99,7 @@ impl SshTransport {
         let mut backoff = crate::transport::backoff::ExponentialBackoff::from_config(
             self.base.config.max_reconnect_attempts,
             self.base.config.reconnect_delay_ms,
-        );
+        ).with_hints(&self.base.config.reconnect_hints);
         
         while backoff.should_retry() {
             match self.try_connect().await {
@@ -242,8 +240,9 @@ impl Transport for SshTransport {
     }
     
     async fn connect(&self) -> TransportResult<()> {
+        // Connecting is idempotent
         if self.is_connected() {
-            return Err(TransportError::AlreadyConnected);
+            return Ok(());
         }
         
         self.base.set_state(ConnectionState::Connecting).await;
@@ -301,6 +300,7 @@ impl Transport for SshTransport {
                         stats.bytes_sent += data.len() as u64;
                         stats.transactions_success += 1;
                     }).await;
+                    self.base.wire_trace.record(&self.base.name, WireDirection::Tx, data).await;
                     
                     // Enforce minimum latency requirement (100ms for network)
                     self.base.enforce_latency(start).await?;
@@ -353,6 +353,7 @@ impl Transport for SshTransport {
             self.base.update_stats(|stats| {
                 stats.bytes_received += data.len() as u64;
             }).await;
+            self.base.wire_trace.record(&self.base.name, WireDirection::Rx, &data).await;
             
             // Enforce minimum latency
             self.base.enforce_latency(start).await?;
@@ -368,7 +369,7 @@ impl Transport for SshTransport {
     }
     
     fn stats(&self) -> TransportStats {
-        TransportStats::default()
+        self.base.stats_snapshot()
     }
     
     async fn reset(&self) -> TransportResult<()> {
@@ -415,6 +416,14 @@ impl Transport for SshTransport {
         tracing::debug!("SSH transport resources cleaned up");
         Ok(())
     }
+    
+    fn on_reconnect(&self, callback: ReconnectCallback) {
+        self.base.on_reconnect(callback);
+    }
+    
+    fn wire_trace(&self) -> Option<&WireTrace> {
+        Some(&self.base.wire_trace)
+    }
 }
 
 /// Mock SSH session for testing (will be replaced with real implementation)
@@ -615,9 +624,10 @@ mod tests {
         assert!(result.is_ok());
         assert!(transport.is_connected());
         
-        // Try to connect again (should fail)
+        // Connecting again is a no-op
         let result = transport.connect().await;
-        assert!(matches!(result, Err(TransportError::AlreadyConnected)));
+        assert!(result.is_ok());
+        assert!(transport.is_connected());
         
         // Disconnect
         let result = transport.disconnect().await;
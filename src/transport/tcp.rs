@@ -4,26 +4,32 @@ use std::time::{Duration, Instant};
 use std::net::SocketAddr;
 use tokio::net::{TcpStream, TcpListener};
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, Notify};
 use tokio::time::timeout;
 use tokio::task::JoinHandle;
 
 use crate::transport::{
     Transport, TransportBase, TransportConfig, TransportError, TransportResult,
-    TransportStats, TransportType, ConnectionState,
+    TransportStats, TransportType, ConnectionState, ReconnectCallback, WireDirection, WireTrace,
 };
 use crate::transport::common::TcpSettings;
 
 /// TCP transport implementation
 pub struct TcpTransport {
     base: TransportBase,
-    stream: Option<Mutex<TcpStream>>,
+    stream: Arc<Mutex<Option<TcpStream>>>,
     settings: TcpSettings,
-    reconnect_attempts: u32,
-    task_handles: Vec<JoinHandle<()>>,  // Track spawned tasks for cleanup
+    reconnect_attempts: Arc<Mutex<u32>>,
+    task_handles: Mutex<Vec<JoinHandle<()>>>,  // Track spawned tasks for cleanup
     cleanup_flag: Arc<AtomicBool>,      // Signal for cooperative shutdown
+    last_activity: Arc<Mutex<Instant>>, // Last time data arrived from the peer
+    link_down: Arc<AtomicBool>,         // Set by the heartbeat monitor on a half-open connection
+    link_down_notify: Arc<Notify>,      // Wakes reads blocked on a dead connection
 }
 
+/// How often the heartbeat monitor checks, as a fraction of the heartbeat timeout
+const HEARTBEAT_CHECKS_PER_TIMEOUT: u32 = 4;
+
 impl TcpTransport {
     /// Create a new TCP transport
     pub fn new(config: TransportConfig) -> TransportResult<Self> {
@@ -38,11 +44,14 @@ impl TcpTransport {
                 TransportType::Tcp,
                 config,
             ),
-            stream: None,
+            stream: Arc::new(Mutex::new(None)),
             settings,
-            reconnect_attempts: 0,
-            task_handles: Vec::new(),
+            reconnect_attempts: Arc::new(Mutex::new(0)),
+            task_handles: Mutex::new(Vec::new()),
             cleanup_flag: Arc::new(AtomicBool::new(false)),
+            last_activity: Arc::new(Mutex::new(Instant::now())),
+            link_down: Arc::new(AtomicBool::new(false)),
+            link_down_notify: Arc::new(Notify::new()),
         })
     }
     
@@ -54,16 +63,16 @@ impl TcpTransport {
     }
     
     /// Try to connect with exponential backoff using shared module
-    async fn connect_with_backoff(&mut self) -> TransportResult<()> {
+    async fn connect_with_backoff(&self) -> TransportResult<()> {
         let mut backoff = crate::transport::backoff::ExponentialBackoff::from_config(
             self.base.config.max_reconnect_attempts,
             self.base.config.reconnect_delay_ms,
-        );
+        ).with_hints(&self.base.config.reconnect_hints);
         
         while backoff.should_retry() {
             match self.try_connect().await {
                 Ok(()) => {
-                    self.reconnect_attempts = 0;
+                    *self.reconnect_attempts.lock().await = 0;
                     return Ok(());
                 }
                 Err(e) => {
@@ -74,6 +83,7 @@ impl TcpTransport {
                     }
                     
                     if let Some(delay) = backoff.next_delay() {
+                        *self.reconnect_attempts.lock().await += 1;
                         tracing::warn!(
                             "TCP connection failed (attempt {}/{}), retrying in {:?}: {}",
                             backoff.current_attempt(),
@@ -96,7 +106,7 @@ impl TcpTransport {
     }
     
     /// Attempt a single connection
-    async fn try_connect(&mut self) -> TransportResult<()> {
+    async fn try_connect(&self) -> TransportResult<()> {
         let addr = format!("{}:{}", self.settings.host, self.settings.port)
             .parse::<SocketAddr>()
             .map_err(|e| TransportError::ConfigError(format!("Invalid address: {}", e)))?;
@@ -114,15 +124,83 @@ impl TcpTransport {
         
         // TODO: Configure keep-alive if supported
         
-        self.stream = Some(Mutex::new(stream));
+        *self.stream.lock().await = Some(stream);
+        self.link_down.store(false, Ordering::Relaxed);
+        *self.last_activity.lock().await = Instant::now();
+        self.start_heartbeat_monitor().await;
         
         tracing::info!("Connected to TCP {}:{}", self.settings.host, self.settings.port);
         Ok(())
     }
+    
+    /// Watch for half-open connections (peer gone without a RST)
+    /// If nothing arrives within `heartbeat_timeout_ms` the connection is marked
+    /// down proactively, instead of waiting for the next write to fail
+    async fn start_heartbeat_monitor(&self) {
+        let heartbeat_timeout = Duration::from_millis(self.settings.heartbeat_timeout_ms as u64);
+        if heartbeat_timeout.is_zero() {
+            return;
+        }
+        
+        let check_interval = heartbeat_timeout / HEARTBEAT_CHECKS_PER_TIMEOUT;
+        let last_activity = self.last_activity.clone();
+        let link_down = self.link_down.clone();
+        let link_down_notify = self.link_down_notify.clone();
+        let cleanup_flag = self.cleanup_flag.clone();
+        let base_state = self.base.state.clone();
+        let auto_reconnect = self.base.config.auto_reconnect;
+        let peer = format!("{}:{}", self.settings.host, self.settings.port);
+        
+        let handle = tokio::spawn(async move {
+            while !cleanup_flag.load(Ordering::Relaxed) {
+                tokio::time::sleep(check_interval).await;
+                
+                let idle = last_activity.lock().await.elapsed();
+                if idle >= heartbeat_timeout {
+                    tracing::warn!(
+                        "No data from TCP {} for {:?}, treating connection as half-open",
+                        peer, idle
+                    );
+                    link_down.store(true, Ordering::Relaxed);
+                    link_down_notify.notify_waiters();
+                    
+                    // Reconnecting tells the next operation to re-establish the link
+                    *base_state.write().await = if auto_reconnect {
+                        ConnectionState::Reconnecting
+                    } else {
+                        ConnectionState::Disconnected
+                    };
+                    break;
+                }
+            }
+        });
+        
+        self.task_handles.lock().await.push(handle);
+    }
+    
+    /// Whether TCP_NODELAY is set on the open socket
+    pub async fn nodelay(&self) -> TransportResult<bool> {
+        let stream = self.stream.lock().await;
+        stream.as_ref().ok_or(TransportError::NotConnected)?.nodelay().map_err(TransportError::IoError)
+    }
+    
+    /// Drop a connection the heartbeat monitor declared dead
+    async fn drop_dead_link(&self) {
+        if self.link_down.load(Ordering::Relaxed) {
+            if let Some(mut stream) = self.stream.lock().await.take() {
+                let _ = stream.shutdown().await;
+            }
+        }
+    }
+    
+    /// Record that the peer is still there (any successful I/O counts)
+    async fn touch_activity(&self) {
+        *self.last_activity.lock().await = Instant::now();
+    }
 }
 
 impl TcpTransport {
-    async fn handle_not_connected(&mut self, timeout_duration: Duration) -> TransportResult<Vec<u8>> {
+    async fn handle_not_connected(&self, timeout_duration: Duration) -> TransportResult<Vec<u8>> {
         self.base.update_stats(|stats| {
             stats.transactions_failed += 1;
             stats.last_error = Some("Not connected".into());
@@ -150,22 +228,32 @@ impl Transport for TcpTransport {
     }
     
     fn is_connected(&self) -> bool {
-        self.stream.is_some()
+        // The stream lock is held across reads, so go by the connection state
+        let connected = match self.base.state.try_read() {
+            Ok(state) => matches!(*state, ConnectionState::Connected),
+            Err(_) => false,  // Conservative default if contended
+        };
+        connected && !self.link_down.load(Ordering::Relaxed)
     }
     
     async fn connect(&self) -> TransportResult<()> {
+        // Connecting is idempotent
         if self.is_connected() {
-            return Err(TransportError::AlreadyConnected);
+            return Ok(());
         }
         
+        // Discard a half-open stream before reconnecting
+        self.drop_dead_link().await;
+        
         self.base.set_state(ConnectionState::Connecting).await;
         
         // Connect with exponential backoff
         match self.connect_with_backoff().await {
             Ok(()) => {
                 self.base.set_state(ConnectionState::Connected).await;
+                let attempts = *self.reconnect_attempts.lock().await;
                 self.base.update_stats(|stats| {
-                    stats.reconnect_count += self.reconnect_attempts;
+                    stats.reconnect_count += attempts;
                 }).await;
                 Ok(())
             }
@@ -183,7 +271,7 @@ impl Transport for TcpTransport {
         
         // Clean up all resources before disconnecting
         self.cleanup_resources().await?;
-        self.reconnect_attempts = 0;
+        *self.reconnect_attempts.lock().await = 0;
         
         tracing::info!("Disconnected from TCP {}:{}", self.settings.host, self.settings.port);
         Ok(())
@@ -193,7 +281,7 @@ impl Transport for TcpTransport {
         let start = Instant::now();
         
         // Check connection and reconnect if needed (before creating guard)
-        if self.stream.is_none() && self.base.config.auto_reconnect {
+        if !self.is_connected() && self.base.config.auto_reconnect {
             self.base.update_stats(|stats| {
                 stats.transactions_failed += 1;
                 stats.last_error = Some("Not connected".into());
@@ -204,10 +292,10 @@ impl Transport for TcpTransport {
             return self.send(data).await;
         }
         
-        if let Some(ref stream) = self.stream {
+        let mut stream_guard = self.stream.lock().await;
+        if let Some(stream) = stream_guard.as_mut() {
             // Start monitoring this operation
             let guard = self.base.monitor.start_operation("tcp_send");
-            let mut stream = stream.lock().await;
             
             let write_timeout = Duration::from_millis(self.base.config.write_timeout_ms as u64);
             
@@ -217,11 +305,14 @@ impl Transport for TcpTransport {
                 .map_err(|e| TransportError::IoError(e))?;
             
             stream.flush().await?;
+            drop(stream_guard);
+            self.touch_activity().await;
             
             self.base.update_stats(|stats| {
                 stats.bytes_sent += data.len() as u64;
                 stats.transactions_success += 1;
             }).await;
+            self.base.wire_trace.record(&self.base.name, WireDirection::Tx, data).await;
             
             // Enforce minimum latency requirement (100ms for TCP)
             self.base.enforce_latency(start).await?;
@@ -243,26 +334,37 @@ impl Transport for TcpTransport {
     async fn receive(&self, timeout_duration: Duration) -> TransportResult<Vec<u8>> {
         let start = Instant::now();
         
+        if self.link_down.load(Ordering::Relaxed) {
+            return self.handle_not_connected(timeout_duration).await;
+        }
+        
         // Handle the case where stream exists
-        let result = if let Some(ref stream) = self.stream {
-            let mut stream = stream.lock().await;
-            
+        let mut stream_guard = self.stream.lock().await;
+        let result = if let Some(stream) = stream_guard.as_mut() {
             let mut buffer = vec![0u8; self.base.config.read_buffer_size];
             
-            let n = timeout(timeout_duration, stream.read(&mut buffer))
-                .await
-                .map_err(|_| TransportError::Timeout(format!("Read timeout after {:?}", timeout_duration)))?
-                .map_err(|e| TransportError::IoError(e))?;
+            // A read on a half-open connection never completes; the heartbeat
+            // monitor wakes it so the caller isn't stuck until the timeout
+            let n = tokio::select! {
+                read = timeout(timeout_duration, stream.read(&mut buffer)) => read
+                    .map_err(|_| TransportError::Timeout(format!("Read timeout after {:?}", timeout_duration)))?
+                    .map_err(|e| TransportError::IoError(e))?,
+                _ = self.link_down_notify.notified() => {
+                    return Err(TransportError::ConnectionFailed("Connection half-open (heartbeat timeout)".into()));
+                }
+            };
             
             if n == 0 {
-                // Connection closed by peer - will handle after releasing lock
+                // Connection closed by peer - handled below
                 None
             } else {
                 buffer.truncate(n);
+                self.touch_activity().await;
                 
                 self.base.update_stats(|stats| {
                     stats.bytes_received += n as u64;
                 }).await;
+                self.base.wire_trace.record(&self.base.name, WireDirection::Rx, &buffer).await;
                 
                 // Enforce minimum latency
                 self.base.enforce_latency(start).await?;
@@ -270,12 +372,14 @@ impl Transport for TcpTransport {
                 Some(buffer)
             }
         } else {
+            drop(stream_guard);
             return self.handle_not_connected(timeout_duration).await;
         };
         
         // Handle connection closed case
         if result.is_none() {
-            self.stream = None;
+            *stream_guard = None;
+            drop(stream_guard);
             self.base.set_state(ConnectionState::Disconnected).await;
             return Err(TransportError::ConnectionFailed("Connection closed by peer".into()));
         }
@@ -284,15 +388,13 @@ impl Transport for TcpTransport {
     }
     
     fn stats(&self) -> TransportStats {
-        TransportStats::default()
+        self.base.stats_snapshot()
     }
     
     async fn reset(&self) -> TransportResult<()> {
         // TCP doesn't have a buffer to flush like serial
         // But we can try to clear any pending data
-        if let Some(ref stream) = self.stream {
-            let mut stream = stream.lock().await;
-            
+        if let Some(stream) = self.stream.lock().await.as_mut() {
             // Try to read and discard any pending data
             let mut discard = vec![0u8; 1024];
             while let Ok(Ok(n)) = timeout(
@@ -317,13 +419,12 @@ impl Transport for TcpTransport {
         self.cleanup_flag.store(true, Ordering::Relaxed);
         
         // Abort all spawned tasks
-        for handle in self.task_handles.drain(..) {
+        for handle in self.task_handles.lock().await.drain(..) {
             handle.abort();
         }
         
         // Properly shutdown and drop the TCP stream
-        if let Some(stream) = self.stream.take() {
-            let mut stream = stream.lock().await;
+        if let Some(mut stream) = self.stream.lock().await.take() {
             let _ = stream.shutdown().await; // Ignore errors during cleanup
         }
         
@@ -336,6 +437,14 @@ impl Transport for TcpTransport {
         tracing::debug!("TCP transport resources cleaned up");
         Ok(())
     }
+    
+    fn on_reconnect(&self, callback: ReconnectCallback) {
+        self.base.on_reconnect(callback);
+    }
+    
+    fn wire_trace(&self) -> Option<&WireTrace> {
+        Some(&self.base.wire_trace)
+    }
 }
 
 /// Information about discovered TCP service
@@ -394,12 +503,13 @@ impl TcpServer {
                 no_delay: true,
                 keep_alive: true,
                 keep_alive_interval_ms: 10000,
+                heartbeat_timeout_ms: 0,
             }),
             ..Default::default()
         };
         
-        let mut transport = TcpTransport::new(config)?;
-        transport.stream = Some(Mutex::new(stream));
+        let transport = TcpTransport::new(config)?;
+        *transport.stream.lock().await = Some(stream);
         transport.base.set_state(ConnectionState::Connected).await;
         
         Ok(transport)
@@ -422,6 +532,7 @@ mod tests {
                 no_delay: true,
                 keep_alive: true,
                 keep_alive_interval_ms: 10000,
+                heartbeat_timeout_ms: 0,
             }),
             ..Default::default()
         };
@@ -451,19 +562,20 @@ mod tests {
                 no_delay: true,
                 keep_alive: false,
                 keep_alive_interval_ms: 0,
+                heartbeat_timeout_ms: 0,
             }),
             auto_reconnect: false,
             ..Default::default()
         };
         
-        let mut client = TcpTransport::new(config).unwrap();
+        let client = TcpTransport::new(config).unwrap();
         
         // Connect should succeed
         client.connect().await.unwrap();
         assert!(client.is_connected());
         
         // Server should accept connection
-        let mut server_transport = server_handle.await.unwrap().unwrap();
+        let server_transport = server_handle.await.unwrap().unwrap();
         assert!(server_transport.is_connected());
         
         // Test data exchange
@@ -475,4 +587,83 @@ mod tests {
         client.disconnect().await.unwrap();
         server_transport.disconnect().await.unwrap();
     }
-}
\ No newline at end of file
+    
+    #[tokio::test]
+    async fn test_nodelay_applied_on_connect() {
+        for no_delay in [true, false] {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let port = listener.local_addr().unwrap().port();
+            let peer_handle = tokio::spawn(async move { listener.accept().await.unwrap() });
+            
+            let config = TransportConfig {
+                transport_type: TransportType::Tcp,
+                address: format!("127.0.0.1:{}", port),
+                settings: TransportSettings::Tcp(TcpSettings {
+                    host: "127.0.0.1".to_string(),
+                    port,
+                    no_delay,
+                    ..Default::default()
+                }),
+                auto_reconnect: false,
+                ..Default::default()
+            };
+            
+            let client = TcpTransport::new(config).unwrap();
+            client.connect().await.unwrap();
+            let _peer = peer_handle.await.unwrap();
+            
+            assert_eq!(client.nodelay().await.unwrap(), no_delay);
+            client.disconnect().await.unwrap();
+        }
+    }
+    
+    #[test]
+    fn test_nodelay_defaults_on_when_omitted() {
+        let settings: TcpSettings = serde_json::from_str(
+            r#"{"host": "10.0.0.5", "port": 502, "keep_alive": true, "keep_alive_interval_ms": 1000}"#
+        ).unwrap();
+        assert!(settings.no_delay);
+    }
+    
+    #[tokio::test]
+    async fn test_half_open_connection_detected() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        
+        // Peer accepts, then goes silent without closing (no FIN/RST)
+        let peer_handle = tokio::spawn(async move { listener.accept().await.unwrap() });
+        
+        let config = TransportConfig {
+            transport_type: TransportType::Tcp,
+            address: format!("127.0.0.1:{}", port),
+            settings: TransportSettings::Tcp(TcpSettings {
+                host: "127.0.0.1".to_string(),
+                port,
+                no_delay: true,
+                keep_alive: true,
+                keep_alive_interval_ms: 100,
+                heartbeat_timeout_ms: 200,
+            }),
+            auto_reconnect: false,
+            ..Default::default()
+        };
+        
+        let client = TcpTransport::new(config).unwrap();
+        client.connect().await.unwrap();
+        // Held open (and silent) until the end of the test
+        let _peer = peer_handle.await.unwrap();
+        assert!(client.is_connected());
+        
+        // A long read is woken when the heartbeat monitor declares the link dead
+        let start = Instant::now();
+        let result = client.receive(Duration::from_secs(5)).await;
+        let elapsed = start.elapsed();
+        
+        assert!(matches!(result, Err(TransportError::ConnectionFailed(_))), "{:?}", result);
+        assert!(elapsed < Duration::from_millis(500), "detected after {:?}", elapsed);
+        assert!(!client.is_connected());
+        assert_eq!(*client.base.state.read().await, ConnectionState::Disconnected);
+        
+        client.disconnect().await.unwrap();
+    }
+}
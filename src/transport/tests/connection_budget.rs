@@ -0,0 +1,54 @@
+/// Global cap on open transports
+use crate::transport::{ConnectionBudget, TransportConfig, TransportError, TransportFactory, TransportType};
+
+fn serial_config(address: &str) -> TransportConfig {
+    TransportConfig {
+        address: address.to_string(),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_budget_caps_open_transports() {
+    let budget = ConnectionBudget::new(2);
+    
+    let first = TransportFactory::create_with_budget(serial_config("/dev/ttyUSB0"), &budget).await.unwrap();
+    let _second = TransportFactory::create_with_budget(serial_config("/dev/ttyUSB1"), &budget).await.unwrap();
+    assert_eq!(first.transport_type(), TransportType::Serial);
+    assert_eq!(budget.usage().open, 2);
+    assert_eq!(budget.usage().available(), Some(0));
+    
+    match TransportFactory::create_with_budget(serial_config("/dev/ttyUSB2"), &budget).await {
+        Err(TransportError::ResourceUnavailable(msg)) => assert!(msg.contains("2 of 2"), "{}", msg),
+        Err(e) => panic!("Expected ResourceUnavailable, got {}", e),
+        Ok(_) => panic!("Expected the budget to reject a third transport"),
+    }
+    assert_eq!(budget.usage().rejected, 1);
+    
+    // Closing one frees its slot
+    drop(first);
+    assert_eq!(budget.usage().open, 1);
+    let _third = TransportFactory::create_with_budget(serial_config("/dev/ttyUSB2"), &budget).await.unwrap();
+    assert_eq!(budget.usage().open, 2);
+}
+
+#[tokio::test]
+async fn test_failed_create_releases_slot() {
+    let budget = ConnectionBudget::new(1);
+    let config = TransportConfig {
+        transport_type: TransportType::Tcp,
+        ..serial_config("127.0.0.1")
+    };
+    
+    assert!(matches!(
+        TransportFactory::create_with_budget(config, &budget).await,
+        Err(TransportError::ConfigError(_))
+    ));
+    assert_eq!(budget.usage().open, 0);
+    
+    // Unlimited budgets report no remaining count
+    let unlimited = ConnectionBudget::new(0);
+    let _transports: Vec<_> = (0..3).map(|_| unlimited.try_acquire().unwrap()).collect();
+    assert_eq!(unlimited.usage().open, 3);
+    assert_eq!(unlimited.usage().available(), None);
+}
@@ -0,0 +1,74 @@
+//! Tests for debouncing discovery scans
+
+use std::time::{Duration, Instant};
+use crate::transport::discovery_debounce::{DiscoveryChange, DiscoveryDebouncer};
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn test_first_scan_lists_present_devices() {
+    let mut debouncer = DiscoveryDebouncer::new(ms(1000));
+    let changes = debouncer.observe(vec!["COM3"], Instant::now());
+    
+    assert_eq!(changes, vec![DiscoveryChange::Added("COM3")]);
+    assert!(debouncer.is_stable(&"COM3"));
+}
+
+#[test]
+fn test_flapping_device_produces_no_change() {
+    let start = Instant::now();
+    let mut debouncer = DiscoveryDebouncer::new(ms(1000));
+    debouncer.observe(vec!["COM3"], start);
+    
+    // COM4 appears and disappears every 300ms, COM3 drops out briefly
+    let scans: [(u64, Vec<&str>); 6] = [
+        (300, vec!["COM3", "COM4"]),
+        (600, vec!["COM3"]),
+        (900, vec!["COM4"]),
+        (1200, vec!["COM3"]),
+        (1500, vec!["COM3", "COM4"]),
+        (1800, vec!["COM3"]),
+    ];
+    for (at, present) in scans {
+        let changes = debouncer.observe(present, start + ms(at));
+        assert!(changes.is_empty(), "flap at {}ms reported {:?}", at, changes);
+    }
+    
+    let stable: Vec<&&str> = debouncer.stable().collect();
+    assert_eq!(stable, vec![&"COM3"]);
+}
+
+#[test]
+fn test_change_reported_once_stable_past_window() {
+    let start = Instant::now();
+    let mut debouncer = DiscoveryDebouncer::new(ms(1000));
+    debouncer.observe(vec!["COM3"], start);
+    
+    assert!(debouncer.observe(vec!["COM3", "COM4"], start + ms(200)).is_empty());
+    assert!(debouncer.observe(vec!["COM3", "COM4"], start + ms(700)).is_empty());
+    assert_eq!(
+        debouncer.observe(vec!["COM3", "COM4"], start + ms(1200)),
+        vec![DiscoveryChange::Added("COM4")]
+    );
+    // Reported only once
+    assert!(debouncer.observe(vec!["COM3", "COM4"], start + ms(1700)).is_empty());
+    
+    assert!(debouncer.observe(vec!["COM4"], start + ms(2000)).is_empty());
+    assert_eq!(
+        debouncer.observe(vec!["COM4"], start + ms(3000)),
+        vec![DiscoveryChange::Removed("COM3")]
+    );
+    assert!(!debouncer.is_stable(&"COM3"));
+}
+
+#[test]
+fn test_zero_window_reports_immediately() {
+    let start = Instant::now();
+    let mut debouncer = DiscoveryDebouncer::new(Duration::ZERO);
+    debouncer.observe(Vec::<&str>::new(), start);
+    
+    assert_eq!(debouncer.observe(vec!["COM5"], start), vec![DiscoveryChange::Added("COM5")]);
+    assert_eq!(debouncer.observe(Vec::<&str>::new(), start), vec![DiscoveryChange::Removed("COM5")]);
+}
@@ -8,7 +8,7 @@ async fn test_not_connected_errors() {
     let config = TransportConfig::default();
     let mock_config = MockConfig::default();
     
-    let mut transport = MockTransport::new("test".into(), config, mock_config);
+    let transport = MockTransport::new("test".into(), config, mock_config);
     
     // All operations should fail when not connected
     assert!(matches!(
@@ -35,7 +35,7 @@ async fn test_send_failure_handling() {
         ..Default::default()
     };
     
-    let mut transport = MockTransport::new("test".into(), config, mock_config);
+    let transport = MockTransport::new("test".into(), config, mock_config);
     transport.connect().await.unwrap();
     
     // First 3 sends should fail
@@ -64,7 +64,7 @@ async fn test_receive_timeout() {
         ..Default::default()
     };
     
-    let mut transport = MockTransport::new("test".into(), config, mock_config);
+    let transport = MockTransport::new("test".into(), config, mock_config);
     transport.connect().await.unwrap();
     
     // First 2 receives should timeout
@@ -83,7 +83,7 @@ async fn test_partial_data_handling() {
     let config = TransportConfig::default();
     let mock_config = MockConfig::default();
     
-    let mut transport = MockTransport::new("test".into(), config, mock_config);
+    let transport = MockTransport::new("test".into(), config, mock_config);
     transport.connect().await.unwrap();
     
     // Send partial data
@@ -105,7 +105,7 @@ async fn test_connection_lost_during_operation() {
         ..Default::default()
     };
     
-    let mut transport = MockTransport::new("test".into(), config, mock_config);
+    let transport = MockTransport::new("test".into(), config, mock_config);
     transport.connect().await.unwrap();
     
     // First operation succeeds
@@ -125,7 +125,7 @@ async fn test_error_message_propagation() {
         ..Default::default()
     };
     
-    let mut transport = MockTransport::new("test".into(), config, mock_config);
+    let transport = MockTransport::new("test".into(), config, mock_config);
     
     // Capture error message
     let error = transport.connect().await.unwrap_err();
@@ -145,7 +145,7 @@ async fn test_transact_error_handling() {
         ..Default::default()
     };
     
-    let mut transport = MockTransport::new("test".into(), config, mock_config);
+    let transport = MockTransport::new("test".into(), config, mock_config);
     transport.connect().await.unwrap();
     
     // Transact should fail on send error
@@ -165,7 +165,7 @@ async fn test_reset_clears_error_state() {
         ..Default::default()
     };
     
-    let mut transport = MockTransport::new("test".into(), config, mock_config.clone());
+    let transport = MockTransport::new("test".into(), config, mock_config.clone());
     transport.connect().await.unwrap();
     
     // First send fails
@@ -194,7 +194,7 @@ async fn test_cleanup_resources_on_error() {
         ..Default::default()
     };
     
-    let mut transport = MockTransport::new("test".into(), config, mock_config);
+    let transport = MockTransport::new("test".into(), config, mock_config);
     transport.connect().await.unwrap();
     
     // Operation that triggers disconnect
@@ -215,7 +215,7 @@ async fn test_error_recovery_sequence() {
         ..Default::default()
     };
     
-    let mut transport = MockTransport::new("test".into(), config, mock_config);
+    let transport = MockTransport::new("test".into(), config, mock_config);
     transport.connect().await.unwrap();
     
     // Test recovery sequence
@@ -258,7 +258,7 @@ async fn test_concurrent_error_handling() {
     for i in 0..10 {
         let transport = transport.clone();
         let handle = tokio::spawn(async move {
-            let mut transport = transport.lock().await;
+            let transport = transport.lock().await;
             let result = transport.send(format!("data{}", i).as_bytes()).await;
             result.is_ok()
         });
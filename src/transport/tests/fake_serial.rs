@@ -0,0 +1,254 @@
+/// Fake serial port for exercising the serial read/write paths without hardware
+/// Reads are served from a scripted queue so tests can inject data and errors
+use std::collections::VecDeque;
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use serialport::{ClearBuffer, DataBits, FlowControl, Parity, SerialPort, StopBits};
+use crate::transport::serial::ControlLines;
+
+/// Scripted outcome for a single read call
+#[derive(Debug)]
+pub enum FakeRead {
+    /// Return these bytes
+    Data(Vec<u8>),
+    /// Return these bytes once the delay has passed; reads with a shorter
+    /// timeout time out and wait out part of the delay
+    Delayed(Duration, Vec<u8>),
+    /// Fail with an I/O error of this kind and message
+    Error(io::ErrorKind, String),
+}
+
+/// State shared between the fake port and the test that drives it
+#[derive(Debug, Default)]
+pub struct FakeSerialState {
+    pub reads: VecDeque<FakeRead>,
+    pub written: Vec<u8>,
+    pub timeout: Duration,
+    pub control_lines: ControlLines,
+    /// Read calls made on the port, including ones that timed out
+    pub read_calls: usize,
+    /// Reads queued once this many bytes have been written, in order
+    pub on_write: Vec<(usize, FakeRead)>,
+}
+
+/// Handle used by tests to script a `FakeSerialPort` after it has been boxed
+#[derive(Debug, Clone, Default)]
+pub struct FakeSerialHandle {
+    state: Arc<Mutex<FakeSerialState>>,
+}
+
+impl FakeSerialHandle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Create a boxed port backed by this handle's state
+    pub fn port(&self) -> Box<dyn SerialPort> {
+        Box::new(FakeSerialPort {
+            state: self.state.clone(),
+        })
+    }
+    
+    /// Queue bytes for the next read
+    pub fn push_data(&self, data: &[u8]) {
+        self.state.lock().unwrap().reads.push_back(FakeRead::Data(data.to_vec()));
+    }
+    
+    /// Queue bytes that arrive `delay` after the previous read
+    pub fn push_data_after(&self, delay: Duration, data: &[u8]) {
+        self.state.lock().unwrap().reads.push_back(FakeRead::Delayed(delay, data.to_vec()));
+    }
+    
+    /// Queue a read once `written` bytes in total have been written to the port
+    /// (e.g. a device answering, or sending XOFF, partway through a long write)
+    pub fn push_on_write(&self, written: usize, read: FakeRead) {
+        self.state.lock().unwrap().on_write.push((written, read));
+    }
+    
+    /// Queue an I/O error for the next read
+    pub fn push_error(&self, kind: io::ErrorKind, message: &str) {
+        self.state.lock().unwrap().reads.push_back(FakeRead::Error(kind, message.to_string()));
+    }
+    
+    /// Set the modem status lines reported by the port
+    pub fn set_control_lines(&self, lines: ControlLines) {
+        self.state.lock().unwrap().control_lines = lines;
+    }
+    
+    /// All bytes written to the port so far
+    pub fn written(&self) -> Vec<u8> {
+        self.state.lock().unwrap().written.clone()
+    }
+    
+    /// Number of read calls made so far (a proxy for polling activity)
+    pub fn read_calls(&self) -> usize {
+        self.state.lock().unwrap().read_calls
+    }
+}
+
+/// Fake serial port; an empty read queue blocks for the port timeout, like real hardware
+pub struct FakeSerialPort {
+    state: Arc<Mutex<FakeSerialState>>,
+}
+
+impl io::Read for FakeSerialPort {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let (next, timeout) = {
+            let mut state = self.state.lock().unwrap();
+            state.read_calls += 1;
+            (state.reads.pop_front(), state.timeout)
+        };
+        match next {
+            Some(FakeRead::Data(data)) => {
+                let n = data.len().min(buf.len());
+                buf[..n].copy_from_slice(&data[..n]);
+                Ok(n)
+            }
+            Some(FakeRead::Delayed(delay, data)) if delay <= timeout => {
+                std::thread::sleep(delay);
+                let n = data.len().min(buf.len());
+                buf[..n].copy_from_slice(&data[..n]);
+                Ok(n)
+            }
+            Some(FakeRead::Delayed(delay, data)) => {
+                std::thread::sleep(timeout);
+                self.state.lock().unwrap().reads.push_front(FakeRead::Delayed(delay - timeout, data));
+                Err(io::Error::new(io::ErrorKind::TimedOut, "Operation timed out"))
+            }
+            Some(FakeRead::Error(kind, message)) => Err(io::Error::new(kind, message)),
+            None => {
+                std::thread::sleep(timeout);
+                Err(io::Error::new(io::ErrorKind::TimedOut, "Operation timed out"))
+            }
+        }
+    }
+}
+
+impl io::Write for FakeSerialPort {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut state = self.state.lock().unwrap();
+        state.written.extend_from_slice(buf);
+        
+        let written = state.written.len();
+        let (due, waiting) = std::mem::take(&mut state.on_write)
+            .into_iter()
+            .partition::<Vec<_>, _>(|(after, _)| *after <= written);
+        state.on_write = waiting;
+        state.reads.extend(due.into_iter().map(|(_, read)| read));
+        Ok(buf.len())
+    }
+    
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl SerialPort for FakeSerialPort {
+    fn name(&self) -> Option<String> {
+        Some("FAKE0".to_string())
+    }
+    
+    fn baud_rate(&self) -> serialport::Result<u32> {
+        Ok(115200)
+    }
+    
+    fn data_bits(&self) -> serialport::Result<DataBits> {
+        Ok(DataBits::Eight)
+    }
+    
+    fn flow_control(&self) -> serialport::Result<FlowControl> {
+        Ok(FlowControl::None)
+    }
+    
+    fn parity(&self) -> serialport::Result<Parity> {
+        Ok(Parity::None)
+    }
+    
+    fn stop_bits(&self) -> serialport::Result<StopBits> {
+        Ok(StopBits::One)
+    }
+    
+    fn timeout(&self) -> Duration {
+        self.state.lock().unwrap().timeout
+    }
+    
+    fn set_baud_rate(&mut self, _baud_rate: u32) -> serialport::Result<()> {
+        Ok(())
+    }
+    
+    fn set_data_bits(&mut self, _data_bits: DataBits) -> serialport::Result<()> {
+        Ok(())
+    }
+    
+    fn set_flow_control(&mut self, _flow_control: FlowControl) -> serialport::Result<()> {
+        Ok(())
+    }
+    
+    fn set_parity(&mut self, _parity: Parity) -> serialport::Result<()> {
+        Ok(())
+    }
+    
+    fn set_stop_bits(&mut self, _stop_bits: StopBits) -> serialport::Result<()> {
+        Ok(())
+    }
+    
+    fn set_timeout(&mut self, timeout: Duration) -> serialport::Result<()> {
+        self.state.lock().unwrap().timeout = timeout;
+        Ok(())
+    }
+    
+    fn write_request_to_send(&mut self, _level: bool) -> serialport::Result<()> {
+        Ok(())
+    }
+    
+    fn write_data_terminal_ready(&mut self, _level: bool) -> serialport::Result<()> {
+        Ok(())
+    }
+    
+    fn read_clear_to_send(&mut self) -> serialport::Result<bool> {
+        Ok(self.state.lock().unwrap().control_lines.cts)
+    }
+    
+    fn read_data_set_ready(&mut self) -> serialport::Result<bool> {
+        Ok(self.state.lock().unwrap().control_lines.dsr)
+    }
+    
+    fn read_ring_indicator(&mut self) -> serialport::Result<bool> {
+        Ok(self.state.lock().unwrap().control_lines.ri)
+    }
+    
+    fn read_carrier_detect(&mut self) -> serialport::Result<bool> {
+        Ok(self.state.lock().unwrap().control_lines.cd)
+    }
+    
+    fn bytes_to_read(&self) -> serialport::Result<u32> {
+        let state = self.state.lock().unwrap();
+        Ok(state.reads.iter().map(|r| match r {
+            FakeRead::Data(data) => data.len() as u32,
+            FakeRead::Delayed(..) | FakeRead::Error(..) => 0,
+        }).sum())
+    }
+    
+    fn bytes_to_write(&self) -> serialport::Result<u32> {
+        Ok(0)
+    }
+    
+    fn clear(&self, _buffer_to_clear: ClearBuffer) -> serialport::Result<()> {
+        Ok(())
+    }
+    
+    fn try_clone(&self) -> serialport::Result<Box<dyn SerialPort>> {
+        Ok(Box::new(FakeSerialPort {
+            state: self.state.clone(),
+        }))
+    }
+    
+    fn set_break(&self) -> serialport::Result<()> {
+        Ok(())
+    }
+    
+    fn clear_break(&self) -> serialport::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,68 @@
+/// File transfer tests over an echoing loopback
+use std::io::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tempfile::NamedTempFile;
+use crate::transport::mock::MockTransport;
+use crate::transport::{send_file, TransportError};
+
+/// Mock with TX wired to RX, like a loopback plug, echoing in small pieces as a
+/// serial link would; the echo can be corrupted at one offset to simulate a bad link
+fn loopback(corrupt_at: Option<usize>) -> MockTransport {
+    let offset = AtomicUsize::new(0);
+    MockTransport::scripted(move |data| {
+        let start = offset.fetch_add(data.len(), Ordering::Relaxed);
+        let echoed: Vec<u8> = data.iter().enumerate()
+            .map(|(i, &byte)| if corrupt_at == Some(start + i) { !byte } else { byte })
+            .collect();
+        echoed.chunks(7).map(<[u8]>::to_vec).collect()
+    })
+}
+
+/// Every byte the transfer wrote
+async fn received(transport: &MockTransport) -> Vec<u8> {
+    transport.get_sent_history().await.concat()
+}
+
+fn temp_file(len: usize) -> (NamedTempFile, Vec<u8>) {
+    let contents: Vec<u8> = (0..len).map(|i| (i * 31 % 251) as u8).collect();
+    let mut file = NamedTempFile::new().unwrap();
+    file.write_all(&contents).unwrap();
+    file.flush().unwrap();
+    (file, contents)
+}
+
+#[tokio::test]
+async fn test_send_file_transfers_all_bytes() {
+    let (file, contents) = temp_file(1000);
+    let transport = loopback(None);
+    let mut progress = Vec::new();
+    
+    let sent = send_file(&transport, file.path(), 64, true, |p| progress.push(p)).await.unwrap();
+    assert_eq!(sent, 1000);
+    assert_eq!(received(&transport).await, contents);
+    
+    // 15 full chunks and a 40 byte tail
+    assert_eq!(progress.len(), 16);
+    assert_eq!(progress[0].bytes_sent, 64);
+    assert_eq!(progress.last().unwrap().bytes_sent, 1000);
+    assert!(progress.iter().all(|p| p.total_bytes == 1000));
+    assert_eq!(progress.last().unwrap().fraction(), 1.0);
+}
+
+#[tokio::test]
+async fn test_verification_detects_mismatch() {
+    let (file, _) = temp_file(500);
+    let transport = loopback(Some(300));
+    let mut progress = Vec::new();
+    
+    let result = send_file(&transport, file.path(), 128, true, |p| progress.push(p)).await;
+    assert!(matches!(result, Err(TransportError::InvalidData(ref msg)) if msg.contains("byte 300")));
+    
+    // The transfer stops at the bad chunk
+    assert_eq!(progress.len(), 2);
+    assert_eq!(received(&transport).await.len(), 384);
+    
+    // Without verification the corruption goes unnoticed
+    let transport = loopback(Some(300));
+    assert_eq!(send_file(&transport, file.path(), 128, false, |_| {}).await.unwrap(), 500);
+}
@@ -76,7 +76,7 @@ async fn test_minimum_latency_enforcement() {
         ..Default::default()
     };
     
-    let mut transport = MockTransport::new("test".into(), config, mock_config);
+    let transport = MockTransport::new("test".into(), config, mock_config);
     transport.connect().await.unwrap();
     
     // Rapid operations should be delayed
@@ -139,7 +139,7 @@ async fn test_no_latency_enforcement() {
         ..Default::default()
     };
     
-    let mut transport = MockTransport::new("test".into(), config, mock_config);
+    let transport = MockTransport::new("test".into(), config, mock_config);
     transport.connect().await.unwrap();
     
     // Operations should be fast
@@ -166,7 +166,7 @@ async fn test_high_throughput_latency() {
         ..Default::default()
     };
     
-    let mut transport = MockTransport::new("test".into(), config, mock_config);
+    let transport = MockTransport::new("test".into(), config, mock_config);
     transport.connect().await.unwrap();
     
     let mut monitor = LatencyMonitor::new(100);
@@ -203,7 +203,7 @@ async fn test_transact_latency() {
         ..Default::default()
     };
     
-    let mut transport = MockTransport::new("test".into(), config, mock_config);
+    let transport = MockTransport::new("test".into(), config, mock_config);
     transport.connect().await.unwrap();
     
     // Transact involves send + receive
@@ -298,7 +298,7 @@ async fn test_concurrent_latency_enforcement() {
             // Stagger starts slightly
             tokio::time::sleep(Duration::from_millis(i * 5)).await;
             
-            let mut transport = transport.lock().await;
+            let transport = transport.lock().await;
             let op_start = Instant::now();
             transport.send(format!("data{}", i).as_bytes()).await.unwrap();
             op_start.elapsed()
@@ -9,6 +9,27 @@ mod error_handling;
 #[cfg(test)]
 mod latency;
 
+#[cfg(test)]
+mod status_summary;
+
+#[cfg(test)]
+mod resilient_transact;
+
+#[cfg(test)]
+mod stats_delta;
+
+#[cfg(test)]
+mod file_transfer;
+
+#[cfg(test)]
+mod connection_budget;
+
+#[cfg(test)]
+mod discovery_debounce;
+
+#[cfg(test)]
+pub mod fake_serial;
+
 // Re-export test utilities for use in integration tests
 #[cfg(test)]
 pub use crate::transport::mock::{MockTransport, MockConfig};
\ No newline at end of file
@@ -2,7 +2,10 @@
 use std::sync::{Arc, atomic::{AtomicU32, Ordering}};
 use std::time::Duration;
 use tokio::time::sleep;
-use crate::transport::{Transport, TransportConfig, TransportError};
+use crate::transport::{
+    Transport, TransportBase, TransportConfig, TransportError, TransportResult, TransportType,
+    ReconnectOutcome,
+};
 use crate::transport::mock::{MockTransport, MockConfig};
 use crate::transport::backoff::ExponentialBackoff;
 
@@ -19,7 +22,7 @@ async fn test_basic_reconnection() {
         ..Default::default()
     };
     
-    let mut transport = MockTransport::new("test".into(), config, mock_config);
+    let transport = MockTransport::new("test".into(), config, mock_config);
     
     // First attempt fails
     assert!(transport.connect().await.is_err());
@@ -71,7 +74,7 @@ async fn test_reconnection_with_backoff() {
         ..Default::default()
     };
     
-    let mut transport = MockTransport::new("test".into(), config.clone(), mock_config);
+    let transport = MockTransport::new("test".into(), config.clone(), mock_config);
     
     // Simulate reconnection loop with backoff
     let mut backoff = ExponentialBackoff::new()
@@ -115,7 +118,7 @@ async fn test_max_reconnect_attempts() {
         ..Default::default()
     };
     
-    let mut transport = MockTransport::new("test".into(), config.clone(), mock_config);
+    let transport = MockTransport::new("test".into(), config.clone(), mock_config);
     
     let mut attempts = 0;
     let max = config.max_reconnect_attempts;
@@ -141,7 +144,7 @@ async fn test_reconnection_after_disconnect() {
         ..Default::default()
     };
     
-    let mut transport = MockTransport::new("test".into(), config, mock_config);
+    let transport = MockTransport::new("test".into(), config, mock_config);
     
     // Connect successfully
     transport.connect().await.unwrap();
@@ -190,7 +193,7 @@ async fn test_concurrent_reconnection_attempts() {
         let handle = tokio::spawn(async move {
             sleep(Duration::from_millis(i * 10)).await;
             
-            let mut transport = transport.lock().await;
+            let transport = transport.lock().await;
             match transport.connect().await {
                 Ok(_) => {
                     success_count.fetch_add(1, Ordering::Relaxed);
@@ -218,7 +221,7 @@ async fn test_reconnection_clears_buffers() {
     let config = TransportConfig::default();
     let mock_config = MockConfig::default();
     
-    let mut transport = MockTransport::new("test".into(), config, mock_config);
+    let transport = MockTransport::new("test".into(), config, mock_config);
     
     // Connect and send data
     transport.connect().await.unwrap();
@@ -245,7 +248,7 @@ async fn test_reconnection_preserves_stats() {
     let config = TransportConfig::default();
     let mock_config = MockConfig::default();
     
-    let mut transport = MockTransport::new("test".into(), config, mock_config);
+    let transport = MockTransport::new("test".into(), config, mock_config);
     
     // First connection
     transport.connect().await.unwrap();
@@ -285,4 +288,71 @@ async fn test_reconnection_with_jitter() {
     // Verify delays are not all identical (jitter working)
     let unique_delays: std::collections::HashSet<_> = delays.iter().collect();
     assert!(unique_delays.len() > 1);
-}
\ No newline at end of file
+}
+
+type ConnectFuture = std::pin::Pin<Box<dyn std::future::Future<Output = TransportResult<()>> + Send>>;
+
+#[tokio::test]
+async fn test_reconnect_callbacks_receive_each_outcome() {
+    let config = TransportConfig {
+        max_reconnect_attempts: 3,
+        reconnect_delay_ms: 10,
+        ..Default::default()
+    };
+    let base = TransportBase::new("test".into(), TransportType::Serial, config);
+    
+    let outcomes = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let calls = Arc::new(AtomicU32::new(0));
+    {
+        let outcomes = outcomes.clone();
+        base.on_reconnect(Arc::new(move |outcome: &ReconnectOutcome| outcomes.lock().unwrap().push(outcome.clone())));
+        let calls = calls.clone();
+        base.on_reconnect(Arc::new(move |_: &ReconnectOutcome| { calls.fetch_add(1, Ordering::Relaxed); }));
+    }
+    
+    // Fail once, then succeed
+    let attempts = Arc::new(AtomicU32::new(0));
+    let attempts_clone = attempts.clone();
+    base.trigger_reconnection(move || -> ConnectFuture {
+        let attempt = attempts_clone.fetch_add(1, Ordering::Relaxed);
+        Box::pin(async move {
+            if attempt == 0 {
+                Err(TransportError::ConnectionFailed("port busy".into()))
+            } else {
+                Ok(())
+            }
+        })
+    }).await.unwrap();
+    
+    sleep(Duration::from_millis(500)).await;
+    
+    assert_eq!(
+        *outcomes.lock().unwrap(),
+        vec![ReconnectOutcome::Failed("Connection failed: port busy".into()), ReconnectOutcome::Succeeded]
+    );
+    assert_eq!(calls.load(Ordering::Relaxed), 2);
+}
+
+#[tokio::test]
+async fn test_failed_reconnect_notifies_failure() {
+    let config = TransportConfig {
+        max_reconnect_attempts: 1,
+        reconnect_delay_ms: 10,
+        ..Default::default()
+    };
+    let base = TransportBase::new("test".into(), TransportType::Serial, config);
+    
+    let outcomes = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let outcomes_clone = outcomes.clone();
+    base.on_reconnect(Arc::new(move |outcome: &ReconnectOutcome| outcomes_clone.lock().unwrap().push(outcome.clone())));
+    
+    base.trigger_reconnection(|| -> ConnectFuture {
+        Box::pin(async { Err(TransportError::ConnectionFailed("no device".into())) })
+    }).await.unwrap();
+    
+    sleep(Duration::from_millis(300)).await;
+    
+    let outcomes = outcomes.lock().unwrap();
+    assert!(!outcomes.is_empty());
+    assert!(outcomes.iter().all(|o| !o.is_success()));
+}
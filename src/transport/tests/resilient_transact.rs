@@ -0,0 +1,52 @@
+/// Retry-aware transact tests
+use std::time::Duration;
+use crate::transport::mock::{MockConfig, MockTransport};
+use crate::transport::{Transport, TransportConfig, TransportError};
+
+/// Connected mock whose link drops on the first `drops` sends and comes back on `connect`
+async fn glitchy(drops: u32) -> MockTransport {
+    let config = TransportConfig {
+        reconnect_delay_ms: 10,
+        ..Default::default()
+    };
+    let mock_config = MockConfig {
+        send_failures: drops,
+        disconnect_on_failure: true,
+        latency_ms: 0,
+        enforce_latency: false,
+        ..Default::default()
+    };
+    let transport = MockTransport::new("glitchy".into(), config, mock_config)
+        .with_responder(|_| vec![b"OK\r\n".to_vec()]);
+    transport.connect().await.unwrap();
+    transport.reset_counters();
+    transport
+}
+
+#[tokio::test]
+async fn test_retry_after_reconnect_returns_response() {
+    let transport = glitchy(1).await;
+    
+    let response = transport.transact_resilient(b"READ A0\n", Duration::from_millis(100), 3).await.unwrap();
+    assert_eq!(response, b"OK\r\n".to_vec());
+    assert_eq!(transport.send_count(), 2);
+    assert_eq!(transport.connect_count(), 1);
+}
+
+#[tokio::test]
+async fn test_gives_up_after_max_retries() {
+    let transport = glitchy(5).await;
+    
+    let result = transport.transact_resilient(b"READ A0\n", Duration::from_millis(100), 2).await;
+    assert!(matches!(result, Err(TransportError::IoError(_))));
+    assert_eq!(transport.send_count(), 3);
+}
+
+#[tokio::test]
+async fn test_zero_retries_behaves_like_transact() {
+    let transport = glitchy(1).await;
+    
+    assert!(transport.transact_resilient(b"PULSE 7\n", Duration::from_millis(100), 0).await.is_err());
+    assert_eq!(transport.send_count(), 1);
+    assert_eq!(transport.connect_count(), 0);
+}
@@ -0,0 +1,61 @@
+/// Rate computation between stats snapshots
+use std::time::Duration;
+use crate::transport::TransportStats;
+
+fn snapshot(bytes_sent: u64, bytes_received: u64, success: u64, failed: u64) -> TransportStats {
+    TransportStats {
+        bytes_sent,
+        bytes_received,
+        transactions_success: success,
+        transactions_failed: failed,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_delta_rates() {
+    let earlier = snapshot(1_000, 500, 90, 10);
+    let later = TransportStats {
+        reconnect_count: 1,
+        line_errors: 2,
+        ..snapshot(3_000, 1_500, 190, 10)
+    };
+    
+    let delta = later.delta(&earlier, Duration::from_secs(2));
+    assert_eq!(delta.bytes_sent_per_sec, 1_000.0);
+    assert_eq!(delta.bytes_received_per_sec, 500.0);
+    assert_eq!(delta.transactions_per_sec, 50.0);
+    assert_eq!(delta.error_rate, 0.0);
+    assert_eq!(delta.reconnects, 1);
+    assert_eq!(delta.line_errors, 2);
+    
+    // Cumulative failure fraction fell from 10% to 5%
+    assert!((delta.error_rate_change + 0.05).abs() < 1e-12);
+}
+
+#[test]
+fn test_delta_error_rate_for_interval() {
+    let earlier = snapshot(0, 0, 10, 0);
+    let later = snapshot(0, 0, 13, 1);
+    
+    let delta = later.delta(&earlier, Duration::from_millis(500));
+    assert_eq!(delta.transactions_per_sec, 8.0);
+    assert_eq!(delta.error_rate, 0.25);
+}
+
+#[test]
+fn test_zero_elapsed_and_reset_counters() {
+    let earlier = snapshot(1_000, 1_000, 5, 5);
+    let later = snapshot(2_000, 1_000, 6, 5);
+    
+    let delta = later.delta(&earlier, Duration::ZERO);
+    assert_eq!(delta.bytes_sent_per_sec, 0.0);
+    assert_eq!(delta.transactions_per_sec, 0.0);
+    assert!(delta.bytes_sent_per_sec.is_finite());
+    
+    // Stats were reset between snapshots
+    let delta = TransportStats::default().delta(&earlier, Duration::from_secs(1));
+    assert_eq!(delta.bytes_sent_per_sec, 0.0);
+    assert_eq!(delta.error_rate, 0.0);
+    assert_eq!(delta.error_rate_change, -0.5);
+}
@@ -0,0 +1,36 @@
+/// Sidebar status line tests
+use crate::transport::mock::MockTransport;
+use crate::transport::{Transport, TransportStats};
+
+#[tokio::test]
+async fn test_connected_summary_shows_latency_and_reconnects() {
+    let transport = MockTransport::scripted(|_| Vec::new());
+    transport.set_stats(TransportStats {
+        avg_latency_ms: 12.4,
+        ..Default::default()
+    }).await;
+    assert_eq!(transport.status_summary(), "Connected, 12ms, 0 reconnects");
+    
+    transport.set_stats(TransportStats {
+        avg_latency_ms: 3.0,
+        reconnect_count: 1,
+        ..Default::default()
+    }).await;
+    assert_eq!(transport.status_summary(), "Connected, 3ms, 1 reconnect");
+}
+
+#[tokio::test]
+async fn test_disconnected_summary_shows_last_error() {
+    let transport = MockTransport::scripted(|_| Vec::new());
+    transport.disconnect().await.unwrap();
+    transport.set_stats(TransportStats {
+        avg_latency_ms: 12.0,
+        reconnect_count: 2,
+        last_error: Some("Connection lost: device unplugged".into()),
+        ..Default::default()
+    }).await;
+    assert_eq!(transport.status_summary(), "Disconnected: Connection lost: device unplugged, 2 reconnects");
+    
+    transport.set_stats(TransportStats::default()).await;
+    assert_eq!(transport.status_summary(), "Disconnected, 0 reconnects");
+}
@@ -9,9 +9,10 @@ use tokio::task::JoinHandle;
 
 use crate::transport::{
     Transport, TransportBase, TransportConfig, TransportError, TransportResult,
-    TransportStats, TransportType, ConnectionState,
+    TransportStats, TransportType, ConnectionState, ReconnectCallback, WireDirection, WireTrace,
 };
 use crate::transport::common::UdpSettings;
+use crate::transport::backoff::ExponentialBackoff;
 
 /// UDP transport implementation
 pub struct UdpTransport {
@@ -112,9 +113,10 @@ impl UdpTransport {
     /// Try to connect with exponential backoff
     async fn connect_with_backoff(&mut self) -> TransportResult<()> {
         let max_attempts = self.base.config.max_reconnect_attempts;
-        let base_delay = self.base.config.reconnect_delay_ms;
+        let mut backoff = ExponentialBackoff::from_config(max_attempts, self.base.config.reconnect_delay_ms)
+            .with_hints(&self.base.config.reconnect_hints);
         
-        while self.reconnect_attempts < max_attempts {
+        loop {
             match self.try_connect().await {
                 Ok(()) => {
                     self.reconnect_attempts = 0;
@@ -123,30 +125,27 @@ impl UdpTransport {
                 Err(e) => {
                     self.reconnect_attempts += 1;
                     
-                    if self.reconnect_attempts >= max_attempts {
+                    if self.base.config.reconnect_attempts_exhausted(self.reconnect_attempts) {
                         return Err(e);
                     }
                     
-                    // Exponential backoff with jitter
-                    use rand::Rng;
-                    let delay = base_delay * 2u32.pow(self.reconnect_attempts - 1);
-                    let jitter = rand::thread_rng().gen_range(0..delay / 4);
-                    let total_delay = delay + jitter;
+                    // Exponential backoff with jitter, growing from the device's hinted first delay
+                    let Some(delay) = backoff.next_delay() else {
+                        return Err(e);
+                    };
                     
                     tracing::warn!(
-                        "UDP connection failed (attempt {}/{}), retrying in {}ms: {}",
+                        "UDP connection failed (attempt {}/{}), retrying in {:?}: {}",
                         self.reconnect_attempts,
                         max_attempts,
-                        total_delay,
+                        delay,
                         e
                     );
                     
-                    tokio::time::sleep(Duration::from_millis(total_delay as u64)).await;
+                    tokio::time::sleep(delay).await;
                 }
             }
         }
-        
-        Err(TransportError::ConnectionFailed("Max reconnection attempts exceeded".into()))
     }
     
     /// Attempt a single connection
@@ -225,8 +224,9 @@ impl Transport for UdpTransport {
     }
     
     async fn connect(&self) -> TransportResult<()> {
+        // Connecting is idempotent
         if self.is_connected() {
-            return Err(TransportError::AlreadyConnected);
+            return Ok(());
         }
         
         self.base.set_state(ConnectionState::Connecting).await;
@@ -293,6 +293,7 @@ impl Transport for UdpTransport {
                 stats.bytes_sent += bytes_sent as u64;
                 stats.transactions_success += 1;
             }).await;
+            self.base.wire_trace.record(&self.base.name, WireDirection::Tx, data).await;
             
             // Enforce minimum latency requirement (100ms for UDP)
             self.base.enforce_latency(start).await?;
@@ -343,6 +344,7 @@ impl Transport for UdpTransport {
             self.base.update_stats(|stats| {
                 stats.bytes_received += n as u64;
             }).await;
+            self.base.wire_trace.record(&self.base.name, WireDirection::Rx, &buffer).await;
             
             // Enforce minimum latency
             self.base.enforce_latency(start).await?;
@@ -366,7 +368,7 @@ impl Transport for UdpTransport {
     }
     
     fn stats(&self) -> TransportStats {
-        TransportStats::default()
+        self.base.stats_snapshot()
     }
     
     async fn reset(&self) -> TransportResult<()> {
@@ -416,6 +418,14 @@ impl Transport for UdpTransport {
         tracing::debug!("UDP transport resources cleaned up");
         Ok(())
     }
+    
+    fn on_reconnect(&self, callback: ReconnectCallback) {
+        self.base.on_reconnect(callback);
+    }
+    
+    fn wire_trace(&self) -> Option<&WireTrace> {
+        Some(&self.base.wire_trace)
+    }
 }
 
 /// Information about discovered UDP service
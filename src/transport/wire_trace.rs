@@ -0,0 +1,75 @@
+//! Opt-in raw byte tracing for field debugging
+//!
+//! Each transport carries a `WireTrace` whose `log_raw_io` flag can be flipped
+//! at runtime. While it is on, every chunk sent or received is logged as hex
+//! to `tracing` and, if a sink is attached, to the device I/O log buffer.
+//! While it is off the only cost per operation is one atomic load.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use crate::logging::{LogLevel, LoggingSystem};
+
+/// Direction of a traced chunk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WireDirection {
+    Tx,
+    Rx,
+}
+
+/// Runtime-toggleable raw I/O trace shared by clones
+#[derive(Clone, Default)]
+pub struct WireTrace {
+    log_raw_io: Arc<AtomicBool>,
+    sink: Arc<RwLock<Option<Arc<LoggingSystem>>>>,
+}
+
+impl WireTrace {
+    /// Turn raw byte logging on or off
+    pub fn set_enabled(&self, enabled: bool) {
+        self.log_raw_io.store(enabled, Ordering::Relaxed);
+    }
+    
+    /// Whether raw byte logging is on
+    pub fn is_enabled(&self) -> bool {
+        self.log_raw_io.load(Ordering::Relaxed)
+    }
+    
+    /// Also write traced chunks to `logging`'s device I/O buffer
+    pub fn set_sink(&self, logging: Arc<LoggingSystem>) {
+        *self.sink.write().unwrap() = Some(logging);
+    }
+    
+    /// Log `data` if tracing is enabled
+    pub async fn record(&self, transport: &str, direction: WireDirection, data: &[u8]) {
+        if !self.is_enabled() || data.is_empty() {
+            return;
+        }
+        
+        let arrow = match direction {
+            WireDirection::Tx => "->",
+            WireDirection::Rx => "<-",
+        };
+        let message = format!("{} {} {} bytes: {}", transport, arrow, data.len(), hex(data));
+        tracing::debug!(target: "wire", "{}", message);
+        
+        let sink = self.sink.read().unwrap().clone();
+        if let Some(logging) = sink {
+            logging.log_device_io(LogLevel::Debug, message, Some(data.to_vec())).await;
+        }
+    }
+}
+
+impl std::fmt::Debug for WireTrace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WireTrace")
+            .field("log_raw_io", &self.is_enabled())
+            .finish()
+    }
+}
+
+fn hex(data: &[u8]) -> String {
+    data.iter()
+        .map(|b| format!("{:02X}", b))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
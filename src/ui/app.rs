@@ -1,17 +1,27 @@
 use egui::{Context, Ui, CentralPanel, SidePanel, TopBottomPanel, ScrollArea};
 use std::sync::Arc;
-use tokio::sync::{Mutex, RwLock, mpsc};
+use tokio::sync::mpsc;
 use serde_json::{json, Value};
-use crate::device::{DeviceManager, DeviceSession};
+use crate::device::{DeviceManager, DeviceSession, DeviceResult, SessionCommand, DeviceRegistry, KnownDevice, CommandFailureTracker, FailureAlert, CommandHistory};
+use crate::device::identify::{identify_shared, IdentifyPattern};
+use crate::device::calibration::LinearCalibration;
+use crate::device::clock_sync::ClockOffset;
+use crate::device::registry::default_registry_path;
 use crate::device::session::StreamData;
-use crate::transport::{TransportFactory, TransportConfig, TransportType};
+use crate::transport::{Transport, TransportFactory, TransportConfig, TransportType, WireTrace};
 use crate::ui::panels::{PerformancePanel, TelemetryPanel, LogPanel};
+use crate::ui::feature_gate::{FeatureGate, CAP_GPIO, CAP_PWM, CAP_ANALOG, CAP_SERVO};
 use crate::logging::{LogLevel, LogEntry};
-use crate::telemetry::{TelemetrySystem, TelemetryConfig, TelemetryChannel, TelemetrySample, SampleType, SampleValue, ChannelConfig};
+use crate::telemetry::{TelemetrySystem, TelemetryConfig, MemoryPressurePolicy, TelemetryChannel, TelemetrySample, SampleType, SampleValue, ChannelConfig};
+use crate::telemetry::ingest::{ingest_channel, IngestSender, IngestReceiver, OverflowPolicy, DEFAULT_INGEST_CAPACITY};
 use crate::performance::{PerformanceMonitor, MonitorConfig, PerformanceAlert};
 use crate::logging::LoggingSystem;
-use std::collections::{HashMap, VecDeque};
-use std::time::{SystemTime, UNIX_EPOCH, Instant};
+use crate::profile::config::{DeviceSettings, DEFAULT_COMMAND_TIMEOUT_MS, SerialPreset, COMMON_BAUD_RATES};
+use crate::transport::common::{SerialSettings, DataBits, Parity, StopBits};
+use crate::transport::serial::{ComPortFallback, ComPortRange, DiscoveryFilter};
+use crate::transport::discovery_debounce::{DiscoveryDebouncer, DiscoveryChange, DEFAULT_DISCOVERY_DEBOUNCE};
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::time::{SystemTime, UNIX_EPOCH, Instant, Duration};
 use serde::{Serialize, Deserialize};
 
 /// Device connection info
@@ -48,12 +58,36 @@ pub struct MultiControllerApp {
     /// Currently selected device ID
     selected_device: Option<String>,
     
-    /// Current active device session
-    current_session: Option<Arc<Mutex<Box<dyn DeviceSession>>>>,
+    /// Device whose configuration window is open
+    configuring_device: Option<String>,
+    
+    /// Serial settings chosen per device (device_id -> settings)
+    device_serial_settings: HashMap<String, SerialSettings>,
+    
+    /// LED blink used to locate each device on the bench (device_id -> pattern)
+    device_identify_patterns: HashMap<String, IdentifyPattern>,
+    
+    /// Named serial presets from app settings
+    serial_presets: Vec<SerialPreset>,
+    
+    /// Which serial ports the discovery task reports (shared with the task)
+    discovery_filter: Arc<parking_lot::RwLock<DiscoveryFilter>>,
+    
+    /// How long discovery must agree before a device is listed or dropped (shared with the task)
+    discovery_debounce: Arc<parking_lot::RwLock<Duration>>,
+    
+    /// COM range probed for ports Windows did not enumerate, if enabled (shared with the task)
+    com_fallback: Arc<parking_lot::RwLock<Option<Arc<ComPortFallback>>>>,
+    
+    /// Remembered devices, shown in the sidebar even while offline
+    device_registry: Arc<parking_lot::Mutex<DeviceRegistry>>,
     
     /// Current active tab
     active_tab: Tab,
     
+    /// Tabs and controls the connected device supports
+    feature_gate: FeatureGate,
+    
     /// UI state
     sidebar_width: f32,
     dark_mode: bool,
@@ -61,6 +95,13 @@ pub struct MultiControllerApp {
     /// Runtime handle for spawning tasks
     runtime: Arc<tokio::runtime::Runtime>,
     
+    /// Timeout applied to every dispatched device command
+    command_timeout: Duration,
+    
+    /// Consecutive command failures, and the alert shown once they pass the threshold
+    command_failures: CommandFailureTracker,
+    failure_alert: Option<FailureAlert>,
+    
     /// Channel for receiving device updates
     device_update_rx: mpsc::UnboundedReceiver<DeviceUpdateEvent>,
     device_update_tx: mpsc::UnboundedSender<DeviceUpdateEvent>,
@@ -69,9 +110,9 @@ pub struct MultiControllerApp {
     command_tx: mpsc::UnboundedSender<DeviceCommand>,
     command_rx: mpsc::UnboundedReceiver<DeviceCommand>,
     
-    /// Channel for receiving device responses
-    response_tx: mpsc::UnboundedSender<DeviceResponse>,
-    response_rx: mpsc::UnboundedReceiver<DeviceResponse>,
+    /// Bounded channel for receiving device responses (drops oldest when full)
+    response_tx: IngestSender<DeviceResponse>,
+    response_rx: IngestReceiver<DeviceResponse>,
     
     /// Telemetry system for managing data channels
     telemetry_system: Arc<TelemetrySystem>,
@@ -98,6 +139,8 @@ pub struct MultiControllerApp {
     current_script: String,
     selected_script: Option<String>,
     script_output: Vec<String>,
+    /// Commands sent this session, exportable to a script
+    command_history: CommandHistory,
     
     /// Profiles tab state
     profiles: HashMap<String, DeviceProfile>,
@@ -109,6 +152,13 @@ pub struct MultiControllerApp {
     /// Logging system
     logging_system: Arc<LoggingSystem>,
     
+    /// Raw byte tracing toggle, applied to every connected transport
+    wire_trace_enabled: bool,
+    wire_traces: Arc<parking_lot::Mutex<Vec<WireTrace>>>,
+    
+    /// Transport of each connected device, for the sidebar status line
+    device_transports: Arc<parking_lot::Mutex<HashMap<String, Arc<dyn Transport>>>>,
+    
     /// Startup time tracking
     startup_instant: Option<Instant>,
 }
@@ -120,6 +170,10 @@ enum DeviceUpdateEvent {
     DeviceConnected(String, String), // device_id, session_id
     DeviceDisconnected(String),
     DeviceRemoved(String),
+    /// Capabilities of the newly connected session (None = not reported)
+    CapabilitiesReported(Option<BTreeSet<String>>),
+    /// New clock offset of a session after a re-sync
+    ClockSynced { session_id: String, offset: ClockOffset },
 }
 
 /// Commands to send to devices
@@ -136,13 +190,43 @@ pub enum DeviceCommand {
     CustomCommand { endpoint: String, args: Vec<Value> },
 }
 
+impl DeviceCommand {
+    /// The session endpoint call for this command, if it maps to one
+    pub fn into_session_command(self) -> Option<SessionCommand> {
+        match self {
+            DeviceCommand::DigitalWrite { pin, value } => {
+                Some(SessionCommand::new("digitalWrite", vec![json!(pin), json!(value)]))
+            }
+            DeviceCommand::AnalogWrite { pin, value } => {
+                Some(SessionCommand::new("analogWrite", vec![json!(pin), json!(value)]))
+            }
+            DeviceCommand::DigitalRead { pin } => {
+                Some(SessionCommand::new("digitalRead", vec![json!(pin)]))
+            }
+            DeviceCommand::AnalogRead { pin } => {
+                Some(SessionCommand::new("analogRead", vec![json!(pin)]))
+            }
+            DeviceCommand::SetServo { index, position } => {
+                Some(SessionCommand::new("setServo", vec![json!(index), json!(position)]))
+            }
+            DeviceCommand::CustomCommand { endpoint, args } => {
+                Some(SessionCommand { endpoint, args })
+            }
+            _ => None,
+        }
+    }
+}
+
 /// Responses from devices
 #[derive(Debug, Clone)]
 pub enum DeviceResponse {
     DigitalValue { pin: u8, value: bool },
     AnalogValue { pin: u8, value: u16 },
     StreamData { stream: String, data: Value, timestamp: u64 },
-    CommandResult { success: bool, data: Option<Value> },
+    /// Outcome of a command sent to `device_id`
+    CommandResult { device_id: String, success: bool, data: Option<Value> },
+    /// A command sent to `device_id` failed or timed out
+    CommandFailed { device_id: String, message: String },
     Error { message: String },
     Log { level: LogLevel, message: String },
 }
@@ -170,14 +254,27 @@ impl MultiControllerApp {
         let runtime = Arc::new(tokio::runtime::Runtime::new().expect("Failed to create runtime"));
         let (tx, rx) = mpsc::unbounded_channel();
         let (cmd_tx, cmd_rx) = mpsc::unbounded_channel();
-        let (resp_tx, resp_rx) = mpsc::unbounded_channel();
+        let (resp_tx, resp_rx) = ingest_channel(DEFAULT_INGEST_CAPACITY, OverflowPolicy::DropOldest);
         
         // Start device discovery
+        let discovery_filter = Arc::new(parking_lot::RwLock::new(DiscoveryFilter::default()));
+        let discovery_debounce = Arc::new(parking_lot::RwLock::new(DEFAULT_DISCOVERY_DEBOUNCE));
+        let com_fallback = Arc::new(parking_lot::RwLock::new(None));
+        let device_registry = Arc::new(parking_lot::Mutex::new(
+            DeviceRegistry::load(default_registry_path()).unwrap_or_else(|e| {
+                tracing::warn!("Could not load device registry, starting empty: {}", e);
+                DeviceRegistry::new(default_registry_path())
+            })
+        ));
         let tx_clone = tx.clone();
+        let filter_clone = discovery_filter.clone();
+        let debounce_clone = discovery_debounce.clone();
+        let fallback_clone = com_fallback.clone();
+        let registry_clone = device_registry.clone();
         let rt = runtime.clone();
         std::thread::spawn(move || {
             rt.block_on(async {
-                Self::start_device_discovery(tx_clone).await;
+                Self::start_device_discovery(tx_clone, filter_clone, debounce_clone, fallback_clone, registry_clone).await;
             });
         });
         
@@ -198,6 +295,7 @@ impl MultiControllerApp {
             max_memory_bytes: 50 * 1024 * 1024,  // 50MB limit
             auto_memory_management: true,
             default_sample_rate: 30.0,  // 30 FPS for charts
+            memory_pressure_policy: MemoryPressurePolicy::DropOldestGlobally,
         };
         let telemetry_system = Arc::new(TelemetrySystem::with_config(telemetry_config));
         
@@ -209,6 +307,10 @@ impl MultiControllerApp {
                 sample_rate: 30.0,
                 name: "main_telemetry".to_string(),
                 sample_type: SampleType::Float32,
+                persist_path: None,
+                tags: Vec::new(),
+                group: None,
+                timestamp_source: Default::default(),
             })
         );
         
@@ -253,6 +355,9 @@ impl MultiControllerApp {
             perf_monitor_clone.start().await;
         });
         
+        // Re-sync device clocks as their offsets go stale
+        runtime.spawn(Self::run_clock_resync(device_manager.clone(), tx.clone()));
+        
         // Create performance panel with monitor
         let performance_panel = PerformancePanel::new(performance_monitor.clone());
         
@@ -261,11 +366,22 @@ impl MultiControllerApp {
             available_devices: Vec::new(),
             active_sessions: HashMap::new(),
             selected_device: None,
-            current_session: None,
+            configuring_device: None,
+            device_serial_settings: HashMap::new(),
+            device_identify_patterns: HashMap::new(),
+            serial_presets: SerialPreset::builtin(),
+            discovery_filter,
+            discovery_debounce,
+            com_fallback,
+            device_registry,
             active_tab: Tab::default(),
+            feature_gate: FeatureGate::default(),
             sidebar_width: 250.0,
             dark_mode: true,
             runtime,
+            command_timeout: Duration::from_millis(DEFAULT_COMMAND_TIMEOUT_MS as u64),
+            command_failures: CommandFailureTracker::default(),
+            failure_alert: None,
             device_update_rx: rx,
             device_update_tx: tx,
             command_tx: cmd_tx,
@@ -285,24 +401,129 @@ impl MultiControllerApp {
             current_script: String::new(),
             selected_script: None,
             script_output: Vec::new(),
+            command_history: CommandHistory::new(),
             profiles: HashMap::new(),
             current_profile_name: String::new(),
             performance_monitor,
             logging_system,
+            wire_trace_enabled: false,
+            wire_traces: Arc::new(parking_lot::Mutex::new(Vec::new())),
+            device_transports: Arc::new(parking_lot::Mutex::new(HashMap::new())),
             startup_instant: Some(Instant::now()),
         }
     }
     
+    /// Apply the device settings of the loaded profile
+    pub fn apply_device_settings(&mut self, settings: &DeviceSettings) {
+        self.set_com_port_fallback(settings.com_port_fallback);
+        self.set_failure_alert_threshold(settings.failure_alert_threshold);
+        self.set_command_timeout(Duration::from_millis(settings.command_timeout_ms as u64));
+        self.set_serial_presets(settings.serial_presets.clone());
+        self.set_discovery_debounce(Duration::from_millis(settings.discovery_debounce_ms as u64));
+        self.set_discovery_filter(settings.discovery_filter);
+        for config in &settings.device_configs {
+            self.set_on_connect_commands(&config.address, config.on_connect_commands.clone());
+            self.set_calibrations(&config.address, config.calibrations.clone());
+        }
+    }
+    
+    /// Replace the serial presets offered in the configure window (from app settings)
+    pub fn set_serial_presets(&mut self, presets: Vec<SerialPreset>) {
+        self.serial_presets = presets;
+    }
+    
+    /// Put a device into a safe state whenever it connects (e.g. PWM to 0, servos centered)
+    /// Commands run in order right after the session opens; failures are logged, not fatal
+    pub fn set_on_connect_commands(&self, address: &str, commands: Vec<SessionCommand>) {
+        let device_manager = self.device_manager.clone();
+        let address = address.to_string();
+        self.runtime.spawn(async move {
+            device_manager.set_on_connect_commands(&address, commands).await;
+        });
+    }
+    
+    /// Apply stored input calibrations to every read on sessions opened on `address`
+    pub fn set_calibrations(&self, address: &str, calibrations: HashMap<String, LinearCalibration>) {
+        let device_manager = self.device_manager.clone();
+        let address = address.to_string();
+        self.runtime.spawn(async move {
+            device_manager.set_calibrations(&address, calibrations).await;
+        });
+    }
+    
+    /// Turn raw byte tracing on or off for all connected transports
+    pub fn set_wire_trace(&mut self, enabled: bool) {
+        self.wire_trace_enabled = enabled;
+        for trace in self.wire_traces.lock().iter() {
+            trace.set_enabled(enabled);
+        }
+    }
+    
+    /// Change which serial ports are listed (from app settings)
+    /// The list is cleared and repopulated on the next discovery pass
+    pub fn set_discovery_filter(&mut self, filter: DiscoveryFilter) {
+        *self.discovery_filter.write() = filter;
+        self.refresh_devices();
+    }
+    
+    /// Change how long a device must stay present or absent before the list changes (from app settings)
+    pub fn set_discovery_debounce(&mut self, window: Duration) {
+        *self.discovery_debounce.write() = window;
+    }
+    
+    /// Probe `range` for COM ports Windows did not enumerate, or stop probing with `None` (from app settings)
+    /// The probe result is reused between discovery passes; a new range probes afresh
+    pub fn set_com_port_fallback(&mut self, range: Option<ComPortRange>) {
+        let mut current = self.com_fallback.write();
+        if current.as_ref().map(|fallback| fallback.range()) != range {
+            *current = range.map(|range| Arc::new(ComPortFallback::new(range)));
+        }
+    }
+    
+    /// Set the timeout applied to dispatched device commands (from app settings)
+    pub fn set_command_timeout(&mut self, timeout: Duration) {
+        self.command_timeout = timeout;
+    }
+    
+    /// Set how many commands must fail in a row before the user is alerted (from app settings)
+    pub fn set_failure_alert_threshold(&mut self, threshold: u32) {
+        self.command_failures.set_threshold(threshold);
+    }
+    
     /// Validate startup performance (Task 17 requirement)
     pub async fn validate_startup_performance(&self) -> bool {
         self.performance_monitor.validate_startup_performance().await
     }
     
+    /// Re-sync the clock of every session that is due, checking every `CLOCK_RESYNC_CHECK_INTERVAL`
+    async fn run_clock_resync(device_manager: Arc<DeviceManager>, tx: mpsc::UnboundedSender<DeviceUpdateEvent>) {
+        let mut ticker = tokio::time::interval(CLOCK_RESYNC_CHECK_INTERVAL);
+        loop {
+            ticker.tick().await;
+            for (session_id, offset) in device_manager.resync_clocks().await {
+                let _ = tx.send(DeviceUpdateEvent::ClockSynced { session_id, offset });
+            }
+        }
+    }
+    
     /// Start device discovery task
-    async fn start_device_discovery(tx: mpsc::UnboundedSender<DeviceUpdateEvent>) {
+    async fn start_device_discovery(
+        tx: mpsc::UnboundedSender<DeviceUpdateEvent>,
+        filter: Arc<parking_lot::RwLock<DiscoveryFilter>>,
+        debounce: Arc<parking_lot::RwLock<Duration>>,
+        com_fallback: Arc<parking_lot::RwLock<Option<Arc<ComPortFallback>>>>,
+        registry: Arc<parking_lot::Mutex<DeviceRegistry>>,
+    ) {
+        // Devices are keyed by (type, address); `seen` keeps the latest info for each
+        let mut debouncer = DiscoveryDebouncer::new(*debounce.read());
+        let mut seen: HashMap<(TransportType, String), DeviceInfo> = HashMap::new();
+        
         loop {
-            // Discover available transports
-            if let Ok(transports) = TransportFactory::list_available().await {
+            // Discover available transports, hiding ports the filter rejects
+            let current_filter = *filter.read();
+            let fallback = com_fallback.read().clone();
+            if let Ok(transports) = TransportFactory::list_available_filtered(current_filter, fallback.as_deref()).await {
+                let mut present = Vec::with_capacity(transports.len());
                 for transport_info in transports {
                     let device_info = DeviceInfo {
                         name: match transport_info.transport_type {
@@ -316,8 +537,33 @@ impl MultiControllerApp {
                         session_id: None,
                         connected: false,
                     };
-                    
-                    let _ = tx.send(DeviceUpdateEvent::DeviceDiscovered(device_info));
+                    let key = (device_info.transport_type, device_info.address.clone());
+                    seen.insert(key.clone(), device_info);
+                    present.push(key);
+                }
+                
+                // Only changes that outlast the debounce window reach the device list
+                debouncer.set_window(*debounce.read());
+                for change in debouncer.observe(present.iter().cloned(), Instant::now()) {
+                    if let DiscoveryChange::Removed(key) = change {
+                        if let Some(info) = seen.get(&key) {
+                            let _ = tx.send(DeviceUpdateEvent::DeviceRemoved(format!("{}_{}", info.name, info.address)));
+                        }
+                    }
+                }
+                seen.retain(|key, _| debouncer.is_stable(key) || present.contains(key));
+                
+                // Known devices that are stably present go online, the rest offline
+                let found: Vec<(TransportType, String)> = debouncer.stable().cloned().collect();
+                if let Err(e) = registry.lock().apply_discovery(&found) {
+                    tracing::warn!("Failed to save device registry: {}", e);
+                }
+                
+                // Re-announce every stable device so a cleared list repopulates
+                for key in &found {
+                    if let Some(info) = seen.get(key) {
+                        let _ = tx.send(DeviceUpdateEvent::DeviceDiscovered(info.clone()));
+                    }
                 }
             }
             
@@ -328,8 +574,10 @@ impl MultiControllerApp {
     
     /// Main update function called every frame
     pub fn update(&mut self, ctx: &Context, _frame: &mut eframe::Frame) {
-        // Process device update events
-        while let Ok(event) = self.device_update_rx.try_recv() {
+        // Process device update events, at most a frame's worth at a time
+        let events = drain_bounded(&mut self.device_update_rx, MAX_EVENTS_PER_FRAME);
+        let events_pending = events.len() == MAX_EVENTS_PER_FRAME;
+        for event in events {
             match event {
                 DeviceUpdateEvent::DeviceDiscovered(info) => {
                     // Check if device already exists
@@ -347,6 +595,7 @@ impl MultiControllerApp {
                         device.connected = true;
                         device.session_id = Some(session_id.clone());
                     }
+                    self.refresh_capabilities(&session_id);
                     self.active_sessions.insert(session_id, device_id);
                 }
                 DeviceUpdateEvent::DeviceDisconnected(device_id) => {
@@ -356,18 +605,37 @@ impl MultiControllerApp {
                         device.connected = false;
                         device.session_id = None;
                     }
-                    self.current_session = None;
+                    self.device_transports.lock().remove(&device_id);
+                    apply_clock_offset(&self.telemetry_system, &device_id, None);
+                    self.command_failures.forget(&device_id);
+                    if self.failure_alert.as_ref().map_or(false, |alert| alert.device_id == device_id) {
+                        self.failure_alert = None;
+                    }
+                    self.set_feature_gate(FeatureGate::default());
                 }
                 DeviceUpdateEvent::DeviceRemoved(device_id) => {
                     self.available_devices.retain(|d| 
                         format!("{}_{}", d.name, d.address) != device_id
                     );
+                    self.device_transports.lock().remove(&device_id);
+                }
+                DeviceUpdateEvent::CapabilitiesReported(capabilities) => {
+                    self.set_feature_gate(FeatureGate::from_capabilities(capabilities.as_ref()));
+                }
+                DeviceUpdateEvent::ClockSynced { session_id, offset } => {
+                    if let Some(device_id) = self.active_sessions.get(&session_id) {
+                        apply_clock_offset(&self.telemetry_system, device_id, Some(offset));
+                    }
                 }
             }
         }
         
-        // Process device responses
-        while let Ok(response) = self.response_rx.try_recv() {
+        // Process device responses; telemetry bursts become one update per stream
+        let batch = drain_responses(&self.response_rx, MAX_EVENTS_PER_FRAME);
+        for (stream, samples) in batch.telemetry {
+            self.apply_stream_samples(stream, samples);
+        }
+        for response in batch.others {
             match response {
                 DeviceResponse::DigitalValue { pin, value } => {
                     self.digital_pin_states.insert(pin, value);
@@ -375,55 +643,8 @@ impl MultiControllerApp {
                 DeviceResponse::AnalogValue { pin, value } => {
                     self.analog_values.insert(pin, value);
                 }
-                DeviceResponse::StreamData { stream, data, timestamp } => {
-                    // Parse telemetry data
-                    if let Some(value) = data.as_f64() {
-                        // Add to legacy buffer for backward compatibility
-                        let entry = self.telemetry_buffers
-                            .entry(stream.clone())
-                            .or_insert_with(|| VecDeque::with_capacity(1000));
-                        
-                        let time = timestamp as f64 / 1000.0;
-                        entry.push_back((time, value));
-                        
-                        // Keep only last 1000 samples
-                        while entry.len() > 1000 {
-                            entry.pop_front();
-                        }
-                        
-                        // Feed data into the telemetry system
-                        // Get or create channel for this stream
-                        let channel = self.telemetry_system.get_channel(&stream)
-                            .unwrap_or_else(|| {
-                                self.telemetry_system.create_channel(
-                                    stream.clone(),
-                                    Some(ChannelConfig {
-                                        buffer_size: 2000,
-                                        sample_rate: 30.0,
-                                        name: stream.clone(),
-                                        sample_type: SampleType::Float32,
-                                    })
-                                )
-                            });
-                        
-                        // Add sample to the channel
-                        let sample = TelemetrySample::with_timestamp(
-                            SampleValue::Float32(value as f32),
-                            timestamp
-                        );
-                        channel.add_sample(sample);
-                        
-                        // If this is the main telemetry stream, update panel's channel
-                        if stream == "telemetry" || stream == "main_telemetry" {
-                            if self.telemetry_panel.channel.is_none() {
-                                self.telemetry_panel.set_channel(channel);
-                            }
-                        }
-                        
-                        // Update telemetry panel (legacy compatibility)
-                        self.telemetry_panel.add_data(value as f32);
-                    }
-                }
+                // Stream samples were coalesced into `batch.telemetry`
+                DeviceResponse::StreamData { .. } => {}
                 DeviceResponse::Log { level, message } => {
                     self.log_panel.add_log(LogEntry {
                         timestamp: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64,
@@ -434,18 +655,33 @@ impl MultiControllerApp {
                         thread_id: format!("{:?}", std::thread::current().id()),
                     });
                 }
-                DeviceResponse::CommandResult { success, data } => {
-                    if !success {
+                DeviceResponse::CommandResult { device_id, success, data } => {
+                    if success {
+                        self.record_command_outcome(&device_id, None);
+                    } else {
+                        let message = format!("Command failed: {:?}", data);
+                        self.record_command_outcome(&device_id, Some(&message));
                         self.log_panel.add_log(LogEntry {
                             timestamp: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64,
                             level: LogLevel::Error,
-                            message: format!("Command failed: {:?}", data),
+                            message,
                             source: "Device".to_string(),
                             data: None,
                             thread_id: format!("{:?}", std::thread::current().id()),
                         });
                     }
                 }
+                DeviceResponse::CommandFailed { device_id, message } => {
+                    self.record_command_outcome(&device_id, Some(&message));
+                    self.log_panel.add_log(LogEntry {
+                        timestamp: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64,
+                        level: LogLevel::Error,
+                        message,
+                        source: "Device".to_string(),
+                        data: None,
+                        thread_id: format!("{:?}", std::thread::current().id()),
+                    });
+                }
                 DeviceResponse::Error { message } => {
                     self.log_panel.add_log(LogEntry {
                         timestamp: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64,
@@ -459,6 +695,11 @@ impl MultiControllerApp {
             }
         }
         
+        // Leftover events are handled next frame; make sure one comes soon
+        if events_pending || !self.response_rx.is_empty() {
+            ctx.request_repaint();
+        }
+        
         // Apply Windows 10 theme
         self.apply_theme(ctx);
         
@@ -468,6 +709,9 @@ impl MultiControllerApp {
         // Left sidebar for device selection
         self.render_device_sidebar(ctx);
         
+        // Device configuration window (opened from the sidebar)
+        self.render_device_config_window(ctx);
+        
         // Main content area with tabs
         self.render_main_content(ctx);
         
@@ -475,6 +719,61 @@ impl MultiControllerApp {
         self.render_status_bar(ctx);
     }
     
+    /// Record a stream's samples from one frame, updating the telemetry panel once
+    fn apply_stream_samples(&mut self, stream: String, samples: Vec<(u64, f64)>) {
+        let Some(&(_, latest)) = samples.last() else {
+            return;
+        };
+        
+        // Add to legacy buffer for backward compatibility
+        let entry = self.telemetry_buffers
+            .entry(stream.clone())
+            .or_insert_with(|| VecDeque::with_capacity(1000));
+        for &(timestamp, value) in &samples {
+            entry.push_back((timestamp as f64 / 1000.0, value));
+        }
+        
+        // Keep only last 1000 samples
+        while entry.len() > 1000 {
+            entry.pop_front();
+        }
+        
+        // Feed data into the telemetry system
+        // Get or create channel for this stream
+        let channel = self.telemetry_system.get_channel(&stream)
+            .unwrap_or_else(|| {
+                self.telemetry_system.create_channel(
+                    stream.clone(),
+                    Some(ChannelConfig {
+                        buffer_size: 2000,
+                        sample_rate: 30.0,
+                        name: stream.clone(),
+                        sample_type: SampleType::Float32,
+                        persist_path: None,
+                        tags: Vec::new(),
+                        group: None,
+                        timestamp_source: Default::default(),
+                    })
+                )
+            });
+        
+        // Flush the frame's samples as one batch
+        let batch: Vec<TelemetrySample> = samples.iter()
+            .map(|&(timestamp, value)| TelemetrySample::with_timestamp(SampleValue::Float32(value as f32), timestamp))
+            .collect();
+        channel.add_samples(&batch);
+        
+        // If this is the main telemetry stream, update panel's channel
+        if stream == "telemetry" || stream == "main_telemetry" {
+            if self.telemetry_panel.channel.is_none() {
+                self.telemetry_panel.set_channel(channel);
+            }
+        }
+        
+        // Update telemetry panel (legacy compatibility) with the newest value only
+        self.telemetry_panel.add_data(latest as f32);
+    }
+    
     /// Apply Windows 10-style theme
     fn apply_theme(&self, ctx: &Context) {
         let mut style = (*ctx.style()).clone();
@@ -560,6 +859,35 @@ impl MultiControllerApp {
                 ui.separator();
                 
                 ScrollArea::vertical().show(ui, |ui| {
+                    // Remembered devices, online or not
+                    let known_devices = self.device_registry.lock().devices();
+                    if !known_devices.is_empty() {
+                        ui.label("Known devices");
+                        for known in known_devices {
+                            ui.horizontal(|ui| {
+                                let status_color = if known.online {
+                                    egui::Color32::from_rgb(0, 200, 0)
+                                } else {
+                                    egui::Color32::from_rgb(128, 128, 128)
+                                };
+                                ui.colored_label(status_color, "●");
+                                ui.label(&known.friendly_name);
+                                
+                                if !known.online {
+                                    ui.small("offline");
+                                } else if ui.small_button("Connect").clicked() {
+                                    self.connect_known_device(known.clone());
+                                }
+                                if ui.small_button("Forget").clicked() {
+                                    if let Err(e) = self.device_registry.lock().remove(&known.id) {
+                                        tracing::warn!("Failed to forget {}: {}", known.friendly_name, e);
+                                    }
+                                }
+                            });
+                        }
+                        ui.separator();
+                    }
+                    
                     // Show real discovered devices
                     let devices = self.available_devices.clone();
                     
@@ -582,6 +910,11 @@ impl MultiControllerApp {
                             }
                         });
                         
+                        // State, latency and reconnects at a glance
+                        if let Some(transport) = self.device_transports.lock().get(&device_id) {
+                            ui.small(transport.status_summary());
+                        }
+                        
                         if is_selected {
                             ui.indent("device_details", |ui| {
                                 ui.label(format!("Type: {:?}", device.transport_type));
@@ -591,13 +924,22 @@ impl MultiControllerApp {
                                         if ui.small_button("Disconnect").clicked() {
                                             self.disconnect_device(device_id.clone());
                                         }
+                                        if ui.small_button("Identify")
+                                            .on_hover_text("Blink the board's LED")
+                                            .clicked()
+                                        {
+                                            self.identify_device(&device_id);
+                                        }
                                     } else {
                                         if ui.small_button("Connect").clicked() {
                                             self.connect_device(device.clone());
                                         }
                                     }
                                     if ui.small_button("Configure").clicked() {
-                                        // TODO: Open device config
+                                        self.configuring_device = Some(device_id.clone());
+                                    }
+                                    if ui.small_button("Remember").clicked() {
+                                        self.remember_device(&device);
                                     }
                                 });
                             });
@@ -621,12 +963,141 @@ impl MultiControllerApp {
             });
     }
     
+    /// Render the serial configuration window for the device being configured
+    fn render_device_config_window(&mut self, ctx: &Context) {
+        let Some(device_id) = self.configuring_device.clone() else {
+            return;
+        };
+        
+        let mut open = true;
+        let presets = self.serial_presets.clone();
+        let settings = self.device_serial_settings
+            .entry(device_id.clone())
+            .or_insert_with(SerialSettings::default);
+        let identify = self.device_identify_patterns
+            .entry(device_id.clone())
+            .or_default();
+        
+        egui::Window::new("Configure Device")
+            .id(egui::Id::new(("device_config", &device_id)))
+            .open(&mut open)
+            .resizable(false)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.label(&device_id);
+                ui.separator();
+                
+                // Preset picker; shows "Custom" once any field is edited
+                let current = presets.iter()
+                    .find(|p| p.matches(settings))
+                    .map(|p| p.name.clone())
+                    .unwrap_or_else(|| "Custom".to_string());
+                egui::ComboBox::from_label("Preset")
+                    .selected_text(current)
+                    .show_ui(ui, |ui| {
+                        for preset in &presets {
+                            let label = format!("{} ({})", preset.name, preset.summary());
+                            if ui.selectable_label(preset.matches(settings), label).clicked() {
+                                preset.apply_to(settings);
+                            }
+                        }
+                    });
+                
+                ui.add_space(4.0);
+                
+                egui::ComboBox::from_label("Baud rate")
+                    .selected_text(settings.baud_rate.to_string())
+                    .show_ui(ui, |ui| {
+                        for &baud in COMMON_BAUD_RATES {
+                            ui.selectable_value(&mut settings.baud_rate, baud, baud.to_string());
+                        }
+                    });
+                
+                egui::ComboBox::from_label("Data bits")
+                    .selected_text(format!("{:?}", settings.data_bits))
+                    .show_ui(ui, |ui| {
+                        for bits in [DataBits::Five, DataBits::Six, DataBits::Seven, DataBits::Eight] {
+                            ui.selectable_value(&mut settings.data_bits, bits, format!("{:?}", bits));
+                        }
+                    });
+                
+                egui::ComboBox::from_label("Parity")
+                    .selected_text(format!("{:?}", settings.parity))
+                    .show_ui(ui, |ui| {
+                        for parity in [Parity::None, Parity::Odd, Parity::Even] {
+                            ui.selectable_value(&mut settings.parity, parity, format!("{:?}", parity));
+                        }
+                    });
+                
+                egui::ComboBox::from_label("Stop bits")
+                    .selected_text(format!("{:?}", settings.stop_bits))
+                    .show_ui(ui, |ui| {
+                        for stop_bits in [StopBits::One, StopBits::Two] {
+                            ui.selectable_value(&mut settings.stop_bits, stop_bits, format!("{:?}", stop_bits));
+                        }
+                    });
+                
+                ui.checkbox(&mut settings.exclusive, "Exclusive access")
+                    .on_hover_text("Refuse to open the port while another process holds it");
+                
+                ui.add(egui::Slider::new(&mut identify.pin, 0..=69).text("Identify LED pin"));
+                
+                ui.add_space(4.0);
+                ui.label("Settings apply on next connect");
+            });
+        
+        if !open {
+            self.configuring_device = None;
+        }
+    }
+    
+    /// Add a discovered device to the persistent registry
+    fn remember_device(&mut self, device: &DeviceInfo) {
+        let device_id = format!("{}_{}", device.name, device.address);
+        let mut config = TransportConfig {
+            transport_type: device.transport_type,
+            address: device.address.clone(),
+            ..Default::default()
+        };
+        if device.transport_type == TransportType::Serial {
+            let settings = self.device_serial_settings.get(&device_id).cloned().unwrap_or_default();
+            config.settings = crate::transport::common::TransportSettings::Serial(settings);
+        }
+        
+        if let Err(e) = self.device_registry.lock().register(KnownDevice::new(&device.name, config)) {
+            tracing::error!("Failed to remember {}: {}", device.name, e);
+        }
+    }
+    
+    /// Connect to a remembered device with its saved serial settings
+    fn connect_known_device(&mut self, known: KnownDevice) {
+        let device = DeviceInfo {
+            name: known.friendly_name.clone(),
+            transport_type: known.transport.transport_type,
+            address: known.transport.address.clone(),
+            session_id: None,
+            connected: false,
+        };
+        if let crate::transport::common::TransportSettings::Serial(settings) = known.transport.settings {
+            self.device_serial_settings.insert(format!("{}_{}", device.name, device.address), settings);
+        }
+        self.connect_device(device);
+    }
+    
     /// Connect to a device
     fn connect_device(&mut self, device: DeviceInfo) {
         let device_id = format!("{}_{}", device.name, device.address);
+        let serial_settings = self.device_serial_settings
+            .get(&device_id)
+            .cloned()
+            .unwrap_or_default();
         let device_manager = self.device_manager.clone();
         let tx = self.device_update_tx.clone();
         let runtime = self.runtime.clone();
+        let wire_traces = self.wire_traces.clone();
+        let wire_trace_enabled = self.wire_trace_enabled;
+        let logging_system = self.logging_system.clone();
+        let device_transports = self.device_transports.clone();
         
         runtime.spawn(async move {
             // Create transport config
@@ -635,7 +1106,7 @@ impl MultiControllerApp {
                 address: device.address.clone(),
                 connect_timeout_ms: 5000,
                 settings: match device.transport_type {
-                    TransportType::Serial => crate::transport::common::TransportSettings::Serial(Default::default()),
+                    TransportType::Serial => crate::transport::common::TransportSettings::Serial(serial_settings),
                     TransportType::Tcp => crate::transport::common::TransportSettings::Tcp(Default::default()),
                     TransportType::Udp => crate::transport::common::TransportSettings::Udp(Default::default()),
                     TransportType::Ssh => crate::transport::common::TransportSettings::Ssh(Default::default()),
@@ -645,19 +1116,31 @@ impl MultiControllerApp {
             
             // Create transport
             if let Ok(mut transport) = TransportFactory::create(config).await {
+                // Follow the status bar wire-trace toggle
+                if let Some(trace) = transport.wire_trace() {
+                    trace.set_sink(logging_system);
+                    trace.set_enabled(wire_trace_enabled);
+                    wire_traces.lock().push(trace.clone());
+                }
+                
+                let transport: Arc<dyn Transport> = Arc::from(transport);
+                device_transports.lock().insert(device_id.clone(), transport.clone());
+                
                 // Connect the transport first
                 if transport.connect().await.is_ok() {
                     // Try to open device
                     if let Ok(session_id) = device_manager.open_device(
-                        Arc::from(transport), 
+                        transport, 
                         Some(device_id.clone())
                     ).await {
                         let _ = tx.send(DeviceUpdateEvent::DeviceConnected(device_id, session_id));
                     } else {
                         tracing::error!("Failed to open device: {}", device_id);
+                        device_transports.lock().remove(&device_id);
                     }
                 } else {
                     tracing::error!("Failed to connect transport for: {}", device_id);
+                    device_transports.lock().remove(&device_id);
                 }
             } else {
                 tracing::error!("Failed to create transport for: {}", device_id);
@@ -683,15 +1166,76 @@ impl MultiControllerApp {
         });
     }
     
+    /// Ask a newly connected device's session for its capabilities and gate the UI on them
+    fn refresh_capabilities(&mut self, session_id: &str) {
+        let device_manager = self.device_manager.clone();
+        let session_id = session_id.to_string();
+        let tx = self.device_update_tx.clone();
+        
+        self.runtime.spawn(async move {
+            let Some(session) = device_manager.get_session(&session_id).await else {
+                let _ = tx.send(DeviceUpdateEvent::CapabilitiesReported(None));
+                return;
+            };
+            let mut session = session.lock().await;
+            let capabilities = match session.query_capabilities().await {
+                Ok(capabilities) => Some(capabilities),
+                Err(e) => {
+                    tracing::debug!("Capabilities unavailable for {}: {}", session.device_name(), e);
+                    None
+                }
+            };
+            let _ = tx.send(DeviceUpdateEvent::CapabilitiesReported(capabilities));
+        });
+    }
+    
+    /// Replace the feature gate, leaving a tab the device no longer supports
+    fn set_feature_gate(&mut self, gate: FeatureGate) {
+        if !gate.tab_enabled(self.active_tab) {
+            self.active_tab = Tab::Manual;
+        }
+        self.feature_gate = gate;
+    }
+    
+    /// Blink a connected board's identify pattern so it can be found physically
+    fn identify_device(&mut self, device_id: &str) {
+        let session_id = self.available_devices.iter()
+            .find(|d| format!("{}_{}", d.name, d.address) == device_id)
+            .and_then(|d| d.session_id.clone());
+        let Some(session_id) = session_id else {
+            tracing::warn!("Cannot identify {}: no open session", device_id);
+            return;
+        };
+        let pattern = self.device_identify_patterns.get(device_id).cloned().unwrap_or_default();
+        let device_manager = self.device_manager.clone();
+        let device_id = device_id.to_string();
+        
+        self.runtime.spawn(async move {
+            let Some(session) = device_manager.get_session(&session_id).await else {
+                tracing::warn!("Cannot identify {}: session {} is closed", device_id, session_id);
+                return;
+            };
+            if let Err(e) = identify_shared(&session, &pattern).await {
+                tracing::warn!("Identify failed on {}: {}", device_id, e);
+            }
+        });
+    }
+    
     /// Refresh device list
     fn refresh_devices(&mut self) {
         self.available_devices.clear();
         // Discovery task will repopulate the list
     }
     
-    /// Send a command to the current device
-    fn send_device_command(&mut self, command: DeviceCommand) {
-        if self.current_session.is_none() {
+    /// Device id and session id of the selected device, if it is connected
+    fn command_target(&mut self) -> Option<(String, String)> {
+        let target = self.selected_device.as_ref().and_then(|device_id| {
+            self.available_devices.iter()
+                .find(|d| format!("{}_{}", d.name, d.address) == *device_id)
+                .and_then(|d| d.session_id.clone())
+                .map(|session_id| (device_id.clone(), session_id))
+        });
+        if target.is_none() {
             self.log_panel.add_log(LogEntry {
                 timestamp: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64,
                 level: LogLevel::Warning,
@@ -700,142 +1244,113 @@ impl MultiControllerApp {
                 data: None,
                 thread_id: format!("{:?}", std::thread::current().id()),
             });
+        }
+        target
+    }
+    
+    /// Send a command to the selected device
+    fn send_device_command(&mut self, command: DeviceCommand) {
+        let Some((device_id, session_id)) = self.command_target() else {
             return;
+        };
+        
+        if let Some(recorded) = command.clone().into_session_command() {
+            self.command_history.record(recorded);
         }
         
-        let session = self.current_session.clone();
+        let device_manager = self.device_manager.clone();
         let response_tx = self.response_tx.clone();
-        let runtime = self.runtime.clone();
+        let timeout = self.command_timeout;
         
-        runtime.spawn(async move {
-            if let Some(session_arc) = session {
-                let mut session = session_arc.lock().await;
-                
-                let result = match command {
-                    DeviceCommand::DigitalWrite { pin, value } => {
-                        session.invoke_async(
-                            "digitalWrite",
-                            vec![json!(pin), json!(value)]
-                        ).await
-                    },
-                    DeviceCommand::AnalogWrite { pin, value } => {
-                        session.invoke_async(
-                            "analogWrite",
-                            vec![json!(pin), json!(value)]
-                        ).await
-                    },
-                    DeviceCommand::DigitalRead { pin } => {
-                        session.invoke_async(
-                            "digitalRead",
-                            vec![json!(pin)]
-                        ).await
-                    },
-                    DeviceCommand::AnalogRead { pin } => {
-                        session.invoke_async(
-                            "analogRead",
-                            vec![json!(pin)]
-                        ).await
-                    },
-                    DeviceCommand::SetServo { index, position } => {
-                        session.invoke_async(
-                            "setServo",
-                            vec![json!(index), json!(position)]
-                        ).await
-                    },
-                    DeviceCommand::CustomCommand { endpoint, args } => {
-                        session.invoke_async(&endpoint, args).await
-                    },
-                    _ => {
-                        Ok(json!(null))
-                    }
-                };
-                
-                match result {
-                    Ok(data) => {
-                        let _ = response_tx.send(DeviceResponse::CommandResult {
-                            success: true,
-                            data: Some(data),
-                        });
-                    }
-                    Err(e) => {
-                        let _ = response_tx.send(DeviceResponse::Error {
-                            message: e.to_string(),
-                        });
-                    }
+        self.runtime.spawn(async move {
+            let response = run_on_session(&device_manager, &device_id, &session_id, command, timeout).await;
+            let _ = response_tx.send(response).await;
+        });
+    }
+    
+    /// Count a command result toward `device_id`'s failure alert
+    fn record_command_outcome(&mut self, device_id: &str, error: Option<&str>) {
+        match error {
+            None => {
+                let cleared = self.command_failures.record_success(device_id);
+                if cleared && self.failure_alert.as_ref().map_or(false, |alert| alert.device_id == device_id) {
+                    self.failure_alert = None;
                 }
             }
-        });
+            Some(error) => {
+                if let Some(alert) = self.command_failures.record_failure(device_id, error) {
+                    self.log_panel.add_log(LogEntry {
+                        timestamp: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64,
+                        level: LogLevel::Error,
+                        message: alert.message(),
+                        source: "System".to_string(),
+                        data: None,
+                        thread_id: format!("{:?}", std::thread::current().id()),
+                    });
+                    self.failure_alert = Some(alert);
+                }
+            }
+        }
     }
     
     /// Execute the current script
     fn execute_script(&mut self) {
-        let lines: Vec<String> = self.current_script.lines().map(|s| s.to_string()).collect();
-        
-        for line in lines {
-            let line = line.trim();
-            
-            // Skip empty lines and comments
-            if line.is_empty() || line.starts_with("//") {
-                continue;
-            }
-            
-            // Parse and execute commands
-            if line.starts_with("digitalWrite(") && line.ends_with(")") {
-                // Parse digitalWrite command
-                let args = &line[13..line.len()-1];
-                let parts: Vec<&str> = args.split(',').collect();
-                if parts.len() == 2 {
-                    if let Ok(pin) = parts[0].trim().parse::<u8>() {
-                        let value = parts[1].trim().to_lowercase() == "true" || parts[1].trim() == "1";
-                        self.send_device_command(DeviceCommand::DigitalWrite { pin, value });
-                        self.script_output.push(format!("digitalWrite({}, {})", pin, value));
-                    } else {
-                        self.script_output.push(format!("Error: Invalid pin number in '{}'", line));
-                    }
-                } else {
-                    self.script_output.push(format!("Error: Invalid arguments in '{}'", line));
+        let mut steps = Vec::new();
+        for line in self.current_script.lines() {
+            match parse_script_line(line) {
+                Ok(Some(step)) => {
+                    self.script_output.push(step.describe());
+                    steps.push(step);
                 }
-            } else if line.starts_with("analogWrite(") && line.ends_with(")") {
-                // Parse analogWrite command
-                let args = &line[12..line.len()-1];
-                let parts: Vec<&str> = args.split(',').collect();
-                if parts.len() == 2 {
-                    if let (Ok(pin), Ok(value)) = (parts[0].trim().parse::<u8>(), parts[1].trim().parse::<u8>()) {
-                        self.send_device_command(DeviceCommand::AnalogWrite { pin, value });
-                        self.script_output.push(format!("analogWrite({}, {})", pin, value));
-                    } else {
-                        self.script_output.push(format!("Error: Invalid arguments in '{}'", line));
-                    }
-                } else {
-                    self.script_output.push(format!("Error: Invalid arguments in '{}'", line));
-                }
-            } else if line.starts_with("setServo(") && line.ends_with(")") {
-                // Parse setServo command
-                let args = &line[9..line.len()-1];
-                let parts: Vec<&str> = args.split(',').collect();
-                if parts.len() == 2 {
-                    if let (Ok(index), Ok(position)) = (parts[0].trim().parse::<u8>(), parts[1].trim().parse::<u8>()) {
-                        self.send_device_command(DeviceCommand::SetServo { index, position });
-                        self.script_output.push(format!("setServo({}, {})", index, position));
-                    } else {
-                        self.script_output.push(format!("Error: Invalid arguments in '{}'", line));
+                Ok(None) => {}
+                Err(e) => self.script_output.push(format!("Error: {}", e)),
+            }
+        }
+        
+        let runtime_ms: u128 = steps.iter().map(|step| match step {
+            ScriptStep::Sleep(duration) => duration.as_millis(),
+            ScriptStep::Command(_) => 0,
+        }).sum();
+        if self.run_script_steps(steps) {
+            self.script_output.push(format!("=== Script Started ({}ms of delays) ===", runtime_ms));
+        }
+    }
+    
+    /// Send the script's commands in order, waiting out its sleeps between them
+    /// Runs on the runtime so the sleeps don't block the UI; false without a device
+    fn run_script_steps(&mut self, steps: Vec<ScriptStep>) -> bool {
+        let Some((device_id, session_id)) = self.command_target() else {
+            return false;
+        };
+        
+        // History keeps each command at the time it will be sent
+        let mut at = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64;
+        for step in &steps {
+            match step {
+                ScriptStep::Sleep(duration) => at += duration.as_millis() as u64,
+                ScriptStep::Command(command) => {
+                    if let Some(recorded) = command.clone().into_session_command() {
+                        self.command_history.record_at(recorded, at);
                     }
-                } else {
-                    self.script_output.push(format!("Error: Invalid arguments in '{}'", line));
                 }
-            } else if line.starts_with("delay(") && line.ends_with(")") {
-                // Parse delay command (note: this is synchronous and will block UI)
-                let args = &line[6..line.len()-1];
-                if let Ok(ms) = args.trim().parse::<u64>() {
-                    self.script_output.push(format!("delay({}ms)", ms));
-                    // Note: In a real implementation, we'd handle this asynchronously
-                }
-            } else {
-                self.script_output.push(format!("Error: Unknown command '{}'", line));
             }
         }
         
-        self.script_output.push("=== Script Execution Complete ===".to_string());
+        let device_manager = self.device_manager.clone();
+        let response_tx = self.response_tx.clone();
+        let timeout = self.command_timeout;
+        self.runtime.spawn(async move {
+            for step in steps {
+                match step {
+                    ScriptStep::Sleep(duration) => tokio::time::sleep(duration).await,
+                    ScriptStep::Command(command) => {
+                        let response = run_on_session(&device_manager, &device_id, &session_id, command, timeout).await;
+                        let _ = response_tx.send(response).await;
+                    }
+                }
+            }
+        });
+        true
     }
     
     /// Render the main content area with tabs
@@ -855,7 +1370,16 @@ impl MultiControllerApp {
                 ];
                 
                 for (tab, label) in tabs.iter() {
-                    if ui.selectable_label(self.active_tab == *tab, *label).clicked() {
+                    let gated = self.feature_gate.tab(*tab);
+                    let response = ui.add_enabled(
+                        gated.is_ok(),
+                        egui::SelectableLabel::new(self.active_tab == *tab, *label),
+                    );
+                    let response = match gated {
+                        Ok(()) => response,
+                        Err(reason) => response.on_disabled_hover_text(reason),
+                    };
+                    if response.clicked() {
                         self.active_tab = *tab;
                     }
                     ui.add_space(4.0);
@@ -879,7 +1403,7 @@ impl MultiControllerApp {
     }
     
     /// Render the status bar
-    fn render_status_bar(&self, ctx: &Context) {
+    fn render_status_bar(&mut self, ctx: &Context) {
         TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
             ui.horizontal(|ui| {
                 // Connection status
@@ -896,8 +1420,29 @@ impl MultiControllerApp {
                 ui.label(format!("CPU: {:.1}%", 1.5)); // TODO: Real CPU
                 ui.label(format!("RAM: {} MB", 145)); // TODO: Real RAM
                 
+                // Repeated command failures, cleared by the next success
+                if let Some(alert) = &self.failure_alert {
+                    ui.separator();
+                    ui.colored_label(egui::Color32::from_rgb(220, 50, 50), format!("⚠ {} failed commands", alert.consecutive_failures))
+                        .on_hover_text(alert.message());
+                }
+                
+                // Responses lost because the UI fell behind the device
+                let dropped = self.response_rx.dropped();
+                if dropped > 0 {
+                    ui.separator();
+                    ui.colored_label(egui::Color32::from_rgb(255, 165, 0), format!("Dropped: {}", dropped))
+                        .on_hover_text("Device responses discarded because the UI could not keep up");
+                }
+                
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                     ui.label(format!("v{}", env!("CARGO_PKG_VERSION")));
+                    
+                    let toggle = ui.checkbox(&mut self.wire_trace_enabled, "Wire trace")
+                        .on_hover_text("Log raw bytes sent and received to the device I/O log");
+                    if toggle.changed() {
+                        self.set_wire_trace(self.wire_trace_enabled);
+                    }
                 });
             });
         });
@@ -913,8 +1458,9 @@ impl MultiControllerApp {
             return;
         }
         
-        // Control sections
-        ui.collapsing("Digital I/O", |ui| {
+        // Control sections, grayed out when the device lacks the capability
+        let gate = self.feature_gate.clone();
+        gated_collapsing(ui, &gate, "Digital I/O", CAP_GPIO, |ui| {
             ui.horizontal_wrapped(|ui| {
                 for pin in 0..14 {
                     let current_state = *self.digital_pin_states.get(&pin).unwrap_or(&false);
@@ -948,7 +1494,7 @@ impl MultiControllerApp {
             });
         });
         
-        ui.collapsing("PWM Control", |ui| {
+        gated_collapsing(ui, &gate, "PWM Control", CAP_PWM, |ui| {
             for pin in [3u8, 5, 6, 9, 10, 11] {
                 ui.horizontal(|ui| {
                     ui.label(format!("PWM{}: ", pin));
@@ -963,7 +1509,7 @@ impl MultiControllerApp {
             }
         });
         
-        ui.collapsing("Analog Inputs", |ui| {
+        gated_collapsing(ui, &gate, "Analog Inputs", CAP_ANALOG, |ui| {
             for pin in 0..6 {
                 ui.horizontal(|ui| {
                     ui.label(format!("A{}: ", pin));
@@ -977,7 +1523,7 @@ impl MultiControllerApp {
             }
         });
         
-        ui.collapsing("Servo Control", |ui| {
+        gated_collapsing(ui, &gate, "Servo Control", CAP_SERVO, |ui| {
             for i in 0..4 {
                 ui.horizontal(|ui| {
                     ui.label(format!("Servo {}: ", i));
@@ -1052,6 +1598,16 @@ impl MultiControllerApp {
                 self.execute_script();
             }
             
+            // Replace the editor contents with the commands sent so far
+            let export = ui.add_enabled(!self.command_history.is_empty(), egui::Button::new("📋 From History"))
+                .on_hover_text("Turn the commands sent this session into a script");
+            if export.clicked() {
+                let device_id = self.selected_device.clone().unwrap_or_else(|| "device".to_string());
+                self.current_script = self.command_history.to_script(&device_id);
+                self.selected_script = None;
+                self.script_output.push(format!("Loaded {} commands from history.", self.command_history.len()));
+            }
+            
             // Clear output button
             if ui.button("🗑 Clear Output").clicked() {
                 self.script_output.clear();
@@ -1125,21 +1681,25 @@ impl MultiControllerApp {
             
             ui.separator();
             
-            // Channel selector
-            let channels = self.telemetry_system.channel_names();
-            if !channels.is_empty() {
+            // Channel selector, one collapsible section per group
+            let groups = self.telemetry_system.channels_by_group();
+            if !groups.is_empty() {
                 ui.label("Channel:");
                 egui::ComboBox::from_label("")
                     .selected_text(self.telemetry_panel.channel.as_ref()
                         .map(|c| "main_telemetry")
                         .unwrap_or("Select Channel"))
                     .show_ui(ui, |ui| {
-                        for channel_name in channels {
-                            if ui.selectable_label(false, &channel_name).clicked() {
-                                if let Some(channel) = self.telemetry_system.get_channel(&channel_name) {
-                                    self.telemetry_panel.set_channel(channel);
-                                }
-                            }
+                        for (group, channels) in groups {
+                            egui::CollapsingHeader::new(format!("{} ({})", group, channels.len()))
+                                .default_open(true)
+                                .show(ui, |ui| {
+                                    for channel in channels {
+                                        if ui.selectable_label(false, &channel.config().name).clicked() {
+                                            self.telemetry_panel.set_channel(channel);
+                                        }
+                                    }
+                                });
                         }
                     });
             }
@@ -1398,4 +1958,359 @@ impl eframe::App for MultiControllerApp {
     fn update(&mut self, ctx: &Context, frame: &mut eframe::Frame) {
         self.update(ctx, frame);
     }
-}
\ No newline at end of file
+}
+
+/// Invoke the session endpoint for a UI command
+async fn dispatch_command(session: &mut dyn DeviceSession, command: DeviceCommand) -> DeviceResult<Value> {
+    match command.into_session_command() {
+        Some(call) => session.invoke_async(&call.endpoint, call.args).await,
+        None => Ok(json!(null)),
+    }
+}
+
+/// One step of a script run from the Scripts tab
+#[derive(Debug, Clone)]
+enum ScriptStep {
+    Command(DeviceCommand),
+    Sleep(Duration),
+}
+
+impl ScriptStep {
+    /// Line shown in the script output
+    fn describe(&self) -> String {
+        match self {
+            ScriptStep::Sleep(duration) => format!("sleep({}ms)", duration.as_millis()),
+            ScriptStep::Command(command) => match command.clone().into_session_command() {
+                Some(call) => format!("{}({})", call.endpoint, Value::Array(call.args)),
+                None => format!("{:?}", command),
+            },
+        }
+    }
+}
+
+/// Parse one script line: `device_write(device, "endpoint", [args])` and
+/// `sleep(ms)` as exported from the command history, plus the shorthand
+/// `digitalWrite`, `analogWrite`, `setServo` and `delay` calls
+/// Blank lines, comments and the `get_device` binding give `None`
+fn parse_script_line(line: &str) -> Result<Option<ScriptStep>, String> {
+    // Statements may end in `;`, as in scripts exported from the command history
+    let line = line.trim().trim_end_matches(';').trim_end();
+    if line.is_empty() || line.starts_with("//") || (line.starts_with("let ") && line.contains("get_device(")) {
+        return Ok(None);
+    }
+    
+    let Some((name, args)) = line.strip_suffix(')').and_then(|call| call.split_once('(')) else {
+        return Err(format!("Unknown command '{}'", line));
+    };
+    let invalid = || format!("Invalid arguments in '{}'", line);
+    let parts: Vec<&str> = args.split(',').map(str::trim).collect();
+    
+    let step = match name.trim() {
+        "device_write" => {
+            // The handle is always the connected device; the rest reads as JSON
+            let (_, call) = args.split_once(',').ok_or_else(invalid)?;
+            let call: Vec<Value> = serde_json::from_str(&format!("[{}]", call)).map_err(|_| invalid())?;
+            match call.as_slice() {
+                [Value::String(endpoint), Value::Array(args)] => ScriptStep::Command(DeviceCommand::CustomCommand {
+                    endpoint: endpoint.clone(),
+                    args: args.clone(),
+                }),
+                _ => return Err(invalid()),
+            }
+        }
+        "sleep" | "delay" => {
+            let ms = args.trim().parse::<u64>().map_err(|_| invalid())?;
+            ScriptStep::Sleep(Duration::from_millis(ms))
+        }
+        "digitalWrite" => match parts.as_slice() {
+            [pin, value] => {
+                let pin = pin.parse::<u8>().map_err(|_| format!("Invalid pin number in '{}'", line))?;
+                let value = value.eq_ignore_ascii_case("true") || *value == "1";
+                ScriptStep::Command(DeviceCommand::DigitalWrite { pin, value })
+            }
+            _ => return Err(invalid()),
+        },
+        "analogWrite" => match parts.as_slice() {
+            [pin, value] => match (pin.parse::<u8>(), value.parse::<u8>()) {
+                (Ok(pin), Ok(value)) => ScriptStep::Command(DeviceCommand::AnalogWrite { pin, value }),
+                _ => return Err(invalid()),
+            },
+            _ => return Err(invalid()),
+        },
+        "setServo" => match parts.as_slice() {
+            [index, position] => match (index.parse::<u8>(), position.parse::<u8>()) {
+                (Ok(index), Ok(position)) => ScriptStep::Command(DeviceCommand::SetServo { index, position }),
+                _ => return Err(invalid()),
+            },
+            _ => return Err(invalid()),
+        },
+        _ => return Err(format!("Unknown command '{}'", line)),
+    };
+    Ok(Some(step))
+}
+
+/// Set the clock offset used for device timestamps on every channel tagged with `device_id`
+/// Channels fed by a device carry its id as a tag
+fn apply_clock_offset(telemetry: &TelemetrySystem, device_id: &str, offset: Option<ClockOffset>) {
+    for channel in telemetry.channels_with_tag(device_id) {
+        channel.set_clock_offset(offset);
+    }
+}
+
+/// Most events of each kind handled per frame; the rest wait for the next frame
+const MAX_EVENTS_PER_FRAME: usize = 256;
+
+/// How often sessions are checked for a stale clock offset
+/// Each session decides whether it is due; this only bounds how late a re-sync runs
+const CLOCK_RESYNC_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Responses taken off the ingest channel for one frame
+#[derive(Debug, Default)]
+struct ResponseBatch {
+    /// Numeric stream samples as (timestamp, value), grouped per stream in arrival order
+    telemetry: Vec<(String, Vec<(u64, f64)>)>,
+    /// Every other response, in arrival order
+    others: Vec<DeviceResponse>,
+}
+
+/// Take at most `max` events without waiting
+fn drain_bounded<T>(rx: &mut mpsc::UnboundedReceiver<T>, max: usize) -> Vec<T> {
+    let mut events = Vec::new();
+    while events.len() < max {
+        match rx.try_recv() {
+            Ok(event) => events.push(event),
+            Err(_) => break,
+        }
+    }
+    events
+}
+
+/// Take at most `max` responses, coalescing stream samples by stream
+/// Non-numeric stream data is discarded, as the telemetry views can't plot it
+fn drain_responses(rx: &IngestReceiver<DeviceResponse>, max: usize) -> ResponseBatch {
+    let mut batch = ResponseBatch::default();
+    
+    for _ in 0..max {
+        let Some(response) = rx.try_recv() else {
+            break;
+        };
+        match response {
+            DeviceResponse::StreamData { stream, data, timestamp } => {
+                let Some(value) = data.as_f64() else {
+                    continue;
+                };
+                match batch.telemetry.iter_mut().find(|(name, _)| *name == stream) {
+                    Some((_, samples)) => samples.push((timestamp, value)),
+                    None => batch.telemetry.push((stream, vec![(timestamp, value)])),
+                }
+            }
+            other => batch.others.push(other),
+        }
+    }
+    
+    batch
+}
+
+/// Collapsing section, disabled with an explanatory tooltip when `gate` lacks `capability`
+fn gated_collapsing(ui: &mut Ui, gate: &FeatureGate, title: &str, capability: &str, add_contents: impl FnOnce(&mut Ui)) {
+    match gate.check(capability) {
+        Ok(()) => {
+            ui.collapsing(title, add_contents);
+        }
+        Err(reason) => {
+            ui.add_enabled_ui(false, |ui| {
+                ui.collapsing(title, add_contents).header_response.on_disabled_hover_text(reason);
+            });
+        }
+    }
+}
+
+/// Run a command future, converting a hang into a "command timed out" error response
+async fn run_command_with_timeout<F>(device_id: &str, command: F, timeout: Duration) -> DeviceResponse
+where
+    F: std::future::Future<Output = DeviceResult<Value>>,
+{
+    match tokio::time::timeout(timeout, command).await {
+        Ok(Ok(data)) => DeviceResponse::CommandResult {
+            device_id: device_id.to_string(),
+            success: true,
+            data: Some(data),
+        },
+        Ok(Err(e)) => DeviceResponse::CommandFailed {
+            device_id: device_id.to_string(),
+            message: e.to_string(),
+        },
+        Err(_) => DeviceResponse::CommandFailed {
+            device_id: device_id.to_string(),
+            message: format!("Command timed out after {}ms", timeout.as_millis()),
+        },
+    }
+}
+
+/// Run `command` on the open session `session_id`, reporting the outcome for `device_id`
+async fn run_on_session(
+    device_manager: &DeviceManager,
+    device_id: &str,
+    session_id: &str,
+    command: DeviceCommand,
+    timeout: Duration,
+) -> DeviceResponse {
+    let Some(session) = device_manager.get_session(session_id).await else {
+        return DeviceResponse::CommandFailed {
+            device_id: device_id.to_string(),
+            message: format!("Session {} is closed", session_id),
+        };
+    };
+    run_command_with_timeout(
+        device_id,
+        async {
+            let mut session = session.lock().await;
+            dispatch_command(&mut **session, command).await
+        },
+        timeout,
+    ).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::device::mock::MockSession;
+    
+    #[tokio::test]
+    async fn test_hung_command_produces_timeout_error() {
+        // Commands never complete, like a hung device
+        let mut session = MockSession::inert().with_delay(Duration::from_secs(3600));
+        let timeout = Duration::from_millis(100);
+        let start = Instant::now();
+        
+        let response = run_command_with_timeout(
+            "uno",
+            dispatch_command(&mut session, DeviceCommand::DigitalRead { pin: 7 }),
+            timeout,
+        ).await;
+        
+        let elapsed = start.elapsed();
+        assert!(elapsed >= timeout && elapsed < timeout * 5, "timed out after {:?}", elapsed);
+        match response {
+            DeviceResponse::CommandFailed { device_id, message } => {
+                assert_eq!(device_id, "uno");
+                assert!(message.contains("timed out"), "{}", message);
+            }
+            other => panic!("Expected timeout error, got {:?}", other),
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_completed_command_produces_result() {
+        let response = run_command_with_timeout(
+            "uno",
+            async { Ok(json!({ "value": true })) },
+            Duration::from_millis(100),
+        ).await;
+        
+        assert!(matches!(response, DeviceResponse::CommandResult { success: true, .. }));
+    }
+    
+    fn stream_sample(stream: &str, value: f64, timestamp: u64) -> DeviceResponse {
+        DeviceResponse::StreamData { stream: stream.to_string(), data: json!(value), timestamp }
+    }
+    
+    #[test]
+    fn test_drain_bounded_leaves_rest_for_next_call() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        for i in 0..10 {
+            tx.send(i).unwrap();
+        }
+        
+        assert_eq!(drain_bounded(&mut rx, 4), vec![0, 1, 2, 3]);
+        assert_eq!(drain_bounded(&mut rx, 4), vec![4, 5, 6, 7]);
+        assert_eq!(drain_bounded(&mut rx, 4), vec![8, 9]);
+        assert!(drain_bounded(&mut rx, 4).is_empty());
+    }
+    
+    #[test]
+    fn test_drain_responses_bounded_and_coalesced() {
+        let (tx, rx) = ingest_channel(64, OverflowPolicy::DropOldest);
+        for i in 0..5 {
+            tx.try_send(stream_sample("temp", 20.0 + i as f64, 1_000 + i)).unwrap();
+        }
+        tx.try_send(DeviceResponse::DigitalValue { pin: 7, value: true }).unwrap();
+        tx.try_send(stream_sample("rpm", 1200.0, 1_010)).unwrap();
+        tx.try_send(stream_sample("temp", 30.0, 1_011)).unwrap();
+        tx.try_send(DeviceResponse::StreamData { stream: "mode".into(), data: json!("idle"), timestamp: 1_012 }).unwrap();
+        tx.try_send(DeviceResponse::Error { message: "late".into() }).unwrap();
+        
+        // Only the first 8 responses are taken
+        let batch = drain_responses(&rx, 8);
+        assert_eq!(rx.len(), 2);
+        
+        // Six temp samples collapse into one per-stream update, order preserved
+        assert_eq!(batch.telemetry.len(), 2);
+        let (stream, samples) = &batch.telemetry[0];
+        assert_eq!(stream, "temp");
+        assert_eq!(
+            samples.iter().map(|&(_, v)| v).collect::<Vec<_>>(),
+            vec![20.0, 21.0, 22.0, 23.0, 24.0, 30.0]
+        );
+        assert_eq!(batch.telemetry[1].0, "rpm");
+        assert_eq!(batch.others.len(), 1);
+        assert!(matches!(batch.others[0], DeviceResponse::DigitalValue { pin: 7, value: true }));
+        
+        // The rest arrives on the next call
+        let batch = drain_responses(&rx, 8);
+        assert!(batch.telemetry.is_empty());
+        assert_eq!(batch.others.len(), 1);
+        assert!(rx.is_empty());
+    }
+    
+    #[test]
+    fn test_exported_history_parses_into_timed_steps() {
+        let mut history = CommandHistory::new();
+        history.record_at(SessionCommand::new("digitalWrite", vec![json!(13), json!(true)]), 1_000);
+        history.record_at(SessionCommand::new("i2cWrite", vec![json!(64), json!("ff")]), 1_250);
+        
+        let steps: Vec<ScriptStep> = history.to_script("uno").lines()
+            .filter_map(|line| parse_script_line(line).unwrap())
+            .collect();
+        assert_eq!(steps.len(), 3);
+        assert!(matches!(&steps[0], ScriptStep::Command(DeviceCommand::CustomCommand { endpoint, args })
+            if endpoint == "digitalWrite" && *args == vec![json!(13), json!(true)]));
+        assert!(matches!(steps[1], ScriptStep::Sleep(d) if d == Duration::from_millis(250)));
+        assert!(matches!(&steps[2], ScriptStep::Command(DeviceCommand::CustomCommand { endpoint, args })
+            if endpoint == "i2cWrite" && *args == vec![json!(64), json!("ff")]));
+        
+        // The shorthand calls still work, and bad lines are reported
+        assert!(matches!(parse_script_line("delay(40);"), Ok(Some(ScriptStep::Sleep(d))) if d == Duration::from_millis(40)));
+        assert!(matches!(parse_script_line("setServo(0, 90)"), Ok(Some(ScriptStep::Command(DeviceCommand::SetServo { index: 0, position: 90 })))));
+        assert!(parse_script_line("digitalWrite(x, true)").unwrap_err().contains("Invalid pin"));
+        assert!(parse_script_line("explode()").unwrap_err().contains("Unknown command"));
+    }
+    
+    #[test]
+    fn test_clock_offset_reaches_device_channels() {
+        use crate::telemetry::TimestampSource;
+        
+        let telemetry = TelemetrySystem::new();
+        let device_channel = |name: &str, tags: Vec<String>| telemetry.create_channel(name.to_string(), Some(ChannelConfig {
+            buffer_size: 16,
+            sample_rate: 1000.0,
+            name: name.to_string(),
+            sample_type: SampleType::Float32,
+            persist_path: None,
+            tags,
+            group: None,
+            timestamp_source: TimestampSource::Device,
+        }));
+        let uno = device_channel("uno/temperature", vec!["Arduino Uno_COM3".to_string()]);
+        let other = device_channel("mega/temperature", vec!["Arduino Mega_COM4".to_string()]);
+        
+        let offset = ClockOffset::estimate(1_000_000, 5_000, 1_000_010);
+        apply_clock_offset(&telemetry, "Arduino Uno_COM3", Some(offset));
+        
+        for channel in [&uno, &other] {
+            channel.add_sample(TelemetrySample::with_timestamp(SampleValue::Float32(1.0), 5_000));
+        }
+        assert_eq!(uno.snapshot().last().unwrap().timestamp_ms, 1_000_005);
+        assert_eq!(other.snapshot().last().unwrap().timestamp_ms, 5_000);
+    }
+}
@@ -1,5 +1,6 @@
 use std::collections::VecDeque;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use egui::{Ui, Color32, Stroke, Vec2};
 use egui_plot::{Plot, PlotPoints, Line, Legend, Corner, GridMark, GridInput};
 use egui_plot::{MarkerShape, Points, PlotBounds, AxisHints};
@@ -15,6 +16,18 @@ pub enum ChartType {
     StepLine,
 }
 
+/// How the Y axis range is chosen
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum YScaleMode {
+    /// Fit the visible data every frame
+    Auto,
+    /// Always show `y_min..=y_max`
+    Manual,
+    /// Expand at once to fit new data, but only contract once the range has
+    /// not needed to grow for `decay`, so a transient spike doesn't cause constant rescaling
+    AutoDecay { decay: Duration },
+}
+
 /// Configuration for telemetry charts
 #[derive(Debug, Clone)]
 pub struct ChartConfig {
@@ -27,10 +40,10 @@ pub struct ChartConfig {
     /// Time window in seconds (0 = show all)
     pub time_window_seconds: f64,
     
-    /// Enable auto-scaling for Y axis
-    pub auto_scale_y: bool,
+    /// Y axis scaling
+    pub y_scale: YScaleMode,
     
-    /// Fixed Y axis range (if not auto-scaling)
+    /// Fixed Y axis range (for `YScaleMode::Manual`)
     pub y_min: f64,
     pub y_max: f64,
     
@@ -62,7 +75,7 @@ impl Default for ChartConfig {
             chart_type: ChartType::Line,
             max_points: 300,  // Good balance for 30 FPS
             time_window_seconds: 10.0,  // Show last 10 seconds
-            auto_scale_y: true,
+            y_scale: YScaleMode::Auto,
             y_min: 0.0,
             y_max: 100.0,
             show_grid: true,
@@ -90,10 +103,65 @@ pub struct TelemetryChart {
     /// Last update timestamp
     last_update: std::time::Instant,
     
+    /// Y range shown in decaying autoscale mode
+    y_range: YRangeTracker,
+    
     /// Statistics
     stats: ChartStats,
 }
 
+/// Remembers the displayed Y range between frames
+#[derive(Debug, Default)]
+pub struct YRangeTracker {
+    shown: Option<(f64, f64)>,
+    last_change: Option<Instant>,
+}
+
+impl YRangeTracker {
+    /// Y range to display for `data` under `config` at `now`
+    /// `None` when there is nothing to scale to
+    pub fn update(&mut self, config: &ChartConfig, data: &[[f64; 2]], now: Instant) -> Option<(f64, f64)> {
+        let fitted = data.iter()
+            .map(|p| p[1])
+            .filter(|y| y.is_finite())
+            .fold(None, |range: Option<(f64, f64)>, y| match range {
+                Some((lo, hi)) => Some((lo.min(y), hi.max(y))),
+                None => Some((y, y)),
+            });
+        
+        match config.y_scale {
+            YScaleMode::Manual => Some((config.y_min, config.y_max)),
+            YScaleMode::Auto => fitted,
+            YScaleMode::AutoDecay { decay } => {
+                let (lo, hi) = fitted.or(self.shown)?;
+                let next = match self.shown {
+                    // Grow immediately to cover anything outside the shown range
+                    Some((shown_lo, shown_hi)) if lo < shown_lo || hi > shown_hi => {
+                        self.last_change = Some(now);
+                        (shown_lo.min(lo), shown_hi.max(hi))
+                    }
+                    // Shrink back to the data once the range has been stable for the decay window
+                    Some(shown) => match self.last_change {
+                        Some(changed) if now.duration_since(changed) < decay => shown,
+                        _ => {
+                            if shown != (lo, hi) {
+                                self.last_change = Some(now);
+                            }
+                            (lo, hi)
+                        }
+                    },
+                    None => {
+                        self.last_change = Some(now);
+                        (lo, hi)
+                    }
+                };
+                self.shown = Some(next);
+                Some(next)
+            }
+        }
+    }
+}
+
 #[derive(Debug)]
 struct ChartStats {
     total_samples: u64,
@@ -123,6 +191,7 @@ impl TelemetryChart {
             name: name.into(),
             render_buffer: Arc::new(RwLock::new(Vec::with_capacity(300))),
             last_update: std::time::Instant::now(),
+            y_range: YRangeTracker::default(),
             stats: ChartStats::default(),
         }
     }
@@ -134,6 +203,7 @@ impl TelemetryChart {
             name: name.into(),
             render_buffer: Arc::new(RwLock::new(Vec::with_capacity(300))),
             last_update: std::time::Instant::now(),
+            y_range: YRangeTracker::default(),
             stats: ChartStats::default(),
         }
     }
@@ -228,11 +298,16 @@ impl TelemetryChart {
     pub fn show(&mut self, ui: &mut Ui) -> egui::Response {
         // Get current data
         let data = self.render_buffer.read().clone();
+        let y_range = match self.config.y_scale {
+            YScaleMode::Auto => None,
+            _ => self.y_range.update(&self.config, &data, Instant::now()),
+        };
+        let x_range = data.first().zip(data.last()).map(|(first, last)| (first[0], last[0]));
         
         // Create plot
         let plot = Plot::new(&self.name)
             .height(200.0)
-            .auto_bounds([true, self.config.auto_scale_y].into())
+            .auto_bounds([true, y_range.is_none()].into())
             .show_grid(self.config.show_grid)
             .allow_drag(true)
             .allow_zoom(true)
@@ -252,12 +327,13 @@ impl TelemetryChart {
             plot
         };
         
-        // Note: In egui_plot 0.29, bounds are set differently
-        // We'll use auto_bounds for now and can add manual bounds later if needed
-        let plot = plot;
-        
         // Render the plot
         let response = plot.show(ui, |plot_ui| {
+            // Pin the Y axis; X keeps following the data
+            if let (Some((y_min, y_max)), Some((x_min, x_max))) = (y_range, x_range) {
+                plot_ui.set_plot_bounds(PlotBounds::from_min_max([x_min, y_min], [x_max, y_max]));
+            }
+            
             // Create line/scatter based on chart type
             match self.config.chart_type {
                 ChartType::Line => {
@@ -443,4 +519,67 @@ pub fn create_test_chart(name: &str) -> TelemetryChart {
     
     chart.update_with_data(&data);
     chart
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn points(values: &[f64]) -> Vec<[f64; 2]> {
+        values.iter().enumerate().map(|(i, &y)| [i as f64, y]).collect()
+    }
+    
+    fn config(y_scale: YScaleMode) -> ChartConfig {
+        ChartConfig {
+            y_scale,
+            y_min: -5.0,
+            y_max: 5.0,
+            ..Default::default()
+        }
+    }
+    
+    #[test]
+    fn test_manual_range_is_exact() {
+        let mut tracker = YRangeTracker::default();
+        let config = config(YScaleMode::Manual);
+        let now = Instant::now();
+        
+        assert_eq!(tracker.update(&config, &points(&[100.0, -300.0]), now), Some((-5.0, 5.0)));
+        assert_eq!(tracker.update(&config, &[], now), Some((-5.0, 5.0)));
+    }
+    
+    #[test]
+    fn test_auto_range_follows_data() {
+        let mut tracker = YRangeTracker::default();
+        let config = config(YScaleMode::Auto);
+        let now = Instant::now();
+        
+        assert_eq!(tracker.update(&config, &points(&[1.0, 9.0, 4.0]), now), Some((1.0, 9.0)));
+        assert_eq!(tracker.update(&config, &points(&[2.0, 3.0]), now), Some((2.0, 3.0)));
+        assert_eq!(tracker.update(&config, &[], now), None);
+    }
+    
+    #[test]
+    fn test_decay_expands_on_spike_and_contracts_after_window() {
+        let decay = Duration::from_secs(3);
+        let mut tracker = YRangeTracker::default();
+        let config = config(YScaleMode::AutoDecay { decay });
+        let start = Instant::now();
+        
+        assert_eq!(tracker.update(&config, &points(&[10.0, 20.0]), start), Some((10.0, 20.0)));
+        
+        // A spike expands the range immediately
+        let spike = start + Duration::from_secs(1);
+        assert_eq!(tracker.update(&config, &points(&[10.0, 95.0, 20.0]), spike), Some((10.0, 95.0)));
+        
+        // The spike has scrolled out but the expanded range is held for the decay window
+        assert_eq!(tracker.update(&config, &points(&[12.0, 18.0]), spike + Duration::from_secs(1)), Some((10.0, 95.0)));
+        assert_eq!(tracker.update(&config, &points(&[12.0, 18.0]), spike + Duration::from_millis(2900)), Some((10.0, 95.0)));
+        
+        // After the window it contracts to the data
+        assert_eq!(tracker.update(&config, &points(&[12.0, 18.0]), spike + decay), Some((12.0, 18.0)));
+        
+        // Growth is always immediate, even right after contracting
+        assert_eq!(tracker.update(&config, &points(&[12.0, 30.0]), spike + decay), Some((12.0, 30.0)));
+    }
+}
@@ -0,0 +1,102 @@
+//! Capability-based gating of tabs and control sections
+//!
+//! A device that can't run scripts shouldn't offer a Scripts tab that only
+//! fails when used. `FeatureGate` decides from the capabilities the session
+//! reported which tabs and controls are usable, and why the rest are not.
+//! Without a device, or with one that doesn't report capabilities, every
+//! feature stays available.
+
+use std::collections::BTreeSet;
+use crate::ui::app::Tab;
+
+pub const CAP_SCRIPTING: &str = "scripting";
+pub const CAP_TELEMETRY: &str = "telemetry";
+pub const CAP_GPIO: &str = "gpio";
+pub const CAP_PWM: &str = "pwm";
+pub const CAP_ANALOG: &str = "analog";
+pub const CAP_SERVO: &str = "servo";
+
+impl Tab {
+    /// Capability a device must report for this tab to be usable
+    pub fn required_capability(&self) -> Option<&'static str> {
+        match self {
+            Tab::Scripts => Some(CAP_SCRIPTING),
+            Tab::Telemetry => Some(CAP_TELEMETRY),
+            Tab::Manual | Tab::Logs | Tab::Profiles | Tab::Performance => None,
+        }
+    }
+}
+
+/// Which features the connected device supports
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FeatureGate {
+    /// Reported capabilities; `None` enables everything
+    capabilities: Option<BTreeSet<String>>,
+}
+
+impl FeatureGate {
+    /// Gate for a session's capabilities, or the permissive default when `None`
+    pub fn from_capabilities(capabilities: Option<&BTreeSet<String>>) -> Self {
+        Self {
+            capabilities: capabilities.cloned(),
+        }
+    }
+    
+    /// Whether capabilities were reported (false = default gating)
+    pub fn is_restricted(&self) -> bool {
+        self.capabilities.is_some()
+    }
+    
+    /// `Ok` if `capability` is available, otherwise the reason shown to the user
+    pub fn check(&self, capability: &str) -> Result<(), String> {
+        match &self.capabilities {
+            Some(capabilities) if !capabilities.contains(capability) => {
+                Err(format!("The connected device does not support \"{}\"", capability))
+            }
+            _ => Ok(()),
+        }
+    }
+    
+    /// `Ok` if `tab` can be opened, otherwise why not
+    pub fn tab(&self, tab: Tab) -> Result<(), String> {
+        tab.required_capability().map_or(Ok(()), |capability| self.check(capability))
+    }
+    
+    pub fn tab_enabled(&self, tab: Tab) -> bool {
+        self.tab(tab).is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn caps(names: &[&str]) -> BTreeSet<String> {
+        names.iter().map(|s| s.to_string()).collect()
+    }
+    
+    #[test]
+    fn test_scripts_tab_follows_scripting_capability() {
+        let without = caps(&["gpio", "telemetry"]);
+        let gate = FeatureGate::from_capabilities(Some(&without));
+        assert!(!gate.tab_enabled(Tab::Scripts));
+        assert!(gate.tab(Tab::Scripts).unwrap_err().contains("scripting"));
+        assert!(gate.tab_enabled(Tab::Telemetry));
+        assert!(gate.tab_enabled(Tab::Manual));
+        
+        let with = caps(&["gpio", "scripting"]);
+        let gate = FeatureGate::from_capabilities(Some(&with));
+        assert!(gate.tab_enabled(Tab::Scripts));
+        assert!(!gate.tab_enabled(Tab::Telemetry));
+    }
+    
+    #[test]
+    fn test_no_device_enables_everything() {
+        let gate = FeatureGate::from_capabilities(None);
+        assert!(!gate.is_restricted());
+        for tab in [Tab::Manual, Tab::Scripts, Tab::Telemetry, Tab::Logs, Tab::Profiles, Tab::Performance] {
+            assert!(gate.tab_enabled(tab));
+        }
+        assert!(gate.check(CAP_SERVO).is_ok());
+    }
+}
@@ -5,9 +5,11 @@ pub mod charts;
 pub mod theme;
 pub mod accessibility;
 pub mod controls;
+pub mod feature_gate;
 
 pub use app::MultiControllerApp;
 pub use charts::{TelemetryChart, ChartConfig, ChartType, MultiChart, ChartLayout};
 pub use theme::Windows10Theme;
+pub use feature_gate::FeatureGate;
 pub use accessibility::{AccessibilityHelpers, KeyboardShortcuts, NavigationAction, FocusManager, ScreenReaderAnnouncer};
 pub use controls::{ManualControlManager, ManualControlState, ControlWidget, ControlValue, ControlAuthority, ControlEvent};
\ No newline at end of file
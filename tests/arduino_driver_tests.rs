@@ -76,7 +76,7 @@ mod arduino_driver_tests {
 
     #[tokio::test]
     async fn test_probe_with_mock_transport() {
-        use multi_controller_app::transport::{serial::SerialTransport, TransportConfig, common::{TransportSettings, SerialSettings, DataBits, Parity, StopBits, FlowControl}};
+        use multi_controller_app::transport::{serial::SerialTransport, TransportConfig, ReconnectHints, common::{TransportSettings, SerialSettings, DataBits, Parity, StopBits, FlowControl, ReadStrategy}};
         
         let driver = ArduinoUnoDriver::new();
         let config = TransportConfig {
@@ -88,6 +88,12 @@ mod arduino_driver_tests {
                 parity: Parity::None,
                 stop_bits: StopBits::One,
                 flow_control: FlowControl::None,
+                report_line_errors: true,
+                inter_byte_timeout_ms: None,
+                overflow_marker: None,
+                exclusive: false,
+                goodbye_marker: None,
+                read_strategy: ReadStrategy::Polled,
             }),
             auto_reconnect: false,
             reconnect_delay_ms: 1000,
@@ -98,6 +104,7 @@ mod arduino_driver_tests {
             read_buffer_size: 4096,
             write_buffer_size: 4096,
             require_handshake: false,
+            reconnect_hints: ReconnectHints::default(),
         };
         
         let transport = SerialTransport::new(config).expect("Failed to create transport");
@@ -111,7 +118,7 @@ mod arduino_driver_tests {
 
     #[tokio::test]
     async fn test_open_session() {
-        use multi_controller_app::transport::{serial::SerialTransport, TransportConfig, common::{TransportSettings, SerialSettings, DataBits, Parity, StopBits, FlowControl}};
+        use multi_controller_app::transport::{serial::SerialTransport, TransportConfig, ReconnectHints, common::{TransportSettings, SerialSettings, DataBits, Parity, StopBits, FlowControl, ReadStrategy}};
         
         let driver = ArduinoUnoDriver::new();
         let config = TransportConfig {
@@ -123,6 +130,12 @@ mod arduino_driver_tests {
                 parity: Parity::None,
                 stop_bits: StopBits::One,
                 flow_control: FlowControl::None,
+                report_line_errors: true,
+                inter_byte_timeout_ms: None,
+                overflow_marker: None,
+                exclusive: false,
+                goodbye_marker: None,
+                read_strategy: ReadStrategy::Polled,
             }),
             auto_reconnect: false,
             reconnect_delay_ms: 1000,
@@ -133,6 +146,7 @@ mod arduino_driver_tests {
             read_buffer_size: 4096,
             write_buffer_size: 4096,
             require_handshake: false,
+            reconnect_hints: ReconnectHints::default(),
         };
         
         let transport = SerialTransport::new(config).expect("Failed to create transport");
@@ -1,4 +1,6 @@
-/// Common test utilities and helpers
+//! Common test utilities and helpers
+// Each test crate declaring `mod common;` uses only some of these
+#![allow(dead_code)]
 use std::time::Duration;
 use tokio::time::timeout;
 
@@ -81,6 +83,82 @@ impl Drop for TestEnv {
     }
 }
 
+/// Result of comparing the runtime's alive task count against a baseline
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskLeakReport {
+    pub context: String,
+    pub baseline: usize,
+    pub current: usize,
+}
+
+impl TaskLeakReport {
+    /// Number of tasks still alive beyond the baseline
+    pub fn leaked(&self) -> usize {
+        self.current.saturating_sub(self.baseline)
+    }
+}
+
+impl std::fmt::Display for TaskLeakReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Leaked tokio tasks after '{}': baseline {} alive, now {} alive (+{})",
+            self.context, self.baseline, self.current, self.leaked()
+        )
+    }
+}
+
+/// Grace period for aborted tasks to be reaped by the runtime
+const TASK_SETTLE_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Snapshot the number of alive tasks on the current tokio runtime
+pub fn alive_task_count() -> usize {
+    tokio::runtime::Handle::current().metrics().num_alive_tasks()
+}
+
+/// Wait for the alive task count to return to `baseline`
+///
+/// Aborted tasks are only removed once the runtime polls them again, so
+/// this yields repeatedly until the count settles or the grace period ends.
+pub async fn check_task_balance(baseline: usize, context: &str) -> Result<(), TaskLeakReport> {
+    let deadline = std::time::Instant::now() + TASK_SETTLE_TIMEOUT;
+    
+    loop {
+        let current = alive_task_count();
+        if current <= baseline {
+            return Ok(());
+        }
+        
+        if std::time::Instant::now() >= deadline {
+            return Err(TaskLeakReport {
+                context: context.to_string(),
+                baseline,
+                current,
+            });
+        }
+        
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+}
+
+/// Run `future` and assert that every task it spawned has finished afterwards
+///
+/// Intended for transport and session teardown tests, e.g. wrapping a
+/// connect/disconnect cycle to catch reconnection tasks that outlive it.
+pub async fn assert_no_leaked_tasks<F, T>(context: &str, future: F) -> T
+where
+    F: std::future::Future<Output = T>,
+{
+    let baseline = alive_task_count();
+    let output = future.await;
+    
+    if let Err(report) = check_task_balance(baseline, context).await {
+        panic!("{}", report);
+    }
+    
+    output
+}
+
 /// Helper to run async code in blocking context (for property tests)
 pub fn block_on<F: std::future::Future>(future: F) -> F::Output {
     tokio::runtime::Runtime::new()
@@ -119,6 +197,35 @@ mod tests {
         assert_eq!(result, 42);
     }
     
+    #[tokio::test]
+    async fn test_task_balance_after_abort() {
+        assert_no_leaked_tasks("spawn and abort", async {
+            let handle = tokio::spawn(async {
+                tokio::time::sleep(Duration::from_secs(60)).await;
+            });
+            handle.abort();
+            let _ = handle.await;
+        }).await;
+    }
+    
+    #[tokio::test]
+    async fn test_task_leak_detected() {
+        let baseline = alive_task_count();
+        let handle = tokio::spawn(async {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+        });
+        
+        let report = check_task_balance(baseline, "intentional leak")
+            .await
+            .expect_err("leaked task should be reported");
+        assert_eq!(report.baseline, baseline);
+        assert_eq!(report.leaked(), 1);
+        assert!(report.to_string().contains("intentional leak"));
+        
+        handle.abort();
+        assert!(check_task_balance(baseline, "after cleanup").await.is_ok());
+    }
+    
     #[test]
     fn test_env_helper() {
         let env = TestEnv::new("sample_test");
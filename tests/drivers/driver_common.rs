@@ -379,6 +379,7 @@ mod tests {
                 requires_auth: false,
                 max_data_rate: Some(115200 / 10),
                 min_latency_ms: Some(50),
+                adc_resolution_bits: Some(10),
             }
         }
         
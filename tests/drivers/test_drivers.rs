@@ -84,6 +84,7 @@ impl DeviceDriver for MockArduinoDriver {
             requires_auth: false,
             max_data_rate: Some(115200 / 10),
             min_latency_ms: Some(50),
+            adc_resolution_bits: Some(10),
         }
     }
 }
@@ -138,6 +139,7 @@ impl DeviceDriver for MockRaspberryPiDriver {
             requires_auth: true,
             max_data_rate: Some(1_000_000), // 1MB/s over network
             min_latency_ms: Some(20), // Lower latency over network
+            adc_resolution_bits: Some(10),
         }
     }
 }
@@ -197,6 +199,7 @@ impl DeviceDriver for MockGenericDriver {
             requires_auth: false,
             max_data_rate: Some(10_000_000), // 10MB/s
             min_latency_ms: Some(10),
+            adc_resolution_bits: None,
         }
     }
 }
@@ -3,6 +3,7 @@
 /// requiring the full GUI dependencies
 
 use multi_controller_app::transport::*;
+use multi_controller_app::transport::common::{ReconnectHints, SerialSettings, TransportSettings};
 
 #[cfg(test)]
 mod tests {
@@ -23,6 +24,7 @@ mod tests {
             write_buffer_size: 1024,
             require_handshake: false,
             min_latency: None,
+            reconnect_hints: ReconnectHints::default(),
             settings: TransportSettings::Serial(SerialSettings::default()),
         };
         
@@ -0,0 +1,76 @@
+/// Teardown tests: closing a transport or session must stop every task it spawned
+///
+/// Runs without hardware, over TCP connections to a local listener.
+
+mod common;
+
+use std::sync::Arc;
+use std::time::Duration;
+use common::assert_no_leaked_tasks;
+use multi_controller_app::device::{DeviceDriver, DeviceSession, KeepAliveSettings};
+use multi_controller_app::drivers::ArduinoUnoDriver;
+use multi_controller_app::transport::common::{TcpSettings, TransportSettings};
+use multi_controller_app::transport::tcp::TcpTransport;
+use multi_controller_app::transport::*;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+
+/// Listener on a free local port and a TCP transport pointed at it
+async fn local_tcp(heartbeat_timeout_ms: u32) -> (TcpListener, TcpTransport) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let config = TransportConfig {
+        transport_type: TransportType::Tcp,
+        address: format!("127.0.0.1:{}", port),
+        settings: TransportSettings::Tcp(TcpSettings {
+            host: "127.0.0.1".to_string(),
+            port,
+            heartbeat_timeout_ms,
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    (listener, TcpTransport::new(config).unwrap())
+}
+
+#[tokio::test]
+async fn tcp_disconnect_stops_heartbeat_monitor() {
+    let (listener, transport) = local_tcp(200).await;
+    
+    assert_no_leaked_tasks("tcp connect/disconnect", async {
+        let (connected, accepted) = tokio::join!(transport.connect(), listener.accept());
+        connected.unwrap();
+        let (_peer, _) = accepted.unwrap();
+        assert!(transport.is_connected());
+        
+        transport.disconnect().await.unwrap();
+    }).await;
+}
+
+#[tokio::test]
+async fn session_close_stops_keep_alive() {
+    let (listener, transport) = local_tcp(0).await;
+    // Device answering every command line with OK, running for the whole test
+    tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let (reader, mut writer) = stream.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        while let Ok(Some(_)) = lines.next_line().await {
+            if writer.write_all(b"OK\r\n").await.is_err() {
+                break;
+            }
+        }
+    });
+    let transport = Arc::new(transport);
+    let driver = ArduinoUnoDriver::new()
+        .with_keep_alive(KeepAliveSettings::new("PING", Duration::from_millis(50)));
+    
+    assert_no_leaked_tasks("arduino session open/close", async {
+        transport.connect().await.unwrap();
+        let mut session = driver.open_async(transport.clone()).await.unwrap();
+        // Idle long enough for the keep-alive to ping
+        tokio::time::sleep(Duration::from_millis(120)).await;
+        session.close_async().await.unwrap();
+        transport.disconnect().await.unwrap();
+    }).await;
+}
@@ -11,6 +11,10 @@ fn test_chart_ring_buffer_integration() {
         buffer_size: 2000,  // Minimum size per requirements
         sample_rate: 30.0,  // 30 FPS
         sample_type: SampleType::Float32,
+        persist_path: None,
+        tags: Vec::new(),
+        group: None,
+        timestamp_source: Default::default(),
     };
     
     let channel = TelemetryChannel::new(config);
@@ -54,6 +58,10 @@ fn test_chart_decimation_with_large_buffer() {
         buffer_size: 5000,  // Large buffer
         sample_rate: 0.0,    // No rate limiting for test
         sample_type: SampleType::Float32,
+        persist_path: None,
+        tags: Vec::new(),
+        group: None,
+        timestamp_source: Default::default(),
     };
     
     let channel = TelemetryChannel::new(config);
@@ -83,6 +91,10 @@ fn test_chart_time_window_filtering() {
         buffer_size: 2000,
         sample_rate: 0.0,
         sample_type: SampleType::Float32,
+        persist_path: None,
+        tags: Vec::new(),
+        group: None,
+        timestamp_source: Default::default(),
     };
     
     let channel = TelemetryChannel::new(config);
@@ -116,6 +128,10 @@ fn test_chart_update_rate_limiting() {
         buffer_size: 2000,
         sample_rate: 0.0,
         sample_type: SampleType::Float32,
+        persist_path: None,
+        tags: Vec::new(),
+        group: None,
+        timestamp_source: Default::default(),
     };
     
     let channel = TelemetryChannel::new(config);
@@ -162,6 +178,10 @@ fn test_different_chart_types() {
         buffer_size: 2000,
         sample_rate: 0.0,
         sample_type: SampleType::Float32,
+        persist_path: None,
+        tags: Vec::new(),
+        group: None,
+        timestamp_source: Default::default(),
     };
     
     let channel = TelemetryChannel::new(config);
@@ -139,6 +139,10 @@ fn test_30_fps_update_rate() {
         buffer_size: 2000,
         sample_rate: 30.0,
         sample_type: SampleType::Float32,
+        persist_path: None,
+        tags: Vec::new(),
+        group: None,
+        timestamp_source: Default::default(),
     };
     
     let channel = TelemetryChannel::new(config);
@@ -268,6 +272,10 @@ fn test_memory_pressure_handling() {
                 buffer_size: 2000,
                 sample_rate: 30.0,
                 sample_type: SampleType::Float32,
+                persist_path: None,
+                tags: Vec::new(),
+                group: None,
+                timestamp_source: Default::default(),
             };
             TelemetryChannel::new(config)
         })